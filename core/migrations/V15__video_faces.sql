@@ -0,0 +1,118 @@
+-- Faces can be detected in videos as well as pictures.
+-- A face belongs to exactly one picture or one video, so picture_id must become nullable.
+-- SQLite can't alter a column constraint, so the table must be rebuilt.
+CREATE TABLE pictures_faces_new (
+        face_id        INTEGER PRIMARY KEY UNIQUE NOT NULL, -- unique ID for face
+
+        model_name     TEXT NOT NULL, -- face detection model used
+
+        picture_id     INTEGER, -- unique ID for picture, if face is in a picture
+
+        video_id       INTEGER, -- unique ID for video, if face is in a video
+
+        is_confirmed   BOOLEAN NOT NULL CHECK (is_confirmed IN (0, 1)) DEFAULT 0, -- person_id confirmed by user?
+
+        detected_at    DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP, -- timestamp when face was detected
+
+        person_id      INTEGER, -- person associated with face
+
+        thumbnail_path TEXT UNIQUE NOT NULL, -- path to square face thumbnail
+        bounds_path    TEXT UNIQUE NOT NULL, -- path to face cropped to exact detected bounds
+
+        bounds_x       DECIMAL NOT NULL, -- face bounds X coordinate
+        bounds_y       DECIMAL NOT NULL, -- face bounds Y coordinate
+        bounds_width   DECIMAL NOT NULL, -- face bounds width
+        bounds_height  DECIMAL NOT NULL, -- face bounds height
+
+        right_eye_x DECIMAL NOT NULL, -- facial landmarks
+        right_eye_y DECIMAL NOT NULL,
+
+        left_eye_x DECIMAL NOT NULL,
+        left_eye_y DECIMAL NOT NULL,
+
+        nose_x DECIMAL NOT NULL,
+        nose_y DECIMAL NOT NULL,
+
+        right_mouth_corner_x DECIMAL NOT NULL,
+        right_mouth_corner_y DECIMAL NOT NULL,
+
+        left_mouth_corner_x DECIMAL NOT NULL,
+        left_mouth_corner_y DECIMAL NOT NULL,
+
+        confidence DECIMAL NOT NULL, -- confidence (0.0 to 1.0) that detected face is a face.
+
+        is_ignored BOOLEAN NOT NULL CHECK (is_ignored IN (0, 1)) DEFAULT 0, -- ignored by user?
+
+        CHECK ((picture_id IS NULL) != (video_id IS NULL)),
+
+        FOREIGN KEY (picture_id) REFERENCES pictures (picture_id) ON DELETE CASCADE,
+        FOREIGN KEY (video_id) REFERENCES videos (video_id) ON DELETE CASCADE,
+        FOREIGN KEY (person_id) REFERENCES people (person_id) ON DELETE SET NULL
+);
+
+INSERT INTO pictures_faces_new (
+        face_id,
+        model_name,
+        picture_id,
+        is_confirmed,
+        detected_at,
+        person_id,
+        thumbnail_path,
+        bounds_path,
+        bounds_x,
+        bounds_y,
+        bounds_width,
+        bounds_height,
+        right_eye_x,
+        right_eye_y,
+        left_eye_x,
+        left_eye_y,
+        nose_x,
+        nose_y,
+        right_mouth_corner_x,
+        right_mouth_corner_y,
+        left_mouth_corner_x,
+        left_mouth_corner_y,
+        confidence,
+        is_ignored
+)
+SELECT
+        face_id,
+        model_name,
+        picture_id,
+        is_confirmed,
+        detected_at,
+        person_id,
+        thumbnail_path,
+        bounds_path,
+        bounds_x,
+        bounds_y,
+        bounds_width,
+        bounds_height,
+        right_eye_x,
+        right_eye_y,
+        left_eye_x,
+        left_eye_y,
+        nose_x,
+        nose_y,
+        right_mouth_corner_x,
+        right_mouth_corner_y,
+        left_mouth_corner_x,
+        left_mouth_corner_y,
+        confidence,
+        is_ignored
+FROM pictures_faces;
+
+DROP TABLE pictures_faces;
+
+ALTER TABLE pictures_faces_new RENAME TO pictures_faces;
+
+-- Face detection runs for videos
+CREATE TABLE videos_face_scans (
+        video_id     INTEGER PRIMARY KEY UNIQUE NOT NULL, -- unique ID for video
+        is_broken    BOOLEAN NOT NULL CHECK (is_broken IN (0, 1)) DEFAULT 1, -- scan failed?
+        scan_ts      DATETIME NOT NULL, -- UTC timestamp of scan
+        face_count   INTEGER NOT NULL, -- count of faces found
+
+        FOREIGN KEY (video_id) REFERENCES videos (video_id) ON DELETE CASCADE
+);
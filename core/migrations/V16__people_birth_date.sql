@@ -0,0 +1,2 @@
+-- Optional date of birth for a person, for showing their age in photos and videos.
+ALTER TABLE people ADD COLUMN birth_date DATE; -- date of birth, if known
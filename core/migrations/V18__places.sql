@@ -0,0 +1,46 @@
+-- Places from a GeoNames dataset, used for offline reverse geocoding.
+CREATE TABLE places (
+        place_id       INTEGER PRIMARY KEY UNIQUE NOT NULL, -- GeoNames ID of city
+        city           TEXT NOT NULL, -- name of city or town
+        region         TEXT, -- name of first-level administrative division, such as a state
+        country        TEXT NOT NULL, -- name of country
+        country_code   TEXT NOT NULL, -- ISO 3166 two letter country code
+        timezone       TEXT -- IANA time zone identifier
+);
+
+-- Nearest place to the location of a picture
+CREATE TABLE pictures_places (
+        picture_id     INTEGER PRIMARY KEY UNIQUE NOT NULL, -- unique ID for picture
+        place_id       INTEGER NOT NULL, -- nearest place
+        FOREIGN KEY (picture_id) REFERENCES pictures (picture_id) ON DELETE CASCADE,
+        FOREIGN KEY (place_id) REFERENCES places (place_id) ON DELETE CASCADE
+);
+
+-- Nearest place to the location of a video
+CREATE TABLE videos_places (
+        video_id       INTEGER PRIMARY KEY UNIQUE NOT NULL, -- unique ID for video
+        place_id       INTEGER NOT NULL, -- nearest place
+        FOREIGN KEY (video_id) REFERENCES videos (video_id) ON DELETE CASCADE,
+        FOREIGN KEY (place_id) REFERENCES places (place_id) ON DELETE CASCADE
+);
+
+-- A place must be looked up again when a location changes or is removed.
+CREATE TRIGGER pictures_geo_updated AFTER UPDATE ON pictures_geo
+BEGIN
+        DELETE FROM pictures_places WHERE picture_id = OLD.picture_id;
+END;
+
+CREATE TRIGGER pictures_geo_deleted AFTER DELETE ON pictures_geo
+BEGIN
+        DELETE FROM pictures_places WHERE picture_id = OLD.picture_id;
+END;
+
+CREATE TRIGGER videos_geo_updated AFTER UPDATE ON videos_geo
+BEGIN
+        DELETE FROM videos_places WHERE video_id = OLD.video_id;
+END;
+
+CREATE TRIGGER videos_geo_deleted AFTER DELETE ON videos_geo
+BEGIN
+        DELETE FROM videos_places WHERE video_id = OLD.video_id;
+END;
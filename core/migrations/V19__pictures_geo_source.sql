@@ -0,0 +1,4 @@
+-- Where the location of a picture came from.
+-- 'exif' for GPS data embedded in the picture, 'track' for a location matched
+-- by time against a GPS track.
+ALTER TABLE pictures_geo ADD COLUMN source TEXT NOT NULL DEFAULT 'exif';
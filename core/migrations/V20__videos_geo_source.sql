@@ -0,0 +1,4 @@
+-- Where the location of a video came from. Same values as pictures_geo.source.
+-- 'manual' locations were set by the user and must not be replaced when
+-- metadata is extracted again.
+ALTER TABLE videos_geo ADD COLUMN source TEXT NOT NULL DEFAULT 'exif';
@@ -0,0 +1,17 @@
+-- Areas where locations are private, such as home or work.
+-- A zone is either a circle or a set of H3 cells.
+CREATE TABLE privacy_zones (
+        zone_id        INTEGER PRIMARY KEY UNIQUE NOT NULL, -- unique ID for zone
+        name           TEXT NOT NULL, -- name of zone, such as "Home"
+        latitude       REAL, -- centre of circular zone
+        longitude      REAL, -- centre of circular zone
+        radius_metres  REAL -- radius of circular zone
+);
+
+-- Cells of a zone made up of H3 cells
+CREATE TABLE privacy_zones_cells (
+        zone_id        INTEGER NOT NULL,
+        cell_index     INTEGER NOT NULL, -- H3 cell index
+        PRIMARY KEY (zone_id, cell_index),
+        FOREIGN KEY (zone_id) REFERENCES privacy_zones (zone_id) ON DELETE CASCADE
+);
@@ -0,0 +1,107 @@
+-- Local date and time in the file name, such as IMG_20230514_103455.jpg, for
+-- pictures and videos without dates in their metadata.
+ALTER TABLE pictures ADD COLUMN file_name_local_ts TEXT;
+ALTER TABLE videos ADD COLUMN file_name_local_ts TEXT;
+
+DROP VIEW visual;
+
+CREATE VIEW visual AS
+SELECT
+  -- Unique ID
+  COALESCE(pictures.picture_id, 'x') || '_' || COALESCE(videos.video_id, 'x') AS visual_id,
+  COALESCE(pictures.link_path_b64, videos.link_path_b64) AS link_path_b64,
+
+  pictures.picture_id,
+  pictures.picture_path_b64,
+  pictures.picture_path_lossy, -- for debug only. Never read in Fotema.
+  pictures.orientation AS picture_orientation,
+
+-- If the thumbnail path is absent in the database, then compute the path we know it
+-- will have. Eventually the thumbnail generation background process will create the file
+-- and it will show up in the UI without having to refresh the data.
+  CASE pictures.picture_id
+        WHEN NOT NULL THEN pictures.thumbnail_path
+        ELSE 'photo_thumbnails/' || printf('%04d', pictures.picture_id / 1000) || '/' || CAST(pictures.picture_id AS TEXT) || '_200x200.png'
+  END AS picture_thumbnail,
+
+  pictures.is_selfie,
+
+  videos.video_id,
+  videos.video_path_b64,
+  videos.video_path_lossy, -- for debug only. Never read in Fotema.
+
+-- If the thumbnail path is absent in the database, then compute the path we know it
+-- will have. Eventually the thumbnail generation background process will create the file
+-- and it will show up in the UI without having to refresh the data.
+  CASE videos.video_id
+        WHEN NOT NULL THEN videos.thumbnail_path
+        ELSE 'video_thumbnails/' || printf('%04d', videos.video_id / 1000) || '/' || CAST(videos.video_id AS TEXT) || '_200x200.png'
+  END AS video_thumbnail,
+
+  COALESCE(videos.video_codec, motion_photos.video_codec) AS video_codec,
+
+  COALESCE(videos.video_codec, motion_photos.video_codec) IN ('hevc') AS is_transcode_required,
+
+  COALESCE(videos.transcoded_path, motion_photos.transcoded_path) AS video_transcoded_path,
+
+  COALESCE(videos.rotation, motion_photos.rotation) AS video_rotation,
+
+  -- An iOS live photo is a photo and a video linked with a content ID.
+  -- However, we only really need the video part, and short (<3 seconds)
+  -- videos are possibly live photos that have a missing or misnamed photo.
+  CASE
+        WHEN videos.content_id IS NOT NULL THEN true
+        WHEN videos.duration_millis <= 3000 THEN true
+        WHEN motion_photos.video_path IS NOT NULL THEN true
+        ELSE false
+  END AS is_live_photo,
+
+  COALESCE(videos.duration_millis, motion_photos.duration_millis) as duration_millis,
+
+  motion_photos.video_path AS motion_photo_video_path,
+
+  -- Prefer the location of the picture of a live photo over the location of the video.
+  COALESCE(pictures_geo.longitude, videos_geo.longitude) AS longitude,
+  COALESCE(pictures_geo.latitude, videos_geo.latitude) AS latitude,
+
+  -- Offset from UTC of the local time the item was taken.
+  COALESCE(pictures.capture_offset_secs, videos.capture_offset_secs) AS capture_offset_secs,
+
+  -- Timestamp to order visual items by.
+  -- Prefer a date and time set by the user, then embedded metadata, then a date and time
+  -- in the file name, then file system metadata.
+  -- Timestamps are normalised to UTC so items taken in different time zones sort correctly.
+  datetime(COALESCE(
+    pictures.override_ts,
+    videos.override_ts,
+    datetime(pictures.capture_local_ts, printf('%+d seconds', -COALESCE(pictures.capture_offset_secs, 0))),
+    pictures.exif_created_ts, -- until metadata is scanned again
+    videos.stream_created_ts,
+    pictures.exif_modified_ts,
+    datetime(pictures.file_name_local_ts, printf('%+d seconds', -COALESCE(pictures.capture_offset_secs, 0))),
+    datetime(videos.file_name_local_ts, printf('%+d seconds', -COALESCE(videos.capture_offset_secs, 0))),
+    pictures.fs_created_ts,
+    videos.fs_created_ts,
+    pictures.fs_modified_ts,
+    videos.fs_modified_ts,
+    CURRENT_TIMESTAMP
+  )) AS ordering_ts,
+
+  -- Where ordering_ts came from.
+  CASE
+    WHEN COALESCE(pictures.override_ts, videos.override_ts) IS NOT NULL THEN 'manual'
+    WHEN COALESCE(pictures.capture_local_ts, pictures.exif_created_ts, videos.stream_created_ts, pictures.exif_modified_ts) IS NOT NULL THEN 'metadata'
+    WHEN COALESCE(pictures.file_name_local_ts, videos.file_name_local_ts) IS NOT NULL THEN 'filename'
+    WHEN COALESCE(pictures.fs_created_ts, videos.fs_created_ts, pictures.fs_modified_ts, videos.fs_modified_ts) IS NOT NULL THEN 'filesystem'
+  END AS ordering_ts_source
+FROM
+  pictures
+  FULL OUTER JOIN videos USING (link_path_b64, content_id)
+  FULL OUTER JOIN motion_photos USING (picture_id)
+  FULL OUTER JOIN pictures_geo USING (picture_id)
+  LEFT OUTER JOIN videos_geo USING (video_id)
+WHERE COALESCE(pictures.is_broken, FALSE) IS FALSE
+AND COALESCE(videos.is_broken, FALSE) IS FALSE
+ORDER BY
+  ordering_ts ASC;
+
@@ -0,0 +1,31 @@
+-- Albums curated by the user.
+CREATE TABLE albums (
+        album_id          INTEGER PRIMARY KEY UNIQUE NOT NULL, -- unique ID for album
+        name              TEXT NOT NULL, -- name chosen by user
+        -- Item chosen by user to represent album. Live photos have both a picture
+        -- and a video, so either or both IDs can be set.
+        cover_picture_id  INTEGER,
+        cover_video_id    INTEGER,
+        created_ts        DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+        FOREIGN KEY (cover_picture_id) REFERENCES pictures (picture_id) ON DELETE SET NULL,
+        FOREIGN KEY (cover_video_id) REFERENCES videos (video_id) ON DELETE SET NULL
+);
+
+-- Pictures and videos in albums.
+-- Items reference pictures and videos rather than visual items, so they
+-- survive the visual view being redefined.
+CREATE TABLE album_items (
+        album_item_id  INTEGER PRIMARY KEY UNIQUE NOT NULL, -- unique ID for album item
+        album_id       INTEGER NOT NULL, -- album item is in
+        picture_id     INTEGER,
+        video_id       INTEGER,
+        position       INTEGER NOT NULL, -- manual ordering of items in album
+        FOREIGN KEY (album_id) REFERENCES albums (album_id) ON DELETE CASCADE,
+        FOREIGN KEY (picture_id) REFERENCES pictures (picture_id) ON DELETE CASCADE,
+        FOREIGN KEY (video_id) REFERENCES videos (video_id) ON DELETE CASCADE,
+        UNIQUE (album_id, picture_id),
+        UNIQUE (album_id, video_id),
+        CHECK (picture_id IS NOT NULL OR video_id IS NOT NULL)
+);
+
+CREATE INDEX album_items_album_id_idx ON album_items (album_id, position);
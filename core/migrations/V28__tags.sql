@@ -0,0 +1,34 @@
+-- Keyword tags, imported from metadata or added by the user.
+-- Tags are hierarchical, with levels of the path separated by '|', such as
+-- 'Places|Europe|Paris'. Each level of a path has its own tag so that
+-- browsing a tag can include the items of its descendants.
+CREATE TABLE tags (
+        tag_id         INTEGER PRIMARY KEY UNIQUE NOT NULL, -- unique ID for tag
+        parent_tag_id  INTEGER, -- tag one level up the hierarchy, or NULL for top level tags
+        name           TEXT NOT NULL, -- last level of path, such as 'Paris'
+        path           TEXT NOT NULL UNIQUE, -- full path, such as 'Places|Europe|Paris'
+        FOREIGN KEY (parent_tag_id) REFERENCES tags (tag_id) ON DELETE CASCADE
+);
+
+CREATE INDEX tags_parent_tag_id_idx ON tags (parent_tag_id);
+
+-- Pictures and videos with tags.
+-- Live photos have both a picture and a video, so either or both IDs can be set.
+CREATE TABLE tag_items (
+        tag_item_id    INTEGER PRIMARY KEY UNIQUE NOT NULL, -- unique ID for tag item
+        tag_id         INTEGER NOT NULL,
+        picture_id     INTEGER,
+        video_id       INTEGER,
+        -- 'user' for tags added in Fotema, 'metadata' for keywords imported from
+        -- IPTC or XMP metadata.
+        source         TEXT NOT NULL DEFAULT 'user',
+        FOREIGN KEY (tag_id) REFERENCES tags (tag_id) ON DELETE CASCADE,
+        FOREIGN KEY (picture_id) REFERENCES pictures (picture_id) ON DELETE CASCADE,
+        FOREIGN KEY (video_id) REFERENCES videos (video_id) ON DELETE CASCADE,
+        UNIQUE (tag_id, picture_id),
+        UNIQUE (tag_id, video_id),
+        CHECK (picture_id IS NOT NULL OR video_id IS NOT NULL)
+);
+
+CREATE INDEX tag_items_picture_id_idx ON tag_items (picture_id);
+CREATE INDEX tag_items_video_id_idx ON tag_items (video_id);
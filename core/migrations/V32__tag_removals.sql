@@ -0,0 +1,3 @@
+-- Tags removed by the user are kept and marked as removed, so that keywords
+-- imported again on the next metadata scan don't bring them back.
+ALTER TABLE tag_items ADD COLUMN is_removed BOOLEAN NOT NULL CHECK (is_removed IN (0, 1)) DEFAULT 0;
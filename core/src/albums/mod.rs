@@ -0,0 +1,12 @@
+// SPDX-FileCopyrightText: © 2024 David Bliss
+//
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+pub mod model;
+pub mod repo;
+
+pub use model::Album;
+pub use model::AlbumId;
+pub use model::AlbumItem;
+pub use model::Placement;
+pub use repo::Repository;
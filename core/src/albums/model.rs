@@ -0,0 +1,125 @@
+// SPDX-FileCopyrightText: © 2024 David Bliss
+//
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use crate::photo::PictureId;
+use crate::video::VideoId;
+use crate::visual::Visual;
+use std::fmt::Display;
+
+/// Database ID
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AlbumId(i64);
+
+impl AlbumId {
+    pub fn new(id: i64) -> Self {
+        Self(id)
+    }
+
+    /// FIXME replace this with a To/From SQL implementation.
+    pub fn id(&self) -> i64 {
+        self.0
+    }
+}
+
+impl Display for AlbumId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+/// A picture or video in an album.
+/// Live photos have both a picture and a video.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AlbumItem {
+    pub picture_id: Option<PictureId>,
+    pub video_id: Option<VideoId>,
+}
+
+impl AlbumItem {
+    pub fn new(picture_id: Option<PictureId>, video_id: Option<VideoId>) -> Self {
+        Self {
+            picture_id,
+            video_id,
+        }
+    }
+
+    /// Is this item for a visual item?
+    pub fn is_for(&self, visual: &Visual) -> bool {
+        (self.picture_id.is_some() && self.picture_id == visual.picture_id)
+            || (self.video_id.is_some() && self.video_id == visual.video_id)
+    }
+}
+
+impl From<&Visual> for AlbumItem {
+    fn from(visual: &Visual) -> Self {
+        Self::new(visual.picture_id, visual.video_id)
+    }
+}
+
+/// A named collection of pictures and videos curated by the user.
+#[derive(Debug, Clone)]
+pub struct Album {
+    pub album_id: AlbumId,
+
+    pub name: String,
+
+    /// Item chosen to represent the album.
+    pub cover: Option<AlbumItem>,
+
+    /// Items in the order chosen by the user.
+    pub items: Vec<AlbumItem>,
+}
+
+impl Album {
+    /// Item to represent the album. The first item if no cover has been chosen.
+    pub fn cover_or_first(&self) -> Option<AlbumItem> {
+        self.cover.or_else(|| self.items.first().copied())
+    }
+}
+
+/// Where to move items to within an album.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Placement {
+    Start,
+    End,
+}
+
+/// Move some items to the start or end of an album, keeping the relative
+/// order of both the moved items and the others.
+pub fn move_items(items: &[AlbumItem], moving: &[AlbumItem], placement: Placement) -> Vec<AlbumItem> {
+    let (moved, others): (Vec<AlbumItem>, Vec<AlbumItem>) =
+        items.iter().partition(|item| moving.contains(item));
+
+    match placement {
+        Placement::Start => moved.into_iter().chain(others).collect(),
+        Placement::End => others.into_iter().chain(moved).collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn item(id: i64) -> AlbumItem {
+        AlbumItem::new(Some(PictureId::new(id)), None)
+    }
+
+    #[test]
+    fn test_move_items() {
+        let items = vec![item(1), item(2), item(3), item(4)];
+
+        assert_eq!(
+            vec![item(2), item(4), item(1), item(3)],
+            move_items(&items, &[item(4), item(2)], Placement::Start)
+        );
+
+        assert_eq!(
+            vec![item(1), item(4), item(2), item(3)],
+            move_items(&items, &[item(3), item(2)], Placement::End)
+        );
+
+        // Items not in album are ignored.
+        assert_eq!(items, move_items(&items, &[item(5)], Placement::End));
+    }
+}
@@ -0,0 +1,238 @@
+// SPDX-FileCopyrightText: © 2024 David Bliss
+//
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use crate::albums::model::{Album, AlbumId, AlbumItem};
+use crate::photo::PictureId;
+use crate::video::VideoId;
+
+use anyhow::*;
+use rusqlite;
+use rusqlite::params;
+use rusqlite::Row;
+use std::result::Result::Ok;
+use std::sync::{Arc, Mutex};
+
+/// Repository of albums curated by the user.
+/// Repository is backed by a Sqlite database.
+#[derive(Debug, Clone)]
+pub struct Repository {
+    /// Connection to backing Sqlite database.
+    con: Arc<Mutex<rusqlite::Connection>>,
+}
+
+impl Repository {
+    pub fn open(con: Arc<Mutex<rusqlite::Connection>>) -> Result<Repository> {
+        let repo = Repository { con };
+        Ok(repo)
+    }
+
+    /// All albums, ordered by name, with their items.
+    pub fn all(&self) -> Result<Vec<Album>> {
+        let con = self.con.lock().unwrap();
+
+        let mut stmt = con.prepare(
+            "SELECT
+                album_id,
+                name,
+                cover_picture_id,
+                cover_video_id
+            FROM albums
+            ORDER BY name COLLATE NOCASE ASC",
+        )?;
+
+        let mut albums = stmt
+            .query_map([], |row| Self::to_album(row))?
+            .flatten()
+            .collect::<Vec<Album>>();
+
+        let mut stmt = con.prepare(
+            "SELECT
+                picture_id,
+                video_id
+            FROM album_items
+            WHERE album_id = ?1
+            ORDER BY position ASC",
+        )?;
+
+        for album in albums.iter_mut() {
+            album.items = stmt
+                .query_map([album.album_id.id()], |row| Self::to_album_item(row))?
+                .flatten()
+                .collect();
+        }
+
+        Ok(albums)
+    }
+
+    /// Album with its items.
+    pub fn get(&self, album_id: AlbumId) -> Result<Option<Album>> {
+        let albums = self.all()?;
+        Ok(albums.into_iter().find(|album| album.album_id == album_id))
+    }
+
+    /// Create a new empty album.
+    pub fn create(&mut self, name: &str) -> Result<AlbumId> {
+        let con = self.con.lock().unwrap();
+
+        let mut stmt = con.prepare_cached("INSERT INTO albums (name) VALUES (?1)")?;
+
+        stmt.execute([name])?;
+
+        Ok(AlbumId::new(con.last_insert_rowid()))
+    }
+
+    pub fn rename(&mut self, album_id: AlbumId, name: &str) -> Result<()> {
+        let con = self.con.lock().unwrap();
+
+        let mut stmt = con.prepare_cached(
+            "UPDATE albums SET name = ?2 WHERE album_id = ?1",
+        )?;
+
+        stmt.execute(params![album_id.id(), name])?;
+
+        Ok(())
+    }
+
+    /// Delete an album. No pictures or videos are deleted.
+    pub fn delete(&mut self, album_id: AlbumId) -> Result<()> {
+        let con = self.con.lock().unwrap();
+
+        let mut stmt = con.prepare_cached("DELETE FROM albums WHERE album_id = ?1")?;
+
+        stmt.execute([album_id.id()])?;
+
+        Ok(())
+    }
+
+    /// Choose the item to represent an album, or None to use the first item.
+    pub fn set_cover(&mut self, album_id: AlbumId, cover: Option<AlbumItem>) -> Result<()> {
+        let con = self.con.lock().unwrap();
+
+        let mut stmt = con.prepare_cached(
+            "UPDATE albums
+            SET cover_picture_id = ?2, cover_video_id = ?3
+            WHERE album_id = ?1",
+        )?;
+
+        stmt.execute(params![
+            album_id.id(),
+            cover.and_then(|x| x.picture_id).map(|x| x.id()),
+            cover.and_then(|x| x.video_id).map(|x| x.id()),
+        ])?;
+
+        Ok(())
+    }
+
+    /// Add items to the end of an album. Items already in the album are left where they are.
+    pub fn add_items(&mut self, album_id: AlbumId, items: Vec<AlbumItem>) -> Result<()> {
+        let mut con = self.con.lock().unwrap();
+        let tx = con.transaction()?;
+
+        {
+            let mut stmt = tx.prepare_cached(
+                "INSERT INTO album_items (album_id, picture_id, video_id, position)
+                VALUES (
+                    ?1, ?2, ?3,
+                    (SELECT COALESCE(MAX(position), -1) + 1 FROM album_items WHERE album_id = ?1)
+                )
+                ON CONFLICT DO NOTHING",
+            )?;
+
+            for item in items {
+                stmt.execute(params![
+                    album_id.id(),
+                    item.picture_id.map(|x| x.id()),
+                    item.video_id.map(|x| x.id()),
+                ])?;
+            }
+        }
+
+        tx.commit()?;
+
+        Ok(())
+    }
+
+    /// Remove items from an album. No pictures or videos are deleted.
+    pub fn remove_items(&mut self, album_id: AlbumId, items: Vec<AlbumItem>) -> Result<()> {
+        let mut con = self.con.lock().unwrap();
+        let tx = con.transaction()?;
+
+        {
+            let mut stmt = tx.prepare_cached(
+                "DELETE FROM album_items
+                WHERE album_id = ?1
+                AND (picture_id = ?2 OR video_id = ?3)",
+            )?;
+
+            for item in items {
+                stmt.execute(params![
+                    album_id.id(),
+                    item.picture_id.map(|x| x.id()),
+                    item.video_id.map(|x| x.id()),
+                ])?;
+            }
+        }
+
+        tx.commit()?;
+
+        Ok(())
+    }
+
+    /// Set the manual order of all items in an album.
+    pub fn set_order(&mut self, album_id: AlbumId, items: Vec<AlbumItem>) -> Result<()> {
+        let mut con = self.con.lock().unwrap();
+        let tx = con.transaction()?;
+
+        {
+            let mut stmt = tx.prepare_cached(
+                "UPDATE album_items
+                SET position = ?4
+                WHERE album_id = ?1
+                AND (picture_id = ?2 OR video_id = ?3)",
+            )?;
+
+            for (position, item) in items.into_iter().enumerate() {
+                stmt.execute(params![
+                    album_id.id(),
+                    item.picture_id.map(|x| x.id()),
+                    item.video_id.map(|x| x.id()),
+                    position,
+                ])?;
+            }
+        }
+
+        tx.commit()?;
+
+        Ok(())
+    }
+
+    fn to_album(row: &Row<'_>) -> rusqlite::Result<Album> {
+        let album_id = row.get("album_id").map(AlbumId::new)?;
+        let name = row.get("name")?;
+
+        let cover_picture_id: Option<PictureId> =
+            row.get("cover_picture_id").map(|x: Option<i64>| x.map(PictureId::new))?;
+        let cover_video_id: Option<VideoId> =
+            row.get("cover_video_id").map(|x: Option<i64>| x.map(VideoId::new))?;
+
+        let cover = if cover_picture_id.is_some() || cover_video_id.is_some() {
+            Some(AlbumItem::new(cover_picture_id, cover_video_id))
+        } else {
+            None
+        };
+
+        Ok(Album {
+            album_id,
+            name,
+            cover,
+            items: Vec::new(),
+        })
+    }
+
+    fn to_album_item(row: &Row<'_>) -> rusqlite::Result<AlbumItem> {
+        let picture_id = row.get("picture_id").map(|x: Option<i64>| x.map(PictureId::new))?;
+        let video_id = row.get("video_id").map(|x: Option<i64>| x.map(VideoId::new))?;
+        Ok(AlbumItem::new(picture_id, video_id))
+    }
+}
@@ -0,0 +1,164 @@
+// SPDX-FileCopyrightText: © 2024 David Bliss
+//
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//! Group pictures and videos into events, such as a party or a day out, by gaps
+//! in time between them and by distance between their locations. Events far from
+//! where most pictures and videos are taken are trips.
+
+use chrono::{DateTime, TimeDelta, Utc};
+use h3o::{CellIndex, LatLng, Resolution};
+use std::collections::HashMap;
+use std::ops::Range;
+
+/// Resolution for finding the usual location. Cells are about 250 square kilometres,
+/// so roughly the size of a town and its surroundings.
+const USUAL_LOCATION_RESOLUTION: Resolution = Resolution::Five;
+
+/// When and where a picture or video was taken.
+#[derive(Debug, Clone, Copy)]
+pub struct Moment {
+    pub timestamp: DateTime<Utc>,
+
+    pub location: Option<LatLng>,
+}
+
+/// Thresholds for splitting a timeline into events.
+#[derive(Debug, Clone)]
+pub struct Clustering {
+    /// A gap longer than this between consecutive items starts a new event.
+    pub max_gap: TimeDelta,
+
+    /// Moving further than this, in metres, from the last known location starts a new event.
+    pub max_distance: f64,
+
+    /// Groups with fewer items than this aren't events.
+    pub min_items: usize,
+
+    /// Events further than this, in metres, from the usual location are trips.
+    pub trip_distance: f64,
+}
+
+impl Default for Clustering {
+    fn default() -> Self {
+        Self {
+            max_gap: TimeDelta::hours(6),
+            max_distance: 50_000.0,
+            min_items: 5,
+            trip_distance: 100_000.0,
+        }
+    }
+}
+
+impl Clustering {
+    /// Split moments into events. Moments must be sorted by timestamp.
+    /// Returns the range of indexes into moments of each event.
+    pub fn cluster(&self, moments: &[Moment]) -> Vec<Range<usize>> {
+        let mut events = Vec::new();
+        let mut start = 0;
+        let mut last_location: Option<LatLng> = None;
+
+        for (index, pair) in moments.windows(2).enumerate() {
+            let (previous, current) = (&pair[0], &pair[1]);
+            last_location = previous.location.or(last_location);
+
+            let is_gap = current.timestamp - previous.timestamp > self.max_gap;
+
+            let is_far = last_location
+                .zip(current.location)
+                .is_some_and(|(a, b)| a.distance_m(b) > self.max_distance);
+
+            if is_gap || is_far {
+                events.push(start..index + 1);
+                start = index + 1;
+                last_location = None;
+            }
+        }
+
+        if start < moments.len() {
+            events.push(start..moments.len());
+        }
+
+        events.retain(|event| event.len() >= self.min_items);
+        events
+    }
+
+    /// An event is a trip if everywhere it happened is far from the usual location.
+    /// Events without any locations are never trips.
+    pub fn is_trip(&self, usual_location: LatLng, locations: &[LatLng]) -> bool {
+        !locations.is_empty()
+            && locations
+                .iter()
+                .all(|location| usual_location.distance_m(*location) > self.trip_distance)
+    }
+}
+
+/// Centre of the area with the most located items, such as the user's home town.
+pub fn usual_location(locations: impl IntoIterator<Item = LatLng>) -> Option<LatLng> {
+    let mut counts: HashMap<CellIndex, usize> = HashMap::new();
+
+    for location in locations {
+        *counts
+            .entry(location.to_cell(USUAL_LOCATION_RESOLUTION))
+            .or_default() += 1;
+    }
+
+    counts
+        .into_iter()
+        // Break ties by cell so the result is stable.
+        .max_by_key(|(cell, count)| (*count, *cell))
+        .map(|(cell, _)| LatLng::from(cell))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cluster() {
+        let t0 = DateTime::parse_from_rfc3339("2023-05-14T10:00:00Z")
+            .unwrap()
+            .to_utc();
+
+        let lisbon = LatLng::new(38.7223, -9.1393).unwrap();
+        let porto = LatLng::new(41.1579, -8.6291).unwrap();
+
+        let moment = |minutes: i64, location: Option<LatLng>| Moment {
+            timestamp: t0 + TimeDelta::minutes(minutes),
+            location,
+        };
+
+        let moments = vec![
+            // Morning in Lisbon
+            moment(0, Some(lisbon)),
+            moment(10, None),
+            moment(20, Some(lisbon)),
+            // Afternoon in Porto, without a long gap
+            moment(200, Some(porto)),
+            moment(210, None),
+            // Next day, after a long gap
+            moment(24 * 60, None),
+        ];
+
+        let clustering = Clustering {
+            min_items: 1,
+            ..Clustering::default()
+        };
+
+        assert_eq!(vec![0..3, 3..5, 5..6], clustering.cluster(&moments));
+
+        let clustering = Clustering {
+            min_items: 2,
+            ..Clustering::default()
+        };
+
+        assert_eq!(vec![0..3, 3..5], clustering.cluster(&moments));
+
+        let usual = usual_location(vec![lisbon, lisbon, porto]).unwrap();
+        assert!(usual.distance_m(lisbon) < 10_000.0);
+
+        assert!(clustering.is_trip(usual, &[porto]));
+        assert!(!clustering.is_trip(usual, &[porto, lisbon]));
+        assert!(!clustering.is_trip(usual, &[]));
+    }
+}
@@ -0,0 +1,157 @@
+// SPDX-FileCopyrightText: © 2024 David Bliss
+//
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use crate::photo::PictureId;
+use crate::video::VideoId;
+
+use anyhow::*;
+use rusqlite;
+use rusqlite::params;
+use std::result::Result::Ok;
+use std::sync::{Arc, Mutex};
+
+/// Repository of favourite pictures and videos.
+/// Favourites reference pictures and videos rather than visual items, so they
+/// survive the visual view being redefined.
+/// Repository is backed by a Sqlite database.
+#[derive(Debug, Clone)]
+pub struct Repository {
+    /// Connection to backing Sqlite database.
+    con: Arc<Mutex<rusqlite::Connection>>,
+}
+
+impl Repository {
+    pub fn open(con: Arc<Mutex<rusqlite::Connection>>) -> Result<Repository> {
+        let repo = Repository { con };
+        Ok(repo)
+    }
+
+    /// Is the picture or video of an item a favourite?
+    pub fn is_favourite(
+        &self,
+        picture_id: Option<PictureId>,
+        video_id: Option<VideoId>,
+    ) -> Result<bool> {
+        let con = self.con.lock().unwrap();
+
+        let mut stmt = con.prepare(
+            "SELECT EXISTS (
+                SELECT 1 FROM favourites
+                WHERE (picture_id = ?1 OR video_id = ?2)
+                AND is_removed IS FALSE
+            )",
+        )?;
+
+        let is_favourite = stmt.query_row(
+            params![picture_id.map(|x| x.id()), video_id.map(|x| x.id())],
+            |row| row.get(0),
+        )?;
+
+        Ok(is_favourite)
+    }
+
+    /// Add or remove an item from favourites.
+    /// Live photos have a picture and a video, so both are used.
+    /// Removed favourites are only marked as removed so that importing ratings
+    /// doesn't add them again.
+    pub fn set_favourite(
+        &mut self,
+        picture_id: Option<PictureId>,
+        video_id: Option<VideoId>,
+        is_favourite: bool,
+    ) -> Result<()> {
+        let con = self.con.lock().unwrap();
+
+        let picture_id = picture_id.map(|x| x.id());
+        let video_id = video_id.map(|x| x.id());
+
+        if is_favourite {
+            let mut stmt = con.prepare_cached(
+                "UPDATE favourites
+                SET
+                    is_removed = FALSE,
+                    source = 'user'
+                WHERE picture_id = ?1
+                OR video_id = ?2",
+            )?;
+            stmt.execute(params![picture_id, video_id])?;
+
+            let mut stmt = con.prepare_cached(
+                "INSERT INTO favourites (picture_id, video_id, source)
+                VALUES (?1, ?2, 'user')
+                ON CONFLICT DO NOTHING",
+            )?;
+            stmt.execute(params![picture_id, video_id])?;
+        } else {
+            let mut stmt = con.prepare_cached(
+                "UPDATE favourites
+                SET
+                    is_removed = TRUE
+                WHERE picture_id = ?1
+                OR video_id = ?2",
+            )?;
+            stmt.execute(params![picture_id, video_id])?;
+        }
+
+        Ok(())
+    }
+
+    /// Add pictures rated five stars in other photo managers to favourites.
+    /// Pictures the user has removed from favourites are skipped.
+    pub fn add_rated_pictures(&mut self, pictures: Vec<PictureId>) -> Result<()> {
+        let mut con = self.con.lock().unwrap();
+        let tx = con.transaction()?;
+
+        {
+            let mut stmt = tx.prepare_cached(
+                "INSERT INTO favourites (picture_id, source)
+                VALUES (?1, 'rating')
+                ON CONFLICT DO NOTHING",
+            )?;
+
+            for picture_id in pictures {
+                stmt.execute([picture_id.id()])?;
+            }
+        }
+
+        tx.commit()?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database;
+
+    fn repo() -> Repository {
+        let con = database::setup_in_memory().unwrap();
+        con.execute_batch(
+            "INSERT INTO pictures (picture_id, picture_path_b64, picture_path_lossy, link_path_b64, link_path_lossy)
+            VALUES (1, 'a', 'a', 'a', 'a')",
+        )
+        .unwrap();
+        Repository::open(Arc::new(Mutex::new(con))).unwrap()
+    }
+
+    #[test]
+    fn test_removed_favourite_not_added_by_rating() {
+        let mut repo = repo();
+        let picture_id = PictureId::new(1);
+
+        repo.add_rated_pictures(vec![picture_id]).unwrap();
+        assert!(repo.is_favourite(Some(picture_id), None).unwrap());
+
+        repo.set_favourite(Some(picture_id), None, false).unwrap();
+        assert!(!repo.is_favourite(Some(picture_id), None).unwrap());
+
+        // Rescanning metadata imports the rating again
+        repo.add_rated_pictures(vec![picture_id]).unwrap();
+        assert!(!repo.is_favourite(Some(picture_id), None).unwrap());
+
+        repo.set_favourite(Some(picture_id), None, true).unwrap();
+        assert!(repo.is_favourite(Some(picture_id), None).unwrap());
+    }
+}
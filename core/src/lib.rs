@@ -2,15 +2,26 @@
 //
 // SPDX-License-Identifier: GPL-3.0-or-later
 
+pub mod albums;
 pub mod database;
+pub mod events;
+pub mod favourites;
 pub mod machine_learning;
+pub mod memories;
+pub mod map_tiles;
 pub mod path_encoding;
 pub mod people;
 pub mod photo;
+pub mod places;
+pub mod search;
+pub mod smart_albums;
+pub mod tags;
 pub mod time;
+pub mod trash;
 pub mod video;
 pub mod visual;
 
+pub use albums::AlbumId;
 pub use people::model::FaceId;
 pub use people::model::PersonId;
 pub use photo::model::PictureId;
@@ -2,7 +2,10 @@
 //
 // SPDX-License-Identifier: GPL-3.0-or-later
 
-use crate::photo::model::PictureId;
+use crate::machine_learning::face_recognizer::FaceRecognizer;
+use crate::photo::model::{Orientation, PictureId};
+use crate::video::frames;
+use crate::video::model::VideoId;
 use anyhow::*;
 
 use std::path::{Path, PathBuf};
@@ -16,8 +19,12 @@ use rust_faces::{
 use gdk4::prelude::TextureExt;
 use image::DynamicImage;
 use itertools::*;
+use opencv::core::Mat;
 use tracing::{debug, error, info};
 
+/// Model name recorded for faces drawn by the user rather than detected.
+pub const MANUAL_MODEL_NAME: &str = "manual";
+
 #[derive(Debug, Clone)]
 pub struct Rect {
     pub x: f32,
@@ -79,9 +86,33 @@ impl Face {
     }
 }
 
+/// Number of frames to sample from each video when detecting faces.
+const VIDEO_FRAME_SAMPLES: usize = 10;
+
+/// A face followed across the sampled frames of a video.
+struct Track {
+    /// Index of the frame with the best detection of the face.
+    frame_index: usize,
+
+    /// Best detection of the face so far.
+    face: DetectedFace,
+
+    model_name: String,
+
+    /// Features of the best detection, for recognizing the face in other frames.
+    /// None if features couldn't be computed, in which case the face is never matched.
+    features: Option<Mat>,
+
+    /// Most recent bounds of the face.
+    bounds: Rect,
+}
+
 pub struct FaceExtractor {
     base_path: PathBuf,
 
+    /// Base path for faces detected in videos.
+    video_base_path: PathBuf,
+
     /// I think this is the "back model" trained on
     /// photos taken by the back camera of phones.
     blaze_face_640_model: Box<dyn rust_faces::FaceDetector>,
@@ -106,6 +137,9 @@ pub enum ExtractMode {
 
 impl FaceExtractor {
     pub fn build(base_path: &Path) -> Result<FaceExtractor> {
+        let video_base_path = PathBuf::from(base_path).join("video_faces");
+        std::fs::create_dir_all(&video_base_path)?;
+
         let base_path = PathBuf::from(base_path).join("photo_faces");
         std::fs::create_dir_all(&base_path)?;
 
@@ -149,6 +183,7 @@ impl FaceExtractor {
 
         Ok(FaceExtractor {
             base_path,
+            video_base_path,
             blaze_face_640_model,
             blaze_face_320_model,
             mtcnn_model,
@@ -169,6 +204,63 @@ impl FaceExtractor {
 
         let original_image = Self::open_image(picture_path).await?;
 
+        let faces = self.detect(&original_image, extract_mode);
+
+        debug!(
+            "Picture {} has {} faces. Found: {:?}",
+            picture_id,
+            faces.len(),
+            faces
+        );
+
+        let base_path = Self::face_dir(&self.base_path, picture_id.id());
+
+        faces.sort_by_key(|x| x.1.clone());
+
+        let mut faces_flat_grouped: Vec<(String, usize, DetectedFace)> = Vec::new();
+
+        for (model_name, chunk) in &faces.into_iter().chunk_by(|x| x.1.clone()) {
+            let mut vs = chunk
+                .enumerate()
+                .map(|(i, x)| (model_name.clone(), i, x.0))
+                .collect::<Vec<(String, usize, DetectedFace)>>();
+            faces_flat_grouped.append(&mut vs);
+        }
+
+        let faces = faces_flat_grouped
+            .into_iter()
+            .map(|(model_name, index, f)| {
+                let bounds = Rect {
+                    x: f.rect.x,
+                    y: f.rect.y,
+                    width: f.rect.width,
+                    height: f.rect.height,
+                };
+
+                Self::save_face(
+                    &original_image,
+                    &base_path,
+                    &format!("{}_{}", index, model_name),
+                    bounds,
+                    Self::centre(&f),
+                    f.confidence,
+                    f.landmarks,
+                    model_name,
+                )
+            })
+            .collect();
+
+        // Remove duplicates
+
+        Ok(faces)
+    }
+
+    /// Run face detection models over an image.
+    fn detect(
+        &self,
+        original_image: &DynamicImage,
+        extract_mode: ExtractMode,
+    ) -> Vec<(DetectedFace, String)> {
         let image = original_image.clone().into_rgb8().into_array3();
 
         let mut faces: Vec<(DetectedFace, String)> = vec![];
@@ -207,93 +299,102 @@ impl FaceExtractor {
             }
         }
 
-        debug!(
-            "Picture {} has {} faces. Found: {:?}",
-            picture_id,
-            faces.len(),
-            faces
-        );
+        faces
+    }
 
-        let base_path = {
-            // Create a directory per 1000 thumbnails
-            let partition = (picture_id.id() / 1000) as i32;
-            let partition = format!("{:0>4}", partition);
-            let file_name = format!("{}", picture_id);
-            self.base_path.join(partition).join(file_name)
-        };
+    /// Identify faces in a video by sampling frames at regular intervals.
+    /// Faces in different frames are the same face if face recognition says they are of
+    /// the same person. Each face is only returned once, using the frame where the face
+    /// was detected with the highest confidence.
+    pub fn extract_video_faces(
+        &self,
+        video_id: &VideoId,
+        video_path: &Path,
+        rotation: Option<i32>,
+        extract_mode: ExtractMode,
+        recognizer: &FaceRecognizer,
+    ) -> Result<Vec<Face>> {
+        info!(
+            "Detecting faces in video {:?} using {:?} model",
+            video_path, extract_mode
+        );
 
-        faces.sort_by_key(|x| x.1.clone());
+        let frames = frames::sample_frames(video_path, VIDEO_FRAME_SAMPLES)?;
 
-        let mut faces_flat_grouped: Vec<(String, usize, DetectedFace)> = Vec::new();
+        // Frames are decoded without the display matrix rotation applied, so rotate
+        // them to be upright or the face detection models won't find sideways faces.
+        let orientation = rotation.map(Orientation::from_degrees).unwrap_or_default();
 
-        for (model_name, chunk) in &faces.into_iter().chunk_by(|x| x.1.clone()) {
-            let mut vs = chunk
-                .enumerate()
-                .map(|(i, x)| (model_name.clone(), i, x.0))
-                .collect::<Vec<(String, usize, DetectedFace)>>();
-            faces_flat_grouped.append(&mut vs);
-        }
-
-        let faces = faces_flat_grouped
+        let frames: Vec<DynamicImage> = frames
             .into_iter()
-            .map(|(model_name, index, f)| {
-                if !base_path.exists() {
-                    let _ = std::fs::create_dir_all(&base_path);
-                }
+            .map(|(_, frame)| match orientation {
+                Orientation::East => frame.rotate270(),
+                Orientation::South => frame.rotate180(),
+                Orientation::West => frame.rotate90(),
+                _ => frame,
+            })
+            .collect();
 
-                // Extract face and save to thumbnail.
-                // The bounding box is pretty tight, so make it a bit bigger.
-                // Also, make the box a square.
+        let mut tracks: Vec<Track> = vec![];
 
-                let longest: f32 = if f.rect.width < f.rect.height {
-                    f.rect.width
-                } else {
-                    f.rect.height
+        for (frame_index, frame) in frames.iter().enumerate() {
+            for (face, model_name) in self.detect(frame, extract_mode) {
+                let bounds = Rect {
+                    x: face.rect.x,
+                    y: face.rect.y,
+                    width: face.rect.width,
+                    height: face.rect.height,
                 };
 
-                let mut longest = longest * 1.6;
-                let mut half_longest = longest / 2.0;
-
-                let (centre_x, centre_y) = Self::centre(&f);
-
-                // Normalize thumbnail to be a square.
-                if (original_image.width() as f32) < centre_x + half_longest {
-                    half_longest = original_image.width() as f32 - centre_x;
-                    longest = half_longest * 2.0;
-                }
-                if (original_image.height() as f32) < centre_y + half_longest {
-                    half_longest = original_image.height() as f32 - centre_y;
-                    longest = half_longest * 2.0;
-                }
-
-                if centre_x < half_longest {
-                    half_longest = centre_x;
-                    longest = half_longest * 2.0;
-                }
-
-                if centre_y < half_longest {
-                    half_longest = centre_y;
-                    longest = half_longest * 2.0;
+                let features = Self::frame_face_features(recognizer, frame, &face, &bounds)
+                    .inspect_err(|e| debug!("No features for face in video {}: {}", video_id, e))
+                    .ok();
+
+                // Several faces can be close enough to be the same person, such as siblings,
+                // so prefer the face that is in about the same place as in the last frame.
+                let track = tracks
+                    .iter_mut()
+                    .filter_map(|t| {
+                        let distance = Self::match_distance(recognizer, &t.features, &features)?;
+                        let overlap = Self::intersection_over_union(&t.bounds, &bounds);
+                        Some((t, distance, overlap))
+                    })
+                    .max_by(|(_, distance1, overlap1), (_, distance2, overlap2)| {
+                        overlap1
+                            .total_cmp(overlap2)
+                            .then(distance2.total_cmp(distance1))
+                    })
+                    .map(|(t, _, _)| t);
+
+                if let Some(track) = track {
+                    if face.confidence > track.face.confidence {
+                        track.frame_index = frame_index;
+                        track.face = face;
+                        track.model_name = model_name;
+                        track.features = features;
+                    }
+                    track.bounds = bounds;
+                } else {
+                    tracks.push(Track {
+                        frame_index,
+                        face,
+                        model_name,
+                        features,
+                        bounds,
+                    });
                 }
+            }
+        }
 
-                // Don't panic when x or y would be < zero
-                let mut x = centre_x - half_longest;
-                if x < 0.0 {
-                    x = 0.0;
-                }
-                let mut y = centre_y - half_longest;
-                if y < 0.0 {
-                    y = 0.0;
-                }
+        debug!("Video {} has {} faces.", video_id, tracks.len());
 
-                // FIXME use fast_image_resize instead of image-rs
-                let thumbnail =
-                    original_image.crop_imm(x as u32, y as u32, longest as u32, longest as u32);
-                let thumbnail = thumbnail.thumbnail(200, 200);
-                let thumbnail_path =
-                    base_path.join(format!("{}_{}_thumbnail.png", index, model_name));
-                let _ = thumbnail.save(&thumbnail_path);
+        let base_path = Self::face_dir(&self.video_base_path, video_id.id());
 
+        let faces = tracks
+            .into_iter()
+            .enumerate()
+            .map(|(index, track)| {
+                let f = track.face;
                 let bounds = Rect {
                     x: f.rect.x,
                     y: f.rect.y,
@@ -301,32 +402,285 @@ impl FaceExtractor {
                     height: f.rect.height,
                 };
 
-                let bounds_img = original_image.crop_imm(
-                    bounds.x as u32,
-                    bounds.y as u32,
-                    bounds.width as u32,
-                    bounds.height as u32,
-                );
-
-                let bounds_path = base_path.join(format!("{}_{}_original.png", index, model_name));
-                let _ = bounds_img.save(&bounds_path);
-
-                Face {
-                    thumbnail_path,
-                    bounds_path,
+                Self::save_face(
+                    &frames[track.frame_index],
+                    &base_path,
+                    &format!("{}_{}", index, track.model_name),
                     bounds,
-                    confidence: f.confidence,
-                    landmarks: f.landmarks,
-                    model_name,
-                }
+                    Self::centre(&f),
+                    f.confidence,
+                    f.landmarks,
+                    track.model_name,
+                )
             })
             .collect();
 
-        // Remove duplicates
-
         Ok(faces)
     }
 
+    /// Features of a face detected in a video frame, for matching it with faces
+    /// detected in other frames.
+    fn frame_face_features(
+        recognizer: &FaceRecognizer,
+        frame: &DynamicImage,
+        face: &DetectedFace,
+        bounds: &Rect,
+    ) -> Result<Mat> {
+        let Some(landmarks) = face.landmarks.as_ref().filter(|x| x.len() == 5) else {
+            bail!("Face has no landmarks");
+        };
+
+        // Face recognition reads the face from a file, as it does for faces in photos.
+        let face_file = tempfile::Builder::new().suffix(".png").tempfile()?;
+        frame
+            .crop_imm(
+                bounds.x as u32,
+                bounds.y as u32,
+                bounds.width as u32,
+                bounds.height as u32,
+            )
+            .save(face_file.path())?;
+
+        // NOTE landmarks are relative to the frame, not the bounds, so must translate x and y.
+        let mut face_box = vec![0.0, 0.0, bounds.width, bounds.height];
+        for (x, y) in landmarks {
+            face_box.push(x - bounds.x);
+            face_box.push(y - bounds.y);
+        }
+        face_box.push(face.confidence);
+
+        let face_box = Mat::from_exact_iter(face_box.into_iter())?;
+
+        recognizer.features(face_file.path(), &face_box)
+    }
+
+    /// Distance between two faces, if both have features and they are of the same person.
+    fn match_distance(
+        recognizer: &FaceRecognizer,
+        features1: &Option<Mat>,
+        features2: &Option<Mat>,
+    ) -> Option<f64> {
+        let (Some(features1), Some(features2)) = (features1, features2) else {
+            return None;
+        };
+
+        recognizer
+            .match_distance(features1, features2)
+            .inspect_err(|e| error!("Failed matching faces: {}", e))
+            .ok()
+            .flatten()
+    }
+
+    /// Ratio of the area of the intersection of two rectangles to the area of their union.
+    /// Zero for disjoint rectangles and one for identical rectangles.
+    fn intersection_over_union(a: &Rect, b: &Rect) -> f32 {
+        let left = a.x.max(b.x);
+        let top = a.y.max(b.y);
+        let right = (a.x + a.width).min(b.x + b.width);
+        let bottom = (a.y + a.height).min(b.y + b.height);
+
+        if right <= left || bottom <= top {
+            return 0.0;
+        }
+
+        let intersection = (right - left) * (bottom - top);
+        let union = a.width * a.height + b.width * b.height - intersection;
+
+        if union <= 0.0 {
+            0.0
+        } else {
+            intersection / union
+        }
+    }
+
+    /// Build a face from bounds manually drawn by the user, for faces the
+    /// detection models missed. Thumbnails are generated in the same way as
+    /// for detected faces.
+    /// Bounds are relative to the picture after EXIF orientation has been applied.
+    pub async fn extract_manual_face(
+        base_path: &Path,
+        picture_id: &PictureId,
+        picture_path: &Path,
+        bounds: Rect,
+    ) -> Result<Face> {
+        info!("Adding manual face to {:?} at {:?}", picture_path, bounds);
+
+        let original_image = Self::open_image(picture_path).await?;
+
+        let Some(bounds) = Self::clamp_bounds(
+            &bounds,
+            original_image.width() as f32,
+            original_image.height() as f32,
+        ) else {
+            bail!("Face bounds {:?} are outside of picture", bounds);
+        };
+
+        let Rect {
+            x,
+            y,
+            width,
+            height,
+        } = bounds;
+
+        let base_path = Self::face_dir(&base_path.join("photo_faces"), picture_id.id());
+        std::fs::create_dir_all(&base_path)?;
+
+        // Manual faces survive a re-scan, so pick an index that doesn't
+        // collide with an existing manual face.
+        let model_name = String::from(MANUAL_MODEL_NAME);
+        let index = (0..)
+            .find(|i| {
+                !base_path
+                    .join(format!("{}_{}_thumbnail.png", i, model_name))
+                    .exists()
+            })
+            .unwrap_or_default();
+
+        // No landmarks from a hand-drawn box, so estimate them from typical
+        // proportions of a face so that face recognition can still align the face.
+        let landmarks = vec![
+            (x + width * 0.3, y + height * 0.4),
+            (x + width * 0.7, y + height * 0.4),
+            (x + width * 0.5, y + height * 0.6),
+            (x + width * 0.35, y + height * 0.8),
+            (x + width * 0.65, y + height * 0.8),
+        ];
+
+        let centre = (x + width / 2.0, y + height * 0.4);
+
+        let face = Self::save_face(
+            &original_image,
+            &base_path,
+            &format!("{}_{}", index, model_name),
+            bounds,
+            centre,
+            1.0,
+            Some(landmarks),
+            model_name,
+        );
+
+        Ok(face)
+    }
+
+    /// Crop bounds to the part inside an image of the given size.
+    /// None if less than a pixel of the bounds is inside the image.
+    fn clamp_bounds(bounds: &Rect, image_width: f32, image_height: f32) -> Option<Rect> {
+        let x1 = bounds.x.max(0.0);
+        let y1 = bounds.y.max(0.0);
+        let x2 = (bounds.x + bounds.width).min(image_width);
+        let y2 = (bounds.y + bounds.height).min(image_height);
+
+        let width = x2 - x1;
+        let height = y2 - y1;
+
+        if width < 1.0 || height < 1.0 {
+            return None;
+        }
+
+        Some(Rect {
+            x: x1,
+            y: y1,
+            width,
+            height,
+        })
+    }
+
+    /// Directory for the faces of a picture or video.
+    fn face_dir(base_path: &Path, id: i64) -> PathBuf {
+        // Create a directory per 1000 thumbnails
+        let partition = (id / 1000) as i32;
+        let partition = format!("{:0>4}", partition);
+        let file_name = format!("{}", id);
+        base_path.join(partition).join(file_name)
+    }
+
+    /// Save thumbnail and bounds images for a face.
+    #[allow(clippy::too_many_arguments)]
+    fn save_face(
+        original_image: &DynamicImage,
+        base_path: &Path,
+        file_prefix: &str,
+        bounds: Rect,
+        centre: (f32, f32),
+        confidence: f32,
+        landmarks: Option<Vec<(f32, f32)>>,
+        model_name: String,
+    ) -> Face {
+        if !base_path.exists() {
+            let _ = std::fs::create_dir_all(base_path);
+        }
+
+        // Extract face and save to thumbnail.
+        // The bounding box is pretty tight, so make it a bit bigger.
+        // Also, make the box a square.
+
+        let longest: f32 = if bounds.width < bounds.height {
+            bounds.width
+        } else {
+            bounds.height
+        };
+
+        let mut longest = longest * 1.6;
+        let mut half_longest = longest / 2.0;
+
+        let (centre_x, centre_y) = centre;
+
+        // Normalize thumbnail to be a square.
+        if (original_image.width() as f32) < centre_x + half_longest {
+            half_longest = original_image.width() as f32 - centre_x;
+            longest = half_longest * 2.0;
+        }
+        if (original_image.height() as f32) < centre_y + half_longest {
+            half_longest = original_image.height() as f32 - centre_y;
+            longest = half_longest * 2.0;
+        }
+
+        if centre_x < half_longest {
+            half_longest = centre_x;
+            longest = half_longest * 2.0;
+        }
+
+        if centre_y < half_longest {
+            half_longest = centre_y;
+            longest = half_longest * 2.0;
+        }
+
+        // Don't panic when x or y would be < zero
+        let mut x = centre_x - half_longest;
+        if x < 0.0 {
+            x = 0.0;
+        }
+        let mut y = centre_y - half_longest;
+        if y < 0.0 {
+            y = 0.0;
+        }
+
+        // FIXME use fast_image_resize instead of image-rs
+        let thumbnail = original_image.crop_imm(x as u32, y as u32, longest as u32, longest as u32);
+        let thumbnail = thumbnail.thumbnail(200, 200);
+        let thumbnail_path = base_path.join(format!("{}_thumbnail.png", file_prefix));
+        let _ = thumbnail.save(&thumbnail_path);
+
+        let bounds_img = original_image.crop_imm(
+            bounds.x as u32,
+            bounds.y as u32,
+            bounds.width as u32,
+            bounds.height as u32,
+        );
+
+        let bounds_path = base_path.join(format!("{}_original.png", file_prefix));
+        let _ = bounds_img.save(&bounds_path);
+
+        Face {
+            thumbnail_path,
+            bounds_path,
+            bounds,
+            confidence,
+            landmarks,
+            model_name,
+        }
+    }
+
     /// Remove any duplicates where being a duplicate is determined by
     /// the distance between centres being below a certain threshold
     fn remove_duplicates(
@@ -422,6 +776,74 @@ impl FaceExtractor {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_intersection_over_union() {
+        let a = Rect {
+            x: 0.0,
+            y: 0.0,
+            width: 10.0,
+            height: 10.0,
+        };
+
+        let b = Rect {
+            x: 5.0,
+            y: 0.0,
+            width: 10.0,
+            height: 10.0,
+        };
+
+        let c = Rect {
+            x: 20.0,
+            y: 20.0,
+            width: 10.0,
+            height: 10.0,
+        };
+
+        assert_eq!(FaceExtractor::intersection_over_union(&a, &a), 1.0);
+        assert_eq!(FaceExtractor::intersection_over_union(&a, &b), 50.0 / 150.0);
+        assert_eq!(FaceExtractor::intersection_over_union(&a, &c), 0.0);
+    }
+
+    #[test]
+    fn test_clamp_bounds() {
+        let inside = Rect {
+            x: 10.0,
+            y: 20.0,
+            width: 30.0,
+            height: 40.0,
+        };
+        let clamped = FaceExtractor::clamp_bounds(&inside, 100.0, 100.0).unwrap();
+        assert_eq!((clamped.x, clamped.y, clamped.width, clamped.height), (10.0, 20.0, 30.0, 40.0));
+
+        // Spills over the left and top edges, so must be cropped rather than moved.
+        let top_left = Rect {
+            x: -10.0,
+            y: -20.0,
+            width: 30.0,
+            height: 40.0,
+        };
+        let clamped = FaceExtractor::clamp_bounds(&top_left, 100.0, 100.0).unwrap();
+        assert_eq!((clamped.x, clamped.y, clamped.width, clamped.height), (0.0, 0.0, 20.0, 20.0));
+
+        // Spills over the right and bottom edges
+        let bottom_right = Rect {
+            x: 90.0,
+            y: 70.0,
+            width: 30.0,
+            height: 40.0,
+        };
+        let clamped = FaceExtractor::clamp_bounds(&bottom_right, 100.0, 100.0).unwrap();
+        assert_eq!((clamped.x, clamped.y, clamped.width, clamped.height), (90.0, 70.0, 10.0, 30.0));
+
+        let outside = Rect {
+            x: -50.0,
+            y: 10.0,
+            width: 30.0,
+            height: 40.0,
+        };
+        assert!(FaceExtractor::clamp_bounds(&outside, 100.0, 100.0).is_none());
+    }
+
     #[test]
     fn test_find_faces() {
         let dir = env!("CARGO_MANIFEST_DIR");
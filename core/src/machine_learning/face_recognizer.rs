@@ -71,6 +71,39 @@ impl FaceRecognizer {
         Ok(recognizer)
     }
 
+    /// Features of a face for comparing it with other faces rather than with known people.
+    /// The image must be cropped to the face bounds, and the face box and landmarks must be
+    /// relative to that crop, as with `DetectedFace::landmarks_as_mat`.
+    pub fn features(&self, face_path: &Path, face_box: &Mat) -> Result<Mat> {
+        let mut face_recognizer =
+            FaceRecognizerSF::create_def(&self.model_path.to_string_lossy(), "")?;
+
+        let face_img = imgcodecs::imread_def(&face_path.to_string_lossy())?;
+
+        let mut aligned_face = Mat::default();
+        face_recognizer.align_crop(&face_img, face_box, &mut aligned_face)?;
+
+        let mut face_features = Mat::default();
+        face_recognizer.feature(&aligned_face, &mut face_features)?;
+
+        Ok(face_features)
+    }
+
+    /// Distance between the features of two faces, if they are close enough
+    /// to be faces of the same person.
+    pub fn match_distance(&self, features1: &Mat, features2: &Mat) -> Result<Option<f64>> {
+        let mut face_recognizer =
+            FaceRecognizerSF::create_def(&self.model_path.to_string_lossy(), "")?;
+
+        let l2_score = face_recognizer.match_(
+            features1,
+            features2,
+            FaceRecognizerSF_DisType::FR_NORM_L2.into(),
+        )?;
+
+        Ok(Some(l2_score).filter(|x| *x <= Self::L2NORM_SIMILAR_THRESH))
+    }
+
     pub fn recognize(&self, unknown_face: &DetectedFace) -> Result<Option<PersonId>> {
         let mut face_recognizer =
             FaceRecognizerSF::create_def(&self.model_path.to_string_lossy(), "")?;
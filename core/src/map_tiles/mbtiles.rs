@@ -0,0 +1,139 @@
+// SPDX-FileCopyrightText: © 2024 David Bliss
+//
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use super::TileArchive;
+use anyhow::*;
+use rusqlite;
+use rusqlite::params;
+use rusqlite::OpenFlags;
+use rusqlite::OptionalExtension;
+use std::path::Path;
+use std::result::Result::Ok;
+use std::sync::Mutex;
+use tracing::info;
+
+/// Raster tiles in an MBTiles archive, which is a Sqlite database.
+/// See https://github.com/mapbox/mbtiles-spec
+#[derive(Debug)]
+pub struct MbTiles {
+    con: Mutex<rusqlite::Connection>,
+    min_zoom: u8,
+    max_zoom: u8,
+}
+
+impl MbTiles {
+    pub fn open(path: &Path) -> Result<Self> {
+        let con = rusqlite::Connection::open_with_flags(
+            path,
+            OpenFlags::SQLITE_OPEN_READ_ONLY | OpenFlags::SQLITE_OPEN_NO_MUTEX,
+        )
+        .with_context(|| format!("Failed opening {:?}", path))?;
+
+        let format: Option<String> = con
+            .query_row(
+                "SELECT value FROM metadata WHERE name = 'format'",
+                [],
+                |row| row.get(0),
+            )
+            .optional()?;
+
+        if format.as_deref() == Some("pbf") {
+            return Err(anyhow!("Vector tiles are not supported: {:?}", path));
+        }
+
+        let (min_zoom, max_zoom): (Option<u8>, Option<u8>) = con.query_row(
+            "SELECT MIN(zoom_level), MAX(zoom_level) FROM tiles",
+            [],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )?;
+
+        let (Some(min_zoom), Some(max_zoom)) = (min_zoom, max_zoom) else {
+            return Err(anyhow!("No tiles in {:?}", path));
+        };
+
+        info!(
+            "Opened MBTiles archive {:?} with zoom levels {} to {}",
+            path, min_zoom, max_zoom
+        );
+
+        Ok(MbTiles {
+            con: Mutex::new(con),
+            min_zoom,
+            max_zoom,
+        })
+    }
+
+    /// MBTiles uses the TMS scheme with rows counted from the bottom,
+    /// so flip the XYZ row.
+    fn tms_row(zoom: u8, y: u32) -> u32 {
+        (1u32 << zoom) - 1 - y
+    }
+}
+
+impl TileArchive for MbTiles {
+    fn tile(&self, zoom: u8, x: u32, y: u32) -> Result<Option<Vec<u8>>> {
+        if zoom > 31 || y >= (1u32 << zoom) {
+            return Ok(None);
+        }
+
+        let con = self.con.lock().unwrap();
+        let mut stmt = con.prepare_cached(
+            "SELECT tile_data
+            FROM tiles
+            WHERE zoom_level = ?1
+            AND tile_column = ?2
+            AND tile_row = ?3",
+        )?;
+
+        let data = stmt
+            .query_row(params![zoom, x, Self::tms_row(zoom, y)], |row| row.get(0))
+            .optional()?;
+
+        Ok(data)
+    }
+
+    fn min_zoom(&self) -> u8 {
+        self.min_zoom
+    }
+
+    fn max_zoom(&self) -> u8 {
+        self.max_zoom
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tile() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("test.mbtiles");
+
+        {
+            let con = rusqlite::Connection::open(&path).unwrap();
+            con.execute_batch(
+                "CREATE TABLE metadata (name TEXT, value TEXT);
+                CREATE TABLE tiles (zoom_level INTEGER, tile_column INTEGER, tile_row INTEGER, tile_data BLOB);
+                INSERT INTO metadata VALUES ('format', 'png');
+                INSERT INTO tiles VALUES (2, 1, 3, X'01');
+                INSERT INTO tiles VALUES (3, 5, 2, X'02');",
+            )
+            .unwrap();
+        }
+
+        let tiles = MbTiles::open(&path).unwrap();
+        assert_eq!(2, tiles.min_zoom());
+        assert_eq!(3, tiles.max_zoom());
+
+        // TMS row 3 at zoom 2 is XYZ row 0
+        assert_eq!(Some(vec![1]), tiles.tile(2, 1, 0).unwrap());
+
+        // TMS row 2 at zoom 3 is XYZ row 5
+        assert_eq!(Some(vec![2]), tiles.tile(3, 5, 5).unwrap());
+
+        assert_eq!(None, tiles.tile(3, 5, 2).unwrap());
+        assert_eq!(None, tiles.tile(2, 1, 4).unwrap());
+    }
+}
@@ -0,0 +1,39 @@
+// SPDX-FileCopyrightText: © 2024 David Bliss
+//
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//! Offline map tiles read from a local tile archive.
+//! Supports raster tiles in MBTiles and PMTiles (version 3) archives.
+
+pub mod mbtiles;
+pub mod pmtiles;
+
+use anyhow::*;
+use std::path::Path;
+
+/// An archive of map tiles addressed by zoom level and XYZ tile coordinates.
+pub trait TileArchive: Send + Sync + std::fmt::Debug {
+    /// Gets the encoded image data for a tile, or None if the archive doesn't have the tile.
+    /// Coordinates follow the XYZ (slippy map) scheme with the origin at the top left.
+    fn tile(&self, zoom: u8, x: u32, y: u32) -> Result<Option<Vec<u8>>>;
+
+    /// Lowest zoom level with tiles.
+    fn min_zoom(&self) -> u8;
+
+    /// Highest zoom level with tiles.
+    fn max_zoom(&self) -> u8;
+}
+
+/// Open a tile archive. Archive type is determined by the file extension.
+pub fn open(path: &Path) -> Result<Box<dyn TileArchive>> {
+    let extension = path
+        .extension()
+        .and_then(|x| x.to_str())
+        .map(|x| x.to_lowercase());
+
+    match extension.as_deref() {
+        Some("mbtiles") => Ok(Box::new(mbtiles::MbTiles::open(path)?)),
+        Some("pmtiles") => Ok(Box::new(pmtiles::PmTiles::open(path)?)),
+        _ => Err(anyhow!("Unsupported tile archive: {:?}", path)),
+    }
+}
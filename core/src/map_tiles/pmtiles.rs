@@ -0,0 +1,366 @@
+// SPDX-FileCopyrightText: © 2024 David Bliss
+//
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use super::TileArchive;
+use anyhow::*;
+use flate2::read::GzDecoder;
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom};
+use std::path::Path;
+use std::result::Result::Ok;
+use std::sync::Mutex;
+use tracing::info;
+
+const HEADER_LENGTH: usize = 127;
+
+/// Leaf directories can nest, but the spec limits the depth to three.
+const MAX_DIRECTORY_DEPTH: usize = 4;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Compression {
+    None,
+    Gzip,
+}
+
+impl Compression {
+    fn from_byte(value: u8) -> Result<Self> {
+        match value {
+            1 => Ok(Compression::None),
+            2 => Ok(Compression::Gzip),
+            _ => Err(anyhow!("Unsupported PMTiles compression: {}", value)),
+        }
+    }
+
+    fn decompress(&self, data: Vec<u8>) -> Result<Vec<u8>> {
+        match self {
+            Compression::None => Ok(data),
+            Compression::Gzip => {
+                let mut decompressed = Vec::new();
+                GzDecoder::new(&data[..]).read_to_end(&mut decompressed)?;
+                Ok(decompressed)
+            }
+        }
+    }
+}
+
+#[derive(Debug)]
+struct Header {
+    root_dir_offset: u64,
+    root_dir_length: u64,
+    leaf_dirs_offset: u64,
+    tile_data_offset: u64,
+    internal_compression: Compression,
+    tile_compression: Compression,
+    min_zoom: u8,
+    max_zoom: u8,
+}
+
+impl Header {
+    fn parse(bytes: &[u8]) -> Result<Self> {
+        if bytes.len() < HEADER_LENGTH || &bytes[0..7] != b"PMTiles" {
+            return Err(anyhow!("Not a PMTiles archive"));
+        }
+
+        if bytes[7] != 3 {
+            return Err(anyhow!("Unsupported PMTiles version: {}", bytes[7]));
+        }
+
+        let u64_at = |offset: usize| {
+            let mut value = [0u8; 8];
+            value.copy_from_slice(&bytes[offset..offset + 8]);
+            u64::from_le_bytes(value)
+        };
+
+        // Tile types are 1 for vector tiles and 2 to 5 for raster images.
+        let tile_type = bytes[99];
+        if tile_type == 1 {
+            return Err(anyhow!("Vector tiles are not supported"));
+        }
+
+        Ok(Header {
+            root_dir_offset: u64_at(8),
+            root_dir_length: u64_at(16),
+            leaf_dirs_offset: u64_at(40),
+            tile_data_offset: u64_at(56),
+            internal_compression: Compression::from_byte(bytes[97])?,
+            tile_compression: Compression::from_byte(bytes[98])?,
+            min_zoom: bytes[100],
+            max_zoom: bytes[101],
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Entry {
+    tile_id: u64,
+    offset: u64,
+    length: u64,
+
+    /// Number of consecutive tiles sharing the same data. Zero for a leaf directory.
+    run_length: u64,
+}
+
+/// Raster tiles in a PMTiles version 3 archive.
+/// See https://github.com/protomaps/PMTiles/blob/main/spec/v3/spec.md
+#[derive(Debug)]
+pub struct PmTiles {
+    file: Mutex<File>,
+    header: Header,
+    root: Vec<Entry>,
+}
+
+impl PmTiles {
+    pub fn open(path: &Path) -> Result<Self> {
+        let mut file = File::open(path).with_context(|| format!("Failed opening {:?}", path))?;
+
+        let mut bytes = [0u8; HEADER_LENGTH];
+        file.read_exact(&mut bytes)?;
+        let header = Header::parse(&bytes).with_context(|| format!("Invalid {:?}", path))?;
+
+        let root = Self::read(&mut file, header.root_dir_offset, header.root_dir_length)?;
+        let root = header.internal_compression.decompress(root)?;
+        let root = parse_directory(&root)?;
+
+        info!(
+            "Opened PMTiles archive {:?} with zoom levels {} to {}",
+            path, header.min_zoom, header.max_zoom
+        );
+
+        Ok(PmTiles {
+            file: Mutex::new(file),
+            header,
+            root,
+        })
+    }
+
+    fn read(file: &mut File, offset: u64, length: u64) -> Result<Vec<u8>> {
+        let mut data = vec![0u8; length as usize];
+        file.seek(SeekFrom::Start(offset))?;
+        file.read_exact(&mut data)?;
+        Ok(data)
+    }
+}
+
+impl TileArchive for PmTiles {
+    fn tile(&self, zoom: u8, x: u32, y: u32) -> Result<Option<Vec<u8>>> {
+        if zoom > 26 || x >= (1u32 << zoom) || y >= (1u32 << zoom) {
+            return Ok(None);
+        }
+
+        let tile_id = zxy_to_tile_id(zoom, x, y);
+        let mut file = self.file.lock().unwrap();
+
+        let mut directory = self.root.clone();
+
+        for _ in 0..MAX_DIRECTORY_DEPTH {
+            let Some(entry) = find_entry(&directory, tile_id) else {
+                return Ok(None);
+            };
+
+            if entry.run_length > 0 {
+                let data = Self::read(
+                    &mut file,
+                    self.header.tile_data_offset + entry.offset,
+                    entry.length,
+                )?;
+                return self.header.tile_compression.decompress(data).map(Some);
+            }
+
+            let leaf = Self::read(
+                &mut file,
+                self.header.leaf_dirs_offset + entry.offset,
+                entry.length,
+            )?;
+            let leaf = self.header.internal_compression.decompress(leaf)?;
+            directory = parse_directory(&leaf)?;
+        }
+
+        Err(anyhow!("PMTiles directories nested too deeply"))
+    }
+
+    fn min_zoom(&self) -> u8 {
+        self.header.min_zoom
+    }
+
+    fn max_zoom(&self) -> u8 {
+        self.header.max_zoom
+    }
+}
+
+/// Convert XYZ tile coordinates to a PMTiles tile ID.
+/// Tile IDs count every tile at lower zoom levels and then follow a Hilbert curve
+/// through the tiles of the zoom level.
+fn zxy_to_tile_id(zoom: u8, x: u32, y: u32) -> u64 {
+    // Tiles in all lower zoom levels: sum of 4^z for z < zoom.
+    let base = ((1u64 << (2 * zoom as u64)) - 1) / 3;
+
+    let n = 1u64 << zoom;
+    let (mut x, mut y) = (x as u64, y as u64);
+    let mut d = 0u64;
+    let mut s = n / 2;
+
+    while s > 0 {
+        let rx = u64::from(x & s > 0);
+        let ry = u64::from(y & s > 0);
+        d += s * s * ((3 * rx) ^ ry);
+
+        // Rotate quadrant
+        if ry == 0 {
+            if rx == 1 {
+                x = n - 1 - x;
+                y = n - 1 - y;
+            }
+            std::mem::swap(&mut x, &mut y);
+        }
+
+        s /= 2;
+    }
+
+    base + d
+}
+
+/// Read an unsigned LEB128 varint.
+fn read_varint(bytes: &[u8], position: &mut usize) -> Result<u64> {
+    let mut value = 0u64;
+    let mut shift = 0;
+
+    loop {
+        let Some(byte) = bytes.get(*position) else {
+            return Err(anyhow!("Truncated varint"));
+        };
+        *position += 1;
+
+        if shift >= 64 {
+            return Err(anyhow!("Varint too long"));
+        }
+
+        value |= u64::from(byte & 0x7f) << shift;
+        if byte & 0x80 == 0 {
+            return Ok(value);
+        }
+        shift += 7;
+    }
+}
+
+/// Parse a decompressed directory.
+/// Entries are stored column-wise: delta encoded tile IDs, run lengths,
+/// lengths, and then offsets, where an offset of zero means the data
+/// immediately follows the previous entry.
+fn parse_directory(bytes: &[u8]) -> Result<Vec<Entry>> {
+    let mut position = 0;
+    let count = read_varint(bytes, &mut position)? as usize;
+
+    // Guard against absurd allocations from a corrupt file
+    if count > bytes.len() {
+        return Err(anyhow!("Invalid PMTiles directory length: {}", count));
+    }
+
+    let mut entries = vec![
+        Entry {
+            tile_id: 0,
+            offset: 0,
+            length: 0,
+            run_length: 0
+        };
+        count
+    ];
+
+    let mut tile_id = 0;
+    for entry in entries.iter_mut() {
+        tile_id += read_varint(bytes, &mut position)?;
+        entry.tile_id = tile_id;
+    }
+
+    for entry in entries.iter_mut() {
+        entry.run_length = read_varint(bytes, &mut position)?;
+    }
+
+    for entry in entries.iter_mut() {
+        entry.length = read_varint(bytes, &mut position)?;
+    }
+
+    for i in 0..count {
+        let value = read_varint(bytes, &mut position)?;
+        entries[i].offset = if value == 0 && i > 0 {
+            entries[i - 1].offset + entries[i - 1].length
+        } else {
+            value.saturating_sub(1)
+        };
+    }
+
+    Ok(entries)
+}
+
+/// Find entry for a tile. Either the tile itself, a run of tiles including the tile,
+/// or the leaf directory that may contain the tile.
+fn find_entry(entries: &[Entry], tile_id: u64) -> Option<&Entry> {
+    // Index of last entry with a tile ID no greater than the wanted tile ID.
+    let index = entries
+        .partition_point(|entry| entry.tile_id <= tile_id)
+        .checked_sub(1)?;
+
+    let entry = &entries[index];
+
+    if entry.run_length == 0 || tile_id < entry.tile_id + entry.run_length {
+        Some(entry)
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_zxy_to_tile_id() {
+        assert_eq!(0, zxy_to_tile_id(0, 0, 0));
+        assert_eq!(1, zxy_to_tile_id(1, 0, 0));
+        assert_eq!(2, zxy_to_tile_id(1, 0, 1));
+        assert_eq!(3, zxy_to_tile_id(1, 1, 1));
+        assert_eq!(4, zxy_to_tile_id(1, 1, 0));
+        assert_eq!(5, zxy_to_tile_id(2, 0, 0));
+
+        // Every tile at a zoom level has a distinct ID in the zoom level's range
+        let mut ids: Vec<u64> = (0..8)
+            .flat_map(|x| (0..8).map(move |y| zxy_to_tile_id(3, x, y)))
+            .collect();
+        ids.sort();
+        assert_eq!((21..85).collect::<Vec<u64>>(), ids);
+    }
+
+    #[test]
+    fn test_parse_directory() {
+        // Three entries: tile IDs 1, 2, and 10 (a leaf directory).
+        // Second entry's data directly follows the first.
+        let bytes = [
+            3, // count
+            1, 1, 8, // tile ID deltas
+            1, 2, 0, // run lengths
+            10, 20, 30, // lengths
+            1, 0, 6, // offsets
+        ];
+
+        let entries = parse_directory(&bytes).unwrap();
+        assert_eq!(3, entries.len());
+        assert_eq!(
+            Entry { tile_id: 1, offset: 0, length: 10, run_length: 1 },
+            entries[0]
+        );
+        assert_eq!(
+            Entry { tile_id: 2, offset: 10, length: 20, run_length: 2 },
+            entries[1]
+        );
+        assert_eq!(
+            Entry { tile_id: 10, offset: 5, length: 30, run_length: 0 },
+            entries[2]
+        );
+
+        assert_eq!(None, find_entry(&entries, 0));
+        assert_eq!(Some(&entries[0]), find_entry(&entries, 1));
+        assert_eq!(Some(&entries[1]), find_entry(&entries, 3));
+        assert_eq!(None, find_entry(&entries, 4));
+        assert_eq!(Some(&entries[2]), find_entry(&entries, 100));
+    }
+}
@@ -0,0 +1,180 @@
+// SPDX-FileCopyrightText: © 2024 David Bliss
+//
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//! "On this day" memories. Pictures and videos taken on today's month and day
+//! in previous years.
+
+use chrono::{Datelike, NaiveDate};
+use std::path::Path;
+
+/// Most memories to show from any one year.
+pub const MAX_PER_YEAR: usize = 12;
+
+/// Start of the names of screenshots and screen recordings, or of the folders
+/// they are saved to, on common phones and desktops. Lowercase.
+const SCREENSHOT_PREFIXES: [&str; 6] = [
+    "screenshot",
+    "screen shot",
+    "screen_shot",
+    "screencast",
+    "screen recording",
+    "screen_recording",
+];
+
+/// Was a date on the same month and day as today, but in an earlier year?
+/// On the 28th of February in a year that isn't a leap year, the 29th of
+/// February of earlier leap years also counts so those memories aren't lost.
+pub fn is_on_this_day(date: NaiveDate, today: NaiveDate) -> bool {
+    if date.year() >= today.year() || date.month() != today.month() {
+        return false;
+    }
+
+    let is_leap_day_fallback = today.month() == 2
+        && today.day() == 28
+        && date.day() == 29
+        && NaiveDate::from_ymd_opt(today.year(), 2, 29).is_none();
+
+    date.day() == today.day() || is_leap_day_fallback
+}
+
+/// Is a file a screenshot or screen recording, judging by its name or the name of its folder?
+pub fn is_screenshot(path: &Path) -> bool {
+    let is_screenshot_name = |name: &std::ffi::OsStr| {
+        let name = name.to_string_lossy().to_lowercase();
+        SCREENSHOT_PREFIXES.iter().any(|prefix| name.starts_with(prefix))
+    };
+
+    path.file_name().is_some_and(is_screenshot_name)
+        || path
+            .parent()
+            .and_then(|x| x.file_name())
+            .is_some_and(is_screenshot_name)
+}
+
+/// Signals of how worth remembering a picture or video is.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Interest {
+    /// Count of recognized people.
+    pub people: usize,
+
+    /// Count of detected faces, whether recognized or not.
+    pub faces: usize,
+
+    /// Is a still picture rather than a video? Pictures make better memories
+    /// in a grid of thumbnails.
+    pub is_picture: bool,
+
+    /// Is a favourite? The user has already picked it as one of their better shots.
+    pub is_favourite: bool,
+
+    /// Was taken with a camera, according to its metadata? Downloaded and
+    /// generated images usually have no camera.
+    pub has_camera: bool,
+
+    /// Is a screenshot or screen recording?
+    pub is_screenshot: bool,
+}
+
+impl Interest {
+    /// Higher is better. Recognized people matter most, then favourites, then any faces.
+    /// Screenshots rarely make good memories, so always come last.
+    pub fn score(&self) -> usize {
+        if self.is_screenshot {
+            return 0;
+        }
+
+        let people = self.people * 4 + self.faces.min(8) * 2;
+        let quality = usize::from(self.is_favourite) * 6 + usize::from(self.has_camera) * 2;
+
+        people + quality + usize::from(self.is_picture)
+    }
+}
+
+/// Keep at most `limit` of the most interesting items, in their original order.
+pub fn pick_best<T>(items: Vec<(T, Interest)>, limit: usize) -> Vec<T> {
+    let mut ranked: Vec<(usize, T, Interest)> = items
+        .into_iter()
+        .enumerate()
+        .map(|(index, (item, interest))| (index, item, interest))
+        .collect();
+
+    // Stable sort, so equally interesting items keep their original order.
+    ranked.sort_by_key(|(_, _, interest)| std::cmp::Reverse(interest.score()));
+    ranked.truncate(limit);
+    ranked.sort_by_key(|(index, _, _)| *index);
+
+    ranked.into_iter().map(|(_, item, _)| item).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_on_this_day() {
+        let today = NaiveDate::from_ymd_opt(2024, 5, 14).unwrap();
+
+        assert!(is_on_this_day(NaiveDate::from_ymd_opt(2021, 5, 14).unwrap(), today));
+        assert!(!is_on_this_day(NaiveDate::from_ymd_opt(2024, 5, 14).unwrap(), today));
+        assert!(!is_on_this_day(NaiveDate::from_ymd_opt(2021, 5, 15).unwrap(), today));
+        assert!(!is_on_this_day(NaiveDate::from_ymd_opt(2021, 6, 14).unwrap(), today));
+
+        let leap_day = NaiveDate::from_ymd_opt(2020, 2, 29).unwrap();
+        assert!(is_on_this_day(leap_day, NaiveDate::from_ymd_opt(2023, 2, 28).unwrap()));
+        assert!(!is_on_this_day(leap_day, NaiveDate::from_ymd_opt(2028, 2, 28).unwrap()));
+        assert!(is_on_this_day(leap_day, NaiveDate::from_ymd_opt(2028, 2, 29).unwrap()));
+    }
+
+    #[test]
+    fn test_pick_best() {
+        let plain = Interest::default();
+        let picture = Interest {
+            is_picture: true,
+            ..Interest::default()
+        };
+        let person = Interest {
+            people: 1,
+            faces: 1,
+            is_picture: true,
+            ..Interest::default()
+        };
+
+        let items = vec![("a", plain), ("b", person), ("c", picture), ("d", plain)];
+
+        assert_eq!(vec!["b", "c"], pick_best(items.clone(), 2));
+        assert_eq!(vec!["a", "b", "c"], pick_best(items, 3));
+    }
+
+    #[test]
+    fn test_score_quality() {
+        let camera = Interest {
+            is_picture: true,
+            has_camera: true,
+            ..Interest::default()
+        };
+        let favourite = Interest {
+            is_favourite: true,
+            ..camera
+        };
+        let screenshot = Interest {
+            people: 2,
+            faces: 2,
+            is_screenshot: true,
+            ..camera
+        };
+
+        let items = vec![("a", screenshot), ("b", camera), ("c", favourite)];
+
+        assert_eq!(vec!["c"], pick_best(items.clone(), 1));
+        assert_eq!(vec!["b", "c"], pick_best(items, 2));
+    }
+
+    #[test]
+    fn test_is_screenshot() {
+        assert!(is_screenshot(Path::new("/p/Screenshot_2023-05-14-10-34-55.png")));
+        assert!(is_screenshot(Path::new("/p/Screen Shot 2023-05-14 at 10.34.55.png")));
+        assert!(is_screenshot(Path::new("/p/Screenshots/IMG_1234.png")));
+        assert!(!is_screenshot(Path::new("/p/Camera/IMG_1234.jpg")));
+    }
+}
@@ -6,6 +6,7 @@ pub mod model;
 pub mod repo;
 
 pub use model::FaceId;
+pub use model::PeopleQuery;
 pub use model::Person;
 pub use model::PersonId;
 pub use repo::Repository;
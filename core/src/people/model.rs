@@ -3,7 +3,7 @@
 // SPDX-License-Identifier: GPL-3.0-or-later
 
 use crate::photo::model::Orientation;
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, NaiveDate, Utc};
 use opencv::core::Mat;
 use std::fmt::Display;
 use std::path::PathBuf;
@@ -34,6 +34,18 @@ pub struct Person {
     pub person_id: PersonId,
     pub name: String,
     pub thumbnail_path: PathBuf,
+
+    /// Date of birth, if known.
+    pub birth_date: Option<NaiveDate>,
+}
+
+impl Person {
+    /// Age of person in whole years on a date.
+    /// Use the local date an item was taken, so ages change on birthdays wherever it was taken.
+    /// None if birth date is unknown or the date is before the birth date.
+    pub fn age_at(&self, date: NaiveDate) -> Option<u32> {
+        self.birth_date.and_then(|birth_date| date.years_since(birth_date))
+    }
 }
 
 /// Database ID
@@ -57,6 +69,30 @@ impl Display for PersonId {
     }
 }
 
+/// Query for pictures and videos by the people recognized in them.
+/// At least one of `all_of`, `any_of`, or `min_people` must be given for the query
+/// to match anything, because `none_of` only narrows results.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PeopleQuery {
+    /// Every one of these people must be present (AND).
+    pub all_of: Vec<PersonId>,
+
+    /// At least one of these people must be present, if any are given (OR).
+    pub any_of: Vec<PersonId>,
+
+    /// None of these people may be present (NOT).
+    pub none_of: Vec<PersonId>,
+
+    /// Minimum number of different recognized people present. For finding group shots.
+    pub min_people: Option<usize>,
+}
+
+impl PeopleQuery {
+    pub fn is_empty(&self) -> bool {
+        self.all_of.is_empty() && self.any_of.is_empty() && self.min_people.is_none()
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct Rect {
     pub x: f32,
@@ -142,3 +178,28 @@ pub struct PersonForRecognition {
     /// "Best" confirmed face for person.
     pub face: DetectedFace,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_age_at() {
+        let person = Person {
+            person_id: PersonId::new(1),
+            name: String::from("Sam"),
+            thumbnail_path: PathBuf::from("sam.png"),
+            birth_date: NaiveDate::from_ymd_opt(2020, 5, 14),
+        };
+
+        let at = |y, m, d| NaiveDate::from_ymd_opt(y, m, d).unwrap();
+
+        assert_eq!(Some(0), person.age_at(at(2020, 5, 14)));
+        assert_eq!(Some(2), person.age_at(at(2023, 5, 13)));
+        assert_eq!(Some(3), person.age_at(at(2023, 5, 14)));
+        assert_eq!(None, person.age_at(at(2019, 1, 1)));
+
+        let unknown = Person { birth_date: None, ..person };
+        assert_eq!(None, unknown.age_at(at(2023, 5, 14)));
+    }
+}
@@ -12,14 +12,19 @@ use crate::people::model::Rect;
 use crate::people::FaceId;
 use crate::people::PersonId;
 use crate::photo::model::Orientation;
+use crate::video::model::VideoId;
 
 use anyhow::*;
+use chrono::NaiveDate;
+use itertools::Itertools;
 use rusqlite;
 use rusqlite::params;
+use rusqlite::params_from_iter;
 use rusqlite::Row;
 use std::path::{Path, PathBuf};
 use std::result::Result::Ok;
 use std::sync::{Arc, Mutex};
+use tracing::error;
 
 /// Repository of people data.
 /// Repository is backed by a Sqlite database.
@@ -110,14 +115,16 @@ impl Repository {
     /// Deletes faces for a picture so a picture can be re-scanned and new faces.
     /// We must delete before re-scanning a picture for faces to avoid a unique constraint
     /// violation on the bounds_path.
+    /// Faces manually added by the user are kept.
     pub fn delete_faces(&self, picture_id: PictureId) -> Result<()> {
         let con = self.con.lock().unwrap();
         let mut stmt = con.prepare(
             "DELETE FROM pictures_faces
-            WHERE pictures_faces.picture_id = ?1",
+            WHERE pictures_faces.picture_id = ?1
+            AND pictures_faces.model_name != ?2",
         )?;
 
-        stmt.execute([picture_id.id()])?;
+        stmt.execute(params![picture_id.id(), face_extractor::MANUAL_MODEL_NAME])?;
 
         Ok(())
     }
@@ -136,6 +143,7 @@ impl Repository {
                 people.person_id AS person_id,
                 people.name AS person_name,
                 people.thumbnail_path AS person_thumbnail_path,
+                people.birth_date AS person_birth_date,
                 pictures.orientation
             FROM pictures_faces AS faces
             INNER JOIN pictures USING (picture_id)
@@ -152,6 +160,68 @@ impl Repository {
         Ok(result)
     }
 
+    /// Finds faces and people in a video for the thumbnail bar.
+    /// Faces are ordered from left to right, top to bottom.
+    pub fn find_faces_for_video(
+        &self,
+        video_id: &VideoId,
+    ) -> Result<Vec<(model::Face, Option<model::Person>)>> {
+        let con = self.con.lock().unwrap();
+        let mut stmt = con.prepare(
+            "SELECT
+                faces.face_id AS face_id,
+                faces.thumbnail_path AS face_thumbnail_path,
+                people.person_id AS person_id,
+                people.name AS person_name,
+                people.thumbnail_path AS person_thumbnail_path,
+                people.birth_date AS person_birth_date
+            FROM pictures_faces AS faces
+            INNER JOIN videos USING (video_id)
+            LEFT OUTER JOIN people USING (person_id)
+            WHERE video_id = ?1 AND faces.is_ignored = FALSE
+            ORDER BY faces.nose_x ASC, faces.nose_y ASC",
+        )?;
+
+        let result = stmt
+            .query_map([video_id.id()], |row| self.to_face_and_person(row))?
+            .flatten()
+            .collect();
+
+        Ok(result)
+    }
+
+    /// Finds the people recognized in a picture or video.
+    /// A live photo has both a picture and a video, so both IDs can be given.
+    pub fn find_people_for_visual(
+        &self,
+        picture_id: Option<PictureId>,
+        video_id: Option<VideoId>,
+    ) -> Result<Vec<model::Person>> {
+        let con = self.con.lock().unwrap();
+        let mut stmt = con.prepare(
+            "SELECT DISTINCT
+                people.person_id AS person_id,
+                people.name AS person_name,
+                people.thumbnail_path AS person_thumbnail_path,
+                people.birth_date AS person_birth_date
+            FROM pictures_faces AS faces
+            INNER JOIN people USING (person_id)
+            WHERE (faces.picture_id = ?1 OR faces.video_id = ?2)
+            AND faces.is_ignored = FALSE
+            ORDER BY people.name ASC",
+        )?;
+
+        let result = stmt
+            .query_map(
+                params![picture_id.map(|x| x.id()), video_id.map(|x| x.id())],
+                |row| self.to_person(row),
+            )?
+            .flatten()
+            .collect();
+
+        Ok(result)
+    }
+
     pub fn ignore_unknown_faces(&mut self, picture_id: PictureId) -> Result<()> {
         let mut con = self.con.lock().unwrap();
         let tx = con.transaction()?;
@@ -194,7 +264,8 @@ impl Repository {
             "SELECT
                 people.person_id AS person_id,
                 people.name AS person_name,
-                people.thumbnail_path AS person_thumbnail_path
+                people.thumbnail_path AS person_thumbnail_path,
+                people.birth_date AS person_birth_date
             FROM  people
             WHERE person_id = ?1",
         )?;
@@ -246,13 +317,123 @@ impl Repository {
         Ok(())
     }
 
+    /// Sets or clears the date of birth of a person.
+    pub fn set_birth_date(&mut self, person_id: PersonId, birth_date: Option<NaiveDate>) -> Result<()> {
+        let mut con = self.con.lock().unwrap();
+        let tx = con.transaction()?;
+
+        {
+            let mut stmt = tx.prepare_cached(
+                "UPDATE people
+                SET
+                    birth_date = ?2
+                WHERE person_id = ?1",
+            )?;
+            stmt.execute(params![person_id.id(), birth_date])?;
+        }
+
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// Merges two people into one.
+    /// All faces of the `from` person are moved to the `into` person, keeping their
+    /// confirmation state, and then the `from` person is deleted.
+    /// The surviving person is given `name` and the thumbnail of `thumbnail_of`, which
+    /// must be either `from` or `into`.
+    pub fn merge_people(
+        &mut self,
+        from: PersonId,
+        into: PersonId,
+        name: &str,
+        thumbnail_of: PersonId,
+    ) -> Result<()> {
+        if from == into {
+            bail!("Cannot merge person {} with itself", from);
+        }
+
+        if thumbnail_of != from && thumbnail_of != into {
+            bail!("Thumbnail for merged person must come from {} or {}", from, into);
+        }
+
+        // The person whose thumbnail is not kept.
+        let other = if thumbnail_of == from { into } else { from };
+
+        let mut con = self.con.lock().unwrap();
+        let tx = con.transaction()?;
+
+        let unused_thumbnail_path: Option<String>;
+
+        {
+            // Thumbnail paths are unique, so the thumbnail must be read before
+            // the person it belongs to is deleted.
+            let mut stmt =
+                tx.prepare_cached("SELECT thumbnail_path FROM people WHERE person_id = ?1")?;
+            let thumbnail_path: String =
+                stmt.query_row(params![thumbnail_of.id()], |row| row.get(0))?;
+            let other_thumbnail_path: String =
+                stmt.query_row(params![other.id()], |row| row.get(0))?;
+
+            let mut stmt = tx.prepare_cached(
+                "UPDATE pictures_faces
+                SET
+                    person_id = ?2
+                WHERE person_id = ?1",
+            )?;
+            stmt.execute(params![from.id(), into.id(),])?;
+
+            let mut stmt = tx.prepare_cached("DELETE FROM people WHERE person_id = ?1")?;
+            stmt.execute(params![from.id(),])?;
+
+            // Reset recognized_at so that unknown faces are compared against the
+            // combined set of confirmed faces.
+            let mut stmt = tx.prepare_cached(
+                "UPDATE people
+                SET
+                    name = ?2,
+                    thumbnail_path = ?3,
+                    recognized_at = '1970-01-01 00:00:00'
+                WHERE person_id = ?1",
+            )?;
+            stmt.execute(params![into.id(), name, thumbnail_path,])?;
+
+            // A person thumbnail is usually shared with the face it was taken from,
+            // so the file can only be removed once nothing refers to it.
+            let mut stmt = tx.prepare_cached(
+                "SELECT EXISTS(
+                    SELECT 1 FROM people WHERE thumbnail_path = ?1
+                    UNION ALL
+                    SELECT 1 FROM pictures_faces WHERE thumbnail_path = ?1
+                )",
+            )?;
+            let is_referenced: bool =
+                stmt.query_row(params![other_thumbnail_path], |row| row.get(0))?;
+
+            unused_thumbnail_path = (!is_referenced).then_some(other_thumbnail_path);
+        }
+
+        tx.commit()?;
+
+        if let Some(path) = unused_thumbnail_path {
+            let path = self.data_dir_base_path.join(path);
+            if let Err(e) = std::fs::remove_file(&path) {
+                if e.kind() != std::io::ErrorKind::NotFound {
+                    error!("Failed removing {:?}: {}", path, e);
+                }
+            }
+        }
+
+        Ok(())
+    }
+
     pub fn all_people(&self) -> Result<Vec<model::Person>> {
         let con = self.con.lock().unwrap();
         let mut stmt = con.prepare(
             "SELECT
                 people.person_id AS person_id,
                 people.name AS person_name,
-                people.thumbnail_path AS person_thumbnail_path
+                people.thumbnail_path AS person_thumbnail_path,
+                people.birth_date AS person_birth_date
             FROM  people
             ORDER BY name ASC",
         )?;
@@ -371,7 +552,8 @@ impl Repository {
             "SELECT DISTINCT
                 picture_id
             FROM  pictures_faces
-            WHERE person_id == ?1",
+            WHERE person_id == ?1
+            AND picture_id IS NOT NULL",
         )?;
 
         let result: Vec<PictureId> = stmt
@@ -384,6 +566,126 @@ impl Repository {
         Ok(result)
     }
 
+    /// Finds all videos that feature a known person.
+    pub fn find_videos_for_person(&self, person_id: PersonId) -> Result<Vec<VideoId>> {
+        let con = self.con.lock().unwrap();
+        let mut stmt = con.prepare(
+            "SELECT DISTINCT
+                video_id
+            FROM  pictures_faces
+            WHERE person_id == ?1
+            AND video_id IS NOT NULL",
+        )?;
+
+        let result: Vec<VideoId> = stmt
+            .query_map([person_id.id()], |row| row.get("video_id").map(VideoId::new))?
+            .flatten()
+            .collect();
+
+        Ok(result)
+    }
+
+    /// Counts of recognized people and of all faces in each picture and video with faces.
+    /// Returns picture ID or video ID, count of people, and count of faces.
+    pub fn count_faces(&self) -> Result<Vec<(Option<PictureId>, Option<VideoId>, usize, usize)>> {
+        let con = self.con.lock().unwrap();
+        let mut stmt = con.prepare(
+            "SELECT
+                picture_id,
+                video_id,
+                COUNT(DISTINCT person_id) AS people_count,
+                COUNT(*) AS face_count
+            FROM  pictures_faces
+            WHERE is_ignored = FALSE
+            GROUP BY picture_id, video_id",
+        )?;
+
+        let result = stmt
+            .query_map([], |row| {
+                let picture_id: Option<i64> = row.get("picture_id")?;
+                let video_id: Option<i64> = row.get("video_id")?;
+                let people_count: i64 = row.get("people_count")?;
+                let face_count: i64 = row.get("face_count")?;
+                Ok((
+                    picture_id.map(PictureId::new),
+                    video_id.map(VideoId::new),
+                    people_count as usize,
+                    face_count as usize,
+                ))
+            })?
+            .flatten()
+            .collect();
+
+        Ok(result)
+    }
+
+    /// Finds all pictures and videos matching a query over the people in them.
+    pub fn find_visuals_for_people(
+        &self,
+        query: &model::PeopleQuery,
+    ) -> Result<(Vec<PictureId>, Vec<VideoId>)> {
+        if query.is_empty() {
+            return Ok((vec![], vec![]));
+        }
+
+        // Person IDs and counts are bound as parameters, with a placeholder for each ID.
+        let placeholders = |ids: &[PersonId]| ids.iter().map(|_| "?").join(",");
+
+        let mut having = vec![String::from("1 = 1")];
+        let mut values: Vec<i64> = vec![];
+
+        if !query.all_of.is_empty() {
+            having.push(format!(
+                "COUNT(DISTINCT CASE WHEN person_id IN ({}) THEN person_id END) = ?",
+                placeholders(&query.all_of),
+            ));
+            values.extend(query.all_of.iter().map(|id| id.id()));
+            values.push(query.all_of.iter().map(|id| id.id()).unique().count() as i64);
+        }
+
+        if !query.any_of.is_empty() {
+            having.push(format!("SUM(person_id IN ({})) > 0", placeholders(&query.any_of)));
+            values.extend(query.any_of.iter().map(|id| id.id()));
+        }
+
+        if !query.none_of.is_empty() {
+            having.push(format!("SUM(person_id IN ({})) = 0", placeholders(&query.none_of)));
+            values.extend(query.none_of.iter().map(|id| id.id()));
+        }
+
+        if let Some(min_people) = query.min_people {
+            having.push(String::from("COUNT(DISTINCT person_id) >= ?"));
+            values.push(min_people as i64);
+        }
+
+        let sql = format!(
+            "SELECT
+                picture_id,
+                video_id
+            FROM  pictures_faces
+            WHERE person_id IS NOT NULL
+            AND is_ignored = FALSE
+            GROUP BY picture_id, video_id
+            HAVING {}",
+            having.join(" AND ")
+        );
+
+        let con = self.con.lock().unwrap();
+        let mut stmt = con.prepare(&sql)?;
+
+        let rows: Vec<(Option<i64>, Option<i64>)> = stmt
+            .query_map(params_from_iter(values), |row| {
+                Ok((row.get("picture_id")?, row.get("video_id")?))
+            })?
+            .flatten()
+            .collect();
+
+        let picture_ids = rows.iter().filter_map(|x| x.0).map(PictureId::new).collect();
+        let video_ids = rows.iter().filter_map(|x| x.1).map(VideoId::new).collect();
+
+        Ok((picture_ids, video_ids))
+    }
+
     // FIXME probably need a mechanism to undo this in the likely event of user error.
     pub fn mark_ignore(&mut self, face_id: FaceId) -> Result<()> {
         let mut con = self.con.lock().unwrap();
@@ -460,76 +762,102 @@ impl Repository {
 
             scan_insert_stmt.execute(params![picture_id.id(), false, faces.len(),])?;
 
-            let mut face_insert_stmt = tx.prepare_cached(
-                "INSERT INTO pictures_faces (
-                    picture_id,
-                    thumbnail_path,
-                    bounds_path,
+            for face in faces {
+                self.insert_face(&tx, Some(picture_id), None, face)?;
+            }
+        }
 
-                    model_name,
+        tx.commit()?;
+        Ok(())
+    }
 
-                    bounds_x,
-                    bounds_y,
-                    bounds_width,
-                    bounds_height,
+    /// Gets all videos that haven't been scanned for faces.
+    /// Videos that are part of a live photo are skipped because the photo is scanned instead.
+    pub fn find_need_video_face_scan(&self) -> Result<Vec<(VideoId, PathBuf, Option<i32>)>> {
+        let con = self.con.lock().unwrap();
+        let mut stmt = con.prepare(
+            "SELECT
+                    videos.video_id,
+                    videos.video_path_b64,
+                    videos.rotation,
+                    COALESCE(
+                        videos.stream_created_ts,
+                        videos.fs_created_ts,
+                        videos.fs_modified_ts,
+                        CURRENT_TIMESTAMP
+                    ) AS ordering_ts
+                FROM videos
+                LEFT OUTER JOIN videos_face_scans USING (video_id)
+                WHERE videos_face_scans.video_id IS NULL
+                AND videos.content_id IS NULL
+                AND COALESCE(videos.is_broken, FALSE) IS FALSE
+                ORDER BY ordering_ts DESC",
+        )?;
 
-                    right_eye_x,
-                    right_eye_y,
+        let result = stmt
+            .query_map([], |row| self.to_video_id_path_tuple(row))?
+            .flatten()
+            .collect();
+
+        Ok(result)
+    }
 
-                    left_eye_x,
-                    left_eye_y,
+    pub fn mark_video_face_scan_broken(&mut self, video_id: &VideoId) -> Result<()> {
+        let mut con = self.con.lock().unwrap();
+        let tx = con.transaction()?;
 
-                    nose_x,
-                    nose_y,
+        {
+            let mut stmt = tx.prepare_cached(
+                "INSERT INTO videos_face_scans (
+                    video_id,
+                    is_broken,
+                    face_count,
+                    scan_ts
+                ) VALUES (
+                    ?1, TRUE, 0, CURRENT_TIMESTAMP
+                ) ON CONFLICT (video_id) DO UPDATE SET
+                    is_broken = true,
+                    face_count = 0,
+                    scan_ts = CURRENT_TIMESTAMP
+                ",
+            )?;
 
-                    right_mouth_corner_x,
-                    right_mouth_corner_y,
+            stmt.execute(params![video_id.id(),])?;
+        }
 
-                    left_mouth_corner_x,
-                    left_mouth_corner_y,
+        tx.commit()?;
+        Ok(())
+    }
 
-                    confidence,
+    pub fn add_video_face_scans(
+        &mut self,
+        video_id: &VideoId,
+        faces: &Vec<face_extractor::Face>,
+    ) -> Result<()> {
+        let mut con = self.con.lock().unwrap();
+        let tx = con.transaction()?;
 
-                    is_ignored
+        // Create a scope to make borrowing of tx not be an error.
+        {
+            let mut scan_insert_stmt = tx.prepare_cached(
+                "INSERT INTO videos_face_scans (
+                    video_id,
+                    is_broken,
+                    face_count,
+                    scan_ts
                 ) VALUES (
-                    ?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10,
-                    ?11, ?12, ?13, ?14, ?15, ?16, ?17, ?18, ?19, false
-                )
+                    ?1, ?2, ?3, CURRENT_TIMESTAMP
+                ) ON CONFLICT (video_id) DO UPDATE SET
+                    is_broken = ?2,
+                    face_count = ?3,
+                    scan_ts = CURRENT_TIMESTAMP
                 ",
             )?;
 
+            scan_insert_stmt.execute(params![video_id.id(), false, faces.len(),])?;
+
             for face in faces {
-                // convert to relative path before saving to database
-                let thumbnail_path = face.thumbnail_path.strip_prefix(&self.data_dir_base_path)?;
-                let bounds_path = face.bounds_path.strip_prefix(&self.data_dir_base_path)?;
-
-                let right_eye = face.right_eye();
-                let left_eye = face.left_eye();
-                let nose = face.nose();
-                let right_mouth_corner = face.right_mouth_corner();
-                let left_mouth_corner = face.left_mouth_corner();
-
-                face_insert_stmt.execute(params![
-                    picture_id.id(),
-                    thumbnail_path.to_string_lossy(),
-                    bounds_path.to_string_lossy(),
-                    face.model_name,
-                    face.bounds.x,
-                    face.bounds.y,
-                    face.bounds.width,
-                    face.bounds.height,
-                    right_eye.map(|x| x.0),
-                    right_eye.map(|x| x.1),
-                    left_eye.map(|x| x.0),
-                    left_eye.map(|x| x.1),
-                    nose.map(|x| x.0),
-                    nose.map(|x| x.1),
-                    right_mouth_corner.map(|x| x.0),
-                    right_mouth_corner.map(|x| x.1),
-                    left_mouth_corner.map(|x| x.0),
-                    left_mouth_corner.map(|x| x.1),
-                    face.confidence
-                ])?;
+                self.insert_face(&tx, None, Some(video_id), face)?;
             }
         }
 
@@ -537,6 +865,106 @@ impl Repository {
         Ok(())
     }
 
+    /// Add a face the user has drawn by hand.
+    /// Manual faces are not part of a face scan, so are not removed when a picture is re-scanned.
+    pub fn add_manual_face(
+        &mut self,
+        picture_id: &PictureId,
+        face: &face_extractor::Face,
+    ) -> Result<FaceId> {
+        let mut con = self.con.lock().unwrap();
+        let tx = con.transaction()?;
+
+        self.insert_face(&tx, Some(picture_id), None, face)?;
+        let face_id = FaceId::new(tx.last_insert_rowid());
+
+        tx.commit()?;
+        Ok(face_id)
+    }
+
+    /// Insert a face for either a picture or a video.
+    fn insert_face(
+        &self,
+        con: &rusqlite::Connection,
+        picture_id: Option<&PictureId>,
+        video_id: Option<&VideoId>,
+        face: &face_extractor::Face,
+    ) -> Result<()> {
+        let mut face_insert_stmt = con.prepare_cached(
+            "INSERT INTO pictures_faces (
+                picture_id,
+                video_id,
+                thumbnail_path,
+                bounds_path,
+
+                model_name,
+
+                bounds_x,
+                bounds_y,
+                bounds_width,
+                bounds_height,
+
+                right_eye_x,
+                right_eye_y,
+
+                left_eye_x,
+                left_eye_y,
+
+                nose_x,
+                nose_y,
+
+                right_mouth_corner_x,
+                right_mouth_corner_y,
+
+                left_mouth_corner_x,
+                left_mouth_corner_y,
+
+                confidence,
+
+                is_ignored
+            ) VALUES (
+                ?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10,
+                ?11, ?12, ?13, ?14, ?15, ?16, ?17, ?18, ?19, ?20, false
+            )
+            ",
+        )?;
+
+        // convert to relative path before saving to database
+        let thumbnail_path = face.thumbnail_path.strip_prefix(&self.data_dir_base_path)?;
+        let bounds_path = face.bounds_path.strip_prefix(&self.data_dir_base_path)?;
+
+        let right_eye = face.right_eye();
+        let left_eye = face.left_eye();
+        let nose = face.nose();
+        let right_mouth_corner = face.right_mouth_corner();
+        let left_mouth_corner = face.left_mouth_corner();
+
+        face_insert_stmt.execute(params![
+            picture_id.map(|x| x.id()),
+            video_id.map(|x| x.id()),
+            thumbnail_path.to_string_lossy(),
+            bounds_path.to_string_lossy(),
+            face.model_name,
+            face.bounds.x,
+            face.bounds.y,
+            face.bounds.width,
+            face.bounds.height,
+            right_eye.map(|x| x.0),
+            right_eye.map(|x| x.1),
+            left_eye.map(|x| x.0),
+            left_eye.map(|x| x.1),
+            nose.map(|x| x.0),
+            nose.map(|x| x.1),
+            right_mouth_corner.map(|x| x.0),
+            right_mouth_corner.map(|x| x.1),
+            left_mouth_corner.map(|x| x.0),
+            left_mouth_corner.map(|x| x.1),
+            face.confidence
+        ])?;
+
+        Ok(())
+    }
+
     /// Add a new named person derived from a face.
     pub fn add_person(&mut self, face_id: FaceId, name: &str) -> Result<()> {
         let mut con = self.con.lock().unwrap();
@@ -697,6 +1125,22 @@ impl Repository {
         std::result::Result::Ok((picture_id, picture_path))
     }
 
+    fn to_video_id_path_tuple(
+        &self,
+        row: &Row<'_>,
+    ) -> rusqlite::Result<(VideoId, PathBuf, Option<i32>)> {
+        let video_id = row.get("video_id").map(VideoId::new)?;
+
+        let video_path: String = row.get("video_path_b64")?;
+        let video_path =
+            path_encoding::from_base64(&video_path).map_err(|_| rusqlite::Error::InvalidQuery)?;
+        let video_path = self.library_base_path.join(video_path);
+
+        let rotation = row.get("rotation")?;
+
+        std::result::Result::Ok((video_id, video_path, rotation))
+    }
+
     fn to_face_and_person(
         &self,
         row: &Row<'_>,
@@ -727,6 +1171,8 @@ impl Repository {
             .map(|p: String| self.data_dir_base_path.join(p))
             .ok();
 
+        let birth_date = row.get("person_birth_date").ok().flatten();
+
         let person = if let (Some(person_id), Some(name), Some(thumbnail_path)) =
             (person_id, person_name, person_thumbnail_path)
         {
@@ -734,6 +1180,7 @@ impl Repository {
                 person_id,
                 name,
                 thumbnail_path,
+                birth_date,
             })
         } else {
             None
@@ -751,10 +1198,13 @@ impl Repository {
             .get("person_thumbnail_path")
             .map(|p: String| self.data_dir_base_path.join(p))?;
 
+        let birth_date = row.get("person_birth_date")?;
+
         std::result::Result::Ok(model::Person {
             person_id,
             name,
             thumbnail_path,
+            birth_date,
         })
     }
 
@@ -824,3 +1274,98 @@ impl Repository {
         std::result::Result::Ok(person)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database;
+
+    /// Pictures 1 to 4, with people 1, 2, and 3 recognized in them:
+    /// picture 1 has people 1 and 2, picture 2 has person 1, picture 3 has
+    /// people 1, 2, and 3, and picture 4 has person 3 and an ignored face of person 1.
+    fn repo() -> Repository {
+        let con = database::setup_in_memory().unwrap();
+        con.execute_batch(
+            "INSERT INTO pictures (picture_id, picture_path_b64, picture_path_lossy, link_path_b64, link_path_lossy)
+            VALUES (1, 'a', 'a', 'a', 'a'), (2, 'b', 'b', 'b', 'b'), (3, 'c', 'c', 'c', 'c'), (4, 'd', 'd', 'd', 'd');
+
+            INSERT INTO people (person_id, thumbnail_path, name, recognized_at)
+            VALUES (1, '1.png', 'Alice', 0), (2, '2.png', 'Bob', 0), (3, '3.png', 'Carol', 0);",
+        )
+        .unwrap();
+
+        let faces = [
+            (1, 1, 0),
+            (1, 2, 0),
+            (2, 1, 0),
+            (3, 1, 0),
+            (3, 2, 0),
+            (3, 3, 0),
+            (4, 3, 0),
+            (4, 1, 1),
+        ];
+        for (picture_id, person_id, is_ignored) in faces {
+            con.execute(
+                "INSERT INTO pictures_faces (
+                    model_name, picture_id, is_confirmed, detected_at, person_id, thumbnail_path, bounds_path,
+                    bounds_x, bounds_y, bounds_width, bounds_height,
+                    right_eye_x, right_eye_y, left_eye_x, left_eye_y, nose_x, nose_y,
+                    right_mouth_corner_x, right_mouth_corner_y, left_mouth_corner_x, left_mouth_corner_y,
+                    confidence, is_ignored
+                )
+                VALUES ('test', ?1, 1, 0, ?2, ?1 || '_' || ?2, ?1 || '_' || ?2, 0, 0, 1, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1, ?3)",
+                params![picture_id, person_id, is_ignored],
+            )
+            .unwrap();
+        }
+
+        Repository::open(Path::new("/"), Path::new("/"), Arc::new(Mutex::new(con))).unwrap()
+    }
+
+    fn find(repo: &Repository, query: model::PeopleQuery) -> Vec<i64> {
+        let (picture_ids, _) = repo.find_visuals_for_people(&query).unwrap();
+        picture_ids.iter().map(|x| x.id()).sorted().collect()
+    }
+
+    fn ids(ids: &[i64]) -> Vec<PersonId> {
+        ids.iter().map(|x| PersonId::new(*x)).collect()
+    }
+
+    #[test]
+    fn test_find_visuals_for_people() {
+        let repo = repo();
+
+        let query = model::PeopleQuery {
+            all_of: ids(&[1, 2]),
+            ..Default::default()
+        };
+        assert_eq!(vec![1, 3], find(&repo, query));
+
+        let query = model::PeopleQuery {
+            any_of: ids(&[2, 3]),
+            ..Default::default()
+        };
+        assert_eq!(vec![1, 3, 4], find(&repo, query));
+
+        let query = model::PeopleQuery {
+            all_of: ids(&[1]),
+            none_of: ids(&[3]),
+            ..Default::default()
+        };
+        assert_eq!(vec![1, 2], find(&repo, query));
+
+        let query = model::PeopleQuery {
+            min_people: Some(2),
+            ..Default::default()
+        };
+        assert_eq!(vec![1, 3], find(&repo, query));
+
+        let query = model::PeopleQuery {
+            any_of: ids(&[1, 3]),
+            none_of: ids(&[2]),
+            min_people: Some(1),
+            ..Default::default()
+        };
+        assert_eq!(vec![2, 4], find(&repo, query));
+    }
+}
@@ -88,6 +88,21 @@ impl GPSCoord {
 }
 
 impl GPSLocation {
+    /// Location from signed decimal degrees.
+    pub fn from_degrees(latitude: f64, longitude: f64) -> Self {
+        let coord = |degrees: f64| GPSCoord {
+            sing: degrees >= 0.0,
+            deg: degrees.abs(),
+            min: None,
+            sec: None,
+        };
+
+        Self {
+            latitude: coord(latitude),
+            longitude: coord(longitude),
+        }
+    }
+
     pub fn for_exif(
         latitude: &[exif::Rational],
         latitude_ref: &[Vec<u8>],
@@ -136,8 +151,11 @@ impl GPSLocation {
         })
     }
 
+    pub fn to_lat_lng(&self) -> Result<LatLng> {
+        Ok(LatLng::new(self.latitude.to_f64(), self.longitude.to_f64())?)
+    }
+
     pub fn to_cell_index(&self, resolution: Resolution) -> Result<CellIndex> {
-        let ll = LatLng::new(self.latitude.to_f64(), self.longitude.to_f64())?;
-        Ok(ll.to_cell(resolution))
+        Ok(self.to_lat_lng()?.to_cell(resolution))
     }
 }
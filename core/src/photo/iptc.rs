@@ -0,0 +1,146 @@
+// SPDX-FileCopyrightText: © 2024 David Bliss
+//
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//! IPTC-IIM metadata embedded in pictures, such as keywords set in older photo managers.
+//! IPTC-IIM is stored in a Photoshop image resource in the APP13 segment of JPEG files.
+
+use anyhow::*;
+use std::fs;
+use std::io::Read;
+use std::path::Path;
+use std::result::Result::Ok;
+
+/// IPTC metadata is near the start of JPEG files, so only search the first megabyte
+/// instead of reading whole files.
+const MAX_SEARCH_BYTES: u64 = 1024 * 1024;
+
+/// Start of Photoshop image resource with ID 0x0404, which holds IPTC-IIM data.
+const IPTC_RESOURCE: &[u8] = b"8BIM\x04\x04";
+
+/// Start of each IPTC-IIM data set.
+const TAG_MARKER: u8 = 0x1C;
+
+/// Application record.
+const APPLICATION_RECORD: u8 = 2;
+
+/// Keywords data set of application record.
+const KEYWORDS_DATA_SET: u8 = 25;
+
+/// Read IPTC keywords embedded in a picture file, if any.
+pub fn keywords_from_path(path: &Path) -> Result<Vec<String>> {
+    let mut data = Vec::new();
+    fs::File::open(path)?
+        .take(MAX_SEARCH_BYTES)
+        .read_to_end(&mut data)?;
+
+    Ok(keywords(&data))
+}
+
+/// Find IPTC keywords in file data.
+pub fn keywords(data: &[u8]) -> Vec<String> {
+    let Some(start) = data
+        .windows(IPTC_RESOURCE.len())
+        .position(|x| x == IPTC_RESOURCE)
+    else {
+        return Vec::new();
+    };
+
+    resource_data(&data[start + IPTC_RESOURCE.len()..])
+        .map(iim_keywords)
+        .unwrap_or_default()
+}
+
+/// Data of a Photoshop image resource, following its ID.
+fn resource_data(data: &[u8]) -> Option<&[u8]> {
+    // Name is a Pascal string padded to an even length.
+    let name_len = usize::from(*data.first()?);
+    let name_len = if (name_len + 1) % 2 == 0 {
+        name_len + 1
+    } else {
+        name_len + 2
+    };
+
+    let size = data.get(name_len..name_len + 4)?;
+    let size = u32::from_be_bytes([size[0], size[1], size[2], size[3]]);
+    let size = usize::try_from(size).ok()?;
+
+    let start = name_len + 4;
+    data.get(start..start + size)
+}
+
+/// Keywords in IPTC-IIM data sets.
+fn iim_keywords(data: &[u8]) -> Vec<String> {
+    let mut keywords = Vec::new();
+    let mut offset = 0;
+
+    while let Some(header) = data.get(offset..offset + 5) {
+        if header[0] != TAG_MARKER {
+            break;
+        }
+
+        let size = usize::from(u16::from_be_bytes([header[3], header[4]]));
+
+        // Extended data sets are never used for keywords, and their size is
+        // encoded differently, so stop rather than misread them.
+        if size & 0x8000 != 0 {
+            break;
+        }
+
+        let Some(value) = data.get(offset + 5..offset + 5 + size) else {
+            break;
+        };
+
+        if header[1] == APPLICATION_RECORD && header[2] == KEYWORDS_DATA_SET {
+            let keyword = String::from_utf8_lossy(value).trim().to_string();
+            if !keyword.is_empty() {
+                keywords.push(keyword);
+            }
+        }
+
+        offset += 5 + size;
+    }
+
+    keywords
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn data_set(record: u8, data_set: u8, value: &str) -> Vec<u8> {
+        let mut bytes = vec![TAG_MARKER, record, data_set];
+        bytes.extend_from_slice(&(value.len() as u16).to_be_bytes());
+        bytes.extend_from_slice(value.as_bytes());
+        bytes
+    }
+
+    #[test]
+    fn test_keywords() {
+        let mut iim = Vec::new();
+        iim.extend(data_set(1, 90, "\x1b%G"));
+        iim.extend(data_set(2, 25, "Paris"));
+        iim.extend(data_set(2, 5, "Holiday snaps"));
+        iim.extend(data_set(2, 25, "Café"));
+
+        let mut data = b"\xff\xd8\xff\xedjunkPhotoshop 3.0\x00".to_vec();
+        data.extend_from_slice(IPTC_RESOURCE);
+        data.extend_from_slice(&[0, 0]); // empty name, padded
+        data.extend_from_slice(&(iim.len() as u32).to_be_bytes());
+        data.extend(iim);
+        data.extend_from_slice(b"\xff\xd9");
+
+        assert_eq!(
+            vec!["Paris".to_string(), "Café".to_string()],
+            keywords(&data)
+        );
+    }
+
+    #[test]
+    fn test_no_keywords() {
+        assert!(keywords(b"\xff\xd8\xff\xd9").is_empty());
+
+        // Truncated resource
+        assert!(keywords(b"8BIM\x04\x04\x00\x00\x00\x00\x01").is_empty());
+    }
+}
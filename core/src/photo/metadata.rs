@@ -3,11 +3,14 @@
 // SPDX-License-Identifier: GPL-3.0-or-later
 
 use super::gps::GPSLocation;
+use super::iptc;
 use super::model::Orientation;
+use super::xmp::Xmp;
 use super::Metadata;
+use crate::tags::model::merge_keywords;
 use anyhow::*;
 use chrono::prelude::*;
-use chrono::{DateTime, FixedOffset};
+use chrono::FixedOffset;
 use exif;
 use exif::Exif;
 use std::fs;
@@ -25,23 +28,47 @@ use std::result::Result::Ok;
 /// 1. Orientation.
 /// 2. Motion photos.
 /// 3. GPS coordinates.
-pub const VERSION: u32 = 3;
-
-/// Extract EXIF metadata from file
+/// 4. Local capture time and its offset from UTC.
+/// 5. XMP rating.
+/// 6. Camera make and model.
+/// 7. Keywords from IPTC and XMP.
+/// 8. Title and caption.
+pub const VERSION: u32 = 8;
+
+/// Image descriptions that cameras fill in by default, which aren't a caption
+/// written by a person. Compared ignoring case.
+const PLACEHOLDER_CAPTIONS: &[&str] = &[
+    "default",
+    "digital camera",
+    "exif_jpeg_picture",
+    "konica minolta digital camera",
+    "minolta digital camera",
+    "olympus digital camera",
+    "samsung",
+    "sony dsc",
+];
+
+/// Extract EXIF, IPTC, and XMP metadata from file
 pub fn from_path(path: &Path) -> Result<Metadata> {
     let file = fs::File::open(path)?;
     let file = &mut BufReader::new(file);
-    let exif_data = {
-        match exif::Reader::new().read_from_container(file) {
-            Ok(exif) => exif,
-            Err(_) => {
-                // Assume this error is when there is no EXIF data.
-                return Ok(Metadata::default());
-            }
-        }
+
+    let mut metadata = match exif::Reader::new().read_from_container(file) {
+        Ok(exif_data) => from_exif(exif_data)?,
+        // Assume this error is when there is no EXIF data.
+        Err(_) => Metadata::default(),
     };
 
-    let mut metadata = from_exif(exif_data)?;
+    // Ratings, keywords, and captions from other photo managers are in XMP and IPTC
+    // rather than EXIF.
+    let xmp = Xmp::from_path(path).ok().flatten().unwrap_or_default();
+    metadata.rating = xmp.rating;
+    metadata.title = xmp.title;
+    metadata.caption = xmp.description.or(metadata.caption);
+
+    let mut keywords = xmp.subjects;
+    keywords.extend(iptc::keywords_from_path(path).unwrap_or_default());
+    metadata.keywords = merge_keywords(&xmp.hierarchical_subjects, &keywords);
 
     // FIXME what is a better way of doing this?
     //
@@ -83,10 +110,11 @@ pub fn from_raw(data: Vec<u8>) -> Result<Metadata> {
 }
 
 fn from_exif(exif_data: Exif) -> Result<Metadata> {
+    /// Parse local date and time, and the offset from UTC if present.
     fn parse_date_time(
         date_time_field: Option<&exif::Field>,
         time_offset_field: Option<&exif::Field>,
-    ) -> Option<DateTime<FixedOffset>> {
+    ) -> Option<(NaiveDateTime, Option<FixedOffset>)> {
         let date_time_field = date_time_field?;
 
         let mut date_time = match date_time_field.value {
@@ -105,8 +133,10 @@ fn from_exif(exif_data: Exif) -> Result<Metadata> {
             };
         }
 
-        let offset = date_time.offset.unwrap_or(0); // offset in minutes
-        let offset = FixedOffset::east_opt((offset as i32) * 60)?;
+        // offset in minutes
+        let offset = date_time
+            .offset
+            .and_then(|offset| FixedOffset::east_opt((offset as i32) * 60));
 
         let date = NaiveDate::from_ymd_opt(
             date_time.year.into(),
@@ -120,24 +150,40 @@ fn from_exif(exif_data: Exif) -> Result<Metadata> {
             date_time.second.into(),
         )?;
 
-        let naive_date_time = date.and_time(time);
-        Some(offset.from_utc_datetime(&naive_date_time))
+        Some((date.and_time(time), offset))
     }
 
-    let created_at = parse_date_time(
+    let (created_at, created_at_offset) = parse_date_time(
         exif_data.get_field(exif::Tag::DateTimeOriginal, exif::In::PRIMARY),
         exif_data.get_field(exif::Tag::OffsetTimeOriginal, exif::In::PRIMARY),
-    );
+    )
+    .map_or((None, None), |(local, offset)| (Some(local), offset));
 
+    // Without an offset the modification time is only a best guess,
+    // so assume UTC as it is only shown for information.
     let modified_at = parse_date_time(
         exif_data.get_field(exif::Tag::DateTime, exif::In::PRIMARY),
         exif_data.get_field(exif::Tag::OffsetTime, exif::In::PRIMARY),
-    );
+    )
+    .and_then(|(local, offset)| {
+        offset
+            .unwrap_or_else(|| Utc.fix())
+            .from_local_datetime(&local)
+            .earliest()
+    });
 
     let lens_model = exif_data
         .get_field(exif::Tag::LensModel, exif::In::PRIMARY)
         .map(|e| e.display_value().to_string());
 
+    let camera_make = exif_data
+        .get_field(exif::Tag::Make, exif::In::PRIMARY)
+        .and_then(|e| ascii_value(&e.value));
+
+    let camera_model = exif_data
+        .get_field(exif::Tag::Model, exif::In::PRIMARY)
+        .and_then(|e| ascii_value(&e.value));
+
     // How to orient and flip the image.
     // Note that libheif will automatically apply the transformations when loading the image
     // so must be aware of file format before transforming to avoid a double transformation.
@@ -146,22 +192,57 @@ fn from_exif(exif_data: Exif) -> Result<Metadata> {
         .and_then(|e| e.value.get_uint(0))
         .map(Orientation::from);
 
+    let caption = exif_data
+        .get_field(exif::Tag::ImageDescription, exif::In::PRIMARY)
+        .and_then(|e| ascii_value(&e.value))
+        .filter(|x| !is_placeholder_caption(x));
+
     let content_id = ios_content_id(&exif_data);
 
     let location = gps_location(&exif_data);
 
     let metadata = Metadata {
         created_at,
+        created_at_offset,
         modified_at,
         lens_model,
+        camera_make,
+        camera_model,
         orientation,
         content_id,
         location,
+        rating: None,
+        keywords: Vec::new(),
+        title: None,
+        caption,
     };
 
     Ok(metadata)
 }
 
+/// First ASCII string of an EXIF value, without the quoting of display_value().
+fn ascii_value(value: &exif::Value) -> Option<String> {
+    match value {
+        exif::Value::Ascii(ref vec) => vec
+            .first()
+            .map(|x| {
+                String::from_utf8_lossy(x)
+                    .trim_end_matches('\0')
+                    .trim()
+                    .to_string()
+            })
+            .filter(|x| !x.is_empty()),
+        _ => None,
+    }
+}
+
+/// Is an image description one that a camera filled in by default?
+fn is_placeholder_caption(caption: &str) -> bool {
+    PLACEHOLDER_CAPTIONS
+        .iter()
+        .any(|x| x.eq_ignore_ascii_case(caption.trim()))
+}
+
 /// Parse GPS latitude and longitude from EXIF data
 /// Mostly borrowed from Loupe.
 /// See https://gitlab.gnome.org/GNOME/loupe/-/blob/main/src/metadata.rs
@@ -243,6 +324,13 @@ fn ios_content_id(exif_data: &Exif) -> Option<String> {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_is_placeholder_caption() {
+        assert!(is_placeholder_caption("OLYMPUS DIGITAL CAMERA"));
+        assert!(is_placeholder_caption("SONY DSC "));
+        assert!(!is_placeholder_caption("Sunset over the bay"));
+    }
+
     #[test]
     fn test_ios_content_id() {
         let dir = env!("CARGO_MANIFEST_DIR");
@@ -3,12 +3,14 @@
 // SPDX-License-Identifier: GPL-3.0-or-later
 
 pub mod gps;
+pub mod iptc;
 pub mod metadata;
 pub mod model;
 pub mod motion_photo;
 pub mod repo;
 pub mod scanner;
 pub mod thumbnail;
+pub mod xmp;
 
 pub use model::PictureId;
 
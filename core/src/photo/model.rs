@@ -3,13 +3,13 @@
 // SPDX-License-Identifier: GPL-3.0-or-later
 
 use super::gps::GPSLocation;
-use chrono::{DateTime, FixedOffset, TimeDelta, Utc};
+use chrono::{DateTime, FixedOffset, NaiveDateTime, TimeDelta, Utc};
 use std::fmt::Display;
 use std::path::PathBuf;
 use strum::{AsRefStr, EnumIter};
 
 /// Database ID of picture
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub struct PictureId(i64);
 
 impl PictureId {
@@ -60,6 +60,9 @@ pub struct ScannedFile {
 
     pub fs_modified_at: Option<DateTime<Utc>>,
 
+    /// Local date and time in the file name, such as IMG_20230514_103455.jpg.
+    pub file_name_created_at: Option<NaiveDateTime>,
+
     pub fs_file_size_bytes: u64,
 }
 
@@ -142,13 +145,23 @@ impl From<u32> for Orientation {
 
 #[derive(Debug, Default, Clone)]
 pub struct Metadata {
-    pub created_at: Option<DateTime<FixedOffset>>,
+    /// Local date and time the picture was taken, as shown on the camera's clock.
+    pub created_at: Option<NaiveDateTime>,
+
+    /// Offset from UTC of created_at, if the camera recorded it.
+    pub created_at_offset: Option<FixedOffset>,
 
     pub modified_at: Option<DateTime<FixedOffset>>,
 
     /// On iPhone the lens model tells you if it was the front or back camera.
     pub lens_model: Option<String>,
 
+    /// Manufacturer of the camera, such as "Google".
+    pub camera_make: Option<String>,
+
+    /// Model of the camera, such as "Pixel 8".
+    pub camera_model: Option<String>,
+
     // iOS id for linking a video with a photo
     pub content_id: Option<String>,
 
@@ -159,6 +172,18 @@ pub struct Metadata {
 
     // GPS location
     pub location: Option<GPSLocation>,
+
+    /// XMP star rating from other photo managers.
+    pub rating: Option<i32>,
+
+    /// Keywords from IPTC and XMP metadata, as tag paths such as "Places|Europe|Paris".
+    pub keywords: Vec<String>,
+
+    /// Short title from XMP metadata.
+    pub title: Option<String>,
+
+    /// Caption from XMP metadata, or from the EXIF image description.
+    pub caption: Option<String>,
 }
 
 impl Metadata {
@@ -167,6 +192,34 @@ impl Metadata {
             .as_ref()
             .is_some_and(|x| x.contains("front"))
     }
+
+    /// Camera make and model for showing to the user, such as "Google Pixel 8".
+    /// Many cameras already include the make in the model, such as "Canon EOS R5",
+    /// so the make is only prepended when it isn't already present.
+    pub fn camera(&self) -> Option<String> {
+        camera_name(self.camera_make.as_deref(), self.camera_model.as_deref())
+    }
+}
+
+fn camera_name(make: Option<&str>, model: Option<&str>) -> Option<String> {
+    let make = make.map(str::trim).filter(|x| !x.is_empty());
+    let model = model.map(str::trim).filter(|x| !x.is_empty());
+
+    match (make, model) {
+        (Some(make), Some(model)) => {
+            // Some makes have a suffix that the model doesn't, such as
+            // "NIKON CORPORATION" with "NIKON D750".
+            let brand = make.split_whitespace().next().unwrap_or(make);
+            if model.to_lowercase().starts_with(&brand.to_lowercase()) {
+                Some(model.to_string())
+            } else {
+                Some(format!("{} {}", make, model))
+            }
+        }
+        (None, Some(model)) => Some(model.to_string()),
+        (Some(make), None) => Some(make.to_string()),
+        (None, None) => None,
+    }
 }
 
 /// A video extracted from a motion photo
@@ -181,3 +234,29 @@ pub struct MotionPhotoVideo {
     // Should be 90, 180, 270, or the negative of those.
     pub rotation: Option<i32>,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_camera_name() {
+        assert_eq!(
+            Some("Google Pixel 8".into()),
+            camera_name(Some("Google"), Some("Pixel 8"))
+        );
+        assert_eq!(
+            Some("Canon EOS R5".into()),
+            camera_name(Some("Canon"), Some("Canon EOS R5"))
+        );
+        assert_eq!(
+            Some("NIKON D750".into()),
+            camera_name(Some("NIKON CORPORATION"), Some("NIKON D750"))
+        );
+        assert_eq!(
+            Some("iPhone 15".into()),
+            camera_name(Some(" "), Some("iPhone 15"))
+        );
+        assert_eq!(None, camera_name(None, None));
+    }
+}
@@ -9,7 +9,9 @@ use super::model::MotionPhotoVideo;
 use super::motion_photo;
 use super::Metadata;
 use crate::path_encoding;
+use crate::time::OffsetSource;
 use anyhow::*;
+use chrono::{Offset, TimeZone, Utc};
 use rusqlite;
 use rusqlite::params;
 use rusqlite::Row;
@@ -66,7 +68,13 @@ impl Repository {
                     exif_modified_ts = ?4,
                     is_selfie = ?5,
                     content_id = ?6,
-                    orientation = ?7
+                    orientation = ?7,
+                    capture_local_ts = ?8,
+                    capture_offset_secs = ?9,
+                    capture_offset_source = ?10,
+                    camera = ?11,
+                    title = ?12,
+                    caption = ?13
                 WHERE picture_id = ?1",
             )?;
 
@@ -79,19 +87,37 @@ impl Repository {
                     ?1, ?2, ?3
                 ) ON CONFLICT (picture_id) DO UPDATE SET
                     latitude = ?2,
-                    longitude = ?3
+                    longitude = ?3,
+                    source = 'exif'
+                WHERE pictures_geo.source != 'manual'
                 ",
             )?;
 
             for (picture_id, metadata) in pics {
+                // Only a fallback for ordering when the local capture time isn't known,
+                // so assuming UTC for a missing offset is good enough.
+                let created_at = metadata.created_at.and_then(|local| {
+                    metadata
+                        .created_at_offset
+                        .unwrap_or_else(|| Utc.fix())
+                        .from_local_datetime(&local)
+                        .earliest()
+                });
+
                 update_pictures.execute(params![
                     picture_id.id(),
                     metadata::VERSION,
-                    metadata.created_at,
+                    created_at,
                     metadata.modified_at,
                     metadata.is_selfie(),
                     metadata.content_id,
                     metadata.orientation.map(|x| x as u8),
+                    metadata.created_at,
+                    metadata.created_at_offset.map(|x| x.local_minus_utc()),
+                    metadata.created_at_offset.map(|_| OffsetSource::Metadata.as_ref()),
+                    metadata.camera(),
+                    metadata.title,
+                    metadata.caption,
                 ])?;
 
                 if let Some(location) = metadata.location {
@@ -170,12 +196,14 @@ impl Repository {
                     picture_path_b64,
                     picture_path_lossy,
                     link_path_b64,
-                    link_path_lossy
+                    link_path_lossy,
+                    file_name_local_ts
                 ) VALUES (
-                    ?1, ?2, ?3, ?4, ?5, ?6
+                    ?1, ?2, ?3, ?4, ?5, ?6, ?7
                 ) ON CONFLICT (picture_path_b64) DO UPDATE SET
                     fs_created_ts = ?1,
-                    fs_modified_ts = ?2
+                    fs_modified_ts = ?2,
+                    file_name_local_ts = ?7
                 ",
             )?;
 
@@ -200,6 +228,7 @@ impl Repository {
                     picture_path.to_string_lossy(),
                     link_path_b64,
                     link_path.to_string_lossy(),
+                    pic.file_name_created_at,
                 ])?;
             }
         }
@@ -0,0 +1,227 @@
+// SPDX-FileCopyrightText: © 2024 David Bliss
+//
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//! XMP metadata embedded in pictures, such as ratings, keywords, and captions set in
+//! other photo managers.
+
+use anyhow::*;
+use std::fs;
+use std::io::Read;
+use std::path::Path;
+use std::result::Result::Ok;
+
+/// Namespace of basic XMP properties, such as xmp:Rating.
+const XMP_NS: &str = "http://ns.adobe.com/xap/1.0/";
+
+/// Namespace of Dublin Core properties, such as dc:subject.
+const DC_NS: &str = "http://purl.org/dc/elements/1.1/";
+
+/// Namespace of Lightroom properties, such as lr:hierarchicalSubject.
+const LR_NS: &str = "http://ns.adobe.com/lightroom/1.0/";
+
+const RDF_NS: &str = "http://www.w3.org/1999/02/22-rdf-syntax-ns#";
+
+const XML_NS: &str = "http://www.w3.org/XML/1998/namespace";
+
+/// XMP packets are near the start of JPEG files, so only search the first megabyte
+/// instead of reading whole files.
+const MAX_SEARCH_BYTES: u64 = 1024 * 1024;
+
+const PACKET_START: &[u8] = b"<x:xmpmeta";
+const PACKET_END: &[u8] = b"</x:xmpmeta>";
+
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Xmp {
+    /// Star rating from 1 to 5. 0 is unrated and -1 is rejected.
+    pub rating: Option<i32>,
+
+    /// Keywords describing the picture.
+    pub subjects: Vec<String>,
+
+    /// Keywords as paths in a hierarchy, with levels separated by '|'.
+    /// For example, "Places|Europe|Paris".
+    pub hierarchical_subjects: Vec<String>,
+
+    /// Short title of the picture.
+    pub title: Option<String>,
+
+    /// Caption describing the picture.
+    pub description: Option<String>,
+}
+
+impl Xmp {
+    /// Read XMP embedded in a picture file, if any.
+    pub fn from_path(path: &Path) -> Result<Option<Xmp>> {
+        let mut data = Vec::new();
+        fs::File::open(path)?
+            .take(MAX_SEARCH_BYTES)
+            .read_to_end(&mut data)?;
+
+        find_packet(&data).map(Xmp::parse).transpose()
+    }
+
+    /// Parse an XMP packet.
+    pub fn parse(packet: &str) -> Result<Xmp> {
+        let doc = roxmltree::Document::parse(packet)?;
+
+        // Properties can be attributes of rdf:Description or child elements of it.
+        let rating = doc
+            .descendants()
+            .find_map(|n| {
+                if n.has_tag_name((XMP_NS, "Rating")) {
+                    n.text()
+                } else {
+                    n.attribute((XMP_NS, "Rating"))
+                }
+            })
+            .and_then(|x| x.trim().parse::<f64>().ok())
+            .map(|x| x.round() as i32);
+
+        let subjects = list_items(&doc, (DC_NS, "subject"));
+        let hierarchical_subjects = list_items(&doc, (LR_NS, "hierarchicalSubject"));
+        let title = alt_text(&doc, (DC_NS, "title"));
+        let description = alt_text(&doc, (DC_NS, "description"));
+
+        Ok(Xmp {
+            rating,
+            subjects,
+            hierarchical_subjects,
+            title,
+            description,
+        })
+    }
+}
+
+/// Text of the rdf:li items of a list property, such as an rdf:Bag of keywords.
+fn list_items(doc: &roxmltree::Document, name: (&str, &str)) -> Vec<String> {
+    doc.descendants()
+        .filter(|n| n.has_tag_name(name))
+        .flat_map(|n| n.descendants())
+        .filter(|n| n.has_tag_name((RDF_NS, "li")))
+        .filter_map(|n| n.text())
+        .map(|x| x.trim().to_string())
+        .filter(|x| !x.is_empty())
+        .collect()
+}
+
+/// Text of a language alternative property, such as dc:title.
+/// Prefers the default language, otherwise the first language.
+fn alt_text(doc: &roxmltree::Document, name: (&str, &str)) -> Option<String> {
+    let items: Vec<roxmltree::Node> = doc
+        .descendants()
+        .filter(|n| n.has_tag_name(name))
+        .flat_map(|n| n.descendants())
+        .filter(|n| n.has_tag_name((RDF_NS, "li")))
+        .filter(|n| n.text().is_some_and(|x| !x.trim().is_empty()))
+        .collect();
+
+    items
+        .iter()
+        .find(|n| n.attribute((XML_NS, "lang")) == Some("x-default"))
+        .or(items.first())
+        .and_then(|n| n.text())
+        .map(|x| x.trim().to_string())
+}
+
+/// Find XMP packet in file data.
+fn find_packet(data: &[u8]) -> Option<&str> {
+    let start = data
+        .windows(PACKET_START.len())
+        .position(|x| x == PACKET_START)?;
+
+    let end = data[start..]
+        .windows(PACKET_END.len())
+        .position(|x| x == PACKET_END)?;
+
+    std::str::from_utf8(&data[start..start + end + PACKET_END.len()]).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_rating() {
+        let attribute = r#"<x:xmpmeta xmlns:x="adobe:ns:meta/">
+            <rdf:RDF xmlns:rdf="http://www.w3.org/1999/02/22-rdf-syntax-ns#">
+              <rdf:Description rdf:about="" xmlns:xmp="http://ns.adobe.com/xap/1.0/" xmp:Rating="5"/>
+            </rdf:RDF>
+          </x:xmpmeta>"#;
+
+        let element = r#"<x:xmpmeta xmlns:x="adobe:ns:meta/">
+            <rdf:RDF xmlns:rdf="http://www.w3.org/1999/02/22-rdf-syntax-ns#">
+              <rdf:Description rdf:about="" xmlns:xmp="http://ns.adobe.com/xap/1.0/">
+                <xmp:Rating>3</xmp:Rating>
+              </rdf:Description>
+            </rdf:RDF>
+          </x:xmpmeta>"#;
+
+        assert_eq!(Some(5), Xmp::parse(attribute).unwrap().rating);
+        assert_eq!(Some(3), Xmp::parse(element).unwrap().rating);
+    }
+
+    #[test]
+    fn test_parse_subjects() {
+        let packet = r#"<x:xmpmeta xmlns:x="adobe:ns:meta/">
+            <rdf:RDF xmlns:rdf="http://www.w3.org/1999/02/22-rdf-syntax-ns#">
+              <rdf:Description rdf:about=""
+                  xmlns:dc="http://purl.org/dc/elements/1.1/"
+                  xmlns:lr="http://ns.adobe.com/lightroom/1.0/">
+                <dc:subject>
+                  <rdf:Bag>
+                    <rdf:li>Paris</rdf:li>
+                    <rdf:li>Holiday</rdf:li>
+                  </rdf:Bag>
+                </dc:subject>
+                <lr:hierarchicalSubject>
+                  <rdf:Bag>
+                    <rdf:li>Places|Europe|Paris</rdf:li>
+                  </rdf:Bag>
+                </lr:hierarchicalSubject>
+              </rdf:Description>
+            </rdf:RDF>
+          </x:xmpmeta>"#;
+
+        let xmp = Xmp::parse(packet).unwrap();
+        assert_eq!(vec!["Paris", "Holiday"], xmp.subjects);
+        assert_eq!(vec!["Places|Europe|Paris"], xmp.hierarchical_subjects);
+        assert_eq!(None, xmp.rating);
+    }
+
+    #[test]
+    fn test_parse_title_and_description() {
+        let packet = r#"<x:xmpmeta xmlns:x="adobe:ns:meta/">
+            <rdf:RDF xmlns:rdf="http://www.w3.org/1999/02/22-rdf-syntax-ns#">
+              <rdf:Description rdf:about="" xmlns:dc="http://purl.org/dc/elements/1.1/">
+                <dc:title>
+                  <rdf:Alt>
+                    <rdf:li xml:lang="fr-FR">Tour Eiffel</rdf:li>
+                    <rdf:li xml:lang="x-default">Eiffel Tower</rdf:li>
+                  </rdf:Alt>
+                </dc:title>
+                <dc:description>
+                  <rdf:Alt>
+                    <rdf:li xml:lang="en-GB"> Lunch by the river </rdf:li>
+                  </rdf:Alt>
+                </dc:description>
+              </rdf:Description>
+            </rdf:RDF>
+          </x:xmpmeta>"#;
+
+        let xmp = Xmp::parse(packet).unwrap();
+        assert_eq!(Some("Eiffel Tower".to_string()), xmp.title);
+        assert_eq!(Some("Lunch by the river".to_string()), xmp.description);
+    }
+
+    #[test]
+    fn test_find_packet() {
+        let data =
+            b"\xff\xd8\xff\xe1junk<x:xmpmeta xmlns:x=\"adobe:ns:meta/\"></x:xmpmeta>\xff\xd9";
+        assert_eq!(
+            Some("<x:xmpmeta xmlns:x=\"adobe:ns:meta/\"></x:xmpmeta>"),
+            find_packet(data)
+        );
+        assert_eq!(None, find_packet(b"\xff\xd8\xff\xd9"));
+    }
+}
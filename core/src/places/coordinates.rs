@@ -0,0 +1,137 @@
+// SPDX-FileCopyrightText: © 2024 David Bliss
+//
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use h3o::LatLng;
+
+/// Parses coordinates pasted by the user, such as copied from a map website.
+/// Accepts decimal degrees ("38.7223, -9.1393"), degrees with hemispheres
+/// ("38.7223° N, 9.1393° W"), degrees minutes seconds ("38°43'20.3"N 9°08'21.5"W")
+/// and geo URIs ("geo:38.7223,-9.1393").
+pub fn parse_coordinates(text: &str) -> Option<LatLng> {
+    let text = text.trim();
+
+    // geo URIs can have an altitude and parameters, such as "geo:38.7,-9.1,10;u=35"
+    let text = match text.strip_prefix("geo:") {
+        Some(uri) => {
+            let uri = uri.split([';', '?']).next()?;
+            let mut parts = uri.split(',');
+            return to_lat_lng(parts.next()?.trim(), parts.next()?.trim());
+        }
+        None => text,
+    };
+
+    let (first, second) = split_pair(text)?;
+    to_lat_lng(first.trim(), second.trim())
+}
+
+/// Split text into latitude and longitude parts.
+fn split_pair(text: &str) -> Option<(&str, &str)> {
+    let parts: Vec<&str> = text.split(',').collect();
+    if parts.len() == 2 {
+        return Some((parts[0], parts[1]));
+    }
+
+    // Hemispheres separate the coordinates, such as "38°43'N 9°08'W" or "N 38°43' W 9°08'"
+    let hemispheres: Vec<(usize, char)> = text
+        .char_indices()
+        .filter(|(_, c)| "NSEWnsew".contains(*c))
+        .collect();
+
+    if let [(first, c), (second, _)] = hemispheres[..] {
+        let index = if text[..first].trim().is_empty() {
+            second
+        } else {
+            first + c.len_utf8()
+        };
+        return Some(text.split_at(index));
+    }
+
+    let parts: Vec<&str> = text.split_whitespace().collect();
+    if parts.len() == 2 {
+        return Some((parts[0], parts[1]));
+    }
+
+    None
+}
+
+fn to_lat_lng(first: &str, second: &str) -> Option<LatLng> {
+    let (first, first_hemisphere) = parse_degrees(first)?;
+    let (second, second_hemisphere) = parse_degrees(second)?;
+
+    // Longitude is conventionally second, unless hemispheres say otherwise.
+    let (lat, lng) = match (first_hemisphere, second_hemisphere) {
+        (Some('E' | 'W'), _) | (_, Some('N' | 'S')) => (second, first),
+        _ => (first, second),
+    };
+
+    if !(-90.0..=90.0).contains(&lat) || !(-180.0..=180.0).contains(&lng) {
+        return None;
+    }
+
+    LatLng::new(lat, lng).ok()
+}
+
+/// Parse signed decimal degrees from decimal degrees or degrees minutes seconds,
+/// with an optional hemisphere before or after the number.
+fn parse_degrees(text: &str) -> Option<(f64, Option<char>)> {
+    let hemisphere = text
+        .chars()
+        .find(|c| c.is_ascii_alphabetic())
+        .map(|c| c.to_ascii_uppercase());
+
+    if hemisphere.is_some_and(|h| !"NSEW".contains(h)) {
+        return None;
+    }
+
+    let is_negative = text.trim_start().starts_with('-');
+
+    let numbers = text
+        .split(|c: char| !(c.is_ascii_digit() || c == '.'))
+        .filter(|part| !part.is_empty())
+        .map(|part| part.parse::<f64>())
+        .collect::<Result<Vec<f64>, _>>()
+        .ok()?;
+
+    let degrees = match numbers[..] {
+        [degrees] => degrees,
+        [degrees, minutes] if minutes < 60.0 => degrees + minutes / 60.0,
+        [degrees, minutes, seconds] if minutes < 60.0 && seconds < 60.0 => {
+            degrees + minutes / 60.0 + seconds / 3600.0
+        }
+        _ => return None,
+    };
+
+    let is_negative = is_negative || matches!(hemisphere, Some('S' | 'W'));
+    let degrees = if is_negative { -degrees } else { degrees };
+
+    Some((degrees, hemisphere))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_near(expected: (f64, f64), actual: Option<LatLng>) {
+        let actual = actual.expect("Coordinates should parse");
+        assert!((expected.0 - actual.lat()).abs() < 0.0001, "{} != {}", expected.0, actual.lat());
+        assert!((expected.1 - actual.lng()).abs() < 0.0001, "{} != {}", expected.1, actual.lng());
+    }
+
+    #[test]
+    fn test_parse_coordinates() {
+        assert_near((38.7223, -9.1393), parse_coordinates("38.7223, -9.1393"));
+        assert_near((38.7223, -9.1393), parse_coordinates(" 38.7223 -9.1393 "));
+        assert_near((38.7223, -9.1393), parse_coordinates("38.7223° N, 9.1393° W"));
+        assert_near((38.7223, -9.1393), parse_coordinates("9.1393° W, 38.7223° N"));
+        assert_near((38.7223, -9.1393), parse_coordinates("38°43'20.3\"N 9°08'21.5\"W"));
+        assert_near((-33.8568, 151.2153), parse_coordinates("S 33° 51.408' E 151° 12.918'"));
+        assert_near((38.7223, -9.1393), parse_coordinates("geo:38.7223,-9.1393,12;u=35"));
+
+        assert!(parse_coordinates("").is_none());
+        assert!(parse_coordinates("Lisbon").is_none());
+        assert!(parse_coordinates("91.0, 10.0").is_none());
+        assert!(parse_coordinates("38.7223").is_none());
+        assert!(parse_coordinates("38°75'N 9°08'W").is_none());
+    }
+}
@@ -0,0 +1,230 @@
+// SPDX-FileCopyrightText: © 2024 David Bliss
+//
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use super::model::{Place, PlaceId};
+use crate::photo::gps::GPSLocation;
+use anyhow::*;
+use h3o::{CellIndex, LatLng, Resolution};
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+use std::path::Path;
+use std::result::Result::Ok;
+use tracing::{debug, info};
+
+/// GeoNames cities file, such as cities15000.txt renamed.
+pub const CITIES_FILE_NAME: &str = "cities.txt";
+
+/// GeoNames names of first-level administrative divisions.
+pub const ADMIN1_FILE_NAME: &str = "admin1CodesASCII.txt";
+
+/// GeoNames country information.
+pub const COUNTRIES_FILE_NAME: &str = "countryInfo.txt";
+
+/// Resolution of cells used to index cities.
+/// Cells at resolution four have an average edge length of about 22 kilometres.
+const INDEX_RESOLUTION: Resolution = Resolution::Four;
+
+/// How many rings of neighbouring cells to search for a nearby city.
+const SEARCH_RINGS: u32 = 4;
+
+/// Locations further than this from any city don't have a place.
+const MAX_DISTANCE_KM: f64 = 100.0;
+
+#[derive(Debug)]
+struct City {
+    place: Place,
+    location: LatLng,
+}
+
+/// Offline reverse geocoder that finds the nearest city to a location.
+/// Backed by a GeoNames style dataset of cities, administrative divisions, and countries.
+#[derive(Debug)]
+pub struct Geocoder {
+    cities: Vec<City>,
+
+    /// Indexes into cities for cities in each cell.
+    index: HashMap<CellIndex, Vec<usize>>,
+}
+
+impl Geocoder {
+    /// Load geocoder from a directory containing the GeoNames dataset files.
+    pub fn open(dataset_dir: &Path) -> Result<Self> {
+        let open = |name: &str| {
+            let path = dataset_dir.join(name);
+            File::open(&path)
+                .map(BufReader::new)
+                .with_context(|| format!("Failed opening {:?}", path))
+        };
+
+        Self::from_readers(
+            open(CITIES_FILE_NAME)?,
+            open(ADMIN1_FILE_NAME)?,
+            open(COUNTRIES_FILE_NAME)?,
+        )
+    }
+
+    /// Build geocoder from readers over GeoNames tab separated files.
+    pub fn from_readers(
+        cities: impl BufRead,
+        admin1: impl BufRead,
+        countries: impl BufRead,
+    ) -> Result<Self> {
+        // Country code to country name.
+        // Columns: ISO, ISO3, ISO-Numeric, fips, Country, ...
+        let countries: HashMap<String, String> = countries
+            .lines()
+            .map_while(|line| line.ok())
+            .filter(|line| !line.starts_with('#'))
+            .filter_map(|line| {
+                let columns: Vec<&str> = line.split('\t').collect();
+                Some((columns.first()?.to_string(), columns.get(4)?.to_string()))
+            })
+            .collect();
+
+        // Country code and admin1 code, such as "US.CA", to region name.
+        // Columns: code, name, ascii name, geonameid
+        let regions: HashMap<String, String> = admin1
+            .lines()
+            .map_while(|line| line.ok())
+            .filter_map(|line| {
+                let columns: Vec<&str> = line.split('\t').collect();
+                Some((columns.first()?.to_string(), columns.get(1)?.to_string()))
+            })
+            .collect();
+
+        let mut geocoder = Geocoder {
+            cities: Vec::new(),
+            index: HashMap::new(),
+        };
+
+        // Columns: geonameid, name, asciiname, alternatenames, latitude, longitude,
+        // feature class, feature code, country code, cc2, admin1 code, admin2 code,
+        // admin3 code, admin4 code, population, elevation, dem, timezone, modification date
+        for line in cities.lines().map_while(|line| line.ok()) {
+            let columns: Vec<&str> = line.split('\t').collect();
+            if columns.len() < 18 {
+                debug!("Skipping short line in cities file: {}", line);
+                continue;
+            }
+
+            let (Ok(place_id), Ok(latitude), Ok(longitude)) = (
+                columns[0].parse::<i64>(),
+                columns[4].parse::<f64>(),
+                columns[5].parse::<f64>(),
+            ) else {
+                debug!("Skipping invalid line in cities file: {}", line);
+                continue;
+            };
+
+            let gps = GPSLocation::from_degrees(latitude, longitude);
+            let (Ok(location), Ok(cell)) =
+                (gps.to_lat_lng(), gps.to_cell_index(INDEX_RESOLUTION))
+            else {
+                continue;
+            };
+
+            let country_code = columns[8].to_string();
+
+            let Some(country) = countries.get(&country_code) else {
+                debug!("Skipping city with unknown country code: {}", line);
+                continue;
+            };
+
+            let region = regions
+                .get(&format!("{}.{}", country_code, columns[10]))
+                .cloned();
+
+            let timezone = Some(columns[17])
+                .filter(|x| !x.is_empty())
+                .map(String::from);
+
+            let place = Place {
+                place_id: PlaceId::new(place_id),
+                city: columns[1].to_string(),
+                region,
+                country: country.clone(),
+                country_code,
+                timezone,
+            };
+
+            geocoder
+                .index
+                .entry(cell)
+                .or_default()
+                .push(geocoder.cities.len());
+            geocoder.cities.push(City { place, location });
+        }
+
+        info!("Loaded {} cities for reverse geocoding", geocoder.cities.len());
+
+        Ok(geocoder)
+    }
+
+    /// Find nearest place to a location.
+    pub fn lookup(&self, location: LatLng) -> Option<Place> {
+        location
+            .to_cell(INDEX_RESOLUTION)
+            .grid_disk::<Vec<_>>(SEARCH_RINGS)
+            .into_iter()
+            .filter_map(|cell| self.index.get(&cell))
+            .flatten()
+            .map(|i| &self.cities[*i])
+            .map(|city| (city, city.location.distance_km(location)))
+            .filter(|(_, distance)| *distance <= MAX_DISTANCE_KM)
+            .min_by(|(_, a), (_, b)| a.total_cmp(b))
+            .map(|(city, _)| city.place.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CITIES: &str = "2267057\tLisbon\tLisbon\t\t38.71667\t-9.13333\tP\tPPLC\tPT\t\t14\t1106\t\t\t517802\t\t45\tEurope/Lisbon\t2022-12-08
+2735943\tPorto\tPorto\t\t41.14961\t-8.61099\tP\tPPLA\tPT\t\t17\t1312\t\t\t249633\t\t93\tEurope/Lisbon\t2022-12-08
+2267095\tLagos\tLagos\t\t37.10202\t-8.67422\tP\tPPL\tPT\t\t08\t0807\t\t\t22094\t\t13\tEurope/Lisbon\t2022-12-08
+";
+
+    const ADMIN1: &str = "PT.14\tLisbon\tLisbon\t2267056
+PT.17\tPorto\tPorto\t2735941
+PT.08\tFaro\tFaro\t2268337
+";
+
+    const COUNTRIES: &str = "# ISO\tISO3\tISO-Numeric\tfips\tCountry
+PT\tPRT\t620\tPO\tPortugal\tLisbon
+";
+
+    fn geocoder() -> Geocoder {
+        Geocoder::from_readers(CITIES.as_bytes(), ADMIN1.as_bytes(), COUNTRIES.as_bytes()).unwrap()
+    }
+
+    #[test]
+    fn test_lookup_nearest_city() {
+        let geocoder = geocoder();
+
+        // Belém, in the west of Lisbon
+        let place = geocoder.lookup(LatLng::new(38.6975, -9.2064).unwrap()).unwrap();
+        assert_eq!("Lisbon", place.city);
+        assert_eq!(Some(String::from("Lisbon")), place.region);
+        assert_eq!("Portugal", place.country);
+        assert_eq!("PT", place.country_code);
+        assert_eq!(Some(String::from("Europe/Lisbon")), place.timezone);
+
+        // Vila Nova de Gaia, across the river from Porto
+        let place = geocoder.lookup(LatLng::new(41.1239, -8.6118).unwrap()).unwrap();
+        assert_eq!("Porto", place.city);
+
+        let place = geocoder.lookup(LatLng::new(37.0894, -8.6682).unwrap()).unwrap();
+        assert_eq!("Lagos, Faro, Portugal", place.to_string());
+    }
+
+    #[test]
+    fn test_lookup_too_far_away() {
+        let geocoder = geocoder();
+
+        // Middle of the Atlantic
+        assert!(geocoder.lookup(LatLng::new(40.0, -30.0).unwrap()).is_none());
+    }
+}
@@ -0,0 +1,88 @@
+// SPDX-FileCopyrightText: © 2024 David Bliss
+//
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//! Infer locations of pictures without GPS data from pictures and videos taken
+//! shortly before or after, such as indoor shots between outdoor ones.
+
+use super::track::{Track, TrackPoint};
+use crate::photo::PictureId;
+use chrono::{DateTime, TimeDelta, Utc};
+use h3o::LatLng;
+
+/// A proposed location for a picture without one.
+#[derive(Debug, Clone)]
+pub struct Inference {
+    pub picture_id: PictureId,
+
+    pub location: LatLng,
+
+    /// Time between the picture and the nearest located item.
+    pub gap: TimeDelta,
+}
+
+/// Propose locations for pictures without a location.
+///
+/// Known locations are treated as a track, so a picture taken between two located
+/// items within `max_gap` of each other gets a location interpolated between them.
+/// Otherwise a picture gets the location of the nearest item within `max_gap`.
+pub fn infer_locations(
+    known: Vec<TrackPoint>,
+    unknown: Vec<(PictureId, DateTime<Utc>)>,
+    max_gap: TimeDelta,
+) -> Vec<Inference> {
+    let track = Track::new(known);
+
+    unknown
+        .into_iter()
+        .filter_map(|(picture_id, timestamp)| {
+            let location = track.locate(timestamp, max_gap)?;
+            let gap = track.nearest_gap(timestamp)?;
+            Some(Inference {
+                picture_id,
+                location,
+                gap,
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_infer_locations() {
+        let t0 = DateTime::parse_from_rfc3339("2023-05-14T10:00:00Z")
+            .unwrap()
+            .to_utc();
+
+        let known = vec![
+            TrackPoint {
+                timestamp: t0,
+                location: LatLng::new(38.7, -9.1).unwrap(),
+            },
+            TrackPoint {
+                timestamp: t0 + TimeDelta::hours(3),
+                location: LatLng::new(41.1, -8.6).unwrap(),
+            },
+        ];
+
+        let unknown = vec![
+            (PictureId::new(1), t0 + TimeDelta::minutes(10)),
+            (PictureId::new(2), t0 + TimeDelta::minutes(90)),
+            (PictureId::new(3), t0 + TimeDelta::minutes(175)),
+        ];
+
+        let inferences = infer_locations(known, unknown, TimeDelta::minutes(30));
+        assert_eq!(2, inferences.len());
+
+        assert_eq!(PictureId::new(1), inferences[0].picture_id);
+        assert_eq!(38.7, inferences[0].location.lat());
+        assert_eq!(TimeDelta::minutes(10), inferences[0].gap);
+
+        assert_eq!(PictureId::new(3), inferences[1].picture_id);
+        assert_eq!(41.1, inferences[1].location.lat());
+        assert_eq!(TimeDelta::minutes(5), inferences[1].gap);
+    }
+}
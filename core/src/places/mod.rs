@@ -0,0 +1,21 @@
+// SPDX-FileCopyrightText: © 2024 David Bliss
+//
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+pub mod coordinates;
+pub mod geocoder;
+pub mod infer;
+pub mod model;
+pub mod privacy;
+pub mod repo;
+pub mod track;
+
+pub use coordinates::parse_coordinates;
+pub use geocoder::Geocoder;
+pub use infer::Inference;
+pub use model::LocationSource;
+pub use model::Place;
+pub use model::PlaceId;
+pub use privacy::{PrivacyZone, PrivacyZoneId, PrivacyZones, ZoneArea};
+pub use repo::Repository;
+pub use track::Track;
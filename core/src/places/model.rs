@@ -0,0 +1,77 @@
+// SPDX-FileCopyrightText: © 2024 David Bliss
+//
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use std::fmt::Display;
+use strum::{AsRefStr, EnumString};
+
+/// Database ID of place. Same as the GeoNames ID.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PlaceId(i64);
+
+impl PlaceId {
+    pub fn new(id: i64) -> Self {
+        Self(id)
+    }
+
+    /// FIXME replace this with a To/From SQL implementation.
+    pub fn id(&self) -> i64 {
+        self.0
+    }
+}
+
+impl Display for PlaceId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+/// A named place, such as a city or town.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Place {
+    pub place_id: PlaceId,
+
+    /// Name of city or town.
+    pub city: String,
+
+    /// Name of first-level administrative division, such as a state or province.
+    pub region: Option<String>,
+
+    /// Name of country.
+    pub country: String,
+
+    /// ISO 3166 two letter country code.
+    pub country_code: String,
+
+    /// IANA time zone identifier, such as "Europe/Lisbon".
+    pub timezone: Option<String>,
+}
+
+impl Display for Place {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.region {
+            // Some city states have a region of the same name, so don't repeat it.
+            Some(ref region) if *region != self.city => {
+                write!(f, "{}, {}, {}", self.city, region, self.country)
+            }
+            _ => write!(f, "{}, {}", self.city, self.country),
+        }
+    }
+}
+
+/// Where the location of a picture or video came from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, AsRefStr, EnumString)]
+#[strum(serialize_all = "lowercase")]
+pub enum LocationSource {
+    /// GPS data embedded in the picture's EXIF metadata.
+    Exif,
+
+    /// Matched by time against a track recorded by a GPS logger.
+    Track,
+
+    /// Inferred from pictures and videos taken shortly before or after.
+    Inferred,
+
+    /// Set by the user. Never replaced by locations from metadata.
+    Manual,
+}
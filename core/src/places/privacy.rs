@@ -0,0 +1,130 @@
+// SPDX-FileCopyrightText: © 2024 David Bliss
+//
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use h3o::{CellIndex, LatLng};
+use std::fmt::Display;
+
+/// Database ID of privacy zone.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PrivacyZoneId(i64);
+
+impl PrivacyZoneId {
+    pub fn new(id: i64) -> Self {
+        Self(id)
+    }
+
+    /// FIXME replace this with a To/From SQL implementation.
+    pub fn id(&self) -> i64 {
+        self.0
+    }
+}
+
+impl Display for PrivacyZoneId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+/// Area covered by a privacy zone.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ZoneArea {
+    /// Circle around a centre, with a radius in metres.
+    Circle { centre: LatLng, radius: f64 },
+
+    /// Set of H3 cells. Cells can be of any resolution.
+    Cells(Vec<CellIndex>),
+}
+
+impl ZoneArea {
+    pub fn contains(&self, location: LatLng) -> bool {
+        match self {
+            ZoneArea::Circle { centre, radius } => centre.distance_m(location) <= *radius,
+            ZoneArea::Cells(cells) => cells
+                .iter()
+                .any(|cell| location.to_cell(cell.resolution()) == *cell),
+        }
+    }
+}
+
+/// An area, such as home or work, where the locations of pictures and videos
+/// are private and must not be shown.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PrivacyZone {
+    pub zone_id: PrivacyZoneId,
+
+    /// Name of zone, such as "Home".
+    pub name: String,
+
+    pub area: ZoneArea,
+}
+
+/// All privacy zones.
+#[derive(Debug, Clone, Default)]
+pub struct PrivacyZones(Vec<PrivacyZone>);
+
+impl PrivacyZones {
+    pub fn new(zones: Vec<PrivacyZone>) -> Self {
+        Self(zones)
+    }
+
+    /// Is a location inside any privacy zone?
+    pub fn contains(&self, location: LatLng) -> bool {
+        self.0.iter().any(|zone| zone.area.contains(location))
+    }
+
+    /// Location if it is outside all privacy zones.
+    pub fn reveal(&self, location: Option<LatLng>) -> Option<LatLng> {
+        location.filter(|location| !self.contains(*location))
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &PrivacyZone> {
+        self.0.iter()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use h3o::Resolution;
+
+    #[test]
+    fn test_contains() {
+        let home = LatLng::new(38.7223, -9.1393).unwrap();
+        let nearby = LatLng::new(38.7230, -9.1393).unwrap(); // about 80 metres north
+        let elsewhere = LatLng::new(41.1579, -8.6291).unwrap();
+
+        let circle = PrivacyZone {
+            zone_id: PrivacyZoneId::new(1),
+            name: "Home".into(),
+            area: ZoneArea::Circle {
+                centre: home,
+                radius: 100.0,
+            },
+        };
+
+        assert!(circle.area.contains(home));
+        assert!(circle.area.contains(nearby));
+        assert!(!circle.area.contains(elsewhere));
+
+        let cells = PrivacyZone {
+            zone_id: PrivacyZoneId::new(2),
+            name: "Work".into(),
+            area: ZoneArea::Cells(vec![elsewhere.to_cell(Resolution::Nine)]),
+        };
+
+        assert!(cells.area.contains(elsewhere));
+        assert!(!cells.area.contains(home));
+
+        let zones = PrivacyZones::new(vec![circle, cells]);
+        assert!(zones.contains(nearby));
+        assert_eq!(None, zones.reveal(Some(elsewhere)));
+
+        let outside = LatLng::new(37.0194, -7.9304).unwrap();
+        assert_eq!(Some(outside), zones.reveal(Some(outside)));
+    }
+}
@@ -0,0 +1,847 @@
+// SPDX-FileCopyrightText: © 2024 David Bliss
+//
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use super::model::{LocationSource, Place, PlaceId};
+use super::privacy::{PrivacyZone, PrivacyZoneId, PrivacyZones, ZoneArea};
+use super::track::TrackPoint;
+use crate::photo::PictureId;
+use crate::time::OffsetSource;
+use crate::video::VideoId;
+use crate::visual::VisualId;
+
+use anyhow::*;
+use chrono::{DateTime, FixedOffset, NaiveDateTime, Utc};
+use h3o::{CellIndex, LatLng};
+use std::collections::HashMap;
+use std::str::FromStr;
+use rusqlite;
+use rusqlite::params;
+use rusqlite::Row;
+use std::result::Result::Ok;
+use std::sync::{Arc, Mutex};
+
+/// Pictures and videos with a location, but no place.
+type NeedGeocode = (Vec<(PictureId, LatLng)>, Vec<(VideoId, LatLng)>);
+
+/// Repository of places resolved from the locations of pictures and videos.
+/// Repository is backed by a Sqlite database.
+#[derive(Debug, Clone)]
+pub struct Repository {
+    /// Connection to backing Sqlite database.
+    con: Arc<Mutex<rusqlite::Connection>>,
+}
+
+impl Repository {
+    pub fn open(con: Arc<Mutex<rusqlite::Connection>>) -> Result<Repository> {
+        let repo = Repository { con };
+        Ok(repo)
+    }
+
+    /// Finds pictures and videos with a location, but no place.
+    pub fn find_need_geocode(&self) -> Result<NeedGeocode> {
+        let con = self.con.lock().unwrap();
+
+        let mut stmt = con.prepare(
+            "SELECT
+                pictures_geo.picture_id,
+                pictures_geo.latitude,
+                pictures_geo.longitude
+            FROM pictures_geo
+            LEFT OUTER JOIN pictures_places USING (picture_id)
+            WHERE pictures_places.picture_id IS NULL",
+        )?;
+
+        let pictures = stmt
+            .query_map([], |row| {
+                let picture_id = row.get(0).map(PictureId::new)?;
+                let location = Self::to_lat_lng(row)?;
+                Ok((picture_id, location))
+            })?
+            .flatten()
+            .collect();
+
+        let mut stmt = con.prepare(
+            "SELECT
+                videos_geo.video_id,
+                videos_geo.latitude,
+                videos_geo.longitude
+            FROM videos_geo
+            LEFT OUTER JOIN videos_places USING (video_id)
+            WHERE videos_places.video_id IS NULL",
+        )?;
+
+        let videos = stmt
+            .query_map([], |row| {
+                let video_id = row.get(0).map(VideoId::new)?;
+                let location = Self::to_lat_lng(row)?;
+                Ok((video_id, location))
+            })?
+            .flatten()
+            .collect();
+
+        Ok((pictures, videos))
+    }
+
+    /// Finds videos without an offset from UTC that are dated only by their file name,
+    /// which is a local time, along with the time zone of the place they were taken, if known.
+    pub fn find_videos_need_local_capture_offset(
+        &self,
+    ) -> Result<Vec<(VideoId, NaiveDateTime, Option<String>)>> {
+        let con = self.con.lock().unwrap();
+
+        let mut stmt = con.prepare(
+            "SELECT
+                videos.video_id,
+                videos.file_name_local_ts,
+                places.timezone
+            FROM videos
+            LEFT OUTER JOIN videos_places USING (video_id)
+            LEFT OUTER JOIN places USING (place_id)
+            WHERE videos.file_name_local_ts IS NOT NULL
+            AND videos.stream_created_ts IS NULL
+            AND videos.capture_offset_secs IS NULL",
+        )?;
+
+        let videos = stmt
+            .query_map([], |row| {
+                let video_id = row.get("video_id").map(VideoId::new)?;
+                let local: NaiveDateTime = row.get("file_name_local_ts")?;
+                let timezone: Option<String> = row.get("timezone")?;
+                Ok((video_id, local, timezone))
+            })?
+            .flatten()
+            .collect();
+
+        Ok(videos)
+    }
+
+    /// Saves the places of pictures and videos.
+    pub fn add_places(
+        &mut self,
+        pictures: Vec<(PictureId, Place)>,
+        videos: Vec<(VideoId, Place)>,
+    ) -> Result<()> {
+        let mut con = self.con.lock().unwrap();
+        let tx = con.transaction()?;
+
+        {
+            let mut insert_place = tx.prepare_cached(
+                "INSERT INTO places (
+                    place_id,
+                    city,
+                    region,
+                    country,
+                    country_code,
+                    timezone
+                ) VALUES (
+                    ?1, ?2, ?3, ?4, ?5, ?6
+                ) ON CONFLICT (place_id) DO UPDATE SET
+                    city = ?2,
+                    region = ?3,
+                    country = ?4,
+                    country_code = ?5,
+                    timezone = ?6
+                ",
+            )?;
+
+            let mut insert_picture = tx.prepare_cached(
+                "INSERT INTO pictures_places (
+                    picture_id,
+                    place_id
+                ) VALUES (
+                    ?1, ?2
+                ) ON CONFLICT (picture_id) DO UPDATE SET
+                    place_id = ?2
+                ",
+            )?;
+
+            let mut insert_video = tx.prepare_cached(
+                "INSERT INTO videos_places (
+                    video_id,
+                    place_id
+                ) VALUES (
+                    ?1, ?2
+                ) ON CONFLICT (video_id) DO UPDATE SET
+                    place_id = ?2
+                ",
+            )?;
+
+            let mut add_place = |place: &Place| {
+                insert_place.execute(params![
+                    place.place_id.id(),
+                    place.city,
+                    place.region,
+                    place.country,
+                    place.country_code,
+                    place.timezone,
+                ])
+            };
+
+            for (picture_id, place) in pictures {
+                add_place(&place)?;
+                insert_picture.execute(params![picture_id.id(), place.place_id.id()])?;
+            }
+
+            for (video_id, place) in videos {
+                add_place(&place)?;
+                insert_video.execute(params![video_id.id(), place.place_id.id()])?;
+            }
+        }
+
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// Finds pictures and videos without an offset from UTC for the time they were taken,
+    /// along with the time zone of the place they were taken, if known.
+    /// Pictures have a local time, from metadata or the file name, but videos have a UTC time.
+    /// Videos are only found if they have a time zone, as without one their UTC time is
+    /// already good enough to order by.
+    #[allow(clippy::type_complexity)]
+    pub fn find_need_capture_offset(
+        &self,
+    ) -> Result<(
+        Vec<(PictureId, NaiveDateTime, Option<String>)>,
+        Vec<(VideoId, DateTime<Utc>, String)>,
+    )> {
+        let con = self.con.lock().unwrap();
+
+        let mut stmt = con.prepare(
+            "SELECT
+                pictures.picture_id,
+                COALESCE(pictures.capture_local_ts, pictures.file_name_local_ts) AS capture_local_ts,
+                places.timezone
+            FROM pictures
+            LEFT OUTER JOIN pictures_places USING (picture_id)
+            LEFT OUTER JOIN places USING (place_id)
+            WHERE COALESCE(pictures.capture_local_ts, pictures.file_name_local_ts) IS NOT NULL
+            AND pictures.capture_offset_secs IS NULL",
+        )?;
+
+        let pictures = stmt
+            .query_map([], |row| {
+                let picture_id = row.get("picture_id").map(PictureId::new)?;
+                let local: NaiveDateTime = row.get("capture_local_ts")?;
+                let timezone: Option<String> = row.get("timezone")?;
+                Ok((picture_id, local, timezone))
+            })?
+            .flatten()
+            .collect();
+
+        let mut stmt = con.prepare(
+            "SELECT
+                videos.video_id,
+                videos.stream_created_ts,
+                places.timezone
+            FROM videos
+            INNER JOIN videos_places USING (video_id)
+            INNER JOIN places USING (place_id)
+            WHERE videos.stream_created_ts IS NOT NULL
+            AND videos.capture_offset_secs IS NULL
+            AND places.timezone IS NOT NULL",
+        )?;
+
+        let videos = stmt
+            .query_map([], |row| {
+                let video_id = row.get("video_id").map(VideoId::new)?;
+                let utc: DateTime<Utc> = row.get("stream_created_ts")?;
+                let timezone: String = row.get("timezone")?;
+                Ok((video_id, utc, timezone))
+            })?
+            .flatten()
+            .collect();
+
+        Ok((pictures, videos))
+    }
+
+    /// Saves offsets from UTC for the times pictures and videos were taken.
+    pub fn set_capture_offsets(
+        &mut self,
+        pictures: Vec<(PictureId, FixedOffset, OffsetSource)>,
+        videos: Vec<(VideoId, FixedOffset, OffsetSource)>,
+    ) -> Result<()> {
+        let mut con = self.con.lock().unwrap();
+        let tx = con.transaction()?;
+
+        {
+            let mut update_picture = tx.prepare_cached(
+                "UPDATE pictures
+                SET
+                    capture_offset_secs = ?2,
+                    capture_offset_source = ?3
+                WHERE picture_id = ?1",
+            )?;
+
+            for (picture_id, offset, source) in pictures {
+                update_picture.execute(params![
+                    picture_id.id(),
+                    offset.local_minus_utc(),
+                    source.as_ref(),
+                ])?;
+            }
+
+            let mut update_video = tx.prepare_cached(
+                "UPDATE videos
+                SET
+                    capture_offset_secs = ?2,
+                    capture_offset_source = ?3
+                WHERE video_id = ?1",
+            )?;
+
+            for (video_id, offset, source) in videos {
+                update_video.execute(params![
+                    video_id.id(),
+                    offset.local_minus_utc(),
+                    source.as_ref(),
+                ])?;
+            }
+        }
+
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// Sets the locations of pictures, replacing any existing locations.
+    pub fn set_picture_locations(
+        &mut self,
+        source: LocationSource,
+        pictures: Vec<(PictureId, LatLng)>,
+    ) -> Result<()> {
+        let mut con = self.con.lock().unwrap();
+        let tx = con.transaction()?;
+
+        {
+            let mut stmt = tx.prepare_cached(
+                "INSERT INTO pictures_geo (
+                    picture_id,
+                    latitude,
+                    longitude,
+                    source
+                ) VALUES (
+                    ?1, ?2, ?3, ?4
+                ) ON CONFLICT (picture_id) DO UPDATE SET
+                    latitude = ?2,
+                    longitude = ?3,
+                    source = ?4
+                ",
+            )?;
+
+            for (picture_id, location) in pictures {
+                stmt.execute(params![
+                    picture_id.id(),
+                    location.lat(),
+                    location.lng(),
+                    source.as_ref(),
+                ])?;
+            }
+        }
+
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// Sets the locations of videos, replacing any existing locations.
+    pub fn set_video_locations(
+        &mut self,
+        source: LocationSource,
+        videos: Vec<(VideoId, LatLng)>,
+    ) -> Result<()> {
+        let mut con = self.con.lock().unwrap();
+        let tx = con.transaction()?;
+
+        {
+            let mut stmt = tx.prepare_cached(
+                "INSERT INTO videos_geo (
+                    video_id,
+                    latitude,
+                    longitude,
+                    source
+                ) VALUES (
+                    ?1, ?2, ?3, ?4
+                ) ON CONFLICT (video_id) DO UPDATE SET
+                    latitude = ?2,
+                    longitude = ?3,
+                    source = ?4
+                ",
+            )?;
+
+            for (video_id, location) in videos {
+                stmt.execute(params![
+                    video_id.id(),
+                    location.lat(),
+                    location.lng(),
+                    source.as_ref(),
+                ])?;
+            }
+        }
+
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// Finds locations of pictures and videos to infer missing locations from,
+    /// and the pictures with missing locations.
+    /// Inferred locations aren't used to infer further locations.
+    pub fn find_need_inference(
+        &self,
+    ) -> Result<(Vec<TrackPoint>, Vec<(PictureId, DateTime<Utc>)>)> {
+        let con = self.con.lock().unwrap();
+
+        let mut stmt = con.prepare(
+            "SELECT
+                visual.ordering_ts,
+                visual.latitude,
+                visual.longitude
+            FROM visual
+            LEFT OUTER JOIN pictures_geo USING (picture_id)
+            WHERE visual.latitude IS NOT NULL
+            AND visual.longitude IS NOT NULL
+            AND COALESCE(pictures_geo.source, 'exif') != 'inferred'",
+        )?;
+
+        let known = stmt
+            .query_map([], |row| {
+                Ok(TrackPoint {
+                    timestamp: row.get("ordering_ts")?,
+                    location: Self::to_lat_lng(row)?,
+                })
+            })?
+            .flatten()
+            .collect();
+
+        let mut stmt = con.prepare(
+            "SELECT
+                visual.picture_id,
+                visual.ordering_ts
+            FROM visual
+            WHERE visual.picture_id IS NOT NULL
+            AND visual.latitude IS NULL",
+        )?;
+
+        let unknown = stmt
+            .query_map([], |row| {
+                let picture_id = row.get("picture_id").map(PictureId::new)?;
+                let ordering_ts: DateTime<Utc> = row.get("ordering_ts")?;
+                Ok((picture_id, ordering_ts))
+            })?
+            .flatten()
+            .collect();
+
+        Ok((known, unknown))
+    }
+
+    /// Gets the location of a picture and where the location came from.
+    pub fn get_picture_location(
+        &self,
+        picture_id: PictureId,
+    ) -> Result<Option<(LatLng, LocationSource)>> {
+        let con = self.con.lock().unwrap();
+        let mut stmt = con.prepare(
+            "SELECT
+                latitude,
+                longitude,
+                source
+            FROM pictures_geo
+            WHERE picture_id = ?1",
+        )?;
+
+        let result = stmt
+            .query_map(params![picture_id.id()], |row| {
+                let location = Self::to_lat_lng(row)?;
+                let source: String = row.get("source")?;
+                let source = LocationSource::from_str(&source).unwrap_or(LocationSource::Exif);
+                Ok((location, source))
+            })?
+            .flatten()
+            .nth(0);
+
+        Ok(result)
+    }
+
+    /// Gets the location of a video and where the location came from.
+    pub fn get_video_location(
+        &self,
+        video_id: VideoId,
+    ) -> Result<Option<(LatLng, LocationSource)>> {
+        let con = self.con.lock().unwrap();
+        let mut stmt = con.prepare(
+            "SELECT
+                latitude,
+                longitude,
+                source
+            FROM videos_geo
+            WHERE video_id = ?1",
+        )?;
+
+        let result = stmt
+            .query_map(params![video_id.id()], |row| {
+                let location = Self::to_lat_lng(row)?;
+                let source: String = row.get("source")?;
+                let source = LocationSource::from_str(&source).unwrap_or(LocationSource::Exif);
+                Ok((location, source))
+            })?
+            .flatten()
+            .nth(0);
+
+        Ok(result)
+    }
+
+    /// Count of pictures with a location from a source.
+    pub fn count_picture_locations(&self, source: LocationSource) -> Result<usize> {
+        let con = self.con.lock().unwrap();
+        let count: i64 = con.query_row(
+            "SELECT COUNT(*) FROM pictures_geo WHERE source = ?1",
+            params![source.as_ref()],
+            |row| row.get(0),
+        )?;
+        Ok(count as usize)
+    }
+
+    /// Removes locations of pictures, but only if the locations came from a source.
+    /// Removes all locations from the source if no pictures are given.
+    /// Returns count of removed locations.
+    pub fn remove_picture_locations(
+        &mut self,
+        source: LocationSource,
+        picture_ids: Option<Vec<PictureId>>,
+    ) -> Result<usize> {
+        let mut con = self.con.lock().unwrap();
+        let tx = con.transaction()?;
+
+        let mut count = 0;
+
+        {
+            if let Some(picture_ids) = picture_ids {
+                let mut stmt = tx.prepare_cached(
+                    "DELETE FROM pictures_geo WHERE picture_id = ?1 AND source = ?2",
+                )?;
+
+                for picture_id in picture_ids {
+                    count += stmt.execute(params![picture_id.id(), source.as_ref()])?;
+                }
+            } else {
+                let mut stmt = tx.prepare_cached("DELETE FROM pictures_geo WHERE source = ?1")?;
+                count += stmt.execute(params![source.as_ref()])?;
+            }
+        }
+
+        tx.commit()?;
+        Ok(count)
+    }
+
+    /// Gets the place of a picture or video.
+    /// A live photo has both a picture and a video, so prefer the place of the picture.
+    pub fn get_place(
+        &self,
+        picture_id: Option<PictureId>,
+        video_id: Option<VideoId>,
+    ) -> Result<Option<Place>> {
+        let con = self.con.lock().unwrap();
+        let mut stmt = con.prepare(
+            "SELECT
+                places.place_id,
+                places.city,
+                places.region,
+                places.country,
+                places.country_code,
+                places.timezone
+            FROM places
+            WHERE places.place_id = COALESCE(
+                (SELECT place_id FROM pictures_places WHERE picture_id = ?1),
+                (SELECT place_id FROM videos_places WHERE video_id = ?2)
+            )",
+        )?;
+
+        let result = stmt
+            .query_map(
+                params![picture_id.map(|x| x.id()), video_id.map(|x| x.id())],
+                |row| self.to_place(row),
+            )?
+            .flatten()
+            .nth(0);
+
+        Ok(result)
+    }
+
+    /// All places with the count of visual items at each place.
+    /// Items inside privacy zones aren't counted, so places with only such items are left out.
+    /// Ordered by country and then by city.
+    pub fn all_places(&self, zones: &PrivacyZones) -> Result<Vec<(Place, usize)>> {
+        let con = self.con.lock().unwrap();
+        let mut stmt = con.prepare(
+            "SELECT
+                places.place_id,
+                places.city,
+                places.region,
+                places.country,
+                places.country_code,
+                places.timezone,
+                visual.latitude,
+                visual.longitude
+            FROM visual
+            LEFT OUTER JOIN pictures_places USING (picture_id)
+            LEFT OUTER JOIN videos_places USING (video_id)
+            INNER JOIN places ON places.place_id = COALESCE(pictures_places.place_id, videos_places.place_id)
+            ORDER BY places.country ASC, places.city ASC, places.place_id ASC",
+        )?;
+
+        let rows = stmt
+            .query_map([], |row| {
+                let place = self.to_place(row)?;
+                let is_private = Self::is_private(row, zones)?;
+                Ok((place, is_private))
+            })?
+            .flatten()
+            .filter(|(_, is_private)| !is_private);
+
+        // Rows are ordered by place, so count each run of the same place.
+        let mut result: Vec<(Place, usize)> = Vec::new();
+        for (place, _) in rows {
+            match result.last_mut() {
+                Some((last, count)) if last.place_id == place.place_id => *count += 1,
+                _ => result.push((place, 1)),
+            }
+        }
+
+        Ok(result)
+    }
+
+    /// Places of all pictures and videos that have a place.
+    pub fn all_visual_places(&self) -> Result<Vec<(VisualId, Place)>> {
+        let con = self.con.lock().unwrap();
+        let mut stmt = con.prepare(
+            "SELECT
+                visual.visual_id,
+                places.place_id,
+                places.city,
+                places.region,
+                places.country,
+                places.country_code,
+                places.timezone
+            FROM visual
+            LEFT OUTER JOIN pictures_places USING (picture_id)
+            LEFT OUTER JOIN videos_places USING (video_id)
+            INNER JOIN places ON places.place_id = COALESCE(pictures_places.place_id, videos_places.place_id)",
+        )?;
+
+        let result = stmt
+            .query_map([], |row| {
+                let visual_id = row.get("visual_id").map(VisualId::new)?;
+                let place = self.to_place(row)?;
+                Ok((visual_id, place))
+            })?
+            .flatten()
+            .collect();
+
+        Ok(result)
+    }
+
+    /// Finds all pictures and videos at a place, except those inside privacy zones.
+    pub fn find_visuals_for_place(
+        &self,
+        place_id: PlaceId,
+        zones: &PrivacyZones,
+    ) -> Result<(Vec<PictureId>, Vec<VideoId>)> {
+        self.find_visuals("places.place_id = ?1", params![place_id.id()], zones)
+    }
+
+    /// Finds all pictures and videos in a country, except those inside privacy zones.
+    pub fn find_visuals_for_country(
+        &self,
+        country_code: &str,
+        zones: &PrivacyZones,
+    ) -> Result<(Vec<PictureId>, Vec<VideoId>)> {
+        self.find_visuals("places.country_code = ?1", params![country_code], zones)
+    }
+
+    fn find_visuals(
+        &self,
+        condition: &str,
+        params: &[&dyn rusqlite::ToSql],
+        zones: &PrivacyZones,
+    ) -> Result<(Vec<PictureId>, Vec<VideoId>)> {
+        let con = self.con.lock().unwrap();
+        let sql = format!(
+            "SELECT
+                visual.picture_id,
+                visual.video_id,
+                visual.latitude,
+                visual.longitude
+            FROM visual
+            LEFT OUTER JOIN pictures_places USING (picture_id)
+            LEFT OUTER JOIN videos_places USING (video_id)
+            INNER JOIN places ON places.place_id = COALESCE(pictures_places.place_id, videos_places.place_id)
+            WHERE {}",
+            condition
+        );
+
+        let mut stmt = con.prepare(&sql)?;
+
+        let rows: Vec<(Option<i64>, Option<i64>)> = stmt
+            .query_map(params, |row| {
+                Ok((row.get(0)?, row.get(1)?, Self::is_private(row, zones)?))
+            })?
+            .flatten()
+            .filter(|(_, _, is_private)| !is_private)
+            .map(|(picture_id, video_id, _)| (picture_id, video_id))
+            .collect();
+
+        let picture_ids = rows.iter().filter_map(|x| x.0).map(PictureId::new).collect();
+        let video_ids = rows.iter().filter_map(|x| x.1).map(VideoId::new).collect();
+
+        Ok((picture_ids, video_ids))
+    }
+
+    /// Gets all privacy zones.
+    pub fn all_privacy_zones(&self) -> Result<PrivacyZones> {
+        let con = self.con.lock().unwrap();
+
+        let mut stmt = con.prepare(
+            "SELECT
+                zone_id,
+                cell_index
+            FROM privacy_zones_cells
+            ORDER BY zone_id",
+        )?;
+
+        let mut cells: HashMap<i64, Vec<CellIndex>> = HashMap::new();
+
+        stmt.query_map([], |row| {
+            let zone_id: i64 = row.get("zone_id")?;
+            let cell_index: i64 = row.get("cell_index")?;
+            Ok((zone_id, cell_index))
+        })?
+        .flatten()
+        .filter_map(|(zone_id, cell_index)| {
+            CellIndex::try_from(cell_index as u64).ok().map(|cell| (zone_id, cell))
+        })
+        .for_each(|(zone_id, cell)| cells.entry(zone_id).or_default().push(cell));
+
+        let mut stmt = con.prepare(
+            "SELECT
+                zone_id,
+                name,
+                latitude,
+                longitude,
+                radius_metres
+            FROM privacy_zones
+            ORDER BY name",
+        )?;
+
+        let zones = stmt
+            .query_map([], |row| {
+                let zone_id: i64 = row.get("zone_id")?;
+                let radius: Option<f64> = row.get("radius_metres")?;
+
+                let area = match radius {
+                    Some(radius) => ZoneArea::Circle {
+                        centre: Self::to_lat_lng(row)?,
+                        radius,
+                    },
+                    None => ZoneArea::Cells(cells.remove(&zone_id).unwrap_or_default()),
+                };
+
+                Ok(PrivacyZone {
+                    zone_id: PrivacyZoneId::new(zone_id),
+                    name: row.get("name")?,
+                    area,
+                })
+            })?
+            .flatten()
+            .collect();
+
+        Ok(PrivacyZones::new(zones))
+    }
+
+    /// Adds a privacy zone.
+    pub fn add_privacy_zone(&mut self, name: &str, area: &ZoneArea) -> Result<PrivacyZoneId> {
+        let mut con = self.con.lock().unwrap();
+        let tx = con.transaction()?;
+
+        let zone_id = {
+            let mut insert_zone = tx.prepare_cached(
+                "INSERT INTO privacy_zones (
+                    name,
+                    latitude,
+                    longitude,
+                    radius_metres
+                ) VALUES (
+                    ?1, ?2, ?3, ?4
+                )",
+            )?;
+
+            let mut insert_cell = tx.prepare_cached(
+                "INSERT INTO privacy_zones_cells (
+                    zone_id,
+                    cell_index
+                ) VALUES (
+                    ?1, ?2
+                ) ON CONFLICT DO NOTHING",
+            )?;
+
+            match area {
+                ZoneArea::Circle { centre, radius } => {
+                    insert_zone.execute(params![name, centre.lat(), centre.lng(), radius])?;
+                    tx.last_insert_rowid()
+                },
+                ZoneArea::Cells(cells) => {
+                    insert_zone.execute(params![name, None::<f64>, None::<f64>, None::<f64>])?;
+                    let zone_id = tx.last_insert_rowid();
+                    for cell in cells {
+                        insert_cell.execute(params![zone_id, u64::from(*cell) as i64])?;
+                    }
+                    zone_id
+                },
+            }
+        };
+
+        tx.commit()?;
+        Ok(PrivacyZoneId::new(zone_id))
+    }
+
+    /// Removes a privacy zone.
+    pub fn remove_privacy_zone(&mut self, zone_id: PrivacyZoneId) -> Result<()> {
+        let mut con = self.con.lock().unwrap();
+        let tx = con.transaction()?;
+
+        {
+            let mut stmt = tx.prepare_cached("DELETE FROM privacy_zones_cells WHERE zone_id = ?1")?;
+            stmt.execute(params![zone_id.id()])?;
+
+            let mut stmt = tx.prepare_cached("DELETE FROM privacy_zones WHERE zone_id = ?1")?;
+            stmt.execute(params![zone_id.id()])?;
+        }
+
+        tx.commit()?;
+        Ok(())
+    }
+
+    fn to_lat_lng(row: &Row<'_>) -> rusqlite::Result<LatLng> {
+        let latitude: f64 = row.get("latitude")?;
+        let longitude: f64 = row.get("longitude")?;
+        LatLng::new(latitude, longitude).map_err(|_| rusqlite::Error::InvalidQuery)
+    }
+
+    /// Is the location of an item inside a privacy zone?
+    /// Row must have the latitude and longitude of the item.
+    fn is_private(row: &Row<'_>, zones: &PrivacyZones) -> rusqlite::Result<bool> {
+        let latitude: Option<f64> = row.get("latitude")?;
+        let longitude: Option<f64> = row.get("longitude")?;
+        let location = latitude
+            .zip(longitude)
+            .and_then(|(lat, lng)| LatLng::new(lat, lng).ok());
+        std::result::Result::Ok(location.is_some_and(|x| zones.contains(x)))
+    }
+
+    fn to_place(&self, row: &Row<'_>) -> rusqlite::Result<Place> {
+        std::result::Result::Ok(Place {
+            place_id: row.get("place_id").map(PlaceId::new)?,
+            city: row.get("city")?,
+            region: row.get("region")?,
+            country: row.get("country")?,
+            country_code: row.get("country_code")?,
+            timezone: row.get("timezone")?,
+        })
+    }
+}
@@ -0,0 +1,433 @@
+// SPDX-FileCopyrightText: © 2024 David Bliss
+//
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//! GPS tracks recorded by GPS loggers, used to geotag pictures from cameras without GPS.
+//! Supports GPX, KML, and GeoJSON tracks.
+
+use anyhow::*;
+use chrono::{DateTime, NaiveDateTime, TimeDelta, Utc};
+use h3o::LatLng;
+use serde_json::Value;
+use std::path::Path;
+use std::result::Result::Ok;
+use tracing::info;
+
+/// A timestamped location on a track.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TrackPoint {
+    pub timestamp: DateTime<Utc>,
+    pub location: LatLng,
+}
+
+/// Timestamped locations ordered by time.
+#[derive(Debug, Clone, Default)]
+pub struct Track {
+    points: Vec<TrackPoint>,
+}
+
+impl Track {
+    pub fn new(mut points: Vec<TrackPoint>) -> Self {
+        points.sort_by_key(|p| p.timestamp);
+        Track { points }
+    }
+
+    /// Load a track from a file. File type is determined by the file extension.
+    pub fn open(path: &Path) -> Result<Self> {
+        let text = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed reading {:?}", path))?;
+
+        let extension = path
+            .extension()
+            .and_then(|x| x.to_str())
+            .map(|x| x.to_lowercase());
+
+        let track = match extension.as_deref() {
+            Some("gpx") => Self::from_gpx(&text)?,
+            Some("kml") => Self::from_kml(&text)?,
+            Some("geojson") | Some("json") => Self::from_geojson(&text)?,
+            _ => bail!("Unsupported track file: {:?}", path),
+        };
+
+        info!("Loaded {} track points from {:?}", track.len(), path);
+
+        Ok(track)
+    }
+
+    /// Parse track points from a GPX document.
+    /// Track, route, and waypoints are all used if they have a time.
+    pub fn from_gpx(text: &str) -> Result<Self> {
+        let doc = roxmltree::Document::parse(text)?;
+
+        let points = doc
+            .descendants()
+            .filter(|n| n.has_tag_name("trkpt") || n.has_tag_name("rtept") || n.has_tag_name("wpt"))
+            .filter_map(|n| {
+                let latitude = n.attribute("lat")?.trim().parse::<f64>().ok()?;
+                let longitude = n.attribute("lon")?.trim().parse::<f64>().ok()?;
+                let time = n.children().find(|c| c.has_tag_name("time"))?.text()?;
+                Some(TrackPoint {
+                    timestamp: parse_timestamp(time)?,
+                    location: LatLng::new(latitude, longitude).ok()?,
+                })
+            })
+            .collect();
+
+        Ok(Track::new(points))
+    }
+
+    /// Parse track points from a KML document.
+    /// Uses `gx:Track` elements and placemarks with a single time stamp and point.
+    pub fn from_kml(text: &str) -> Result<Self> {
+        let doc = roxmltree::Document::parse(text)?;
+
+        let mut points = Vec::new();
+
+        // A gx:Track has parallel lists of "when" and "gx:coord" elements.
+        for track in doc.descendants().filter(|n| n.has_tag_name("Track")) {
+            let whens = track
+                .children()
+                .filter(|c| c.has_tag_name("when"))
+                .map(|c| c.text().and_then(parse_timestamp));
+
+            let coords = track
+                .children()
+                .filter(|c| c.has_tag_name("coord"))
+                .map(|c| c.text().and_then(|t| parse_coordinates(t, ' ')));
+
+            points.extend(whens.zip(coords).filter_map(|(timestamp, location)| {
+                Some(TrackPoint {
+                    timestamp: timestamp?,
+                    location: location?,
+                })
+            }));
+        }
+
+        for placemark in doc.descendants().filter(|n| n.has_tag_name("Placemark")) {
+            let timestamp = placemark
+                .children()
+                .find(|c| c.has_tag_name("TimeStamp"))
+                .and_then(|c| c.children().find(|c| c.has_tag_name("when")))
+                .and_then(|c| c.text())
+                .and_then(parse_timestamp);
+
+            let location = placemark
+                .children()
+                .find(|c| c.has_tag_name("Point"))
+                .and_then(|c| c.children().find(|c| c.has_tag_name("coordinates")))
+                .and_then(|c| c.text())
+                .and_then(|t| parse_coordinates(t, ','));
+
+            if let (Some(timestamp), Some(location)) = (timestamp, location) {
+                points.push(TrackPoint {
+                    timestamp,
+                    location,
+                });
+            }
+        }
+
+        Ok(Track::new(points))
+    }
+
+    /// Parse track points from a GeoJSON document.
+    /// Times for line strings come from a "coordTimes" or "times" property with
+    /// one time per coordinate. Times for points come from a "time" or "timestamp" property.
+    pub fn from_geojson(text: &str) -> Result<Self> {
+        let doc: Value = serde_json::from_str(text)?;
+
+        let features: Vec<&Value> = match doc["type"].as_str() {
+            Some("FeatureCollection") => doc["features"]
+                .as_array()
+                .map(|xs| xs.iter().collect())
+                .unwrap_or_default(),
+            Some("Feature") => vec![&doc],
+            _ => bail!("Not a GeoJSON feature or feature collection"),
+        };
+
+        let mut points = Vec::new();
+
+        for feature in features {
+            let geometry = &feature["geometry"];
+            let properties = &feature["properties"];
+
+            let times = if properties["coordTimes"].is_array() {
+                &properties["coordTimes"]
+            } else {
+                &properties["times"]
+            };
+
+            match geometry["type"].as_str() {
+                Some("Point") => {
+                    let time = properties["time"]
+                        .as_str()
+                        .or_else(|| properties["timestamp"].as_str())
+                        .or_else(|| times.as_str());
+
+                    let timestamp = time.and_then(parse_timestamp);
+                    let location = geojson_location(&geometry["coordinates"]);
+
+                    if let (Some(timestamp), Some(location)) = (timestamp, location) {
+                        points.push(TrackPoint {
+                            timestamp,
+                            location,
+                        });
+                    }
+                }
+                Some("LineString") => {
+                    points.extend(geojson_line(&geometry["coordinates"], times));
+                }
+                Some("MultiLineString") => {
+                    let lines = geometry["coordinates"].as_array();
+                    let times = times.as_array();
+                    if let (Some(lines), Some(times)) = (lines, times) {
+                        for (line, times) in lines.iter().zip(times) {
+                            points.extend(geojson_line(line, times));
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        Ok(Track::new(points))
+    }
+
+    pub fn len(&self) -> usize {
+        self.points.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.points.is_empty()
+    }
+
+    /// First point of track.
+    pub fn start(&self) -> Option<&TrackPoint> {
+        self.points.first()
+    }
+
+    /// Last point of track.
+    pub fn end(&self) -> Option<&TrackPoint> {
+        self.points.last()
+    }
+
+    /// Find the location at a time.
+    ///
+    /// If the time falls between two track points no more than `max_gap` apart, then the
+    /// location is interpolated between the two points. Otherwise the location of the
+    /// nearest track point is used, as long as it is within `max_gap` of the time.
+    pub fn locate(&self, timestamp: DateTime<Utc>, max_gap: TimeDelta) -> Option<LatLng> {
+        let (before, after) = self.neighbours(timestamp);
+
+        if let (Some(before), Some(after)) = (before, after) {
+            let span = after.timestamp - before.timestamp;
+            if span <= max_gap {
+                return Some(interpolate(before, after, timestamp));
+            }
+        }
+
+        [before, after]
+            .into_iter()
+            .flatten()
+            .map(|p| (p, (p.timestamp - timestamp).abs()))
+            .filter(|(_, gap)| *gap <= max_gap)
+            .min_by_key(|(_, gap)| *gap)
+            .map(|(p, _)| p.location)
+    }
+
+    /// Time to the nearest track point.
+    pub fn nearest_gap(&self, timestamp: DateTime<Utc>) -> Option<TimeDelta> {
+        let (before, after) = self.neighbours(timestamp);
+
+        [before, after]
+            .into_iter()
+            .flatten()
+            .map(|p| (p.timestamp - timestamp).abs())
+            .min()
+    }
+
+    /// Last track point before a time and first track point at or after a time.
+    fn neighbours(&self, timestamp: DateTime<Utc>) -> (Option<&TrackPoint>, Option<&TrackPoint>) {
+        let index = self.points.partition_point(|p| p.timestamp < timestamp);
+
+        let before = index.checked_sub(1).and_then(|i| self.points.get(i));
+        let after = self.points.get(index);
+
+        (before, after)
+    }
+}
+
+/// Linear interpolation between two track points.
+/// Track points are close together, so interpolating degrees is good enough.
+fn interpolate(before: &TrackPoint, after: &TrackPoint, timestamp: DateTime<Utc>) -> LatLng {
+    let span = (after.timestamp - before.timestamp).num_milliseconds();
+    if span == 0 {
+        return before.location;
+    }
+
+    let fraction = (timestamp - before.timestamp).num_milliseconds() as f64 / span as f64;
+
+    let latitude = before.location.lat() + (after.location.lat() - before.location.lat()) * fraction;
+    let longitude = before.location.lng() + (after.location.lng() - before.location.lng()) * fraction;
+
+    LatLng::new(latitude, longitude).unwrap_or(before.location)
+}
+
+/// Parse an ISO 8601 timestamp. Timestamps without an offset are assumed to be UTC.
+fn parse_timestamp(text: &str) -> Option<DateTime<Utc>> {
+    let text = text.trim();
+    DateTime::parse_from_rfc3339(text)
+        .map(|ts| ts.to_utc())
+        .ok()
+        .or_else(|| {
+            NaiveDateTime::parse_from_str(text, "%Y-%m-%dT%H:%M:%S%.f")
+                .map(|ts| ts.and_utc())
+                .ok()
+        })
+}
+
+/// Parse KML coordinates, which are longitude, latitude, and an optional altitude.
+fn parse_coordinates(text: &str, separator: char) -> Option<LatLng> {
+    let mut parts = text.trim().split(separator).filter(|x| !x.is_empty());
+    let longitude = parts.next()?.trim().parse::<f64>().ok()?;
+    let latitude = parts.next()?.trim().parse::<f64>().ok()?;
+    LatLng::new(latitude, longitude).ok()
+}
+
+/// GeoJSON positions are an array of longitude, latitude, and an optional altitude.
+fn geojson_location(position: &Value) -> Option<LatLng> {
+    let longitude = position.get(0)?.as_f64()?;
+    let latitude = position.get(1)?.as_f64()?;
+    LatLng::new(latitude, longitude).ok()
+}
+
+fn geojson_line(coordinates: &Value, times: &Value) -> Vec<TrackPoint> {
+    let (Some(coordinates), Some(times)) = (coordinates.as_array(), times.as_array()) else {
+        return Vec::new();
+    };
+
+    coordinates
+        .iter()
+        .zip(times)
+        .filter_map(|(position, time)| {
+            Some(TrackPoint {
+                timestamp: parse_timestamp(time.as_str()?)?,
+                location: geojson_location(position)?,
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ts(text: &str) -> DateTime<Utc> {
+        parse_timestamp(text).unwrap()
+    }
+
+    #[test]
+    fn test_from_gpx() {
+        let gpx = r#"<?xml version="1.0" encoding="UTF-8"?>
+            <gpx version="1.1" xmlns="http://www.topografix.com/GPX/1/1">
+              <trk><trkseg>
+                <trkpt lat="38.7100" lon="-9.1400"><ele>10</ele><time>2023-05-14T10:00:10Z</time></trkpt>
+                <trkpt lat="38.7000" lon="-9.1300"><time>2023-05-14T10:00:00Z</time></trkpt>
+                <trkpt lat="38.7200" lon="-9.1500"></trkpt>
+              </trkseg></trk>
+            </gpx>"#;
+
+        let track = Track::from_gpx(gpx).unwrap();
+        assert_eq!(2, track.len());
+        assert_eq!(ts("2023-05-14T10:00:00Z"), track.start().unwrap().timestamp);
+        assert_eq!(ts("2023-05-14T10:00:10Z"), track.end().unwrap().timestamp);
+        assert_eq!(38.71, track.end().unwrap().location.lat());
+    }
+
+    #[test]
+    fn test_from_kml() {
+        let kml = r#"<?xml version="1.0" encoding="UTF-8"?>
+            <kml xmlns="http://www.opengis.net/kml/2.2" xmlns:gx="http://www.google.com/kml/ext/2.2">
+              <Document>
+                <Placemark>
+                  <gx:Track>
+                    <when>2023-05-14T10:00:00Z</when>
+                    <when>2023-05-14T10:01:00Z</when>
+                    <gx:coord>-9.13 38.70 10</gx:coord>
+                    <gx:coord>-9.14 38.71 12</gx:coord>
+                  </gx:Track>
+                </Placemark>
+                <Placemark>
+                  <TimeStamp><when>2023-05-14T11:00:00+01:00</when></TimeStamp>
+                  <Point><coordinates>-9.15,38.72,0</coordinates></Point>
+                </Placemark>
+              </Document>
+            </kml>"#;
+
+        let track = Track::from_kml(kml).unwrap();
+        assert_eq!(3, track.len());
+        assert_eq!(-9.13, track.start().unwrap().location.lng());
+        assert_eq!(ts("2023-05-14T10:01:00Z"), track.end().unwrap().timestamp);
+    }
+
+    #[test]
+    fn test_from_geojson() {
+        let geojson = r#"{
+            "type": "FeatureCollection",
+            "features": [{
+                "type": "Feature",
+                "properties": {
+                    "coordTimes": ["2023-05-14T10:00:00Z", "2023-05-14T10:01:00Z"]
+                },
+                "geometry": {
+                    "type": "LineString",
+                    "coordinates": [[-9.13, 38.70, 10], [-9.14, 38.71, 12]]
+                }
+            }, {
+                "type": "Feature",
+                "properties": { "time": "2023-05-14T10:02:00Z" },
+                "geometry": { "type": "Point", "coordinates": [-9.15, 38.72] }
+            }]
+        }"#;
+
+        let track = Track::from_geojson(geojson).unwrap();
+        assert_eq!(3, track.len());
+        assert_eq!(38.72, track.end().unwrap().location.lat());
+    }
+
+    #[test]
+    fn test_locate() {
+        let point = |time: &str, latitude: f64, longitude: f64| TrackPoint {
+            timestamp: ts(time),
+            location: LatLng::new(latitude, longitude).unwrap(),
+        };
+
+        let track = Track::new(vec![
+            point("2023-05-14T10:00:00Z", 38.0, -9.0),
+            point("2023-05-14T10:02:00Z", 39.0, -8.0),
+            point("2023-05-14T12:00:00Z", 40.0, -7.0),
+        ]);
+
+        let max_gap = TimeDelta::minutes(5);
+
+        // Interpolated half way between first two points
+        let location = track.locate(ts("2023-05-14T10:01:00Z"), max_gap).unwrap();
+        assert!((location.lat() - 38.5).abs() < 1e-9);
+        assert!((location.lng() - -8.5).abs() < 1e-9);
+
+        // Exactly on a point
+        let location = track.locate(ts("2023-05-14T12:00:00Z"), max_gap).unwrap();
+        assert_eq!(40.0, location.lat());
+
+        // Points either side are too far apart to interpolate, so use the nearest.
+        let location = track.locate(ts("2023-05-14T10:04:00Z"), max_gap).unwrap();
+        assert_eq!(39.0, location.lat());
+
+        // Before the start of the track, but close enough.
+        let location = track.locate(ts("2023-05-14T09:58:00Z"), max_gap).unwrap();
+        assert_eq!(38.0, location.lat());
+
+        // Too far from any point.
+        assert!(track.locate(ts("2023-05-14T11:00:00Z"), max_gap).is_none());
+        assert!(track.locate(ts("2023-05-14T12:06:00Z"), max_gap).is_none());
+    }
+}
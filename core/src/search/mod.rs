@@ -0,0 +1,10 @@
+// SPDX-FileCopyrightText: © 2024 David Bliss
+//
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+pub mod model;
+pub mod query;
+pub mod repo;
+
+pub use query::Query;
+pub use repo::Repository;
@@ -0,0 +1,38 @@
+// SPDX-FileCopyrightText: © 2024 David Bliss
+//
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+/// Convert text typed by the user into an FTS5 match expression.
+/// Each word is quoted so FTS5 operators and punctuation are matched as plain text,
+/// and is a prefix so results appear while a word is still being typed.
+/// All words must match. Returns None if there is nothing to search for.
+pub fn match_expression(text: &str) -> Option<String> {
+    let terms: Vec<String> = text
+        .split_whitespace()
+        .map(|word| format!("\"{}\"*", word.replace('"', "\"\"")))
+        .collect();
+
+    if terms.is_empty() {
+        None
+    } else {
+        Some(terms.join(" "))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_match_expression() {
+        assert_eq!(
+            Some("\"eiffel\"* \"tow\"*".to_string()),
+            match_expression(" eiffel  tow ")
+        );
+        assert_eq!(
+            Some("\"NOT\"* \"\"\"quoted\"\"\"*".to_string()),
+            match_expression("NOT \"quoted\"")
+        );
+        assert_eq!(None, match_expression("   "));
+    }
+}
@@ -0,0 +1,226 @@
+// SPDX-FileCopyrightText: © 2024 David Bliss
+//
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//! Query language of the search box, such as
+//! `person:Alice place:Lisbon year:2022 type:video camera:"Pixel 8" -selfie`.
+//!
+//! A query is a list of terms that must all match. A term is either a filter
+//! with a key, such as `year:2022`, or words to find in titles, captions, file names,
+//! and folder names. Values with spaces can be quoted, and a term starting with '-'
+//! excludes items that match it. The bare words `selfie` and `favourite` are
+//! shorthands for `is:selfie` and `is:favourite`.
+
+/// Kind of visual item, for `type:` filters.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Kind {
+    /// Pictures that aren't live photos.
+    Photo,
+
+    /// Videos that aren't live photos.
+    Video,
+
+    /// Live photos and motion photos.
+    Live,
+}
+
+impl Kind {
+    fn parse(value: &str) -> Option<Kind> {
+        match value.to_lowercase().as_str() {
+            "photo" | "photos" | "picture" | "pictures" | "image" | "images" => Some(Kind::Photo),
+            "video" | "videos" => Some(Kind::Video),
+            "live" | "motion" | "animated" => Some(Kind::Live),
+            _ => None,
+        }
+    }
+}
+
+/// A single condition of a query.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Filter {
+    /// Words in a title, caption, file name, or folder name.
+    Text(String),
+
+    /// Person recognized in item, with a name containing the value.
+    Person(String),
+
+    /// Place with a city, region, or country containing the value.
+    Place(String),
+
+    /// Year taken, where the item was taken.
+    Year(i32),
+
+    /// Kind of item.
+    Kind(Kind),
+
+    /// Camera make and model containing the value.
+    Camera(String),
+
+    /// Tag with a level named the value, such as "Europe" for "Places|Europe|Paris".
+    Tag(String),
+
+    Selfie,
+
+    Favourite,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Term {
+    pub filter: Filter,
+
+    /// Exclude items matching the filter instead of including them.
+    pub negated: bool,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Query {
+    pub terms: Vec<Term>,
+}
+
+impl Query {
+    /// Parse text typed into the search box.
+    /// Parsing never fails. Filters with an unknown key or an invalid value are
+    /// searched for as words instead.
+    pub fn parse(text: &str) -> Query {
+        let terms = tokens(text)
+            .into_iter()
+            .map(|token| Term {
+                filter: token.to_filter(),
+                negated: token.negated,
+            })
+            .collect();
+
+        Query { terms }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.terms.is_empty()
+    }
+}
+
+/// A term before its key is interpreted.
+#[derive(Debug, Default)]
+struct Token {
+    negated: bool,
+    key: Option<String>,
+    value: String,
+    quoted: bool,
+}
+
+impl Token {
+    fn to_filter(&self) -> Filter {
+        let value = self.value.trim().to_string();
+
+        let Some(ref key) = self.key else {
+            return match value.to_lowercase().as_str() {
+                "selfie" | "selfies" if !self.quoted => Filter::Selfie,
+                "favourite" | "favourites" | "favorite" | "favorites" if !self.quoted => {
+                    Filter::Favourite
+                }
+                _ => Filter::Text(value),
+            };
+        };
+
+        let filter = match key.to_lowercase().as_str() {
+            "person" | "people" => Some(Filter::Person(value.clone())),
+            "place" => Some(Filter::Place(value.clone())),
+            "year" => value.parse().ok().map(Filter::Year),
+            "type" => Kind::parse(&value).map(Filter::Kind),
+            "camera" => Some(Filter::Camera(value.clone())),
+            "tag" => Some(Filter::Tag(value.clone())),
+            "is" => match value.to_lowercase().as_str() {
+                "selfie" => Some(Filter::Selfie),
+                "favourite" | "favorite" => Some(Filter::Favourite),
+                _ => None,
+            },
+            _ => None,
+        };
+
+        filter.unwrap_or_else(|| Filter::Text(format!("{}:{}", key, value)))
+    }
+}
+
+/// Split query text into tokens, keeping quoted values together.
+/// Tokens without a value are dropped.
+fn tokens(text: &str) -> Vec<Token> {
+    let mut tokens = Vec::new();
+    let mut chars = text.chars().peekable();
+
+    loop {
+        while chars.next_if(|c| c.is_whitespace()).is_some() {}
+
+        if chars.peek().is_none() {
+            break;
+        }
+
+        let mut token = Token {
+            negated: chars.next_if_eq(&'-').is_some(),
+            ..Default::default()
+        };
+
+        while let Some(c) = chars.next_if(|c| !c.is_whitespace()) {
+            match c {
+                '"' => {
+                    token.quoted = true;
+                    token.value.extend(chars.by_ref().take_while(|c| *c != '"'));
+                }
+                ':' if token.key.is_none() && !token.quoted && !token.value.is_empty() => {
+                    token.key = Some(std::mem::take(&mut token.value));
+                }
+                c => token.value.push(c),
+            }
+        }
+
+        if !token.value.trim().is_empty() {
+            tokens.push(token);
+        }
+    }
+
+    tokens
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn term(filter: Filter, negated: bool) -> Term {
+        Term { filter, negated }
+    }
+
+    #[test]
+    fn test_parse() {
+        let query = Query::parse(
+            r#"person:Alice place:Lisbon year:2022 type:video camera:"Pixel 8" -selfie"#,
+        );
+
+        assert_eq!(
+            vec![
+                term(Filter::Person("Alice".into()), false),
+                term(Filter::Place("Lisbon".into()), false),
+                term(Filter::Year(2022), false),
+                term(Filter::Kind(Kind::Video), false),
+                term(Filter::Camera("Pixel 8".into()), false),
+                term(Filter::Selfie, true),
+            ],
+            query.terms
+        );
+    }
+
+    #[test]
+    fn test_parse_text() {
+        let query = Query::parse(r#"beach -"sun set" year:soon "selfie" tag:Places|Europe - "#);
+
+        assert_eq!(
+            vec![
+                term(Filter::Text("beach".into()), false),
+                term(Filter::Text("sun set".into()), true),
+                term(Filter::Text("year:soon".into()), false),
+                term(Filter::Text("selfie".into()), false),
+                term(Filter::Tag("Places|Europe".into()), false),
+            ],
+            query.terms
+        );
+
+        assert!(Query::parse("  ").is_empty());
+    }
+}
@@ -0,0 +1,355 @@
+// SPDX-FileCopyrightText: © 2024 David Bliss
+//
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use crate::photo::PictureId;
+use crate::places::PrivacyZones;
+use crate::search::model;
+use crate::search::query::{Filter, Kind, Query};
+use crate::video::VideoId;
+use crate::visual::Visual;
+
+use anyhow::*;
+use h3o::LatLng;
+use rusqlite;
+use rusqlite::params;
+use rusqlite::params_from_iter;
+use rusqlite::types::Value;
+use std::collections::HashMap;
+use std::result::Result::Ok;
+use std::sync::{Arc, Mutex};
+
+/// Row ID and text of each visual item in the search index, by visual ID.
+type Index = HashMap<String, (i64, IndexEntry)>;
+
+/// Text of a visual item in the search index.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct IndexEntry {
+    picture_id: Option<i64>,
+    video_id: Option<i64>,
+    title: Option<String>,
+    caption: Option<String>,
+    file_name: Option<String>,
+    folder_name: Option<String>,
+}
+
+impl IndexEntry {
+    fn for_visual(visual: &Visual) -> Self {
+        let file_name = visual
+            .path()
+            .and_then(|x| x.file_name())
+            .map(|x| x.to_string_lossy().to_string());
+
+        IndexEntry {
+            picture_id: visual.picture_id.map(|x| x.id()),
+            video_id: visual.video_id.map(|x| x.id()),
+            title: visual.title.clone(),
+            caption: visual.caption.clone(),
+            file_name,
+            folder_name: visual.folder_name(),
+        }
+    }
+}
+
+/// Repository for searching visual items with a query, such as `person:Alice year:2022`.
+/// Words are found in titles, captions, file names, and folder names with an FTS5 table,
+/// and other filters are conditions over the visual view and the tables it is built from.
+/// Repository is backed by a Sqlite database.
+#[derive(Debug, Clone)]
+pub struct Repository {
+    /// Connection to backing Sqlite database.
+    con: Arc<Mutex<rusqlite::Connection>>,
+
+    /// Items in the search index. Read from the index on first update,
+    /// so later updates only write what changed.
+    indexed: Arc<Mutex<Option<Index>>>,
+}
+
+impl Repository {
+    pub fn open(con: Arc<Mutex<rusqlite::Connection>>) -> Result<Repository> {
+        let repo = Repository {
+            con,
+            indexed: Arc::new(Mutex::new(None)),
+        };
+        Ok(repo)
+    }
+
+    /// Update the search index with the text of visual items.
+    /// Only items that are new, changed, or gone are written, because the library is
+    /// loaded after every change and the connection is shared with everything else.
+    pub fn update_index(&mut self, visuals: &[Arc<Visual>]) -> Result<()> {
+        let mut indexed = self.indexed.lock().unwrap();
+        if indexed.is_none() {
+            *indexed = Some(self.read_index()?);
+        }
+        let indexed = indexed.as_mut().unwrap();
+
+        let entries: HashMap<&String, IndexEntry> = visuals
+            .iter()
+            .map(|visual| (visual.visual_id.id(), IndexEntry::for_visual(visual)))
+            .collect();
+
+        let removed: Vec<String> = indexed
+            .keys()
+            .filter(|visual_id| !entries.contains_key(visual_id))
+            .cloned()
+            .collect();
+
+        let changed: Vec<(&String, IndexEntry)> = entries
+            .into_iter()
+            .filter(|(visual_id, entry)| {
+                indexed
+                    .get(*visual_id)
+                    .is_none_or(|(_, indexed_entry)| indexed_entry != entry)
+            })
+            .collect();
+
+        if removed.is_empty() && changed.is_empty() {
+            return Ok(());
+        }
+
+        let mut con = self.con.lock().unwrap();
+        let tx = con.transaction()?;
+
+        // Row IDs of entries after the transaction commits.
+        let mut inserted = Vec::with_capacity(changed.len());
+
+        {
+            // FTS5 tables can only be looked up efficiently by row ID.
+            let mut delete_stmt =
+                tx.prepare_cached("DELETE FROM visual_search WHERE rowid = ?1")?;
+
+            let mut insert_stmt = tx.prepare_cached(
+                "INSERT INTO visual_search (
+                    visual_id,
+                    picture_id,
+                    video_id,
+                    title,
+                    caption,
+                    file_name,
+                    folder_name
+                ) VALUES (
+                    ?1, ?2, ?3, ?4, ?5, ?6, ?7
+                )",
+            )?;
+
+            for visual_id in &removed {
+                if let Some((rowid, _)) = indexed.get(visual_id) {
+                    delete_stmt.execute([rowid])?;
+                }
+            }
+
+            for (visual_id, entry) in changed {
+                if let Some((rowid, _)) = indexed.get(visual_id) {
+                    delete_stmt.execute([rowid])?;
+                }
+
+                insert_stmt.execute(params![
+                    visual_id,
+                    entry.picture_id,
+                    entry.video_id,
+                    entry.title,
+                    entry.caption,
+                    entry.file_name,
+                    entry.folder_name,
+                ])?;
+
+                inserted.push((visual_id.clone(), (tx.last_insert_rowid(), entry)));
+            }
+        }
+
+        tx.commit()?;
+
+        for visual_id in removed {
+            indexed.remove(&visual_id);
+        }
+        indexed.extend(inserted);
+
+        Ok(())
+    }
+
+    /// Row ID and text of every item in the search index.
+    fn read_index(&self) -> Result<Index> {
+        let con = self.con.lock().unwrap();
+
+        let mut stmt = con.prepare(
+            "SELECT
+                rowid,
+                visual_id,
+                picture_id,
+                video_id,
+                title,
+                caption,
+                file_name,
+                folder_name
+            FROM visual_search",
+        )?;
+
+        let indexed = stmt
+            .query_map([], |row| {
+                let entry = IndexEntry {
+                    picture_id: row.get(2)?,
+                    video_id: row.get(3)?,
+                    title: row.get(4)?,
+                    caption: row.get(5)?,
+                    file_name: row.get(6)?,
+                    folder_name: row.get(7)?,
+                };
+                Ok((row.get(1)?, (row.get(0)?, entry)))
+            })?
+            .flatten()
+            .collect();
+
+        Ok(indexed)
+    }
+
+    /// Pictures and videos matching a query typed by the user.
+    /// Queries for places leave out items inside privacy zones, so they can't
+    /// reveal where those items were taken.
+    /// See the query module for the query language.
+    pub fn search(
+        &self,
+        text: &str,
+        zones: &PrivacyZones,
+    ) -> Result<(Vec<PictureId>, Vec<VideoId>)> {
+        let query = Query::parse(text);
+        if query.is_empty() {
+            return Ok((Vec::new(), Vec::new()));
+        }
+
+        let is_place_query = query
+            .terms
+            .iter()
+            .any(|term| matches!(term.filter, Filter::Place(_)));
+
+        let mut conditions = Vec::new();
+        let mut values = Vec::new();
+
+        for term in query.terms {
+            let (condition, value) = Self::condition(term.filter);
+
+            // A condition on a missing value, such as the camera of a video, is null.
+            // Treat that as not matching, so negated conditions include those items.
+            let condition = if term.negated {
+                format!("NOT COALESCE(({}), FALSE)", condition)
+            } else {
+                format!("COALESCE(({}), FALSE)", condition)
+            };
+
+            conditions.push(condition);
+            values.extend(value);
+        }
+
+        let con = self.con.lock().unwrap();
+
+        let mut stmt = con.prepare(&format!(
+            "SELECT
+                picture_id,
+                video_id,
+                latitude,
+                longitude
+            FROM visual
+            WHERE {}",
+            conditions.join(" AND ")
+        ))?;
+
+        let rows: Vec<(Option<i64>, Option<i64>)> = stmt
+            .query_map(params_from_iter(values), |row| {
+                let latitude: Option<f64> = row.get(2)?;
+                let longitude: Option<f64> = row.get(3)?;
+                let location = latitude
+                    .zip(longitude)
+                    .and_then(|(lat, lng)| LatLng::new(lat, lng).ok());
+                Ok((row.get(0)?, row.get(1)?, location))
+            })?
+            .flatten()
+            .filter(|(_, _, location)| {
+                !is_place_query || !location.is_some_and(|x| zones.contains(x))
+            })
+            .map(|(picture_id, video_id, _)| (picture_id, video_id))
+            .collect();
+
+        let picture_ids = rows
+            .iter()
+            .filter_map(|x| x.0)
+            .map(PictureId::new)
+            .collect();
+        let video_ids = rows.iter().filter_map(|x| x.1).map(VideoId::new).collect();
+
+        Ok((picture_ids, video_ids))
+    }
+
+    /// SQL condition over the visual view for a filter, and the value of its parameter.
+    /// Text is compared ignoring case.
+    fn condition(filter: Filter) -> (&'static str, Option<Value>) {
+        match filter {
+            Filter::Text(text) => (
+                "visual.visual_id IN (
+                    SELECT visual_id FROM visual_search WHERE visual_search MATCH ?
+                )",
+                model::match_expression(&text).map(Value::Text),
+            ),
+            Filter::Person(name) => (
+                "EXISTS (
+                    SELECT 1
+                    FROM pictures_faces
+                    INNER JOIN people USING (person_id)
+                    WHERE (pictures_faces.picture_id = visual.picture_id
+                        OR pictures_faces.video_id = visual.video_id)
+                    AND instr(lower(people.name), lower(?)) > 0
+                )",
+                Some(Value::Text(name)),
+            ),
+            Filter::Place(name) => (
+                "EXISTS (
+                    SELECT 1
+                    FROM places
+                    WHERE places.place_id IN (
+                        SELECT place_id FROM pictures_places WHERE picture_id = visual.picture_id
+                        UNION
+                        SELECT place_id FROM videos_places WHERE video_id = visual.video_id
+                    )
+                    AND instr(
+                        lower(COALESCE(places.city, '') || '|' || COALESCE(places.region, '') || '|' || COALESCE(places.country, '')),
+                        lower(?)
+                    ) > 0
+                )",
+                Some(Value::Text(name)),
+            ),
+            // Year where the item was taken. Items without an offset from UTC are
+            // assumed to be taken in UTC.
+            Filter::Year(year) => (
+                "CAST(strftime('%Y', visual.ordering_ts, printf('%+d seconds', COALESCE(visual.capture_offset_secs, 0))) AS INTEGER) = ?",
+                Some(Value::Integer(year.into())),
+            ),
+            Filter::Kind(Kind::Photo) => (
+                "visual.picture_id IS NOT NULL AND visual.video_id IS NULL AND NOT visual.is_live_photo",
+                None,
+            ),
+            Filter::Kind(Kind::Video) => (
+                "visual.picture_id IS NULL AND visual.video_id IS NOT NULL AND NOT visual.is_live_photo",
+                None,
+            ),
+            Filter::Kind(Kind::Live) => ("visual.is_live_photo", None),
+            Filter::Camera(camera) => (
+                "instr(lower(visual.camera), lower(?)) > 0",
+                Some(Value::Text(camera)),
+            ),
+            // Match any level of a tag path, or a path of several levels.
+            Filter::Tag(tag) => (
+                "EXISTS (
+                    SELECT 1
+                    FROM tag_items
+                    INNER JOIN tags USING (tag_id)
+                    WHERE (tag_items.picture_id = visual.picture_id
+                        OR tag_items.video_id = visual.video_id)
+                    AND tag_items.is_removed IS FALSE
+                    AND instr('|' || lower(tags.path) || '|', '|' || lower(?) || '|') > 0
+                )",
+                Some(Value::Text(tag)),
+            ),
+            Filter::Selfie => ("visual.is_selfie", None),
+            Filter::Favourite => ("visual.is_favourite", None),
+        }
+    }
+}
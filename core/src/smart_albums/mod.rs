@@ -0,0 +1,17 @@
+// SPDX-FileCopyrightText: © 2024 David Bliss
+//
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+pub mod model;
+pub mod repo;
+
+pub use model::Criterion;
+pub use model::DateRange;
+pub use model::LinkedItems;
+pub use model::MediaType;
+pub use model::Rule;
+pub use model::RuleItems;
+pub use model::RuleKind;
+pub use model::SmartAlbum;
+pub use model::SmartAlbumId;
+pub use repo::Repository;
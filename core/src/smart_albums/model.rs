@@ -0,0 +1,504 @@
+// SPDX-FileCopyrightText: © 2024 David Bliss
+//
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use crate::path_encoding;
+use crate::people::model::PersonId;
+use crate::photo::PictureId;
+use crate::video::VideoId;
+use crate::visual::Visual;
+
+use chrono::{Months, NaiveDate};
+use h3o::CellIndex;
+use std::collections::HashSet;
+use std::fmt::Display;
+use std::path::PathBuf;
+use std::str::FromStr;
+use strum::{AsRefStr, EnumIter, EnumString};
+
+/// Database ID
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SmartAlbumId(i64);
+
+impl SmartAlbumId {
+    pub fn new(id: i64) -> Self {
+        Self(id)
+    }
+
+    /// FIXME replace this with a To/From SQL implementation.
+    pub fn id(&self) -> i64 {
+        self.0
+    }
+}
+
+impl Display for SmartAlbumId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+/// An album whose pictures and videos are those matching a rule, so it
+/// stays up to date as the library changes.
+#[derive(Debug, Clone)]
+pub struct SmartAlbum {
+    pub smart_album_id: SmartAlbumId,
+
+    pub name: String,
+
+    pub rule: Rule,
+}
+
+/// Kind of media for matching.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, AsRefStr, EnumString, EnumIter)]
+#[strum(serialize_all = "snake_case")]
+pub enum MediaType {
+    /// Photos, including live and motion photos.
+    Photo,
+
+    /// Videos that aren't part of a live photo.
+    Video,
+}
+
+/// Range of dates where both ends are inclusive. A missing end is unbounded.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DateRange {
+    pub from: Option<NaiveDate>,
+    pub to: Option<NaiveDate>,
+}
+
+impl DateRange {
+    pub fn contains(&self, date: NaiveDate) -> bool {
+        self.from.is_none_or(|from| from <= date) && self.to.is_none_or(|to| date <= to)
+    }
+
+    /// Parse one end of a range. A year or month covers every day in it, so
+    /// the start of a range is the first day and the end is the last day.
+    fn parse_end(s: &str, is_start: bool) -> Option<Option<NaiveDate>> {
+        let s = s.trim();
+        if s.is_empty() {
+            return Some(None);
+        }
+
+        let parts = s
+            .split('-')
+            .map(|x| x.parse::<u32>().ok())
+            .collect::<Option<Vec<u32>>>()?;
+
+        let date = match parts[..] {
+            [year] => {
+                let year = i32::try_from(year).ok()?;
+                if is_start {
+                    NaiveDate::from_ymd_opt(year, 1, 1)?
+                } else {
+                    NaiveDate::from_ymd_opt(year, 12, 31)?
+                }
+            }
+            [year, month] => {
+                let first = NaiveDate::from_ymd_opt(i32::try_from(year).ok()?, month, 1)?;
+                if is_start {
+                    first
+                } else {
+                    first.checked_add_months(Months::new(1))?.pred_opt()?
+                }
+            }
+            [year, month, day] => NaiveDate::from_ymd_opt(i32::try_from(year).ok()?, month, day)?,
+            _ => return None,
+        };
+
+        Some(Some(date))
+    }
+}
+
+impl FromStr for DateRange {
+    type Err = ();
+
+    /// Parse a date range such as "2022-06-01..2022-08-31" or "2022-06 to 2022-08".
+    /// A single year, month, or day is a range covering just that period.
+    /// Either end can be left out for an unbounded range, such as "2022-06..".
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (from, to) = if let Some((from, to)) = s.split_once("..") {
+            (from, to)
+        } else if let Some((from, to)) = s.split_once(" to ") {
+            (from, to)
+        } else {
+            (s, s)
+        };
+
+        let from = Self::parse_end(from, true).ok_or(())?;
+        let to = Self::parse_end(to, false).ok_or(())?;
+
+        if from.is_none() && to.is_none() {
+            return Err(());
+        }
+
+        Ok(DateRange { from, to })
+    }
+}
+
+impl Display for DateRange {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let fmt = |date: Option<NaiveDate>| {
+            date.map(|x| x.format("%Y-%m-%d").to_string())
+                .unwrap_or_default()
+        };
+        write!(f, "{}..{}", fmt(self.from), fmt(self.to))
+    }
+}
+
+/// A test of a single property of a picture or video.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Criterion {
+    /// In a folder or any of its sub-folders.
+    Folder(PathBuf),
+
+    /// Taken on a local date within a range.
+    DateRange(DateRange),
+
+    /// Person is in picture or video.
+    Person(PersonId),
+
+    /// Taken within an area. The resolution of the cell sets the size of the area.
+    Place(CellIndex),
+
+    MediaType(MediaType),
+
+    /// Camera make or model contains text, ignoring case.
+    Camera(String),
+
+    Selfie,
+
+    /// Live photo or motion photo.
+    Motion,
+
+    Favourite,
+
+    /// Has a keyword tag or one of its descendants, such as "Places|Europe".
+    Tag(String),
+}
+
+/// Expression for choosing the pictures and videos of a smart album.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Rule {
+    /// Every rule must match. Matches nothing if there are no rules.
+    All(Vec<Rule>),
+
+    /// At least one rule must match. Matches nothing if there are no rules.
+    Any(Vec<Rule>),
+
+    /// Rule must not match.
+    Not(Box<Rule>),
+
+    Criterion(Criterion),
+}
+
+/// Pictures and videos linked to something a rule refers to, such as
+/// the items a person has been recognised in.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LinkedItems {
+    pub picture_ids: HashSet<PictureId>,
+    pub video_ids: HashSet<VideoId>,
+}
+
+impl LinkedItems {
+    pub fn contains(&self, visual: &Visual) -> bool {
+        visual
+            .picture_id
+            .is_some_and(|id| self.picture_ids.contains(&id))
+            || visual
+                .video_id
+                .is_some_and(|id| self.video_ids.contains(&id))
+    }
+}
+
+/// Items of the people and tags a rule refers to, which are looked up in the
+/// database rather than being properties of a visual item.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RuleItems {
+    pub people: Vec<(PersonId, LinkedItems)>,
+
+    /// Items for each tag path, including the items of descendant tags.
+    pub tags: Vec<(String, LinkedItems)>,
+}
+
+impl Rule {
+    /// Does a picture or video match this rule?
+    /// Items must include the items of every person and tag the rule refers to.
+    pub fn matches(&self, visual: &Visual, items: &RuleItems) -> bool {
+        match self {
+            Rule::All(rules) => !rules.is_empty() && rules.iter().all(|r| r.matches(visual, items)),
+            Rule::Any(rules) => rules.iter().any(|r| r.matches(visual, items)),
+            Rule::Not(rule) => !rule.matches(visual, items),
+            Rule::Criterion(criterion) => criterion.matches(visual, items),
+        }
+    }
+
+    /// People referred to anywhere in this rule.
+    pub fn people(&self) -> Vec<PersonId> {
+        match self {
+            Rule::All(rules) | Rule::Any(rules) => rules.iter().flat_map(|r| r.people()).collect(),
+            Rule::Not(rule) => rule.people(),
+            Rule::Criterion(Criterion::Person(person_id)) => vec![*person_id],
+            Rule::Criterion(_) => vec![],
+        }
+    }
+
+    /// Tag paths referred to anywhere in this rule.
+    pub fn tags(&self) -> Vec<String> {
+        match self {
+            Rule::All(rules) | Rule::Any(rules) => rules.iter().flat_map(|r| r.tags()).collect(),
+            Rule::Not(rule) => rule.tags(),
+            Rule::Criterion(Criterion::Tag(path)) => vec![path.clone()],
+            Rule::Criterion(_) => vec![],
+        }
+    }
+
+    /// Flatten rule tree into rows for storing, in depth-first order.
+    /// IDs are only unique within the returned rows.
+    pub fn to_rows(&self) -> Vec<RuleRow> {
+        let mut rows = Vec::new();
+        self.push_rows(None, &mut rows);
+        rows
+    }
+
+    fn push_rows(&self, parent_id: Option<i64>, rows: &mut Vec<RuleRow>) {
+        let id = i64::try_from(rows.len()).unwrap_or(i64::MAX);
+
+        let (kind, value, children): (RuleKind, Option<String>, Vec<&Rule>) = match self {
+            Rule::All(rules) => (RuleKind::All, None, rules.iter().collect()),
+            Rule::Any(rules) => (RuleKind::Any, None, rules.iter().collect()),
+            Rule::Not(rule) => (RuleKind::Not, None, vec![rule.as_ref()]),
+            Rule::Criterion(criterion) => {
+                let (kind, value) = criterion.to_kind_value();
+                (kind, value, vec![])
+            }
+        };
+
+        rows.push(RuleRow {
+            id,
+            parent_id,
+            kind,
+            value,
+        });
+
+        for child in children {
+            child.push_rows(Some(id), rows);
+        }
+    }
+
+    /// Rebuild a rule tree from rows, where children are in the order of the rows.
+    /// Criteria with values that can't be understood are left out.
+    pub fn from_rows(rows: &[RuleRow]) -> Option<Rule> {
+        let root = rows.iter().find(|row| row.parent_id.is_none())?;
+        Self::from_row(root, rows)
+    }
+
+    fn from_row(row: &RuleRow, rows: &[RuleRow]) -> Option<Rule> {
+        let children = || {
+            rows.iter()
+                .filter(|x| x.parent_id == Some(row.id))
+                .filter_map(|x| Self::from_row(x, rows))
+        };
+
+        match row.kind {
+            RuleKind::All => Some(Rule::All(children().collect())),
+            RuleKind::Any => Some(Rule::Any(children().collect())),
+            RuleKind::Not => children().next().map(|x| Rule::Not(Box::new(x))),
+            kind => Criterion::from_kind_value(kind, row.value.as_deref()).map(Rule::Criterion),
+        }
+    }
+}
+
+impl Criterion {
+    pub fn matches(&self, visual: &Visual, items: &RuleItems) -> bool {
+        match self {
+            Criterion::Folder(folder) => visual.parent_path.starts_with(folder),
+            Criterion::DateRange(range) => range.contains(visual.local_ts().date_naive()),
+            Criterion::Person(person_id) => items
+                .people
+                .iter()
+                .any(|(id, linked)| id == person_id && linked.contains(visual)),
+            Criterion::Place(cell) => visual
+                .location
+                .is_some_and(|loc| loc.to_cell(cell.resolution()) == *cell),
+            Criterion::MediaType(MediaType::Photo) => visual.picture_id.is_some(),
+            Criterion::MediaType(MediaType::Video) => visual.is_video_only(),
+            Criterion::Camera(text) => {
+                let text = text.to_lowercase();
+                visual
+                    .camera
+                    .as_ref()
+                    .is_some_and(|camera| camera.to_lowercase().contains(&text))
+            }
+            Criterion::Selfie => visual.is_selfie(),
+            Criterion::Motion => visual.is_motion_photo(),
+            Criterion::Favourite => visual.is_favourite,
+            Criterion::Tag(path) => items
+                .tags
+                .iter()
+                .any(|(p, linked)| p == path && linked.contains(visual)),
+        }
+    }
+
+    pub fn kind(&self) -> RuleKind {
+        self.to_kind_value().0
+    }
+
+    fn to_kind_value(&self) -> (RuleKind, Option<String>) {
+        match self {
+            Criterion::Folder(path) => (RuleKind::Folder, Some(path_encoding::to_base64(path))),
+            Criterion::DateRange(range) => (RuleKind::DateRange, Some(range.to_string())),
+            Criterion::Person(person_id) => (RuleKind::Person, Some(person_id.to_string())),
+            Criterion::Place(cell) => (RuleKind::Place, Some(u64::from(*cell).to_string())),
+            Criterion::MediaType(media_type) => {
+                (RuleKind::MediaType, Some(media_type.as_ref().to_string()))
+            }
+            Criterion::Camera(text) => (RuleKind::Camera, Some(text.clone())),
+            Criterion::Selfie => (RuleKind::Selfie, None),
+            Criterion::Motion => (RuleKind::Motion, None),
+            Criterion::Favourite => (RuleKind::Favourite, None),
+            Criterion::Tag(path) => (RuleKind::Tag, Some(path.clone())),
+        }
+    }
+
+    fn from_kind_value(kind: RuleKind, value: Option<&str>) -> Option<Criterion> {
+        let criterion = match kind {
+            RuleKind::All | RuleKind::Any | RuleKind::Not => return None,
+            RuleKind::Folder => {
+                Criterion::Folder(path_encoding::from_base64(&value?.to_string()).ok()?)
+            }
+            RuleKind::DateRange => Criterion::DateRange(DateRange::from_str(value?).ok()?),
+            RuleKind::Person => Criterion::Person(PersonId::new(value?.parse().ok()?)),
+            RuleKind::Place => {
+                Criterion::Place(CellIndex::try_from(value?.parse::<u64>().ok()?).ok()?)
+            }
+            RuleKind::MediaType => Criterion::MediaType(MediaType::from_str(value?).ok()?),
+            RuleKind::Camera => Criterion::Camera(value?.to_string()),
+            RuleKind::Selfie => Criterion::Selfie,
+            RuleKind::Motion => Criterion::Motion,
+            RuleKind::Favourite => Criterion::Favourite,
+            RuleKind::Tag => Criterion::Tag(value?.to_string()),
+        };
+        Some(criterion)
+    }
+}
+
+/// Kind of rule as stored in the database.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, AsRefStr, EnumString, EnumIter)]
+#[strum(serialize_all = "snake_case")]
+pub enum RuleKind {
+    All,
+    Any,
+    Not,
+    Folder,
+    DateRange,
+    Person,
+    Place,
+    MediaType,
+    Camera,
+    Selfie,
+    Motion,
+    Favourite,
+    Tag,
+}
+
+/// A rule flattened for storing in a database table.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RuleRow {
+    pub id: i64,
+    pub parent_id: Option<i64>,
+    pub kind: RuleKind,
+    pub value: Option<String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    #[test]
+    fn test_parse_date_range() {
+        let range = DateRange::from_str("2022-06-01..2022-08-31").unwrap();
+        assert_eq!(Some(date(2022, 6, 1)), range.from);
+        assert_eq!(Some(date(2022, 8, 31)), range.to);
+
+        let range = DateRange::from_str("2024-02 to 2024-02").unwrap();
+        assert_eq!(Some(date(2024, 2, 1)), range.from);
+        assert_eq!(Some(date(2024, 2, 29)), range.to);
+
+        let range = DateRange::from_str("2022").unwrap();
+        assert_eq!(Some(date(2022, 1, 1)), range.from);
+        assert_eq!(Some(date(2022, 12, 31)), range.to);
+
+        let range = DateRange::from_str("..2020-03").unwrap();
+        assert_eq!(None, range.from);
+        assert_eq!(Some(date(2020, 3, 31)), range.to);
+
+        assert!(DateRange::from_str("..").is_err());
+        assert!(DateRange::from_str("last summer").is_err());
+        assert!(DateRange::from_str("2022-13").is_err());
+    }
+
+    #[test]
+    fn test_date_range_round_trip() {
+        let range = DateRange {
+            from: Some(date(2021, 12, 24)),
+            to: None,
+        };
+        assert_eq!("2021-12-24..", range.to_string());
+        assert_eq!(Ok(range), DateRange::from_str(&range.to_string()));
+    }
+
+    #[test]
+    fn test_rule_rows_round_trip() {
+        let rule = Rule::All(vec![
+            Rule::Criterion(Criterion::Person(PersonId::new(7))),
+            Rule::Any(vec![
+                Rule::Criterion(Criterion::Camera("Pixel".into())),
+                Rule::Criterion(Criterion::MediaType(MediaType::Video)),
+                Rule::Criterion(Criterion::Tag("Places|Europe".into())),
+            ]),
+            Rule::Not(Box::new(Rule::Criterion(Criterion::Selfie))),
+        ]);
+
+        let rows = rule.to_rows();
+        assert_eq!(8, rows.len());
+        assert_eq!(None, rows[0].parent_id);
+        assert_eq!(RuleKind::Any, rows[2].kind);
+        assert_eq!(Some(2), rows[3].parent_id);
+
+        assert_eq!(vec!["Places|Europe".to_string()], rule.tags());
+        assert_eq!(Some(rule), Rule::from_rows(&rows));
+    }
+
+    #[test]
+    fn test_from_rows_skips_bad_values() {
+        let rows = vec![
+            RuleRow {
+                id: 10,
+                parent_id: None,
+                kind: RuleKind::Any,
+                value: None,
+            },
+            RuleRow {
+                id: 11,
+                parent_id: Some(10),
+                kind: RuleKind::Person,
+                value: Some("not a number".into()),
+            },
+            RuleRow {
+                id: 12,
+                parent_id: Some(10),
+                kind: RuleKind::Favourite,
+                value: None,
+            },
+        ];
+
+        assert_eq!(
+            Some(Rule::Any(vec![Rule::Criterion(Criterion::Favourite)])),
+            Rule::from_rows(&rows)
+        );
+    }
+}
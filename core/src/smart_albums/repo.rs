@@ -0,0 +1,190 @@
+// SPDX-FileCopyrightText: © 2024 David Bliss
+//
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use crate::smart_albums::model::{Rule, RuleKind, RuleRow, SmartAlbum, SmartAlbumId};
+
+use anyhow::*;
+use rusqlite;
+use rusqlite::params;
+use rusqlite::Row;
+use rusqlite::Transaction;
+use std::collections::HashMap;
+use std::result::Result::Ok;
+use std::str::FromStr;
+use std::sync::{Arc, Mutex};
+
+/// Repository of smart albums and their rules.
+/// Repository is backed by a Sqlite database.
+#[derive(Debug, Clone)]
+pub struct Repository {
+    /// Connection to backing Sqlite database.
+    con: Arc<Mutex<rusqlite::Connection>>,
+}
+
+impl Repository {
+    pub fn open(con: Arc<Mutex<rusqlite::Connection>>) -> Result<Repository> {
+        let repo = Repository { con };
+        Ok(repo)
+    }
+
+    /// All smart albums, ordered by name, with their rules.
+    pub fn all(&self) -> Result<Vec<SmartAlbum>> {
+        let con = self.con.lock().unwrap();
+
+        let mut stmt = con.prepare(
+            "SELECT
+                smart_album_id,
+                name
+            FROM smart_albums
+            ORDER BY name COLLATE NOCASE ASC",
+        )?;
+
+        let albums = stmt
+            .query_map([], |row| {
+                let smart_album_id = row.get("smart_album_id").map(SmartAlbumId::new)?;
+                let name: String = row.get("name")?;
+                Ok((smart_album_id, name))
+            })?
+            .flatten()
+            .collect::<Vec<(SmartAlbumId, String)>>();
+
+        let mut stmt = con.prepare(
+            "SELECT
+                rule_id,
+                parent_rule_id,
+                kind,
+                value
+            FROM smart_album_rules
+            WHERE smart_album_id = ?1
+            ORDER BY position ASC",
+        )?;
+
+        let mut smart_albums = Vec::with_capacity(albums.len());
+
+        for (smart_album_id, name) in albums {
+            let rows = stmt
+                .query_map([smart_album_id.id()], |row| Self::to_rule_row(row))?
+                .flatten()
+                .collect::<Vec<RuleRow>>();
+
+            let rule = Rule::from_rows(&rows).unwrap_or_else(|| Rule::All(vec![]));
+
+            smart_albums.push(SmartAlbum {
+                smart_album_id,
+                name,
+                rule,
+            });
+        }
+
+        Ok(smart_albums)
+    }
+
+    /// Smart album with its rule.
+    pub fn get(&self, smart_album_id: SmartAlbumId) -> Result<Option<SmartAlbum>> {
+        let albums = self.all()?;
+        Ok(albums
+            .into_iter()
+            .find(|album| album.smart_album_id == smart_album_id))
+    }
+
+    /// Create a new smart album.
+    pub fn create(&mut self, name: &str, rule: &Rule) -> Result<SmartAlbumId> {
+        let mut con = self.con.lock().unwrap();
+        let tx = con.transaction()?;
+
+        let smart_album_id = {
+            let mut stmt = tx.prepare_cached("INSERT INTO smart_albums (name) VALUES (?1)")?;
+            stmt.execute([name])?;
+            SmartAlbumId::new(tx.last_insert_rowid())
+        };
+
+        Self::insert_rule(&tx, smart_album_id, rule)?;
+
+        tx.commit()?;
+
+        Ok(smart_album_id)
+    }
+
+    /// Change the name and rule of a smart album.
+    pub fn update(&mut self, smart_album_id: SmartAlbumId, name: &str, rule: &Rule) -> Result<()> {
+        let mut con = self.con.lock().unwrap();
+        let tx = con.transaction()?;
+
+        {
+            let mut stmt =
+                tx.prepare_cached("UPDATE smart_albums SET name = ?2 WHERE smart_album_id = ?1")?;
+            stmt.execute(params![smart_album_id.id(), name])?;
+
+            let mut stmt =
+                tx.prepare_cached("DELETE FROM smart_album_rules WHERE smart_album_id = ?1")?;
+            stmt.execute([smart_album_id.id()])?;
+        }
+
+        Self::insert_rule(&tx, smart_album_id, rule)?;
+
+        tx.commit()?;
+
+        Ok(())
+    }
+
+    /// Delete a smart album. No pictures or videos are deleted.
+    pub fn delete(&mut self, smart_album_id: SmartAlbumId) -> Result<()> {
+        let con = self.con.lock().unwrap();
+
+        let mut stmt = con.prepare_cached("DELETE FROM smart_albums WHERE smart_album_id = ?1")?;
+
+        stmt.execute([smart_album_id.id()])?;
+
+        Ok(())
+    }
+
+    fn insert_rule(tx: &Transaction, smart_album_id: SmartAlbumId, rule: &Rule) -> Result<()> {
+        let mut stmt = tx.prepare_cached(
+            "INSERT INTO smart_album_rules (
+                smart_album_id,
+                parent_rule_id,
+                position,
+                kind,
+                value
+            ) VALUES (
+                ?1, ?2, ?3, ?4, ?5
+            )",
+        )?;
+
+        // Rows are in depth-first order, so a parent is always inserted
+        // before its children and its database ID is known.
+        let mut rule_ids: HashMap<i64, i64> = HashMap::new();
+
+        for (position, row) in rule.to_rows().into_iter().enumerate() {
+            let parent_rule_id = row.parent_id.and_then(|id| rule_ids.get(&id)).copied();
+
+            stmt.execute(params![
+                smart_album_id.id(),
+                parent_rule_id,
+                position,
+                row.kind.as_ref(),
+                row.value,
+            ])?;
+
+            rule_ids.insert(row.id, tx.last_insert_rowid());
+        }
+
+        Ok(())
+    }
+
+    fn to_rule_row(row: &Row<'_>) -> rusqlite::Result<RuleRow> {
+        let id = row.get("rule_id")?;
+        let parent_id = row.get("parent_rule_id")?;
+        let kind: String = row.get("kind")?;
+        let kind = RuleKind::from_str(&kind).map_err(|_| rusqlite::Error::InvalidQuery)?;
+        let value = row.get("value")?;
+
+        Ok(RuleRow {
+            id,
+            parent_id,
+            kind,
+            value,
+        })
+    }
+}
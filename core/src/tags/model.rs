@@ -0,0 +1,156 @@
+// SPDX-FileCopyrightText: © 2024 David Bliss
+//
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use std::fmt::Display;
+
+/// Separates the levels of a hierarchical tag path, such as "Places|Europe|Paris".
+/// Lightroom and digiKam use the same separator in XMP metadata.
+pub const SEPARATOR: char = '|';
+
+/// Database ID
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct TagId(i64);
+
+impl TagId {
+    pub fn new(id: i64) -> Self {
+        Self(id)
+    }
+
+    /// FIXME replace this with a To/From SQL implementation.
+    pub fn id(&self) -> i64 {
+        self.0
+    }
+}
+
+impl Display for TagId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+/// A keyword tag, which might be nested in another tag.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Tag {
+    pub tag_id: TagId,
+
+    pub parent_tag_id: Option<TagId>,
+
+    /// Last level of path, such as "Paris".
+    pub name: String,
+
+    /// Full path, such as "Places|Europe|Paris".
+    pub path: String,
+
+    /// Number of pictures and videos with this tag or any of its descendants.
+    pub item_count: usize,
+}
+
+/// Tidy a tag path by trimming each level and dropping empty levels.
+/// Returns None if there is nothing left.
+pub fn normalise_path(path: &str) -> Option<String> {
+    let levels: Vec<&str> = path
+        .split(SEPARATOR)
+        .map(str::trim)
+        .filter(|x| !x.is_empty())
+        .collect();
+
+    if levels.is_empty() {
+        None
+    } else {
+        Some(levels.join(&SEPARATOR.to_string()))
+    }
+}
+
+/// Paths of a tag and every tag above it, starting at the top level.
+/// For example, "A|B|C" gives "A", "A|B", and "A|B|C".
+pub fn ancestors(path: &str) -> Vec<String> {
+    path.match_indices(SEPARATOR)
+        .map(|(index, _)| path[..index].to_string())
+        .chain(std::iter::once(path.to_string()))
+        .collect()
+}
+
+/// Parent path and name of a tag, such as ("Places|Europe", "Paris").
+pub fn split_path(path: &str) -> (Option<&str>, &str) {
+    match path.rsplit_once(SEPARATOR) {
+        Some((parent, name)) => (Some(parent), name),
+        None => (None, path),
+    }
+}
+
+/// Tag path for showing to the user, such as "Places › Europe › Paris".
+pub fn display_path(path: &str) -> String {
+    path.split(SEPARATOR).collect::<Vec<_>>().join(" › ")
+}
+
+/// Combine keywords from metadata into tag paths.
+/// Photo managers that write hierarchical keywords usually also write each level as
+/// a flat keyword, so flat keywords that are already a level of a hierarchical keyword
+/// are dropped rather than appearing twice.
+pub fn merge_keywords(hierarchical: &[String], flat: &[String]) -> Vec<String> {
+    let hierarchical: Vec<String> = hierarchical
+        .iter()
+        .filter_map(|x| normalise_path(x))
+        .collect();
+
+    let is_level = |keyword: &str| {
+        hierarchical
+            .iter()
+            .any(|path| path.split(SEPARATOR).any(|level| level == keyword))
+    };
+
+    let flat = flat
+        .iter()
+        .map(|x| x.trim())
+        .filter(|x| !x.is_empty() && !is_level(x))
+        // Flat keywords are a single level, so a separator isn't a level separator.
+        .map(|x| x.replace(SEPARATOR, " "));
+
+    let mut paths: Vec<String> = Vec::new();
+    for path in hierarchical.iter().cloned().chain(flat) {
+        if !paths.contains(&path) {
+            paths.push(path);
+        }
+    }
+    paths
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalise_path() {
+        assert_eq!(
+            Some("Places|Europe|Paris".to_string()),
+            normalise_path(" Places | Europe||Paris|")
+        );
+        assert_eq!(Some("Cats".to_string()), normalise_path("Cats"));
+        assert_eq!(None, normalise_path(" | "));
+    }
+
+    #[test]
+    fn test_ancestors() {
+        assert_eq!(vec!["A", "A|B", "A|B|C"], ancestors("A|B|C"));
+        assert_eq!(vec!["A"], ancestors("A"));
+        assert_eq!((Some("A|B"), "C"), split_path("A|B|C"));
+        assert_eq!((None, "A"), split_path("A"));
+    }
+
+    #[test]
+    fn test_merge_keywords() {
+        let hierarchical = vec!["Places|Europe|Paris".to_string()];
+        let flat = vec![
+            "Places".to_string(),
+            "Paris".to_string(),
+            "Holiday".to_string(),
+            "Holiday".to_string(),
+        ];
+
+        assert_eq!(
+            vec!["Places|Europe|Paris", "Holiday"],
+            merge_keywords(&hierarchical, &flat)
+        );
+    }
+}
@@ -0,0 +1,395 @@
+// SPDX-FileCopyrightText: © 2024 David Bliss
+//
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use crate::photo::PictureId;
+use crate::tags::model::{self, Tag, TagId};
+use crate::video::VideoId;
+
+use anyhow::*;
+use rusqlite;
+use rusqlite::params;
+use rusqlite::Row;
+use rusqlite::Transaction;
+use std::result::Result::Ok;
+use std::sync::{Arc, Mutex};
+
+/// Repository of keyword tags and the pictures and videos they are on.
+/// Tags reference pictures and videos rather than visual items, so they
+/// survive the visual view being redefined.
+/// Repository is backed by a Sqlite database.
+#[derive(Debug, Clone)]
+pub struct Repository {
+    /// Connection to backing Sqlite database.
+    con: Arc<Mutex<rusqlite::Connection>>,
+}
+
+impl Repository {
+    pub fn open(con: Arc<Mutex<rusqlite::Connection>>) -> Result<Repository> {
+        let repo = Repository { con };
+        Ok(repo)
+    }
+
+    /// All tags, ordered by path so parents come before their children.
+    /// Tags only kept to remember where the user removed them are left out.
+    pub fn all(&self) -> Result<Vec<Tag>> {
+        let con = self.con.lock().unwrap();
+
+        // Live photos tagged by the user have a picture and a video in the same row,
+        // so count pictures and only count videos without a picture.
+        let mut stmt = con.prepare(
+            "SELECT * FROM (
+                SELECT
+                    tags.tag_id,
+                    tags.parent_tag_id,
+                    tags.name,
+                    tags.path,
+                    (
+                        SELECT COUNT(DISTINCT COALESCE(tag_items.picture_id, -tag_items.video_id))
+                        FROM tags AS descendants
+                        INNER JOIN tag_items USING (tag_id)
+                        WHERE (descendants.path = tags.path
+                            OR substr(descendants.path, 1, length(tags.path) + 1) = tags.path || '|')
+                        AND tag_items.is_removed IS FALSE
+                    ) AS item_count
+                FROM tags
+            )
+            WHERE item_count > 0
+            ORDER BY path COLLATE NOCASE ASC",
+        )?;
+
+        let tags = stmt.query_map([], Self::to_tag)?.flatten().collect();
+
+        Ok(tags)
+    }
+
+    /// Paths of the tags on a picture or video.
+    pub fn find_tags(
+        &self,
+        picture_id: Option<PictureId>,
+        video_id: Option<VideoId>,
+    ) -> Result<Vec<String>> {
+        let con = self.con.lock().unwrap();
+
+        let mut stmt = con.prepare(
+            "SELECT DISTINCT
+                tags.path
+            FROM tag_items
+            INNER JOIN tags USING (tag_id)
+            WHERE (tag_items.picture_id = ?1 OR tag_items.video_id = ?2)
+            AND tag_items.is_removed IS FALSE
+            ORDER BY tags.path COLLATE NOCASE ASC",
+        )?;
+
+        let paths = stmt
+            .query_map(
+                params![picture_id.map(|x| x.id()), video_id.map(|x| x.id())],
+                |row| row.get(0),
+            )?
+            .flatten()
+            .collect();
+
+        Ok(paths)
+    }
+
+    /// Pictures with a tag or any of its descendants.
+    pub fn find_pictures_for_tag(&self, path: &str) -> Result<Vec<PictureId>> {
+        let con = self.con.lock().unwrap();
+
+        let mut stmt = con.prepare(
+            "SELECT DISTINCT
+                tag_items.picture_id
+            FROM tag_items
+            INNER JOIN tags USING (tag_id)
+            WHERE (tags.path = ?1 OR substr(tags.path, 1, length(?1) + 1) = ?1 || '|')
+            AND tag_items.picture_id IS NOT NULL
+            AND tag_items.is_removed IS FALSE",
+        )?;
+
+        let result = stmt
+            .query_map([path], |row| row.get(0).map(PictureId::new))?
+            .flatten()
+            .collect();
+
+        Ok(result)
+    }
+
+    /// Videos with a tag or any of its descendants.
+    pub fn find_videos_for_tag(&self, path: &str) -> Result<Vec<VideoId>> {
+        let con = self.con.lock().unwrap();
+
+        let mut stmt = con.prepare(
+            "SELECT DISTINCT
+                tag_items.video_id
+            FROM tag_items
+            INNER JOIN tags USING (tag_id)
+            WHERE (tags.path = ?1 OR substr(tags.path, 1, length(?1) + 1) = ?1 || '|')
+            AND tag_items.video_id IS NOT NULL
+            AND tag_items.is_removed IS FALSE",
+        )?;
+
+        let result = stmt
+            .query_map([path], |row| row.get(0).map(VideoId::new))?
+            .flatten()
+            .collect();
+
+        Ok(result)
+    }
+
+    /// Add a tag to a picture or video, creating the tag and its ancestors if needed.
+    /// Live photos have a picture and a video, so both are used.
+    pub fn add_tag(
+        &mut self,
+        picture_id: Option<PictureId>,
+        video_id: Option<VideoId>,
+        path: &str,
+    ) -> Result<()> {
+        let Some(path) = model::normalise_path(path) else {
+            bail!("Tag path is empty");
+        };
+
+        let mut con = self.con.lock().unwrap();
+        let tx = con.transaction()?;
+
+        let tag_id = Self::create_tag(&tx, &path)?;
+
+        {
+            let mut stmt = tx.prepare_cached(
+                "INSERT INTO tag_items (tag_id, picture_id, video_id, source)
+                VALUES (?1, ?2, ?3, 'user')
+                ON CONFLICT DO NOTHING",
+            )?;
+
+            stmt.execute(params![
+                tag_id.id(),
+                picture_id.map(|x| x.id()),
+                video_id.map(|x| x.id()),
+            ])?;
+
+            // Keep the tag if the item already had it from metadata, but the
+            // keyword is later removed from the metadata. Also restore the tag
+            // if the user removed it before.
+            let mut stmt = tx.prepare_cached(
+                "UPDATE tag_items SET source = 'user', is_removed = FALSE
+                WHERE tag_id = ?1
+                AND (picture_id = ?2 OR video_id = ?3)",
+            )?;
+
+            stmt.execute(params![
+                tag_id.id(),
+                picture_id.map(|x| x.id()),
+                video_id.map(|x| x.id()),
+            ])?;
+        }
+
+        tx.commit()?;
+
+        Ok(())
+    }
+
+    /// Remove a tag from a picture or video.
+    /// The tag item is kept and marked as removed, so that importing metadata
+    /// again doesn't add the tag back.
+    pub fn remove_tag(
+        &mut self,
+        picture_id: Option<PictureId>,
+        video_id: Option<VideoId>,
+        path: &str,
+    ) -> Result<()> {
+        let mut con = self.con.lock().unwrap();
+        let tx = con.transaction()?;
+
+        {
+            let mut stmt = tx.prepare_cached(
+                "UPDATE tag_items SET is_removed = TRUE
+                WHERE tag_id = (SELECT tag_id FROM tags WHERE path = ?1)
+                AND (picture_id = ?2 OR video_id = ?3)",
+            )?;
+
+            stmt.execute(params![
+                path,
+                picture_id.map(|x| x.id()),
+                video_id.map(|x| x.id()),
+            ])?;
+        }
+
+        tx.commit()?;
+
+        Ok(())
+    }
+
+    /// Update the tags of pictures with keywords from their metadata.
+    /// Tags added by the user are kept, and tags removed by the user are not added back.
+    pub fn add_metadata_tags(&mut self, pictures: Vec<(PictureId, Vec<String>)>) -> Result<()> {
+        let mut con = self.con.lock().unwrap();
+        let tx = con.transaction()?;
+
+        for (picture_id, paths) in pictures {
+            let paths: Vec<String> = paths
+                .iter()
+                .filter_map(|x| model::normalise_path(x))
+                .collect();
+
+            // Only delete keywords no longer in the metadata. Removed tags are kept,
+            // so the conflict below skips them.
+            let stale: Vec<i64> = {
+                let mut stmt = tx.prepare_cached(
+                    "SELECT tag_items.tag_item_id, tags.path
+                    FROM tag_items
+                    INNER JOIN tags USING (tag_id)
+                    WHERE tag_items.picture_id = ?1
+                    AND tag_items.source = 'metadata'
+                    AND tag_items.is_removed IS FALSE",
+                )?;
+                let stale = stmt
+                    .query_map([picture_id.id()], |row| {
+                        Ok((row.get::<_, i64>(0)?, row.get::<_, String>(1)?))
+                    })?
+                    .flatten()
+                    .filter(|(_, path)| !paths.contains(path))
+                    .map(|(tag_item_id, _)| tag_item_id)
+                    .collect();
+                stale
+            };
+
+            {
+                let mut stmt = tx.prepare_cached("DELETE FROM tag_items WHERE tag_item_id = ?1")?;
+                for tag_item_id in stale {
+                    stmt.execute([tag_item_id])?;
+                }
+            }
+
+            for path in paths {
+                let tag_id = Self::create_tag(&tx, &path)?;
+
+                let mut stmt = tx.prepare_cached(
+                    "INSERT INTO tag_items (tag_id, picture_id, source)
+                    VALUES (?1, ?2, 'metadata')
+                    ON CONFLICT DO NOTHING",
+                )?;
+                stmt.execute(params![tag_id.id(), picture_id.id()])?;
+            }
+        }
+
+        Self::prune(&tx)?;
+
+        tx.commit()?;
+
+        Ok(())
+    }
+
+    /// Get or create a tag, and each tag above it.
+    fn create_tag(tx: &Transaction, path: &str) -> Result<TagId> {
+        let mut insert = tx.prepare_cached(
+            "INSERT INTO tags (parent_tag_id, name, path)
+            VALUES ((SELECT tag_id FROM tags WHERE path = ?1), ?2, ?3)
+            ON CONFLICT (path) DO NOTHING",
+        )?;
+
+        for ancestor in model::ancestors(path) {
+            let (parent, name) = model::split_path(&ancestor);
+            insert.execute(params![parent, name, ancestor])?;
+        }
+
+        let mut stmt = tx.prepare_cached("SELECT tag_id FROM tags WHERE path = ?1")?;
+        let tag_id = stmt.query_row([path], |row| row.get(0).map(TagId::new))?;
+
+        Ok(tag_id)
+    }
+
+    /// Delete tags without any items or child tags.
+    /// Deleting a leaf can leave its parent empty, so repeat until nothing changes.
+    pub(crate) fn prune(tx: &Transaction) -> Result<()> {
+        let mut stmt = tx.prepare_cached(
+            "DELETE FROM tags
+            WHERE NOT EXISTS (SELECT 1 FROM tag_items WHERE tag_items.tag_id = tags.tag_id)
+            AND NOT EXISTS (SELECT 1 FROM tags AS children WHERE children.parent_tag_id = tags.tag_id)",
+        )?;
+
+        while stmt.execute([])? > 0 {}
+
+        Ok(())
+    }
+
+    fn to_tag(row: &Row<'_>) -> rusqlite::Result<Tag> {
+        let tag_id = row.get("tag_id").map(TagId::new)?;
+        let parent_tag_id = row
+            .get::<_, Option<i64>>("parent_tag_id")
+            .map(|x| x.map(TagId::new))?;
+        let name = row.get("name")?;
+        let path = row.get("path")?;
+        let item_count = row.get::<_, i64>("item_count")?;
+
+        std::result::Result::Ok(Tag {
+            tag_id,
+            parent_tag_id,
+            name,
+            path,
+            item_count: usize::try_from(item_count).unwrap_or(0),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database;
+
+    fn repo() -> Repository {
+        let con = database::setup_in_memory().unwrap();
+        con.execute_batch(
+            "INSERT INTO pictures (picture_id, picture_path_b64, picture_path_lossy, link_path_b64, link_path_lossy)
+            VALUES (1, 'a', 'a', 'a', 'a')",
+        )
+        .unwrap();
+        Repository::open(Arc::new(Mutex::new(con))).unwrap()
+    }
+
+    #[test]
+    fn test_removed_tag_not_added_by_metadata() {
+        let mut repo = repo();
+        let picture_id = PictureId::new(1);
+        let keywords = vec!["Places|Paris".to_string(), "Holiday".to_string()];
+
+        repo.add_metadata_tags(vec![(picture_id, keywords.clone())])
+            .unwrap();
+        assert_eq!(
+            vec!["Holiday", "Places|Paris"],
+            repo.find_tags(Some(picture_id), None).unwrap()
+        );
+
+        repo.remove_tag(Some(picture_id), None, "Places|Paris")
+            .unwrap();
+        assert_eq!(
+            vec!["Holiday"],
+            repo.find_tags(Some(picture_id), None).unwrap()
+        );
+        assert_eq!(
+            vec!["Holiday"],
+            repo.all()
+                .unwrap()
+                .into_iter()
+                .map(|x| x.path)
+                .collect::<Vec<_>>()
+        );
+
+        // Rescanning metadata imports the keywords again
+        repo.add_metadata_tags(vec![(picture_id, keywords)])
+            .unwrap();
+        assert_eq!(
+            vec!["Holiday"],
+            repo.find_tags(Some(picture_id), None).unwrap()
+        );
+
+        // Keywords no longer in the metadata are removed
+        repo.add_metadata_tags(vec![(picture_id, vec![])]).unwrap();
+        assert!(repo.find_tags(Some(picture_id), None).unwrap().is_empty());
+
+        repo.add_tag(Some(picture_id), None, "Places|Paris")
+            .unwrap();
+        assert_eq!(
+            vec!["Places|Paris"],
+            repo.find_tags(Some(picture_id), None).unwrap()
+        );
+    }
+}
@@ -2,8 +2,13 @@
 //
 // SPDX-License-Identifier: GPL-3.0-or-later
 
-use chrono::{Month, TimeDelta};
+use chrono::{
+    DateTime, FixedOffset, Month, Months, NaiveDate, NaiveDateTime, NaiveTime, Offset, TimeDelta,
+    TimeZone, Utc,
+};
+use chrono_tz::Tz;
 use std::fmt::Display;
+use strum::{AsRefStr, EnumString};
 
 pub type Year = i32;
 
@@ -25,6 +30,237 @@ impl YearMonth {
     }
 }
 
+/// Where the offset from UTC of the time a picture or video was taken came from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, AsRefStr, EnumString)]
+#[strum(serialize_all = "lowercase")]
+pub enum OffsetSource {
+    /// Embedded in the file's metadata, such as EXIF OffsetTimeOriginal or
+    /// the QuickTime creation date.
+    Metadata,
+
+    /// Time zone of the place where the picture or video was taken.
+    Location,
+
+    /// Nothing better is known, so assumed to be the computer's time zone.
+    Assumed,
+}
+
+/// Where the date and time a picture or video was taken came from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, AsRefStr, EnumString)]
+#[strum(serialize_all = "lowercase")]
+pub enum TimestampSource {
+    /// Set by the user to correct a camera with a wrong clock.
+    Manual,
+
+    /// Embedded in the file's metadata, such as EXIF DateTimeOriginal.
+    Metadata,
+
+    /// Derived from a file name such as IMG_20230514_103455.jpg.
+    FileName,
+
+    /// File system creation or modification time, which is often when the file was copied.
+    FileSystem,
+}
+
+/// Offset from UTC of a local date and time in an IANA time zone, such as "Europe/Lisbon".
+/// When clocks go back an hour the local time is ambiguous and the earlier offset is used.
+/// When clocks go forward an hour the local time doesn't exist, so the offset in effect
+/// at about that time is used.
+pub fn offset_at_local(time_zone: &str, local: NaiveDateTime) -> Option<FixedOffset> {
+    let tz: Tz = time_zone.parse().ok()?;
+    let offset = tz
+        .offset_from_local_datetime(&local)
+        .earliest()
+        .unwrap_or_else(|| tz.offset_from_utc_datetime(&local));
+    Some(offset.fix())
+}
+
+/// Offset from UTC at an instant in an IANA time zone, such as "Europe/Lisbon".
+pub fn offset_at_utc(time_zone: &str, utc: DateTime<Utc>) -> Option<FixedOffset> {
+    let tz: Tz = time_zone.parse().ok()?;
+    Some(tz.offset_from_utc_datetime(&utc.naive_utc()).fix())
+}
+
+/// A shift in time to correct a camera with a wrong clock, such as "+1 year -3 hours".
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TimeShift {
+    /// Whole months and years, which vary in length.
+    pub months: i32,
+
+    /// Weeks, days, hours, minutes and seconds.
+    pub delta: TimeDelta,
+}
+
+impl TimeShift {
+    pub fn is_zero(&self) -> bool {
+        self.months == 0 && self.delta.is_zero()
+    }
+
+    /// Shift a local date and time. Shifting by months keeps the day of the month,
+    /// unless the month is too short, in which case the last day of the month is used.
+    pub fn apply(&self, local: NaiveDateTime) -> Option<NaiveDateTime> {
+        let months = Months::new(self.months.unsigned_abs());
+        let local = if self.months < 0 {
+            local.checked_sub_months(months)?
+        } else {
+            local.checked_add_months(months)?
+        };
+        local.checked_add_signed(self.delta)
+    }
+}
+
+/// Parse a time shift such as "+1 year -3 hours", "-2d 4h" or "+30 min".
+/// Each amount may have its own sign and is positive if it has none.
+pub fn parse_time_shift(text: &str) -> Option<TimeShift> {
+    // Accept the Unicode minus sign and commas between amounts.
+    let text = text.replace('\u{2212}', "-").replace(',', " ");
+    let mut chars = text.chars().peekable();
+
+    let mut shift = TimeShift::default();
+    let mut is_empty = true;
+
+    loop {
+        while chars.next_if(|c| c.is_whitespace()).is_some() {}
+
+        let Some(c) = chars.next() else {
+            break;
+        };
+
+        let (sign, first_digit) = match c {
+            '+' => (1, None),
+            '-' => (-1, None),
+            c if c.is_ascii_digit() => (1, Some(c)),
+            _ => return None,
+        };
+
+        while chars.next_if(|c| c.is_whitespace()).is_some() {}
+
+        let mut digits = String::from_iter(first_digit);
+        while let Some(c) = chars.next_if(|c| c.is_ascii_digit()) {
+            digits.push(c);
+        }
+        let amount: i64 = digits.parse::<i64>().ok()? * sign;
+
+        while chars.next_if(|c| c.is_whitespace()).is_some() {}
+
+        let mut unit = String::new();
+        while let Some(c) = chars.next_if(|c| c.is_alphabetic()) {
+            unit.push(c.to_ascii_lowercase());
+        }
+
+        match unit.as_str() {
+            "y" | "yr" | "yrs" | "year" | "years" => {
+                let months = i32::try_from(amount.checked_mul(12)?).ok()?;
+                shift.months = shift.months.checked_add(months)?;
+            }
+            "mo" | "month" | "months" => {
+                let months = i32::try_from(amount).ok()?;
+                shift.months = shift.months.checked_add(months)?;
+            }
+            _ => {
+                let delta = match unit.as_str() {
+                    "w" | "wk" | "wks" | "week" | "weeks" => TimeDelta::try_weeks(amount)?,
+                    "d" | "day" | "days" => TimeDelta::try_days(amount)?,
+                    "h" | "hr" | "hrs" | "hour" | "hours" => TimeDelta::try_hours(amount)?,
+                    "m" | "min" | "mins" | "minute" | "minutes" => TimeDelta::try_minutes(amount)?,
+                    "s" | "sec" | "secs" | "second" | "seconds" => TimeDelta::try_seconds(amount)?,
+                    _ => return None,
+                };
+                shift.delta = shift.delta.checked_add(&delta)?;
+            }
+        }
+
+        is_empty = false;
+    }
+
+    if is_empty {
+        None
+    } else {
+        Some(shift)
+    }
+}
+
+/// Local date and time in a file name, for pictures and videos without dates in
+/// their metadata. Understands names such as:
+///  * IMG_20230514_103455.jpg and PXL_20230514_103455123.jpg from cameras.
+///  * IMG-20230514-WA0003.jpg and VID-20230514-WA0003.mp4 from WhatsApp.
+///  * Screenshot_2023-05-14-10-34-55.png and signal-2023-05-14-10-34-55-123.jpg.
+///
+/// Names with only a date are taken to be at noon, so the date stays the same
+/// in nearby time zones.
+pub fn parse_file_name_date(file_name: &str) -> Option<NaiveDateTime> {
+    let stem = file_name
+        .split_once('.')
+        .map_or(file_name, |(stem, _)| stem);
+
+    let runs: Vec<&str> = stem
+        .split(|c: char| !c.is_ascii_digit())
+        .filter(|run| !run.is_empty())
+        .collect();
+
+    (0..runs.len()).find_map(|index| date_time_from_digits(&runs[index..]))
+}
+
+/// Date and time from digits split by separators, starting at the first run of digits.
+fn date_time_from_digits(runs: &[&str]) -> Option<NaiveDateTime> {
+    let noon = NaiveTime::from_hms_opt(12, 0, 0)?;
+
+    let (date, rest) = match runs {
+        // 20230514103455
+        [run, ..] if run.len() == 14 => {
+            let date = date_from_digits(&run[0..4], &run[4..6], &run[6..8])?;
+            let time = time_from_digits(&run[8..10], &run[10..12], &run[12..14])?;
+            return Some(date.and_time(time));
+        }
+        // 20230514
+        [run, rest @ ..] if run.len() == 8 => {
+            (date_from_digits(&run[0..4], &run[4..6], &run[6..8])?, rest)
+        }
+        // 2023-05-14
+        [year, month, day, rest @ ..] if year.len() == 4 && month.len() == 2 && day.len() == 2 => {
+            (date_from_digits(year, month, day)?, rest)
+        }
+        _ => return None,
+    };
+
+    let time = match rest {
+        // 103455 or 103455123 with milliseconds
+        [run, ..] if run.len() == 6 || run.len() == 9 => {
+            time_from_digits(&run[0..2], &run[2..4], &run[4..6])
+        }
+        // 10-34-55
+        [hour, minute, second, ..] if hour.len() == 2 && minute.len() == 2 && second.len() == 2 => {
+            time_from_digits(hour, minute, second)
+        }
+        _ => None,
+    };
+
+    Some(date.and_time(time.unwrap_or(noon)))
+}
+
+fn date_from_digits(year: &str, month: &str, day: &str) -> Option<NaiveDate> {
+    let year: i32 = year.parse().ok()?;
+
+    // Ignore numbers that are unlikely to be dates.
+    if !(1970..=2100).contains(&year) {
+        return None;
+    }
+
+    NaiveDate::from_ymd_opt(year, month.parse().ok()?, day.parse().ok()?)
+}
+
+fn time_from_digits(hour: &str, minute: &str, second: &str) -> Option<NaiveTime> {
+    NaiveTime::from_hms_opt(hour.parse().ok()?, minute.parse().ok()?, second.parse().ok()?)
+}
+
+/// Parse a local date and time such as "2023-05-14 10:34:55". Seconds are optional.
+pub fn parse_local_date_time(text: &str) -> Option<NaiveDateTime> {
+    let text = text.trim();
+    ["%Y-%m-%d %H:%M:%S", "%Y-%m-%d %H:%M", "%Y-%m-%dT%H:%M:%S", "%Y-%m-%dT%H:%M"]
+        .iter()
+        .find_map(|format| NaiveDateTime::parse_from_str(text, format).ok())
+}
+
 pub fn format_hhmmss(delta: &TimeDelta) -> String {
     let total_seconds = delta.num_seconds();
     let seconds = total_seconds % 60;
@@ -56,4 +292,90 @@ mod tests {
         let one_hour = TimeDelta::try_seconds(3600).unwrap();
         assert_eq!("1:00:00", &format_hhmmss(&one_hour));
     }
+
+    #[test]
+    fn test_offset_at_local() {
+        let local = |s: &str| NaiveDateTime::parse_from_str(s, "%Y-%m-%d %H:%M:%S").unwrap();
+        let hours = |h: i32| FixedOffset::east_opt(h * 3600);
+
+        // Summer and winter time
+        assert_eq!(hours(1), offset_at_local("Europe/Lisbon", local("2023-05-14 10:34:55")));
+        assert_eq!(hours(0), offset_at_local("Europe/Lisbon", local("2023-01-14 10:34:55")));
+
+        // Clocks go back at 02:00 so 01:30 happens twice
+        assert_eq!(hours(1), offset_at_local("Europe/Lisbon", local("2023-10-29 01:30:00")));
+
+        // Clocks go forward at 01:00 so 01:30 never happens
+        assert!(offset_at_local("Europe/Lisbon", local("2023-03-26 01:30:00")).is_some());
+
+        assert_eq!(hours(-7), offset_at_local("America/Los_Angeles", local("2023-05-14 10:34:55")));
+        assert_eq!(None, offset_at_local("Not/A_Zone", local("2023-05-14 10:34:55")));
+    }
+
+    #[test]
+    fn test_offset_at_utc() {
+        let utc = DateTime::parse_from_rfc3339("2023-05-14T10:34:55Z").unwrap().to_utc();
+        assert_eq!(FixedOffset::east_opt(9 * 3600), offset_at_utc("Asia/Tokyo", utc));
+    }
+
+    #[test]
+    fn test_parse_time_shift() {
+        let shift = |months: i32, secs: i64| {
+            Some(TimeShift {
+                months,
+                delta: TimeDelta::try_seconds(secs).unwrap(),
+            })
+        };
+
+        assert_eq!(shift(12, -3 * 3600), parse_time_shift("+1 year -3 hours"));
+        assert_eq!(shift(12, -3 * 3600), parse_time_shift("+1 year \u{2212}3 hours"));
+        assert_eq!(shift(0, -2 * 86400 + 4 * 3600), parse_time_shift("-2d 4h"));
+        assert_eq!(shift(-1, 30 * 60 + 5), parse_time_shift("- 1 month, 30 min 5s"));
+        assert_eq!(shift(0, 7 * 86400), parse_time_shift("1W"));
+
+        assert_eq!(None, parse_time_shift(""));
+        assert_eq!(None, parse_time_shift("+1"));
+        assert_eq!(None, parse_time_shift("1 fortnight"));
+        assert_eq!(None, parse_time_shift("one year"));
+    }
+
+    #[test]
+    fn test_time_shift_apply() {
+        let local = |s: &str| NaiveDateTime::parse_from_str(s, "%Y-%m-%d %H:%M:%S").unwrap();
+
+        let shift = parse_time_shift("+23 years -3 hours").unwrap();
+        assert_eq!(Some(local("2022-12-31 21:00:00")), shift.apply(local("2000-01-01 00:00:00")));
+
+        // No 31st of February
+        let shift = parse_time_shift("-1 month").unwrap();
+        assert_eq!(Some(local("2024-02-29 12:00:00")), shift.apply(local("2024-03-31 12:00:00")));
+    }
+
+    #[test]
+    fn test_parse_local_date_time() {
+        let local = |s: &str| NaiveDateTime::parse_from_str(s, "%Y-%m-%d %H:%M:%S").unwrap();
+
+        assert_eq!(Some(local("2023-05-14 10:34:55")), parse_local_date_time(" 2023-05-14 10:34:55 "));
+        assert_eq!(Some(local("2023-05-14 10:34:00")), parse_local_date_time("2023-05-14 10:34"));
+        assert_eq!(None, parse_local_date_time("2023-05-14"));
+        assert_eq!(None, parse_local_date_time("2023-02-30 10:34:55"));
+    }
+
+    #[test]
+    fn test_parse_file_name_date() {
+        let local = |s: &str| Some(NaiveDateTime::parse_from_str(s, "%Y-%m-%d %H:%M:%S").unwrap());
+
+        assert_eq!(local("2023-05-14 10:34:55"), parse_file_name_date("IMG_20230514_103455.jpg"));
+        assert_eq!(local("2023-05-14 10:34:55"), parse_file_name_date("PXL_20230514_103455123.MP.jpg"));
+        assert_eq!(local("2023-05-14 12:00:00"), parse_file_name_date("VID-20230514-WA0003.mp4"));
+        assert_eq!(local("2023-05-14 10:34:55"), parse_file_name_date("Screenshot_2023-05-14-10-34-55.png"));
+        assert_eq!(local("2023-05-14 10:34:55"), parse_file_name_date("Screenshot from 2023-05-14 10-34-55.png"));
+        assert_eq!(local("2023-05-14 10:34:55"), parse_file_name_date("signal-2023-05-14-10-34-55-123.jpg"));
+        assert_eq!(local("2023-05-14 10:34:55"), parse_file_name_date("20230514103455.jpg"));
+
+        assert_eq!(None, parse_file_name_date("DSC_0001.JPG"));
+        assert_eq!(None, parse_file_name_date("IMG_1234.HEIC"));
+        assert_eq!(None, parse_file_name_date("scan_12345678.png"));
+        assert_eq!(None, parse_file_name_date("IMG_20231340_103455.jpg"));
+    }
 }
@@ -0,0 +1,477 @@
+// SPDX-FileCopyrightText: © 2024 David Bliss
+//
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//! Move pictures and videos to the trash, and restore them from it.
+
+use crate::photo::PictureId;
+use crate::tags;
+use crate::video::VideoId;
+use crate::visual::Visual;
+
+use anyhow::*;
+use gio::prelude::*;
+use rusqlite;
+use rusqlite::params;
+use rusqlite::Transaction;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::result::Result::Ok;
+use std::sync::{Arc, Mutex};
+use tracing::{error, info, warn};
+
+/// Attributes of files in the trash for finding where they came from.
+const TRASH_ATTRIBUTES: &str = "standard::name,trash::orig-path,trash::deletion-date";
+
+/// Files of items moved to the trash, for restoring the items or removing them for good.
+#[derive(Debug, Clone, Default)]
+pub struct Trashed {
+    pub pictures: Vec<(PictureId, PathBuf)>,
+    pub videos: Vec<(VideoId, PathBuf)>,
+}
+
+impl Trashed {
+    /// Number of files moved to the trash.
+    pub fn len(&self) -> usize {
+        self.pictures.len() + self.videos.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+/// Moves the files of visual items to the trash, and restores them.
+/// Items in the trash are hidden rather than deleted, so restoring them keeps
+/// everything known about them. They are deleted when they can no longer be restored.
+/// Backed by the GIO trash and a Sqlite database.
+#[derive(Debug, Clone)]
+pub struct Trash {
+    /// Base path for thumbnails, transcoded videos, and extracted motion photo videos.
+    cache_dir_base_path: PathBuf,
+
+    /// Base path for face thumbnails.
+    data_dir_base_path: PathBuf,
+
+    /// Connection to backing Sqlite database.
+    con: Arc<Mutex<rusqlite::Connection>>,
+}
+
+impl Trash {
+    pub fn open(
+        cache_dir_base_path: &Path,
+        data_dir_base_path: &Path,
+        con: Arc<Mutex<rusqlite::Connection>>,
+    ) -> Result<Trash> {
+        let trash = Trash {
+            cache_dir_base_path: PathBuf::from(cache_dir_base_path),
+            data_dir_base_path: PathBuf::from(data_dir_base_path),
+            con,
+        };
+        Ok(trash)
+    }
+
+    /// Move the files of visual items to the trash, including the video of a live photo.
+    /// Items are hidden until they are restored or purged.
+    pub fn trash(&mut self, visuals: &[Arc<Visual>]) -> Result<Trashed> {
+        let mut trashed = Trashed::default();
+
+        for visual in visuals {
+            if let (Some(picture_id), Some(path)) = (visual.picture_id, &visual.picture_path) {
+                if Self::trash_file(path) {
+                    trashed.pictures.push((picture_id, path.clone()));
+                }
+            }
+
+            if let (Some(video_id), Some(path)) = (visual.video_id, &visual.video_path) {
+                if Self::trash_file(path) {
+                    trashed.videos.push((video_id, path.clone()));
+                }
+            }
+        }
+
+        let picture_ids: Vec<PictureId> = trashed.pictures.iter().map(|x| x.0).collect();
+        let video_ids: Vec<VideoId> = trashed.videos.iter().map(|x| x.0).collect();
+        self.mark(&picture_ids, &video_ids, true)?;
+
+        Ok(trashed)
+    }
+
+    /// Move files back from the trash to where they were, and show their items again.
+    /// Returns the number of files restored.
+    pub fn restore(&mut self, trashed: &Trashed) -> Result<usize> {
+        let trash = gio::File::for_uri("trash:///");
+
+        let mut items = Vec::new();
+        for info in trash.enumerate_children(
+            TRASH_ATTRIBUTES,
+            gio::FileQueryInfoFlags::NONE,
+            None::<&gio::Cancellable>,
+        )? {
+            let info = info?;
+            let Some(orig_path) = info.attribute_byte_string("trash::orig-path") else {
+                continue;
+            };
+            let deleted_at = info
+                .attribute_string("trash::deletion-date")
+                .map(|x| x.to_string())
+                .unwrap_or_default();
+            items.push((PathBuf::from(orig_path.as_str()), deleted_at, info.name()));
+        }
+
+        // A file with the same path might have been trashed before, so prefer the
+        // most recently trashed file.
+        items.sort_by(|a, b| b.1.cmp(&a.1));
+
+        let restore_file = |path: &Path| {
+            let Some((_, _, name)) = items.iter().find(|(orig_path, _, _)| orig_path == path)
+            else {
+                warn!("Not found in trash: {:?}", path);
+                return false;
+            };
+
+            let result = trash.child(name).move_(
+                &gio::File::for_path(path),
+                gio::FileCopyFlags::NONE,
+                None::<&gio::Cancellable>,
+                None,
+            );
+
+            match result {
+                Ok(_) => {
+                    info!("Restored from trash: {:?}", path);
+                    true
+                }
+                Err(e) => {
+                    error!("Failed restoring {:?} from trash: {}", path, e);
+                    false
+                }
+            }
+        };
+
+        let picture_ids: Vec<PictureId> = trashed
+            .pictures
+            .iter()
+            .filter(|(_, path)| restore_file(path))
+            .map(|(picture_id, _)| *picture_id)
+            .collect();
+
+        let video_ids: Vec<VideoId> = trashed
+            .videos
+            .iter()
+            .filter(|(_, path)| restore_file(path))
+            .map(|(video_id, _)| *video_id)
+            .collect();
+
+        self.mark(&picture_ids, &video_ids, false)?;
+
+        Ok(picture_ids.len() + video_ids.len())
+    }
+
+    /// Remove items that can no longer be restored, such as when the chance to undo
+    /// moving them to the trash has passed.
+    pub fn purge(&mut self, trashed: &Trashed) -> Result<()> {
+        let picture_ids: Vec<PictureId> = trashed.pictures.iter().map(|x| x.0).collect();
+        let video_ids: Vec<VideoId> = trashed.videos.iter().map(|x| x.0).collect();
+        self.remove(&picture_ids, &video_ids)
+    }
+
+    /// Remove all items in the trash, such as those left when Fotema last quit.
+    pub fn purge_all(&mut self) -> Result<()> {
+        let (picture_ids, video_ids) = {
+            let con = self.con.lock().unwrap();
+
+            let mut stmt = con.prepare("SELECT picture_id FROM pictures WHERE is_trashed")?;
+            let picture_ids: Vec<PictureId> = stmt
+                .query_map([], |row| row.get(0).map(PictureId::new))?
+                .flatten()
+                .collect();
+
+            let mut stmt = con.prepare("SELECT video_id FROM videos WHERE is_trashed")?;
+            let video_ids: Vec<VideoId> = stmt
+                .query_map([], |row| row.get(0).map(VideoId::new))?
+                .flatten()
+                .collect();
+
+            (picture_ids, video_ids)
+        };
+
+        if !picture_ids.is_empty() || !video_ids.is_empty() {
+            info!(
+                "Purging {} pictures and {} videos from trash",
+                picture_ids.len(),
+                video_ids.len()
+            );
+            self.remove(&picture_ids, &video_ids)?;
+        }
+
+        Ok(())
+    }
+
+    /// Hide or show items.
+    fn mark(
+        &mut self,
+        picture_ids: &[PictureId],
+        video_ids: &[VideoId],
+        is_trashed: bool,
+    ) -> Result<()> {
+        let mut con = self.con.lock().unwrap();
+        let tx = con.transaction()?;
+
+        {
+            let mut stmt =
+                tx.prepare_cached("UPDATE pictures SET is_trashed = ?1 WHERE picture_id = ?2")?;
+            for picture_id in picture_ids {
+                stmt.execute(params![is_trashed, picture_id.id()])?;
+            }
+
+            let mut stmt =
+                tx.prepare_cached("UPDATE videos SET is_trashed = ?1 WHERE video_id = ?2")?;
+            for video_id in video_ids {
+                stmt.execute(params![is_trashed, video_id.id()])?;
+            }
+        }
+
+        tx.commit()?;
+
+        Ok(())
+    }
+
+    /// Move a file to the trash, returning whether it was moved.
+    fn trash_file(path: &Path) -> bool {
+        match gio::File::for_path(path).trash(None::<&gio::Cancellable>) {
+            Ok(_) => {
+                info!("Moved to trash: {:?}", path);
+                true
+            }
+            Err(e) => {
+                error!("Failed moving {:?} to trash: {}", path, e);
+                false
+            }
+        }
+    }
+
+    /// Remove pictures and videos from the database, and their files from the cache.
+    fn remove(&mut self, picture_ids: &[PictureId], video_ids: &[VideoId]) -> Result<()> {
+        let mut con = self.con.lock().unwrap();
+        let tx = con.transaction()?;
+
+        let mut cache_files: Vec<String> = Vec::new();
+        let mut data_files: Vec<String> = Vec::new();
+
+        for picture_id in picture_ids {
+            cache_files.extend(Self::query_paths(
+                &tx,
+                "SELECT thumbnail_path FROM pictures WHERE picture_id = ?1
+                UNION ALL
+                SELECT video_path FROM motion_photos WHERE picture_id = ?1
+                UNION ALL
+                SELECT transcoded_path FROM motion_photos WHERE picture_id = ?1",
+                picture_id.id(),
+            )?);
+
+            data_files.extend(Self::query_face_paths(
+                &tx,
+                "pictures_faces.picture_id = ?1",
+                picture_id.id(),
+            )?);
+
+            tx.prepare_cached("DELETE FROM pictures WHERE picture_id = ?1")?
+                .execute([picture_id.id()])?;
+        }
+
+        for video_id in video_ids {
+            cache_files.extend(Self::query_paths(
+                &tx,
+                "SELECT thumbnail_path FROM videos WHERE video_id = ?1
+                UNION ALL
+                SELECT transcoded_path FROM videos WHERE video_id = ?1",
+                video_id.id(),
+            )?);
+
+            data_files.extend(Self::query_face_paths(
+                &tx,
+                "pictures_faces.video_id = ?1",
+                video_id.id(),
+            )?);
+
+            tx.prepare_cached("DELETE FROM videos WHERE video_id = ?1")?
+                .execute([video_id.id()])?;
+        }
+
+        // Tags left without items would otherwise appear empty in the tag browser.
+        tags::Repository::prune(&tx)?;
+
+        tx.commit()?;
+
+        let cache_files = cache_files.iter().map(|x| self.cache_dir_base_path.join(x));
+        let data_files = data_files.iter().map(|x| self.data_dir_base_path.join(x));
+
+        for path in cache_files.chain(data_files) {
+            if let Err(e) = fs::remove_file(&path) {
+                // Not every file is created, such as thumbnails not yet generated.
+                if e.kind() != std::io::ErrorKind::NotFound {
+                    error!("Failed removing {:?}: {}", path, e);
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Paths selected by a query for a picture or video ID. Null paths are skipped.
+    fn query_paths(tx: &Transaction, sql: &str, id: i64) -> Result<Vec<String>> {
+        let mut stmt = tx.prepare_cached(sql)?;
+        let paths = stmt
+            .query_map([id], |row| row.get::<_, Option<String>>(0))?
+            .flatten()
+            .flatten()
+            .collect();
+        Ok(paths)
+    }
+
+    /// Thumbnail and bounds paths of faces matching a condition.
+    /// A face used as the thumbnail of a person is kept, as the person still shows it.
+    fn query_face_paths(tx: &Transaction, condition: &str, id: i64) -> Result<Vec<String>> {
+        let sql = format!(
+            "SELECT thumbnail_path FROM pictures_faces
+            WHERE {0}
+            AND thumbnail_path NOT IN (SELECT thumbnail_path FROM people)
+            UNION ALL
+            SELECT bounds_path FROM pictures_faces
+            WHERE {0}
+            AND thumbnail_path NOT IN (SELECT thumbnail_path FROM people)",
+            condition
+        );
+        Self::query_paths(tx, &sql, id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database;
+    use tempfile::TempDir;
+
+    /// Pictures 1 and 2 and video 5, each with a thumbnail. Picture 1 has two faces,
+    /// one of which is the thumbnail of a person, and a tag.
+    fn trash() -> (Trash, TempDir) {
+        let dir = tempfile::tempdir().unwrap();
+        let cache_dir = dir.path().join("cache");
+        let data_dir = dir.path().join("data");
+
+        let con = database::setup_in_memory().unwrap();
+        con.execute_batch(
+            "INSERT INTO pictures (picture_id, picture_path_b64, picture_path_lossy, link_path_b64, link_path_lossy, thumbnail_path)
+            VALUES
+                (1, 'a', 'a', 'a', 'a', '1.png'),
+                (2, 'b', 'b', 'b', 'b', '2.png');
+
+            INSERT INTO videos (video_id, video_path_b64, video_path_lossy, link_path_b64, link_path_lossy, thumbnail_path)
+            VALUES (5, 'c', 'c', 'c', 'c', '5.png');
+
+            INSERT INTO people (person_id, thumbnail_path, name, recognized_at)
+            VALUES (1, 'face_1.png', 'Alice', 0);
+
+            INSERT INTO pictures_faces (
+                face_id, model_name, picture_id, is_confirmed, detected_at, person_id,
+                thumbnail_path, bounds_path, bounds_x, bounds_y, bounds_width, bounds_height,
+                right_eye_x, right_eye_y, left_eye_x, left_eye_y, nose_x, nose_y,
+                right_mouth_corner_x, right_mouth_corner_y, left_mouth_corner_x, left_mouth_corner_y,
+                confidence, is_ignored
+            )
+            VALUES
+                (1, 'test', 1, 1, 0, 1, 'face_1.png', 'bounds_1.png', 0, 0, 1, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1, 0),
+                (2, 'test', 1, 0, 0, NULL, 'face_2.png', 'bounds_2.png', 0, 0, 1, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1, 0);
+
+            INSERT INTO tags (tag_id, name, path) VALUES (1, 'Holiday', 'Holiday');
+            INSERT INTO tag_items (tag_id, picture_id) VALUES (1, 1);",
+        )
+        .unwrap();
+
+        fs::create_dir_all(&cache_dir).unwrap();
+        fs::create_dir_all(&data_dir).unwrap();
+        for name in ["1.png", "2.png", "5.png"] {
+            fs::write(cache_dir.join(name), []).unwrap();
+        }
+        for name in ["face_1.png", "bounds_1.png", "face_2.png", "bounds_2.png"] {
+            fs::write(data_dir.join(name), []).unwrap();
+        }
+
+        let trash = Trash::open(&cache_dir, &data_dir, Arc::new(Mutex::new(con))).unwrap();
+        (trash, dir)
+    }
+
+    fn count(trash: &Trash, sql: &str) -> i64 {
+        let con = trash.con.lock().unwrap();
+        con.query_row(sql, [], |row| row.get(0)).unwrap()
+    }
+
+    #[test]
+    fn test_mark() {
+        let (mut trash, _dir) = trash();
+
+        trash
+            .mark(&[PictureId::new(1)], &[VideoId::new(5)], true)
+            .unwrap();
+        assert_eq!(1, count(&trash, "SELECT COUNT(*) FROM visual"));
+        assert_eq!(
+            1,
+            count(&trash, "SELECT COUNT(*) FROM pictures WHERE is_trashed")
+        );
+        assert_eq!(
+            1,
+            count(&trash, "SELECT COUNT(*) FROM videos WHERE is_trashed")
+        );
+
+        trash
+            .mark(&[PictureId::new(1)], &[VideoId::new(5)], false)
+            .unwrap();
+        assert_eq!(3, count(&trash, "SELECT COUNT(*) FROM visual"));
+    }
+
+    #[test]
+    fn test_remove_keeps_person_thumbnail() {
+        let (mut trash, _dir) = trash();
+        let cache_dir = trash.cache_dir_base_path.clone();
+        let data_dir = trash.data_dir_base_path.clone();
+
+        trash.remove(&[PictureId::new(1)], &[]).unwrap();
+
+        assert_eq!(
+            0,
+            count(&trash, "SELECT COUNT(*) FROM pictures WHERE picture_id = 1")
+        );
+        assert_eq!(0, count(&trash, "SELECT COUNT(*) FROM pictures_faces"));
+        assert_eq!(0, count(&trash, "SELECT COUNT(*) FROM tags"));
+        assert_eq!(1, count(&trash, "SELECT COUNT(*) FROM people"));
+
+        assert!(!cache_dir.join("1.png").exists());
+        assert!(cache_dir.join("2.png").exists());
+        assert!(data_dir.join("face_1.png").exists());
+        assert!(data_dir.join("bounds_1.png").exists());
+        assert!(!data_dir.join("face_2.png").exists());
+        assert!(!data_dir.join("bounds_2.png").exists());
+    }
+
+    #[test]
+    fn test_purge_all() {
+        let (mut trash, _dir) = trash();
+        let cache_dir = trash.cache_dir_base_path.clone();
+
+        trash
+            .mark(&[PictureId::new(2)], &[VideoId::new(5)], true)
+            .unwrap();
+        trash.purge_all().unwrap();
+
+        assert_eq!(1, count(&trash, "SELECT COUNT(*) FROM pictures"));
+        assert_eq!(0, count(&trash, "SELECT COUNT(*) FROM videos"));
+        assert!(cache_dir.join("1.png").exists());
+        assert!(!cache_dir.join("2.png").exists());
+        assert!(!cache_dir.join("5.png").exists());
+
+        // Nothing left to purge
+        trash.purge_all().unwrap();
+        assert_eq!(1, count(&trash, "SELECT COUNT(*) FROM pictures"));
+    }
+}
@@ -0,0 +1,187 @@
+// SPDX-FileCopyrightText: © 2024 David Bliss
+//
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use anyhow::*;
+use chrono::TimeDelta;
+use ffmpeg_next as ffmpeg;
+use image::{DynamicImage, RgbImage};
+use std::path::Path;
+use std::result::Result::Ok;
+use tracing::debug;
+
+use ffmpeg::format::Pixel;
+use ffmpeg::software::scaling;
+use ffmpeg::util::frame::video::Video as VideoFrame;
+
+/// Decode a number of frames spread evenly over the duration of a video.
+/// Returns the timestamp of each frame along with the frame as an RGB image.
+///
+/// Seeking lands on the nearest key frame before each sample point, so
+/// frames might not be exactly evenly spaced and short videos might return
+/// the same frame more than once. Only the first frame is returned for a video
+/// of unknown duration.
+pub fn sample_frames(video_path: &Path, count: usize) -> Result<Vec<(TimeDelta, DynamicImage)>> {
+    ffmpeg::init()?;
+
+    let mut context = ffmpeg::format::input(video_path)?;
+
+    let (stream_index, time_base, duration_secs, parameters) = {
+        let stream = context
+            .streams()
+            .best(ffmpeg::media::Type::Video)
+            .ok_or_else(|| anyhow!("No video stream in {:?}", video_path))?;
+
+        let time_base = f64::from(stream.time_base());
+        let duration_secs = stream.duration() as f64 * time_base;
+        (stream.index(), time_base, duration_secs, stream.parameters())
+    };
+
+    let mut decoder = ffmpeg::codec::context::Context::from_parameters(parameters)?
+        .decoder()
+        .video()?;
+
+    let mut scaler = scaling::Context::get(
+        decoder.format(),
+        decoder.width(),
+        decoder.height(),
+        Pixel::RGB24,
+        decoder.width(),
+        decoder.height(),
+        scaling::Flags::BILINEAR,
+    )?;
+
+    let sample_times = sample_times(duration_secs, count);
+
+    let mut frames = Vec::with_capacity(sample_times.len());
+
+    for (i, target_secs) in sample_times.into_iter().enumerate() {
+        // Seek position is in AV_TIME_BASE units (microseconds) when seeking across all streams.
+        let target = (target_secs * 1_000_000.0) as i64;
+        if i > 0 {
+            if let Err(e) = context.seek(target, ..target) {
+                debug!("Failed seeking to {}s in {:?}: {}", target_secs, video_path, e);
+                break;
+            }
+            decoder.flush();
+        }
+
+        let mut decoded = VideoFrame::empty();
+        let mut found = false;
+
+        for (stream, packet) in context.packets() {
+            if stream.index() != stream_index {
+                continue;
+            }
+
+            decoder.send_packet(&packet)?;
+
+            if decoder.receive_frame(&mut decoded).is_ok() {
+                found = true;
+                break;
+            }
+        }
+
+        if !found {
+            // Drain any buffered frames at the end of the stream.
+            let _ = decoder.send_eof();
+            found = decoder.receive_frame(&mut decoded).is_ok();
+        }
+
+        if !found {
+            break;
+        }
+
+        let mut rgb = VideoFrame::empty();
+        scaler.run(&decoded, &mut rgb)?;
+
+        let timestamp = decoded
+            .timestamp()
+            .map(|ts| ts as f64 * time_base)
+            .unwrap_or(target_secs);
+
+        let timestamp = TimeDelta::try_milliseconds((timestamp * 1000.0) as i64).unwrap_or_default();
+
+        frames.push((timestamp, to_image(&rgb)?));
+    }
+
+    Ok(frames)
+}
+
+/// Offsets in seconds of frames spread evenly over a video, starting at the first frame.
+/// A video of unknown duration has only its first frame sampled.
+fn sample_times(duration_secs: f64, count: usize) -> Vec<f64> {
+    if duration_secs <= 0.0 || duration_secs.is_nan() {
+        return vec![0.0];
+    }
+
+    let count = count.max(1);
+    let step_secs = duration_secs / count as f64;
+    (0..count).map(|i| step_secs * i as f64).collect()
+}
+
+/// Copy an RGB24 frame into an image, removing any padding at the end of each row.
+fn to_image(frame: &VideoFrame) -> Result<DynamicImage> {
+    let buffer = remove_padding(
+        frame.data(0),
+        frame.width() as usize * 3,
+        frame.height() as usize,
+        frame.stride(0),
+    )?;
+
+    let image = RgbImage::from_raw(frame.width(), frame.height(), buffer)
+        .ok_or_else(|| anyhow!("Frame buffer too small"))?;
+
+    Ok(DynamicImage::ImageRgb8(image))
+}
+
+/// Copy rows of pixel data, each `stride` bytes apart, dropping the padding after
+/// the first `row_len` bytes of each row.
+fn remove_padding(data: &[u8], row_len: usize, rows: usize, stride: usize) -> Result<Vec<u8>> {
+    let mut buffer = Vec::with_capacity(row_len * rows);
+    for row in 0..rows {
+        let start = row * stride;
+        let row_data = data
+            .get(start..start + row_len)
+            .ok_or_else(|| anyhow!("Frame buffer too small"))?;
+        buffer.extend_from_slice(row_data);
+    }
+    Ok(buffer)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sample_times() {
+        assert_eq!(sample_times(10.0, 5), vec![0.0, 2.0, 4.0, 6.0, 8.0]);
+        assert_eq!(sample_times(10.0, 1), vec![0.0]);
+        assert_eq!(sample_times(10.0, 0), vec![0.0]);
+    }
+
+    #[test]
+    fn test_sample_times_unknown_duration() {
+        assert_eq!(sample_times(0.0, 5), vec![0.0]);
+        assert_eq!(sample_times(-1.0, 5), vec![0.0]);
+        assert_eq!(sample_times(f64::NAN, 5), vec![0.0]);
+    }
+
+    #[test]
+    fn test_remove_padding() {
+        // Two rows of two RGB pixels, with two bytes of padding after each row.
+        let data = [1, 2, 3, 4, 5, 6, 0, 0, 7, 8, 9, 10, 11, 12, 0, 0];
+        let buffer = remove_padding(&data, 6, 2, 8).unwrap();
+        assert_eq!(buffer, vec![1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12]);
+
+        // No padding
+        let buffer = remove_padding(&data[..6], 6, 1, 6).unwrap();
+        assert_eq!(buffer, vec![1, 2, 3, 4, 5, 6]);
+    }
+
+    #[test]
+    fn test_remove_padding_too_small() {
+        let data = [1, 2, 3, 4, 5, 6, 0, 0, 7, 8, 9];
+        assert!(remove_padding(&data, 6, 2, 8).is_err());
+    }
+}
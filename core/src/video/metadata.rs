@@ -4,9 +4,10 @@
 
 use super::Metadata;
 use anyhow::*;
-use chrono::{DateTime, TimeDelta};
+use chrono::{DateTime, FixedOffset, TimeDelta};
 
 use ffmpeg_next as ffmpeg;
+use h3o::LatLng;
 
 use std::path::Path;
 use std::result::Result::Ok;
@@ -17,8 +18,11 @@ use std::process::Command;
 /// a bug fix or feature addition that changes the metadata produced.
 /// Each photo will be saved with a metadata scan version which will allow for
 /// easy selection of videos when there metadata can be updated.
-
-pub const VERSION: u32 = 2;
+///
+/// History:
+/// 3. GPS coordinates.
+/// 4. Offset from UTC of creation time.
+pub const VERSION: u32 = 4;
 
 pub fn from_path(path: &Path) -> Result<Metadata> {
     let mut metadata = Metadata::default();
@@ -32,10 +36,29 @@ pub fn from_path(path: &Path) -> Result<Metadata> {
         dt.map(|y| y.to_utc())
     });
 
+    // The creation_time is always UTC, but Apple also records the local
+    // creation time with its offset from UTC.
+    let local_created_at = context_metadata
+        .get("com.apple.quicktime.creationdate")
+        .and_then(parse_quicktime_date);
+
+    metadata.created_at = metadata
+        .created_at
+        .or_else(|| local_created_at.map(|x| x.to_utc()));
+
+    metadata.created_at_offset = local_created_at.map(|x| *x.offset());
+
     metadata.content_id = context_metadata
         .get("com.apple.quicktime.content.identifier")
         .map(String::from);
 
+    // Apple writes the location to com.apple.quicktime.location.ISO6709 and Android
+    // writes it to location. FFmpeg exposes both as container metadata.
+    metadata.location = context_metadata
+        .get("com.apple.quicktime.location.ISO6709")
+        .or_else(|| context_metadata.get("location"))
+        .and_then(parse_iso6709);
+
     metadata.container_format = Some(String::from(context.format().description()));
 
     if let Some(stream) = context.streams().best(ffmpeg::media::Type::Video) {
@@ -96,10 +119,122 @@ pub fn from_path(path: &Path) -> Result<Metadata> {
     Ok(metadata)
 }
 
+/// Parse a QuickTime date, such as "2023-05-14T10:34:55+0100".
+fn parse_quicktime_date(value: &str) -> Option<DateTime<FixedOffset>> {
+    let value = value.trim();
+    DateTime::parse_from_rfc3339(value)
+        .or_else(|_| DateTime::parse_from_str(value, "%Y-%m-%dT%H:%M:%S%z"))
+        .ok()
+}
+
+/// Parse an ISO 6709 location string, such as "+37.3318-122.0312+050.000/".
+/// Latitude and longitude can be in decimal degrees, degrees and decimal minutes,
+/// or degrees, minutes, and decimal seconds. Altitude and CRS are ignored.
+fn parse_iso6709(value: &str) -> Option<LatLng> {
+    // Remove terminator and optional coordinate reference system suffix.
+    let value = value.trim().trim_end_matches('/');
+    let value = value.find("CRS").map_or(value, |i| &value[..i]);
+
+    // Split into signed components.
+    let mut components = Vec::new();
+    let mut start = 0;
+    for (i, c) in value.char_indices().skip(1) {
+        if c == '+' || c == '-' {
+            components.push(&value[start..i]);
+            start = i;
+        }
+    }
+    components.push(&value[start..]);
+
+    let latitude = parse_iso6709_coord(components.first()?, 2)?;
+    let longitude = parse_iso6709_coord(components.get(1)?, 3)?;
+
+    if latitude == 0.0 && longitude == 0.0 {
+        // Null island is almost certainly a bogus location.
+        return None;
+    }
+
+    LatLng::new(latitude, longitude).ok()
+}
+
+/// Parse a signed ISO 6709 coordinate, where `degree_digits` is the number of
+/// digits used for whole degrees (2 for latitude, 3 for longitude).
+fn parse_iso6709_coord(value: &str, degree_digits: usize) -> Option<f64> {
+    let (sign, value) = if let Some(rest) = value.strip_prefix('+') {
+        (1.0, rest)
+    } else if let Some(rest) = value.strip_prefix('-') {
+        (-1.0, rest)
+    } else {
+        return None;
+    };
+
+    // Number of digits before the decimal point determines the format.
+    let whole_digits = value.find('.').unwrap_or(value.len());
+    if !value[..whole_digits].chars().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+
+    let degrees = if whole_digits == degree_digits {
+        value.parse::<f64>().ok()?
+    } else if whole_digits == degree_digits + 2 {
+        let deg = value[..degree_digits].parse::<f64>().ok()?;
+        let min = value[degree_digits..].parse::<f64>().ok()?;
+        deg + min / 60.0
+    } else if whole_digits == degree_digits + 4 {
+        let deg = value[..degree_digits].parse::<f64>().ok()?;
+        let min = value[degree_digits..degree_digits + 2].parse::<f64>().ok()?;
+        let sec = value[degree_digits + 2..].parse::<f64>().ok()?;
+        deg + min / 60.0 + sec / 3600.0
+    } else {
+        return None;
+    };
+
+    let degrees = sign * degrees;
+    degrees.is_finite().then_some(degrees)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_parse_iso6709() {
+        let ll = parse_iso6709("+37.3318-122.0312+050.000/").unwrap();
+        assert!((ll.lat() - 37.3318).abs() < 0.00001);
+        assert!((ll.lng() - -122.0312).abs() < 0.00001);
+
+        let ll = parse_iso6709("-33.8688+151.2093/").unwrap();
+        assert!((ll.lat() - -33.8688).abs() < 0.00001);
+        assert!((ll.lng() - 151.2093).abs() < 0.00001);
+
+        // Degrees and decimal minutes
+        let ll = parse_iso6709("+4012.5-07530.0/").unwrap();
+        assert!((ll.lat() - 40.208333).abs() < 0.00001);
+        assert!((ll.lng() - -75.5).abs() < 0.00001);
+
+        // Degrees, minutes, and decimal seconds, with CRS
+        let ll = parse_iso6709("+401213.1-0753000.0CRSWGS_84/").unwrap();
+        assert!((ll.lat() - 40.203639).abs() < 0.00001);
+        assert!((ll.lng() - -75.5).abs() < 0.00001);
+
+        assert!(parse_iso6709("+00.0000+000.0000/").is_none());
+        assert!(parse_iso6709("").is_none());
+        assert!(parse_iso6709("garbage").is_none());
+        assert!(parse_iso6709("+37.3318/").is_none());
+    }
+
+    #[test]
+    fn test_parse_quicktime_date() {
+        let dt = parse_quicktime_date("2023-05-14T10:34:55+0100").unwrap();
+        assert_eq!("2023-05-14T09:34:55+00:00", dt.to_utc().to_rfc3339());
+        assert_eq!(3600, dt.offset().local_minus_utc());
+
+        let dt = parse_quicktime_date("2023-05-14T10:34:55-07:00").unwrap();
+        assert_eq!(-7 * 3600, dt.offset().local_minus_utc());
+
+        assert!(parse_quicktime_date("2023-05-14").is_none());
+    }
+
     #[test]
     fn test_ffmpeg_next() {
         ffmpeg::init().unwrap();
@@ -2,6 +2,7 @@
 //
 // SPDX-License-Identifier: GPL-3.0-or-later
 
+pub mod frames;
 pub mod metadata;
 pub mod model;
 pub mod repo;
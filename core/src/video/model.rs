@@ -2,12 +2,13 @@
 //
 // SPDX-License-Identifier: GPL-3.0-or-later
 
-use chrono::{DateTime, TimeDelta, Utc};
+use chrono::{DateTime, FixedOffset, NaiveDateTime, TimeDelta, Utc};
+use h3o::LatLng;
 use std::fmt::Display;
 use std::path::PathBuf;
 
 /// Database ID of video
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub struct VideoId(i64);
 
 impl VideoId {
@@ -62,6 +63,9 @@ pub struct ScannedFile {
 
     pub fs_modified_at: Option<DateTime<Utc>>,
 
+    /// Local date and time in the file name, such as IMG_20230514_103455.jpg.
+    pub file_name_created_at: Option<NaiveDateTime>,
+
     pub fs_file_size_bytes: u64,
 }
 
@@ -69,6 +73,9 @@ pub struct ScannedFile {
 pub struct Metadata {
     pub created_at: Option<DateTime<Utc>>,
 
+    /// Offset from UTC of the local time the video was taken, if recorded.
+    pub created_at_offset: Option<FixedOffset>,
+
     pub width: Option<u64>, // 64?
 
     pub height: Option<u64>,
@@ -86,4 +93,7 @@ pub struct Metadata {
     // Rotation of video in degrees.
     // Should be 90, 180, 270, or the negative of those.
     pub rotation: Option<i32>,
+
+    // GPS location
+    pub location: Option<LatLng>,
 }
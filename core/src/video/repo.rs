@@ -5,6 +5,7 @@
 use super::metadata;
 use super::Metadata;
 use crate::path_encoding;
+use crate::time::OffsetSource;
 use crate::video::model::{ScannedFile, Video, VideoId};
 use anyhow::*;
 use chrono::*;
@@ -130,10 +131,27 @@ impl Repository {
                     duration_millis = ?4,
                     video_codec = ?5,
                     content_id = ?6,
-                    rotation = ?7
+                    rotation = ?7,
+                    capture_offset_secs = ?8,
+                    capture_offset_source = ?9
                 WHERE video_id = ?1",
             )?;
 
+            let mut update_geo = tx.prepare(
+                "INSERT INTO videos_geo (
+                    video_id,
+                    latitude,
+                    longitude
+                ) VALUES (
+                    ?1, ?2, ?3
+                ) ON CONFLICT (video_id) DO UPDATE SET
+                    latitude = ?2,
+                    longitude = ?3,
+                    source = 'exif'
+                WHERE videos_geo.source != 'manual'
+                ",
+            )?;
+
             for (video_id, metadata) in vids {
                 stmt.execute(params![
                     video_id.id(),
@@ -143,7 +161,13 @@ impl Repository {
                     metadata.video_codec,
                     metadata.content_id,
                     metadata.rotation,
+                    metadata.created_at_offset.map(|x| x.local_minus_utc()),
+                    metadata.created_at_offset.map(|_| OffsetSource::Metadata.as_ref()),
                 ])?;
+
+                if let Some(location) = metadata.location {
+                    update_geo.execute(params![video_id.id(), location.lat(), location.lng(),])?;
+                }
             }
         }
 
@@ -164,12 +188,14 @@ impl Repository {
                         video_path_b64,
                         video_path_lossy,
                         link_path_b64,
-                        link_path_lossy
+                        link_path_lossy,
+                        file_name_local_ts
                     ) VALUES (
-                        ?1, ?2, ?3, ?4, ?5, ?6
+                        ?1, ?2, ?3, ?4, ?5, ?6, ?7
                     ) ON CONFLICT (video_path_b64) DO UPDATE SET
                         fs_created_ts = ?1,
-                        fs_modified_ts = ?2
+                        fs_modified_ts = ?2,
+                        file_name_local_ts = ?7
                     ",
             )?;
 
@@ -194,6 +220,7 @@ impl Repository {
                     video_path.to_string_lossy(),
                     link_path_b64,
                     link_path.to_string_lossy(),
+                    vid.file_name_created_at,
                 ])?;
             }
         }
@@ -3,6 +3,7 @@
 // SPDX-License-Identifier: GPL-3.0-or-later
 
 use crate::video::model::ScannedFile;
+use crate::time;
 use anyhow::*;
 use chrono::prelude::*;
 use std::fs;
@@ -83,10 +84,16 @@ impl Scanner {
 
         let fs_file_size_bytes = metadata.len();
 
+        let file_name_created_at = path
+            .file_name()
+            .and_then(|x| x.to_str())
+            .and_then(time::parse_file_name_date);
+
         let scanned = ScannedFile {
             path: PathBuf::from(path),
             fs_created_at,
             fs_modified_at,
+            file_name_created_at,
             fs_file_size_bytes,
         };
 
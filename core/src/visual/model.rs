@@ -6,6 +6,7 @@ use std::fmt::Display;
 use std::path::PathBuf;
 
 use crate::photo::model::Orientation;
+use crate::time::TimestampSource;
 use crate::{PictureId, VideoId, YearMonth};
 
 use chrono::*;
@@ -71,12 +72,30 @@ pub struct Visual {
     /// Best candidate for ordering visual items. With a final fallback of the current timestamp.
     pub ordering_ts: DateTime<Utc>,
 
+    /// Where ordering_ts came from.
+    pub ordering_ts_source: Option<TimestampSource>,
+
+    /// Offset from UTC of the local time the item was taken, if known.
+    pub capture_offset: Option<FixedOffset>,
+
     // Is this a selfie?
     pub is_selfie: Option<bool>,
 
     // Is this an iOS live photo?
     pub is_live_photo: bool,
 
+    /// Has the user marked this as a favourite?
+    pub is_favourite: bool,
+
+    /// Camera that took the picture, such as "Google Pixel 8".
+    pub camera: Option<String>,
+
+    /// Short title from metadata.
+    pub title: Option<String>,
+
+    /// Caption written by the user, or from metadata.
+    pub caption: Option<String>,
+
     // Does the video_code require the video is transcoded?
     pub is_transcode_required: Option<bool>,
 
@@ -112,12 +131,21 @@ impl Visual {
             .unwrap_or(PictureOrientation::North)
     }
 
+    /// Local time the item was taken, as shown on a clock where it was taken.
+    /// Falls back to the computer's time zone if the offset isn't known.
+    pub fn local_ts(&self) -> DateTime<FixedOffset> {
+        match self.capture_offset {
+            Some(offset) => self.ordering_ts.with_timezone(&offset),
+            None => self.ordering_ts.with_timezone(&Local).fixed_offset(),
+        }
+    }
+
     pub fn year(&self) -> u32 {
-        self.ordering_ts.date_naive().year_ce().1
+        self.local_ts().date_naive().year_ce().1
     }
 
     pub fn year_month(&self) -> YearMonth {
-        let date = self.ordering_ts.date_naive();
+        let date = self.local_ts().date_naive();
         let year = date.year();
         let month = date.month();
         let month = chrono::Month::try_from(u8::try_from(month).unwrap()).unwrap();
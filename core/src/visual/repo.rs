@@ -7,6 +7,7 @@ use crate::video::VideoId;
 use crate::visual::model::{PictureOrientation, Visual, VisualId};
 
 use crate::path_encoding;
+use crate::time::TimestampSource;
 use anyhow::*;
 use chrono::*;
 use h3o::LatLng;
@@ -15,6 +16,7 @@ use rusqlite::Row;
 use std::path;
 use std::path::PathBuf;
 use std::result::Result::Ok;
+use std::str::FromStr;
 use std::sync::{Arc, Mutex};
 
 /// Repository of picture metadata.
@@ -67,7 +69,13 @@ impl Repository {
                     motion_photo_video_path,
 
                     ordering_ts,
+                    ordering_ts_source,
+                    capture_offset_secs,
                     is_live_photo,
+                    is_favourite,
+                    camera,
+                    title,
+                    caption,
 
                     video_transcoded_path,
                     is_transcode_required,
@@ -85,6 +93,103 @@ impl Repository {
         Ok(visuals)
     }
 
+    /// Sets dates and times that take precedence over those from metadata or the
+    /// file system, to correct cameras with wrong clocks.
+    pub fn set_date_time_overrides(
+        &mut self,
+        pictures: Vec<(PictureId, DateTime<Utc>)>,
+        videos: Vec<(VideoId, DateTime<Utc>)>,
+    ) -> Result<()> {
+        let mut con = self.con.lock().unwrap();
+        let tx = con.transaction()?;
+
+        {
+            let mut update_picture =
+                tx.prepare_cached("UPDATE pictures SET override_ts = ?2 WHERE picture_id = ?1")?;
+
+            for (picture_id, ts) in pictures {
+                update_picture.execute(rusqlite::params![picture_id.id(), ts])?;
+            }
+
+            let mut update_video =
+                tx.prepare_cached("UPDATE videos SET override_ts = ?2 WHERE video_id = ?1")?;
+
+            for (video_id, ts) in videos {
+                update_video.execute(rusqlite::params![video_id.id(), ts])?;
+            }
+        }
+
+        tx.commit()?;
+
+        Ok(())
+    }
+
+    /// Removes dates and times set by the user, so they come from metadata or the
+    /// file system again.
+    pub fn remove_date_time_overrides(
+        &mut self,
+        pictures: Vec<PictureId>,
+        videos: Vec<VideoId>,
+    ) -> Result<()> {
+        let mut con = self.con.lock().unwrap();
+        let tx = con.transaction()?;
+
+        {
+            let mut update_picture =
+                tx.prepare_cached("UPDATE pictures SET override_ts = NULL WHERE picture_id = ?1")?;
+
+            for picture_id in pictures {
+                update_picture.execute([picture_id.id()])?;
+            }
+
+            let mut update_video =
+                tx.prepare_cached("UPDATE videos SET override_ts = NULL WHERE video_id = ?1")?;
+
+            for video_id in videos {
+                update_video.execute([video_id.id()])?;
+            }
+        }
+
+        tx.commit()?;
+
+        Ok(())
+    }
+
+    /// Sets the caption of a picture or video, replacing any caption from metadata.
+    /// Live photos have a picture and a video, so both are used.
+    /// An empty caption hides the caption from metadata.
+    pub fn set_caption(
+        &mut self,
+        picture_id: Option<PictureId>,
+        video_id: Option<VideoId>,
+        caption: &str,
+    ) -> Result<()> {
+        let mut con = self.con.lock().unwrap();
+        let tx = con.transaction()?;
+
+        {
+            let caption = caption.trim();
+
+            let mut update_picture =
+                tx.prepare_cached("UPDATE pictures SET user_caption = ?2 WHERE picture_id = ?1")?;
+
+            if let Some(picture_id) = picture_id {
+                update_picture.execute(rusqlite::params![picture_id.id(), caption])?;
+            }
+
+            let mut update_video =
+                tx.prepare_cached("UPDATE videos SET user_caption = ?2 WHERE video_id = ?1")?;
+
+            if let Some(video_id) = video_id {
+                update_video.execute(rusqlite::params![video_id.id(), caption])?;
+            }
+        }
+
+        tx.commit()?;
+
+        Ok(())
+    }
+
     fn to_visual(&self, row: &Row<'_>) -> rusqlite::Result<Visual> {
         let visual_id = row
             .get("visual_id")
@@ -148,10 +253,28 @@ impl Repository {
 
         let ordering_ts: DateTime<Utc> = row.get("ordering_ts").expect("Must have ordering_ts");
 
+        let ordering_ts_source: Option<TimestampSource> = row
+            .get("ordering_ts_source")
+            .ok()
+            .and_then(|x: String| TimestampSource::from_str(&x).ok());
+
+        let capture_offset: Option<FixedOffset> = row
+            .get("capture_offset_secs")
+            .ok()
+            .and_then(FixedOffset::east_opt);
+
         let is_live_photo: Option<bool> = row.get("is_live_photo").ok();
 
         let is_live_photo = is_live_photo.is_some_and(|x| x);
 
+        let is_favourite: bool = row.get("is_favourite").unwrap_or(false);
+
+        let camera: Option<String> = row.get("camera").ok();
+
+        let title: Option<String> = row.get("title").ok().flatten();
+
+        let caption: Option<String> = row.get("caption").ok().flatten();
+
         let video_transcoded_path: Option<PathBuf> = row
             .get("video_transcoded_path")
             .ok()
@@ -184,8 +307,14 @@ impl Repository {
             video_id,
             video_path,
             ordering_ts,
+            ordering_ts_source,
+            capture_offset,
             is_selfie,
             is_live_photo,
+            is_favourite,
+            camera,
+            title,
+            caption,
             video_transcoded_path,
             video_orientation,
             is_transcode_required,
@@ -27,15 +27,131 @@ selfies-album = Selfies
 # Title for album of iOS live photos and Android motion photos.
 animated-album = Animated
 
+# Title for album of photos and videos marked as favourites.
+favourites-album = Favourites
+
+# Title for page of albums created by the user.
+# Attributes:
+#   .create-button - Button to create a new album.
+#   .empty-title - Title of status page shown when there are no albums.
+#   .empty-description - Description of status page shown when there are no albums.
+#   .count - Number of photos and videos in an album.
+# Variables:
+#   $count - number of photos and videos
+user-albums-page = Albums
+  .create-button = New Album
+  .empty-title = No Albums
+  .empty-description = Create an album, or select photos and videos and add them to a new album.
+  .count = { $count ->
+      [one] 1 item
+     *[other] { $count } items
+  }
+
+# Title for page of albums with items chosen by rules.
+# Attributes:
+#   .create-button - Button to create a new smart album.
+#   .empty-title - Title of status page shown when there are no smart albums.
+#   .empty-description - Description of status page shown when there are no smart albums.
+#   .count - Number of photos and videos matching the rule of a smart album.
+# Variables:
+#   $count - number of photos and videos
+smart-albums-page = Smart Albums
+  .create-button = New Smart Album
+  .empty-title = No Smart Albums
+  .empty-description = Smart albums show photos and videos matching rules, such as a person or camera, and update as the library changes.
+  .count = { $count ->
+      [one] 1 item
+     *[other] { $count } items
+  }
+
+# Title for album showing keyword tags.
+# Attributes:
+#   .empty-title - Title of status page shown when there are no tags.
+#   .empty-description - Description of status page shown when there are no tags.
+#   .count - Number of photos and videos with a tag or any tag nested in it.
+#   .open-button - Tooltip for button to view the photos and videos of a tag that has nested tags.
+# Variables:
+#   $count - number of photos and videos
+tags-page = Tags
+  .empty-title = No Tags
+  .empty-description = Tags are imported from the keywords of photos and can be added in the properties of a photo or video. Use | to nest tags, such as Places|Europe|Paris.
+  .count = { $count ->
+      [one] 1 item
+     *[other] { $count } items
+  }
+  .open-button = View all items
+
 # Title for album showing all folders.
 folders-album = Folders
 
 # Title for album showing contents of one folder.
 folder-album = Folder
 
+# Title for album of events, such as parties and trips, found by grouping
+# photos and videos taken close together in time and place.
+events-album = Events
+
+# Labels for an event in the events album.
+# Attributes:
+#   .trip - Badge for an event far from where most photos and videos are taken.
+#   .date-range - Dates of an event lasting more than one day.
+# Variables:
+#   $start - first date of event.
+#   $end - last date of event.
+events-album-event =
+  .trip = Trip
+  .date-range = { $start } – { $end }
+
+# Title for album of photos and videos taken on today's month and day in previous years.
+# Attributes:
+#   .years-ago - Heading for memories from one year.
+#   .empty-title - Title when there are no memories for today.
+#   .empty-description - Description when there are no memories for today.
+# Variables:
+#   $years - how many years ago the memories are from.
+#   $year - year the memories are from.
+on-this-day-album = On This Day
+  .years-ago = { $years ->
+       [one] One year ago, { $year }
+      *[other] { $years } years ago, { $year }
+  }
+  .empty-title = No Memories Today
+  .empty-description = Photos and videos taken on this day in previous years will appear here.
+
+# Card at the top of the library for "On this day" memories.
+# Attributes:
+#   .subtitle - Summary of memories.
+#   .view - Button to view memories.
+#   .dismiss - Tooltip for button to hide card.
+# Variables:
+#   $count - count of photos and videos.
+#   $years - count of years memories are from.
+on-this-day-card = On This Day
+  .subtitle = { $count ->
+       [one] One memory
+      *[other] { $count } memories
+  } from { $years ->
+       [one] one year
+      *[other] { $years } years
+  }
+  .view = View
+  .dismiss = Hide until next time
+
 # Title for places page which shows photos overlayed onto a map.
 places-page = Places
 
+# List of countries and cities next to the map on the places page.
+# Attributes:
+#  .all-of-country - choice to view all photos and videos in a country.
+# Variables:
+#  $count - count of photos and videos.
+places-page-list =
+  .all-of-country = Everywhere
+  .count = { $count ->
+      [one] { $count } item
+     *[other] { $count } items
+  }
+
 # Title for people page which shows an album of faces.
 people-page = People
 
@@ -54,6 +170,23 @@ people-page-status-no-people =
   If you've told { -app-name } that a face is for a person you know, then { -app-name }
   will also look for new photos of that person.
 
+# Button on people page to find photos with several people.
+people-query-button = Find people
+  .tooltip = Find photos and videos with several people
+
+# Dialog for finding photos and videos of several people.
+people-query-dialog =
+  .heading = Find people
+  .body = Choose who should, or should not, be in the photos and videos.
+  .ignore = Ignore
+  .with = With
+  .or-with = Or with
+  .without = Without
+  .group-size = Group size
+  .group-size-subtitle = Minimum number of recognized people. Zero for any.
+  .cancel-button = Cancel
+  .find-button = Find
+
 ## Thumbnail decorations
 
 # Label on month album thumbnails.
@@ -92,11 +225,32 @@ about-translator-credits =
 # Tooltip for (i) button to show photo/video information sidebar
 viewer-info-tooltip = Show properties
 
+# Tooltip for button to set location of photo/video
+viewer-edit-location-tooltip = Set location
+
+# Tooltip for button to correct date and time of photo/video
+viewer-edit-date-time-tooltip = Set date and time
+
+# Tooltip for button to add photo/video to albums or remove it from them
+viewer-add-to-album-tooltip = Add to album
+
+# Tooltip for button to move photo/video to the trash
+viewer-move-to-trash-tooltip = Move to trash
+
+# Tooltips for button to add or remove photo/video from favourites
+# Attributes:
+#   .add - Tooltip when item is not a favourite.
+#   .remove - Tooltip when item is a favourite.
+viewer-favourite =
+  .add = Add to favourites
+  .remove = Remove from favourites
+
 viewer-faces-menu =
   .tooltip = Faces menu
   .restore-ignored = Restore all ignored faces
   .ignore-unknown = Ignore all unknown faces
   .scan = Scan for more faces
+  .add = Add a face
 
 # Go to next button when viewing photo or video.
 # Attributes:
@@ -165,6 +319,34 @@ infobar-folder = Folder
 # File name of photo or video
 infobar-file-name = File Name
 
+# Nearest city, region, and country to where photo or video was taken.
+infobar-place = Place
+
+# Latitude and longitude of where photo or video was taken.
+# Attributes:
+#   .track - Location matched from a GPS track.
+#   .inferred - Location inferred from photos taken shortly before or after.
+#   .revert - Tooltip for button to remove an inferred location.
+# Variables:
+#   $location - latitude and longitude
+infobar-location = Location
+  .track = { $location } (from GPS track)
+  .inferred = { $location } (inferred)
+  .manual = { $location } (set manually)
+  .revert = Remove Inferred Location
+
+# Local date and time photo or video was taken, in the time zone where it was taken.
+# Attributes:
+#   .manual - Date and time set by the user.
+#   .file-name - Date and time from a file name, such as IMG_20230514_103455.jpg.
+#   .file-system - Date and time the file was created, which is often when it was copied.
+# Variables:
+#   $date_time - date and time
+infobar-taken = Taken
+  .manual = { $date_time } (set manually)
+  .file-name = { $date_time } (from file name)
+  .file-system = { $date_time } (from file)
+
 # File creation timestamp from file system metadata.
 infobar-file-created = File Created
 
@@ -198,6 +380,34 @@ infobar-audio-codec = Audio Codec
 # Width and height of photo or video.
 infobar-dimensions = Dimensions
 
+# People recognized in photo or video.
+# Attributes:
+#  .age - age of person when photo or video was taken.
+# Variables:
+#  $age - age in whole years.
+infobar-people = People
+  .age = { $age ->
+      [0] Under one year old
+      [one] { $age } year old
+     *[other] { $age } years old
+  }
+
+# Keyword tags of photo or video.
+# Attributes:
+#  .description - Hint for how to nest tags.
+#  .add - Title of entry for adding a tag.
+#  .remove - Tooltip for button to remove a tag.
+infobar-tags = Tags
+  .description = Use | to nest tags, such as Places|Europe|Paris.
+  .add = Add tag
+  .remove = Remove tag
+
+# Title of photo from metadata.
+infobar-title = Title
+
+# Title of entry for the caption of a photo or video.
+infobar-caption = Caption
+
 ## Faces and People
 
 # Menu item to mark a face as the most import face for a person
@@ -235,6 +445,18 @@ prefs-views-section = Views
 prefs-views-selfies = Selfies
   .subtitle = Shows a separate view for selfies taken on iOS devices. Restart {-app-name} to apply.
 
+# Card for "On this day" memories enabled or disabled.
+# Attributes:
+#   .subtitle - Description of preference.
+prefs-views-on-this-day = On This Day
+  .subtitle = Shows photos and videos taken on this day in previous years at the top of the library.
+
+# Add photos rated five stars in other apps to favourites.
+# Attributes:
+#   .subtitle - Description of preference.
+prefs-views-ratings = Five Star Favourites
+  .subtitle = Adds photos rated five stars in other apps to favourites when they are first scanned.
+
 # Set face detection mode. Off, lightweight mobile model, or heavyweight
 # desktop model
 prefs-views-faces = Face Detection
@@ -243,6 +465,267 @@ prefs-views-faces = Face Detection
   .enable-mobile = Mobile
   .enable-desktop = Desktop
 
+# Title of section of preferences for maps
+prefs-map-section = Map
+  .description = Choose where map tiles come from
+
+# Local archive of map tiles for viewing maps offline.
+# Attributes:
+#   .subtitle - Shown when no archive is chosen.
+#   .choose - Tooltip for button to choose an archive file.
+#   .clear - Tooltip for button to stop using the archive.
+#   .filter - Name of file type filter in file chooser.
+prefs-map-offline = Offline Map
+  .subtitle = MBTiles or PMTiles archive of raster map tiles.
+  .choose = Choose Tile Archive
+  .clear = Stop Using Tile Archive
+  .filter = Map Tile Archives
+
+# Toggle to fill gaps in offline map with OpenStreetMap tiles.
+# Attributes:
+#   .subtitle - Description of toggle button action.
+prefs-map-online-fallback = Online Fallback
+  .subtitle = Show OpenStreetMap tiles where the offline map has none.
+
+## Geotag Dialog
+
+# Dialog for adding locations to photos without a location, by matching
+# the time a photo was taken against a track recorded by a GPS logger.
+# Attributes:
+#   .track - Title of row showing chosen track file.
+#   .no-track - Shown when no track file is chosen.
+#   .choose-track - Tooltip for button to choose a track file.
+#   .filter - Name of file type filter in file chooser.
+#   .clock-offset - Title of setting for difference between camera and GPS clocks.
+#   .clock-offset-subtitle - Description of clock offset setting.
+#   .max-gap - Title of setting for maximum time between photo and track point.
+#   .max-gap-subtitle - Description of maximum gap setting.
+#   .preview - Title of list of photos that will be geotagged.
+#   .preview-count - Description of list of photos that will be geotagged.
+#   .apply - Button to save locations.
+# Variables:
+#   $count - number of photos matching track
+#   $total - number of photos without a location
+geotag-dialog = Geotag from Track
+  .track = GPS Track
+  .no-track = Choose a GPX, KML, or GeoJSON file.
+  .choose-track = Choose Track
+  .filter = GPS Tracks
+  .clock-offset = Camera Clock Offset
+  .clock-offset-subtitle = Minutes the camera clock is ahead of the GPS clock, including any time zone difference.
+  .max-gap = Maximum Gap
+  .max-gap-subtitle = Minutes between a photo and the nearest track point.
+  .preview = Preview
+  .preview-count = { $count } of { $total } photos without a location match the track.
+  .apply = Geotag Photos
+
+## Infer Locations Dialog
+
+# Dialog proposing locations for photos without a location, based on
+# photos and videos taken shortly before or after.
+# Attributes:
+#   .max-gap - Title of setting for maximum time between photos.
+#   .max-gap-subtitle - Description of maximum gap setting.
+#   .proposals - Title of list of proposed locations.
+#   .proposals-count - Description of list of proposed locations.
+#   .proposal - Subtitle of a proposed location.
+#   .more-proposals - Row at the end of a long list of proposed locations, for proposals not shown.
+#   .inferred - Title of section for previously inferred locations.
+#   .inferred-count - Count of photos with inferred locations.
+#   .revert-all - Button to remove all inferred locations.
+#   .apply - Button to save accepted locations.
+# Variables:
+#   $count - number of photos
+#   $latitude - proposed latitude
+#   $longitude - proposed longitude
+#   $minutes - minutes between photo and nearest photo with a location
+infer-locations-dialog = Infer Missing Locations
+  .max-gap = Maximum Gap
+  .max-gap-subtitle = Minutes between a photo and the nearest photo or video with a location.
+  .proposals = Proposed Locations
+  .proposals-count = { $count } photos without a location were taken near photos with one.
+  .proposal = { $latitude }, { $longitude } · { $minutes } min apart
+  .more-proposals = And { $count } more, which will also be saved
+  .inferred = Inferred Locations
+  .inferred-count = { $count } photos have inferred locations
+  .revert-all = Revert All
+  .apply = Save Locations
+
+## Location Editor Dialog
+
+# Dialog for manually setting the location of photos and videos.
+# Attributes:
+#   .coordinates - Title of entry for typing or pasting coordinates.
+#   .map-help - Help text for choosing a location on the map.
+#   .apply - Button to save location.
+# Variables:
+#   $count - number of photos and videos
+location-editor-dialog = Set Location
+  .coordinates = Coordinates
+  .map-help = Click the map or paste coordinates, such as 38.7223, -9.1393.
+  .apply = { $count ->
+      [one] Set Location
+     *[other] Set Location of { $count } Items
+  }
+
+## Date and Time Editor Dialog
+
+# Dialog for correcting the date and time of photos and videos taken with a
+# camera with a wrong clock.
+# Attributes:
+#   .date-time - Title of entry for date and time of one photo or video.
+#   .date-time-help - Help text for date and time entry.
+#   .shift - Title of entry for moving dates and times by an amount.
+#   .shift-help - Help text for shift entry. Units must not be translated.
+#   .reset - Button to go back to dates and times from metadata.
+#   .apply - Button to save dates and times.
+# Variables:
+#   $count - number of photos and videos
+date-time-editor-dialog = Set Date and Time
+  .date-time = Date and time
+  .date-time-help = Date and time as shown on the clock where the item was taken, such as 2023-05-14 10:34:55.
+  .shift = Shift by
+  .shift-help = Move dates and times forwards or backwards, such as +1 year -3 hours. Units are years, months, weeks, days, hours, minutes and seconds.
+  .reset = Restore Original
+  .apply = { $count ->
+      [one] Set Date and Time
+     *[other] Shift { $count } Items
+  }
+
+## Privacy Zones Dialog
+
+# Dialog for adding and removing privacy zones, such as home or work, where the
+# locations of photos and videos must not be shown.
+# Attributes:
+#   .zones - Title of list of privacy zones.
+#   .zones-description - Description of what privacy zones do.
+#   .circle-summary - Subtitle of a circular zone.
+#   .cells-summary - Subtitle of a zone made of map cells.
+#   .remove - Tooltip for button to remove a zone.
+#   .new-zone - Title of section for adding a zone.
+#   .name - Title of entry for name of zone.
+#   .centre - Title of entry for coordinates of centre of zone.
+#   .shape - Title of choice of zone shape.
+#   .shape-circle - Circular zone shape.
+#   .shape-cells - Zone shape made of hexagonal map cells.
+#   .radius - Title of setting for radius of circular zone.
+#   .radius-subtitle - Description of radius setting.
+#   .resolution - Title of setting for size of map cells.
+#   .resolution-subtitle - Description of resolution setting.
+#   .rings - Title of setting for cells around centre cell.
+#   .rings-subtitle - Description of rings setting.
+#   .add - Button to add zone.
+# Variables:
+#   $radius - radius in metres
+#   $latitude - latitude of centre
+#   $longitude - longitude of centre
+#   $count - number of map cells
+#   $resolution - resolution of map cells
+privacy-zones-dialog = Privacy Zones
+  .zones = Zones
+  .zones-description = Photos and videos taken inside a privacy zone aren't shown on the map and their locations aren't shown in their properties.
+  .circle-summary = { $radius } m around { $latitude }, { $longitude }
+  .cells-summary = { $count } map cells at resolution { $resolution }
+  .remove = Remove zone
+  .new-zone = New Zone
+  .name = Name
+  .centre = Centre coordinates
+  .shape = Shape
+  .shape-circle = Circle
+  .shape-cells = Map cells
+  .radius = Radius
+  .radius-subtitle = Metres from the centre.
+  .resolution = Cell Resolution
+  .resolution-subtitle = Higher resolutions have smaller cells.
+  .rings = Rings
+  .rings-subtitle = Rings of cells around the centre cell.
+  .add = Add Zone
+
+## Album Chooser Dialog
+
+# Dialog for adding photos and videos to albums or taking them out of albums.
+# Attributes:
+#   .new-album - Title of entry for name of a new album to add items to.
+# Variables:
+#   $count - number of photos and videos
+album-chooser-dialog = { $count ->
+      [one] Add to Album
+     *[other] Add { $count } Items to Album
+  }
+  .new-album = New album
+
+## Album Selection
+
+# Button to show the photos and videos of an album, person, or year on a map.
+# Attributes:
+#   .tooltip - Tooltip for show on map button.
+show-on-map =
+  .tooltip = Show on Map
+
+# Entry in main header bar for searching titles, captions, file names, and folder names.
+# Keys such as "person:" and "year:" are part of the query language, so must not be translated.
+# Attributes:
+#   .placeholder - Placeholder text of search entry.
+#   .tooltip - Explanation of the query language.
+search-entry =
+  .placeholder = Search
+  .tooltip = Search for words, or filter with person:, place:, year:, type:photo, type:video, type:live, camera:, tag:, is:selfie, and is:favourite. Put - before a filter to exclude matching items, such as -selfie.
+
+# Title of album of search results.
+# Variables:
+#   $query - Text that was searched for.
+search-results = Results for “{ $query }”
+
+# Toggle button to select several photos and videos in an album.
+# Attributes:
+#   .tooltip - Tooltip for selection mode toggle button.
+selection-mode =
+  .tooltip = Select items
+
+# Action bar shown when selecting items in an album.
+# Attributes:
+#   .edit-location - Tooltip for button to set location of selected items.
+#   .add-to-album - Tooltip for button to add selected items to albums.
+#   .remove-from-album - Tooltip for button to take selected items out of the current album.
+#   .move-to-start - Tooltip for button to move selected items to the start of the current album.
+#   .move-to-end - Tooltip for button to move selected items to the end of the current album.
+#   .set-cover - Tooltip for button to use the selected item as the cover of the current album.
+#   .move-to-trash - Tooltip for button to move selected items to the trash.
+# Variables:
+#   $count - number of selected items
+album-selection = { $count ->
+      [0] No items selected
+      [one] 1 item selected
+     *[other] { $count } items selected
+  }
+  .edit-location = Set location
+  .edit-date-time = Set date and time
+  .add-to-album = Add to album
+  .remove-from-album = Remove from album
+  .move-to-start = Move to start
+  .move-to-end = Move to end
+  .set-cover = Use as cover
+  .move-to-trash = Move to trash
+
+# Toast shown after moving photos and videos to the trash.
+# A live photo is two files, a picture and a video.
+# Attributes:
+#   .undo - Button to restore the files from the trash.
+#   .failed - Toast shown when no files could be moved to the trash.
+#   .restore-failed - Toast shown when undoing, but some files could not be restored.
+# Variables:
+#   $count - number of files moved to the trash, or that could not be restored
+trash-toast = { $count ->
+      [one] 1 file moved to trash
+     *[other] { $count } files moved to trash
+  }
+  .undo = Undo
+  .failed = Could not move to trash
+  .restore-failed = { $count ->
+      [one] Could not restore 1 file from trash
+     *[other] Could not restore { $count } files from trash
+  }
+
 ## Progress bar for background tasks
 
 # Extracting details from photo EXIF data
@@ -266,6 +749,9 @@ progress-motion-photo = Processing motion photos.
 # Detect faces from photos
 progress-detect-faces-photos = Detecting faces in photos.
 
+# Detect faces from videos
+progress-detect-faces-videos = Detecting faces in videos.
+
 # Recognize faces in photos as known people
 progress-recognize-faces-photos = Recognizing people in photos.
 
@@ -306,9 +792,15 @@ banner-extract-motion-photos = Processing motion photos.
 # Detect and extract faces from photos
 banner-detect-faces-photos = Detecting faces in photos. This will take a while.
 
+# Detect and extract faces from videos
+banner-detect-faces-videos = Detecting faces in videos. This will take a while.
+
 # Recognize faces as people
 banner-recognize-faces-photos = Recognizing people in photos. This will take a while.
 
+# Look up names of places from locations of photos and videos
+banner-geocode = Finding place names.
+
 ## Primary menu
 
 # The "hamburger" menu on the main app navigation sidebar.
@@ -316,6 +808,15 @@ banner-recognize-faces-photos = Recognizing people in photos. This will take a w
 # Menu item to show preferences dialog
 primary-menu-preferences = Preferences
 
+# Menu item to show dialog for adding locations to photos from a GPS track
+primary-menu-geotag = Geotag from Track…
+
+# Menu item to show dialog for inferring missing locations from nearby photos
+primary-menu-infer-locations = Infer Missing Locations…
+
+# Menu item to show privacy zones dialog
+primary-menu-privacy-zones = Privacy Zones…
+
 # Menu item to show "about" dialog
 primary-menu-about = About {-app-name}
 
@@ -327,6 +828,34 @@ person-menu-rename = Rename person
 # Menu item to delete a person
 person-menu-delete = Delete person
 
+# Menu item to merge another person into this person
+person-menu-merge = Merge with…
+
+# Menu item to set the birthday of a person
+person-menu-birthday = Set birthday…
+
+# Person birthday dialog
+# Variables:
+#  $name - name of person.
+person-birthday-dialog =
+  .heading = Birthday of { $name }
+  .body = Birthdays are used to show how old someone was in photos and videos.
+  .cancel-button = Cancel
+  .clear-button = Clear
+  .set-button = Set
+
+# Choices for browsing an album of a person by age.
+# Variables:
+#  $name - name of person.
+#  $age - age in whole years.
+person-album-age =
+  .all = All ages
+  .age = { $age ->
+      [0] { $name } under one year old
+      [one] { $name } at { $age } year old
+     *[other] { $name } at { $age } years old
+  }
+
 # Person delete dialog
 person-delete-dialog =
   .heading = Delete person?
@@ -340,3 +869,116 @@ person-rename-dialog =
   .placeholder = New name
   .cancel-button = Cancel
   .rename-button = Rename
+
+# Person merge dialog to choose the other person.
+# Variables:
+#   $name - name of person being merged into
+person-merge-dialog =
+  .heading = Merge with { $name }?
+  .body = Choose the person to merge. Their faces will be moved to this person.
+  .cancel-button = Cancel
+
+# Person merge dialog to choose the surviving name and thumbnail.
+# Variables:
+#   $this_name - name of person being merged into
+#   $other_name - name of person being merged and removed
+person-merge-with-dialog =
+  .heading = Merge people?
+  .body = All faces of { $other_name } will be moved to { $this_name }. No pictures or videos will be deleted.
+  .name-title = Name
+  .thumbnail-title = Thumbnail
+  .cancel-button = Cancel
+  .merge-button = Merge
+
+## Album menu
+
+# Menu item to rename an album created by the user
+user-album-menu-rename = Rename album
+
+# Menu item to delete an album created by the user
+user-album-menu-delete = Delete album
+
+# Album create dialog
+album-create-dialog =
+  .heading = New album
+  .placeholder = Album name
+  .cancel-button = Cancel
+  .create-button = Create
+
+# Album rename dialog
+album-rename-dialog =
+  .heading = Rename album?
+  .placeholder = New name
+  .cancel-button = Cancel
+  .rename-button = Rename
+
+# Album delete dialog
+# Variables:
+#  $name - name of album.
+album-delete-dialog =
+  .heading = Delete { $name }?
+  .body = No pictures or videos will be deleted.
+  .cancel-button = Cancel
+  .delete-button = Delete
+
+# Menu item to change the name and rules of a smart album
+smart-album-menu-edit = Edit rules
+
+# Menu item to delete a smart album
+smart-album-menu-delete = Delete smart album
+
+# Smart album delete dialog
+# Variables:
+#  $name - name of smart album.
+smart-album-delete-dialog =
+  .heading = Delete { $name }?
+  .body = No pictures or videos will be deleted.
+  .cancel-button = Cancel
+  .delete-button = Delete
+
+# Dialog for creating a smart album or changing its rules.
+# Attributes:
+#   .create-title - Title of dialog when creating a smart album.
+#   .edit-title - Title of dialog when changing a smart album.
+#   .name - Title of entry for name of smart album.
+#   .match - Title of choice between matching all or any criteria.
+#   .criteria - Title of list of criteria.
+#   .add-criterion - Tooltip for button to add a criterion.
+#   .remove-criterion - Tooltip for button to remove a criterion.
+#   .is - Criterion must match.
+#   .is-not - Criterion must not match.
+#   .criterion-* - Names of criteria that photos and videos can be matched by.
+#   .*-placeholder - Hints for the values of criteria.
+#   .place-resolution - Tooltip for size of area around a place, from 5 (large) to 12 (small).
+#   .save - Button to save smart album.
+smart-album-editor =
+  .create-title = New Smart Album
+  .edit-title = Edit Smart Album
+  .name = Name
+  .match = Match
+  .match-all = All criteria
+  .match-any = Any criterion
+  .criteria = Criteria
+  .add-criterion = Add criterion
+  .remove-criterion = Remove criterion
+  .is = is
+  .is-not = is not
+  .criterion-folder = Folder
+  .criterion-date-range = Date taken
+  .criterion-person = Person
+  .criterion-place = Place
+  .criterion-media-type = Media type
+  .criterion-camera = Camera
+  .criterion-tag = Tag
+  .criterion-selfie = Selfie
+  .criterion-motion = Live photo
+  .criterion-favourite = Favourite
+  .media-type-photo = Photo
+  .media-type-video = Video
+  .folder-placeholder = Full path of folder, such as /home/me/Pictures/Holidays
+  .date-range-placeholder = Dates, such as 2022-06-01..2022-08-31 or 2023
+  .place-placeholder = Coordinates, such as 38.7223, -9.1393
+  .camera-placeholder = Camera make or model, such as Pixel 8
+  .tag-placeholder = Tag, such as Places|Europe
+  .place-resolution = Size of area
+  .save = Save
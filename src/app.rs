@@ -12,7 +12,7 @@ use relm4::{
         gio, glib,
         prelude::{
             ApplicationExt, ButtonExt, GtkWindowExt, OrientableExt,
-            SettingsExt, WidgetExt,
+            SettingsExt, ToggleButtonExt, WidgetExt,
         },
     },
     main_application,
@@ -26,15 +26,27 @@ use crate::config::{APP_ID, PROFILE};
 use crate::adaptive;
 use crate::fl;
 
+use fotema_core::albums;
 use fotema_core::database;
+use fotema_core::favourites;
 use fotema_core::video;
+use fotema_core::visual;
+use fotema_core::AlbumId;
 use fotema_core::VisualId;
 use fotema_core::PictureId;
 use fotema_core::people;
+use fotema_core::places;
+use fotema_core::search;
+use fotema_core::smart_albums;
+use fotema_core::smart_albums::SmartAlbumId;
+use fotema_core::tags;
+use fotema_core::trash;
 
 use h3o::CellIndex;
 
+use std::cell::Cell;
 use std::path::PathBuf;
+use std::rc::Rc;
 use std::sync::{Arc, Mutex};
 use std::str::FromStr;
 
@@ -48,17 +60,32 @@ mod components;
 
 use self::components::{
     about::AboutDialog,
+    album_chooser::{AlbumChooserDialog, AlbumChooserInput, AlbumChooserOutput},
     albums:: {
         album::{Album, AlbumInput, AlbumOutput},
         album_filter::AlbumFilter,
+        events_album::{EventsAlbum, EventsAlbumInput, EventsAlbumOutput},
         folders_album::{FoldersAlbum, FoldersAlbumInput, FoldersAlbumOutput},
+        on_this_day_album::{OnThisDayAlbum, OnThisDayAlbumInput, OnThisDayAlbumOutput},
         people_album::{PeopleAlbum, PeopleAlbumInput, PeopleAlbumOutput},
         person_album::{PersonAlbum, PersonAlbumInput, PersonAlbumOutput},
         places_album::{PlacesAlbum, PlacesAlbumInput, PlacesAlbumOutput},
+        smart_album::{SmartAlbum, SmartAlbumInput, SmartAlbumOutput},
+        smart_albums::{SmartAlbums, SmartAlbumsInput, SmartAlbumsOutput},
+        tags_album::{TagsAlbum, TagsAlbumInput, TagsAlbumOutput},
+        user_album::{UserAlbum, UserAlbumInput, UserAlbumOutput},
+        user_albums::{UserAlbums, UserAlbumsInput, UserAlbumsOutput},
     },
     library::{Library, LibraryInput, LibraryOutput},
+    on_this_day_card::{OnThisDayCard, OnThisDayCardInput, OnThisDayCardOutput},
     viewer::view_nav::{ViewNav, ViewNavInput, ViewNavOutput},
     preferences::{PreferencesDialog, PreferencesInput},
+    geotag::{GeotagDialog, GeotagInput, GeotagOutput},
+    infer_locations::{InferLocationsDialog, InferLocationsInput, InferLocationsOutput},
+    location_editor::{LocationEditorDialog, LocationEditorInput, LocationEditorOutput},
+    date_time_editor::{DateTimeEditorDialog, DateTimeEditorInput, DateTimeEditorOutput},
+    privacy_zones::{PrivacyZonesDialog, PrivacyZonesInput, PrivacyZonesOutput},
+    smart_album_editor::{SmartAlbumEditorDialog, SmartAlbumEditorInput, SmartAlbumEditorOutput},
 };
 
 mod background;
@@ -66,6 +93,7 @@ mod background;
 use self::background::{
     bootstrap::{Bootstrap, BootstrapInput, BootstrapOutput, TaskName, MediaType},
     video_transcode::{VideoTranscode, VideoTranscodeInput},
+    move_to_trash::{MoveToTrash, MoveToTrashInput, MoveToTrashOutput},
 };
 
 use self::components::progress_monitor::ProgressMonitor;
@@ -82,11 +110,20 @@ pub enum ViewName {
     Year,
     Videos,
     Animated,
+    Favourites,
+    Albums, // albums created by the user
+    UserAlbum, // one album created by the user
+    SmartAlbums, // albums with items chosen by rules
+    SmartAlbum, // one album with items chosen by a rule
+    Tags, // keyword tags
     Folders,
     Folder,
     People,
     Person,
     Places,
+    Map, // map of items matching a filter
+    Events,
+    OnThisDay,
     Selfies,
 }
 
@@ -113,6 +150,18 @@ pub struct Settings {
 
     /// Enable or disable face detection.
     pub face_detection_mode: FaceDetectionMode,
+
+    /// Local MBTiles or PMTiles archive of map tiles for viewing maps offline.
+    pub offline_map: Option<PathBuf>,
+
+    /// Show OpenStreetMap tiles where the offline map doesn't have tiles.
+    pub online_map_fallback: bool,
+
+    /// Show card for "On this day" memories at the top of the library.
+    pub show_on_this_day: bool,
+
+    /// Add photos rated five stars in other apps to favourites.
+    pub import_ratings_as_favourites: bool,
 }
 
 /// Active settings
@@ -134,18 +183,45 @@ pub(super) struct App {
 
     about_dialog: Controller<AboutDialog>,
     preferences_dialog: Controller<PreferencesDialog>,
+    geotag_dialog: Controller<GeotagDialog>,
+    infer_locations_dialog: Controller<InferLocationsDialog>,
+    location_editor: Controller<LocationEditorDialog>,
+    date_time_editor: Controller<DateTimeEditorDialog>,
+    album_chooser: Controller<AlbumChooserDialog>,
+    privacy_zones_dialog: Controller<PrivacyZonesDialog>,
+    smart_album_editor: Controller<SmartAlbumEditorDialog>,
 
     bootstrap: WorkerController<Bootstrap>,
     video_transcode: WorkerController<VideoTranscode>,
+    move_to_trash: WorkerController<MoveToTrash>,
 
     library: Controller<Library>,
 
+    // Card at top of library for "On this day" memories.
+    on_this_day_card: Controller<OnThisDayCard>,
+
     view_nav: AsyncController<ViewNav>,
 
     show_selfies: bool,
     selfies_page: Controller<Album>,
     videos_page: Controller<Album>,
     motion_page: Controller<Album>,
+    favourites_page: Controller<Album>,
+
+    /// Albums created by the user
+    user_albums_page: Controller<UserAlbums>,
+
+    /// Photos and videos of one album created by the user
+    user_album: Controller<UserAlbum>,
+
+    /// Albums with items chosen by rules
+    smart_albums_page: Controller<SmartAlbums>,
+
+    /// Photos and videos matching the rule of one smart album
+    smart_album: Controller<SmartAlbum>,
+
+    /// Keyword tags, nested in their parents
+    tags_page: Controller<TagsAlbum>,
 
     /// Album with photos overlayed onto a map
     people_page: Controller<PeopleAlbum>,
@@ -162,6 +238,30 @@ pub(super) struct App {
     // Folder album currently being viewed
     folder_album: Controller<Album>,
 
+    // Title of album currently being viewed
+    album_title: gtk::Label,
+
+    // Filter of album currently being viewed
+    album_filter: AlbumFilter,
+
+    // Search with queries such as "person:Alice year:2022 beach"
+    search_repo: search::Repository,
+
+    // Privacy zones to leave out of searches for places
+    places_repo: places::Repository,
+
+    // Events, such as parties and trips, found by grouping items by time and place.
+    events_album: Controller<EventsAlbum>,
+
+    // Items taken on today's month and day in previous years.
+    on_this_day_album: Controller<OnThisDayAlbum>,
+
+    // Map of items matching a filter, such as a person's photos or a folder.
+    map_album: Controller<PlacesAlbum>,
+
+    // Title of map currently being viewed
+    map_title: gtk::Label,
+
     // Main navigation. Parent of library stack.
     main_navigation: adw::OverlaySplitView,
 
@@ -171,12 +271,21 @@ pub(super) struct App {
     // Switch between library views and single image view.
     picture_navigation_view: adw::NavigationView,
 
+    // Shows toasts, such as for undoing moving items to the trash.
+    toast_overlay: adw::ToastOverlay,
+
     // Window header bar
     header_bar: adw::HeaderBar,
 
     // Activity indicator. Only shown when progress bar is hidden.
     spinner: gtk::Spinner,
 
+    // Toggle selection mode of albums in main stack.
+    selection_button: gtk::ToggleButton,
+
+    // Toggle selection mode of album currently being viewed.
+    album_selection_button: gtk::ToggleButton,
+
     bootstrap_progress: Controller<ProgressPanel>,
     transcode_progress: Controller<ProgressPanel>,
 
@@ -211,10 +320,33 @@ pub(super) enum AppMsg {
 
     ViewPerson(people::Person),
 
+    // View items matching a query over several people.
+    ViewPeopleQuery(AlbumFilter),
+
+    // View items at a named place, such as a city or country.
+    ViewPlace(String, AlbumFilter),
+
+    // View items in an event.
+    ViewEvent(String, AlbumFilter),
+
+    // View items with a keyword tag.
+    ViewTag(String, AlbumFilter),
+
+    // View items matching a filter on a map.
+    ViewMap(String, AlbumFilter),
+
+    // View items taken on today's month and day in previous years.
+    ViewOnThisDay,
+
+    // View items in album currently being viewed on a map.
+    ViewAlbumMap,
+
     PersonDeleted,
 
     PersonRenamed,
 
+    PersonMerged,
+
     // A background task has started.
     TaskStarted(TaskName),
 
@@ -226,6 +358,85 @@ pub(super) enum AppMsg {
     ScanPictureForFaces(PictureId),
     ScanPicturesForFaces,
 
+    // Locations of some items have been added or changed
+    LocationsChanged,
+
+    // Privacy zones have been added or removed
+    PrivacyZonesChanged,
+
+    // Show dialog for setting location of items
+    EditLocation(Vec<Arc<fotema_core::Visual>>),
+
+    // Dates and times of some items have been corrected
+    DatesChanged,
+
+    // Show dialog for correcting date and time of items
+    EditDateTime(Vec<Arc<fotema_core::Visual>>),
+
+    // Items have been added to or removed from favourites
+    FavouritesChanged,
+
+    // Show dialog for adding items to albums
+    AddToAlbum(Vec<Arc<fotema_core::Visual>>),
+
+    // Albums have been created or changed
+    AlbumsChanged,
+
+    // Move files of items to the trash
+    MoveToTrash(Vec<Arc<fotema_core::Visual>>),
+
+    // Items have been moved to the trash
+    Trashed(trash::Trashed),
+
+    // Restore files that were moved to the trash
+    RestoreFromTrash(trash::Trashed),
+
+    // Forget items in the trash that can no longer be restored
+    PurgeTrash(trash::Trashed),
+
+    // Files have been restored from the trash. Has count of restored files,
+    // and count of files that should have been restored.
+    RestoredFromTrash(usize, usize),
+
+    // Items have been moved to or restored from the trash
+    TrashChanged,
+
+    // View an album created by the user
+    ViewUserAlbum(AlbumId),
+
+    // Album created by the user has been deleted
+    UserAlbumDeleted,
+
+    // Show dialog for creating a smart album
+    CreateSmartAlbum,
+
+    // Show dialog for changing the name and rule of a smart album
+    EditSmartAlbum(SmartAlbumId),
+
+    // Smart album has been created
+    SmartAlbumCreated(SmartAlbumId),
+
+    // Name or rule of smart album has been changed
+    SmartAlbumChanged(SmartAlbumId),
+
+    // View an album with items chosen by a rule
+    ViewSmartAlbum(SmartAlbumId),
+
+    // Smart album has been deleted
+    SmartAlbumDeleted,
+
+    // Tags have been added to or removed from items
+    TagsChanged,
+
+    // Caption of an item has been changed
+    CaptionsChanged,
+
+    // Search for items matching a query, such as "person:Alice year:2022 beach"
+    Search(String),
+
+    // Enter or leave selection mode for albums
+    SelectionMode(bool),
+
     // Adapt to layout change
     Adapt(adaptive::Layout),
 
@@ -236,6 +447,9 @@ pub(super) enum AppMsg {
 relm4::new_action_group!(pub(super) WindowActionGroup, "win");
 relm4::new_stateless_action!(PreferencesAction, WindowActionGroup, "preferences");
 relm4::new_stateless_action!(AboutAction, WindowActionGroup, "about");
+relm4::new_stateless_action!(GeotagAction, WindowActionGroup, "geotag");
+relm4::new_stateless_action!(InferLocationsAction, WindowActionGroup, "infer_locations");
+relm4::new_stateless_action!(PrivacyZonesAction, WindowActionGroup, "privacy_zones");
 
 #[relm4::component(pub)]
 impl SimpleComponent for App {
@@ -247,6 +461,9 @@ impl SimpleComponent for App {
     menu! {
         primary_menu: {
             section! {
+                &fl!("primary-menu-geotag") => GeotagAction,
+                &fl!("primary-menu-infer-locations") => InferLocationsAction,
+                &fl!("primary-menu-privacy-zones") => PrivacyZonesAction,
                 &fl!("primary-menu-preferences") => PreferencesAction,
                 &fl!("primary-menu-about") => AboutAction,
             }
@@ -288,187 +505,311 @@ impl SimpleComponent for App {
                 connect_unapply => AppMsg::Adapt(adaptive::Layout::Wide),
             },
 
-            // Top-level navigation view containing:
-            // 1. Navigation view containing stack of pages.
-            // 2. Page for displaying a single photo.
+            // Shows toasts over every page.
             #[local_ref]
-            picture_navigation_view -> adw::NavigationView {
-                set_pop_on_escape: true,
-                connect_popped[sender] => move |_,_| sender.input(AppMsg::ViewHidden),
+            toast_overlay -> adw::ToastOverlay {
+                // Top-level navigation view containing:
+                // 1. Navigation view containing stack of pages.
+                // 2. Page for displaying a single photo.
+                #[local_ref]
+                picture_navigation_view -> adw::NavigationView {
+                    set_pop_on_escape: true,
+                    connect_popped[sender] => move |_,_| sender.input(AppMsg::ViewHidden),
 
-                // Page for showing main navigation. Such as "Library", "Selfies", etc.
-                adw::NavigationPage {
+                    // Page for showing main navigation. Such as "Library", "Selfies", etc.
+                    adw::NavigationPage {
 
-                    #[local_ref]
-                    main_navigation -> adw::OverlaySplitView {
+                        #[local_ref]
+                        main_navigation -> adw::OverlaySplitView {
 
-                        set_max_sidebar_width: 200.0,
+                            set_max_sidebar_width: 200.0,
 
-                        #[wrap(Some)]
-                        set_sidebar = &adw::NavigationPage {
-                            adw::ToolbarView {
-                                add_top_bar = &adw::HeaderBar {
-                                    pack_end = &gtk::MenuButton {
-                                        set_icon_name: "open-menu-symbolic",
-                                        set_menu_model: Some(&primary_menu),
-                                    }
-                                },
-                                #[wrap(Some)]
-                                set_content = &gtk::Box {
-                                    set_orientation: gtk::Orientation::Vertical,
-                                    gtk::StackSidebar {
-                                        set_stack: &main_stack,
-                                        set_vexpand: true,
+                            #[wrap(Some)]
+                            set_sidebar = &adw::NavigationPage {
+                                adw::ToolbarView {
+                                    add_top_bar = &adw::HeaderBar {
+                                        pack_end = &gtk::MenuButton {
+                                            set_icon_name: "open-menu-symbolic",
+                                            set_menu_model: Some(&primary_menu),
+                                        }
                                     },
+                                    #[wrap(Some)]
+                                    set_content = &gtk::Box {
+                                        set_orientation: gtk::Orientation::Vertical,
+                                        gtk::StackSidebar {
+                                            set_stack: &main_stack,
+                                            set_vexpand: true,
+                                        },
 
-                                    model.bootstrap_progress.widget(),
-                                    model.transcode_progress.widget(),
+                                        model.bootstrap_progress.widget(),
+                                        model.transcode_progress.widget(),
+                                    }
                                 }
-                            }
-                        },
-
-                        #[wrap(Some)]
-                        set_content = &adw::NavigationPage {
-                            adw::ToolbarView {
-                                #[local_ref]
-                                add_top_bar = &header_bar -> adw::HeaderBar {
-                                    set_hexpand: true,
-                                    pack_start = &gtk::Button {
-                                        set_icon_name: "dock-left-symbolic",
-                                        connect_clicked => AppMsg::ToggleSidebar,
-                                    },
+                            },
 
+                            #[wrap(Some)]
+                            set_content = &adw::NavigationPage {
+                                adw::ToolbarView {
                                     #[local_ref]
-                                    pack_end = &spinner -> gtk::Spinner,
-                                },
+                                    add_top_bar = &header_bar -> adw::HeaderBar {
+                                        set_hexpand: true,
+                                        pack_start = &gtk::Button {
+                                            set_icon_name: "dock-left-symbolic",
+                                            connect_clicked => AppMsg::ToggleSidebar,
+                                        },
 
-                                // NOTE I would like this to be an adw::ViewStack
-                                // so that I could use a adw::ViewSwitcher in the sidebar
-                                // that would show icons.
-                                // However, adw::ViewSwitch can't display vertically.
-                                #[wrap(Some)]
-                                set_content = &gtk::Box {
-                                    set_orientation: gtk::Orientation::Vertical,
+                                        #[local_ref]
+                                        pack_end = &spinner -> gtk::Spinner,
 
-                                    #[local_ref]
-                                    banner -> adw::Banner {
-                                        // Only show when generating thumbnails
-                                        set_button_label: None,
+                                        #[local_ref]
+                                        pack_end = &selection_button -> gtk::ToggleButton {
+                                            set_icon_name: "selection-mode-symbolic",
+                                            set_tooltip_text: Some(&fl!("selection-mode", "tooltip")),
+                                            connect_toggled[sender] => move |button| {
+                                                sender.input(AppMsg::SelectionMode(button.is_active()));
+                                            },
+                                        },
+
+                                        pack_end = &gtk::SearchEntry {
+                                            set_placeholder_text: Some(&fl!("search-entry", "placeholder")),
+                                            set_tooltip_text: Some(&fl!("search-entry", "tooltip")),
+                                            set_width_chars: 16,
+                                            connect_activate[sender] => move |entry| {
+                                                sender.input(AppMsg::Search(entry.text().to_string()));
+                                            },
+                                        },
                                     },
 
-                                    #[local_ref]
-                                    main_stack -> gtk::Stack {
-                                        connect_visible_child_notify => AppMsg::SwitchView,
+                                    // NOTE I would like this to be an adw::ViewStack
+                                    // so that I could use a adw::ViewSwitcher in the sidebar
+                                    // that would show icons.
+                                    // However, adw::ViewSwitch can't display vertically.
+                                    #[wrap(Some)]
+                                    set_content = &gtk::Box {
+                                        set_orientation: gtk::Orientation::Vertical,
+
+                                        #[local_ref]
+                                        banner -> adw::Banner {
+                                            // Only show when generating thumbnails
+                                            set_button_label: None,
+                                        },
 
-                                        add_child = &gtk::Box {
-                                            set_orientation: gtk::Orientation::Vertical,
-                                            container_add: model.library.widget(),
+                                        #[local_ref]
+                                        main_stack -> gtk::Stack {
+                                            connect_visible_child_notify => AppMsg::SwitchView,
+
+                                            add_child = &gtk::Box {
+                                                set_orientation: gtk::Orientation::Vertical,
+                                                container_add: model.on_this_day_card.widget(),
+                                                container_add: model.library.widget(),
+
+                                                #[name(switcher_bar)]
+                                                adw::ViewSwitcherBar {
+                                                    set_stack: Some(model.library.widget()),
+                                                },
+                                            } -> {
+                                                set_title: &fl!("library-page"),
+                                                set_name: ViewName::Library.into(),
+
+                                                // NOTE gtk::StackSidebar doesn't show icon :-/
+                                                set_icon_name: "image-alt-symbolic",
+                                            },
 
-                                            #[name(switcher_bar)]
-                                            adw::ViewSwitcherBar {
-                                                set_stack: Some(model.library.widget()),
+                                            add_child = &gtk::Box {
+                                                set_orientation: gtk::Orientation::Vertical,
+                                                container_add: model.videos_page.widget(),
+                                            } -> {
+                                                set_title: &fl!("videos-album"),
+                                                set_name: ViewName::Videos.into(),
+                                                // NOTE gtk::StackSidebar doesn't show icon :-/
+                                                set_icon_name: "video-reel-symbolic",
                                             },
-                                        } -> {
-                                            set_title: &fl!("library-page"),
-                                            set_name: ViewName::Library.into(),
 
-                                            // NOTE gtk::StackSidebar doesn't show icon :-/
-                                            set_icon_name: "image-alt-symbolic",
-                                        },
+                                            add_child = &gtk::Box {
+                                                set_orientation: gtk::Orientation::Vertical,
+                                                container_add: model.motion_page.widget(),
+                                            } -> {
+                                                set_title: &fl!("animated-album"),
+                                                set_name: ViewName::Animated.into(),
+                                                // NOTE gtk::StackSidebar doesn't show icon :-/
+                                                set_icon_name: "sonar-symbolic",
+                                            },
 
-                                        add_child = &gtk::Box {
-                                            set_orientation: gtk::Orientation::Vertical,
-                                            container_add: model.videos_page.widget(),
-                                        } -> {
-                                            set_title: &fl!("videos-album"),
-                                            set_name: ViewName::Videos.into(),
-                                            // NOTE gtk::StackSidebar doesn't show icon :-/
-                                            set_icon_name: "video-reel-symbolic",
-                                        },
+                                            add_child = &gtk::Box {
+                                                set_orientation: gtk::Orientation::Vertical,
+                                                container_add: model.favourites_page.widget(),
+                                            } -> {
+                                                set_title: &fl!("favourites-album"),
+                                                set_name: ViewName::Favourites.into(),
+                                                // NOTE gtk::StackSidebar doesn't show icon :-/
+                                                set_icon_name: "emblem-favorite-symbolic",
+                                            },
 
-                                        add_child = &gtk::Box {
-                                            set_orientation: gtk::Orientation::Vertical,
-                                            container_add: model.motion_page.widget(),
-                                        } -> {
-                                            set_title: &fl!("animated-album"),
-                                            set_name: ViewName::Animated.into(),
-                                            // NOTE gtk::StackSidebar doesn't show icon :-/
-                                            set_icon_name: "sonar-symbolic",
-                                        },
+                                            add_child = &gtk::Box {
+                                                set_orientation: gtk::Orientation::Vertical,
+                                                container_add: model.user_albums_page.widget(),
+                                            } -> {
+                                                set_title: &fl!("user-albums-page"),
+                                                set_name: ViewName::Albums.into(),
+                                                // NOTE gtk::StackSidebar doesn't show icon :-/
+                                                set_icon_name: "image-alt-symbolic",
+                                            },
 
-                                        add_child = &gtk::Box {
-                                            set_orientation: gtk::Orientation::Vertical,
-                                            container_add: model.people_page.widget(),
-                                        } -> {
-                                            set_title: &fl!("people-page"),
-                                            set_name: ViewName::People.into(),
-                                        },
+                                            add_child = &gtk::Box {
+                                                set_orientation: gtk::Orientation::Vertical,
+                                                container_add: model.smart_albums_page.widget(),
+                                            } -> {
+                                                set_title: &fl!("smart-albums-page"),
+                                                set_name: ViewName::SmartAlbums.into(),
+                                                // NOTE gtk::StackSidebar doesn't show icon :-/
+                                                set_icon_name: "edit-find-symbolic",
+                                            },
 
-                                        add_child = &gtk::Box {
-                                            set_orientation: gtk::Orientation::Vertical,
-                                            container_add: model.places_page.widget(),
-                                        } -> {
-                                            set_title: &fl!("places-page"),
-                                            set_name: ViewName::Places.into(),
-                                        },
+                                            add_child = &gtk::Box {
+                                                set_orientation: gtk::Orientation::Vertical,
+                                                container_add: model.tags_page.widget(),
+                                            } -> {
+                                                set_title: &fl!("tags-page"),
+                                                set_name: ViewName::Tags.into(),
+                                                // NOTE gtk::StackSidebar doesn't show icon :-/
+                                                set_icon_name: "tag-symbolic",
+                                            },
 
-                                        add_child = &gtk::Box {
-                                            set_orientation: gtk::Orientation::Vertical,
-                                            container_add: model.selfies_page.widget(),
-                                        } -> {
-                                            set_visible: model.show_selfies,
-                                            set_title: &fl!("selfies-album"),
-                                            set_name: ViewName::Selfies.into(),
-                                            // NOTE gtk::StackSidebar doesn't show icon :-/
-                                            set_icon_name: "sentiment-very-satisfied-symbolic",
-                                        },
+                                            add_child = &gtk::Box {
+                                                set_orientation: gtk::Orientation::Vertical,
+                                                container_add: model.people_page.widget(),
+                                            } -> {
+                                                set_title: &fl!("people-page"),
+                                                set_name: ViewName::People.into(),
+                                            },
 
-                                        add_child = &adw::NavigationView {
-                                            set_pop_on_escape: true,
+                                            add_child = &gtk::Box {
+                                                set_orientation: gtk::Orientation::Vertical,
+                                                container_add: model.places_page.widget(),
+                                            } -> {
+                                                set_title: &fl!("places-page"),
+                                                set_name: ViewName::Places.into(),
+                                            },
 
-                                            adw::NavigationPage {
-                                                //set_tag: Some("folders"),
-                                                //set_title: "Folder",
-                                                model.folders_album.widget(),
+                                            add_child = &gtk::Box {
+                                                set_orientation: gtk::Orientation::Vertical,
+                                                container_add: model.events_album.widget(),
+                                            } -> {
+                                                set_title: &fl!("events-album"),
+                                                set_name: ViewName::Events.into(),
+                                                // NOTE gtk::StackSidebar doesn't show icon :-/
+                                                set_icon_name: "today-symbolic",
+                                            },
+
+                                            add_child = &gtk::Box {
+                                                set_orientation: gtk::Orientation::Vertical,
+                                                container_add: model.on_this_day_album.widget(),
+                                            } -> {
+                                                set_title: &fl!("on-this-day-album"),
+                                                set_name: ViewName::OnThisDay.into(),
+                                                // NOTE gtk::StackSidebar doesn't show icon :-/
+                                                set_icon_name: "arrow-circular-top-left-symbolic",
+                                            },
+
+                                            add_child = &gtk::Box {
+                                                set_orientation: gtk::Orientation::Vertical,
+                                                container_add: model.selfies_page.widget(),
+                                            } -> {
+                                                set_visible: model.show_selfies,
+                                                set_title: &fl!("selfies-album"),
+                                                set_name: ViewName::Selfies.into(),
+                                                // NOTE gtk::StackSidebar doesn't show icon :-/
+                                                set_icon_name: "sentiment-very-satisfied-symbolic",
+                                            },
+
+                                            add_child = &adw::NavigationView {
+                                                set_pop_on_escape: true,
+
+                                                adw::NavigationPage {
+                                                    //set_tag: Some("folders"),
+                                                    //set_title: "Folder",
+                                                    model.folders_album.widget(),
+                                                },
+                                            } -> {
+                                                set_title: &fl!("folders-album"),
+                                                set_name: ViewName::Folders.into(),
+                                                // NOTE gtk::StackSidebar doesn't show icon :-/
+                                                set_icon_name: "folder-symbolic",
                                             },
-                                        } -> {
-                                            set_title: &fl!("folders-album"),
-                                            set_name: ViewName::Folders.into(),
-                                            // NOTE gtk::StackSidebar doesn't show icon :-/
-                                            set_icon_name: "folder-symbolic",
                                         },
                                     },
                                 },
                             },
                         },
                     },
-                },
 
-                adw::NavigationPage {
-                    set_tag: Some("album"),
-                    adw::ToolbarView {
-                        add_top_bar = &adw::HeaderBar {
+                    adw::NavigationPage {
+                        set_tag: Some("album"),
+                        adw::ToolbarView {
+                            add_top_bar = &adw::HeaderBar {
+                                #[wrap(Some)]
+                                #[local_ref]
+                                set_title_widget = &album_title -> gtk::Label {
+                                    add_css_class: "title",
+                                },
+
+                                #[local_ref]
+                                pack_end = &album_selection_button -> gtk::ToggleButton {
+                                    set_icon_name: "selection-mode-symbolic",
+                                    set_tooltip_text: Some(&fl!("selection-mode", "tooltip")),
+                                    connect_toggled[sender] => move |button| {
+                                        sender.input(AppMsg::SelectionMode(button.is_active()));
+                                    },
+                                },
+
+                                pack_end = &gtk::Button {
+                                    set_icon_name: "find-location-symbolic",
+                                    set_tooltip_text: Some(&fl!("show-on-map", "tooltip")),
+                                    connect_clicked => AppMsg::ViewAlbumMap,
+                                },
+                            },
+
                             #[wrap(Some)]
-                            set_title_widget = &gtk::Label {
-                                set_label: &fl!("folder-album"),
-                                add_css_class: "title",
-                            }
-                        },
+                            set_content = model.folder_album.widget(),
+                        }
+                    },
 
-                        #[wrap(Some)]
-                        set_content = model.folder_album.widget(),
-                    }
-                },
+                    adw::NavigationPage {
+                        set_tag: Some("map_album"),
+                        adw::ToolbarView {
+                            add_top_bar = &adw::HeaderBar {
+                                #[wrap(Some)]
+                                #[local_ref]
+                                set_title_widget = &map_title -> gtk::Label {
+                                    add_css_class: "title",
+                                },
+                            },
 
-                adw::NavigationPage {
-                    set_tag: Some("person_album"),
-                    model.person_album.widget(),
-                },
+                            #[wrap(Some)]
+                            set_content = model.map_album.widget(),
+                        }
+                    },
 
-                // Page for showing a single photo.
-                adw::NavigationPage {
-                    set_tag: Some("picture"),
-                    model.view_nav.widget(),
+                    adw::NavigationPage {
+                        set_tag: Some("person_album"),
+                        model.person_album.widget(),
+                    },
+
+                    adw::NavigationPage {
+                        set_tag: Some("user_album"),
+                        model.user_album.widget(),
+                    },
+
+                    adw::NavigationPage {
+                        set_tag: Some("smart_album"),
+                        model.smart_album.widget(),
+                    },
+
+                    // Page for showing a single photo.
+                    adw::NavigationPage {
+                        set_tag: Some("picture"),
+                        model.view_nav.widget(),
+                    },
                 },
             },
         }
@@ -505,6 +846,20 @@ impl SimpleComponent for App {
             con.clone(),
         ).unwrap();
 
+        let places_repo = places::Repository::open(con.clone()).unwrap();
+
+        let favourites_repo = favourites::Repository::open(con.clone()).unwrap();
+
+        let albums_repo = albums::Repository::open(con.clone()).unwrap();
+
+        let smart_albums_repo = smart_albums::Repository::open(con.clone()).unwrap();
+
+        let tags_repo = tags::Repository::open(con.clone()).unwrap();
+
+        let search_repo = search::Repository::open(con.clone()).unwrap();
+
+        let visual_repo = visual::Repository::open(&pic_base_dir, &cache_dir, con.clone()).unwrap();
+
         let state = SharedState::new(relm4::SharedState::new());
         let active_view = ActiveView::new(relm4::SharedState::new());
         let adaptive_layout = Arc::new(adaptive::LayoutState::new());
@@ -545,19 +900,54 @@ impl SimpleComponent for App {
             .launch((state.clone(), active_view.clone(), adaptive_layout.clone()))
             .forward(sender.input_sender(), |msg| match msg {
                 LibraryOutput::View(id) => AppMsg::View(id, AlbumFilter::All),
+                LibraryOutput::EditLocation(visuals) => AppMsg::EditLocation(visuals),
+                LibraryOutput::EditDateTime(visuals) => AppMsg::EditDateTime(visuals),
+                LibraryOutput::AddToAlbum(visuals) => AppMsg::AddToAlbum(visuals),
+                LibraryOutput::MoveToTrash(visuals) => AppMsg::MoveToTrash(visuals),
+                LibraryOutput::ViewMap(title, filter) => AppMsg::ViewMap(title, filter),
             });
 
+        let on_this_day_card = OnThisDayCard::builder()
+            .launch((state.clone(), settings_state.clone()))
+            .forward(sender.input_sender(), |msg| match msg {
+                OnThisDayCardOutput::Open => AppMsg::ViewOnThisDay,
+            });
+
+        state.subscribe(on_this_day_card.sender(), |_| OnThisDayCardInput::Refresh);
+
         let transcoder = video::Transcoder::new(&cache_dir);
 
         let video_transcode = VideoTranscode::builder()
             .detach_worker((state.clone(), video_repo, transcoder.clone(), transcode_progress_monitor.clone()))
             .detach();
 
+        let mut trash = trash::Trash::open(&cache_dir, &data_dir, con.clone()).unwrap();
+
+        // Items trashed when Fotema last ran can no longer be restored.
+        if let Err(e) = trash.purge_all() {
+            error!("Failed purging trash: {:?}", e);
+        }
+
+        let move_to_trash = MoveToTrash::builder()
+            .detach_worker(trash)
+            .forward(sender.input_sender(), |msg| match msg {
+                MoveToTrashOutput::Trashed(trashed) => AppMsg::Trashed(trashed),
+                MoveToTrashOutput::Restored(count, total) => AppMsg::RestoredFromTrash(count, total),
+            });
+
         let view_nav = ViewNav::builder()
-            .launch((state.clone(), transcode_progress_monitor.clone(), adaptive_layout.clone(), people_repo.clone()))
+            .launch((state.clone(), transcode_progress_monitor.clone(), adaptive_layout.clone(), people_repo.clone(), places_repo.clone(), favourites_repo, tags_repo.clone(), visual_repo.clone(), data_dir.clone()))
             .forward(sender.input_sender(), |msg| match msg {
                 ViewNavOutput::TranscodeAll => AppMsg::TranscodeAll,
                 ViewNavOutput::ScanForFaces(picture_id) => AppMsg::ScanPictureForFaces(picture_id),
+                ViewNavOutput::LocationsChanged => AppMsg::LocationsChanged,
+                ViewNavOutput::TagsChanged => AppMsg::TagsChanged,
+                ViewNavOutput::CaptionsChanged => AppMsg::CaptionsChanged,
+                ViewNavOutput::EditLocation(visual) => AppMsg::EditLocation(vec![visual]),
+                ViewNavOutput::EditDateTime(visual) => AppMsg::EditDateTime(vec![visual]),
+                ViewNavOutput::FavouritesChanged => AppMsg::FavouritesChanged,
+                ViewNavOutput::AddToAlbum(visual) => AppMsg::AddToAlbum(vec![visual]),
+                ViewNavOutput::MoveToTrash(visual) => AppMsg::MoveToTrash(vec![visual]),
             });
 
         let selfies_page = Album::builder()
@@ -565,6 +955,11 @@ impl SimpleComponent for App {
             .forward(sender.input_sender(), |msg| match msg {
                 AlbumOutput::Selected(id, filter) => AppMsg::View(id, filter),
                 AlbumOutput::ScrollOffset(_) => AppMsg::Ignore,
+                AlbumOutput::EditLocation(visuals) => AppMsg::EditLocation(visuals),
+                AlbumOutput::EditDateTime(visuals) => AppMsg::EditDateTime(visuals),
+                AlbumOutput::AddToAlbum(visuals) => AppMsg::AddToAlbum(visuals),
+                AlbumOutput::MoveToTrash(visuals) => AppMsg::MoveToTrash(visuals),
+                AlbumOutput::EditAlbum(_, _) => AppMsg::Ignore,
             });
 
         state.subscribe(selfies_page.sender(), |_| AlbumInput::Refresh);
@@ -577,16 +972,101 @@ impl SimpleComponent for App {
             .forward(sender.input_sender(), |msg| match msg {
                 AlbumOutput::Selected(id, filter) => AppMsg::View(id, filter),
                 AlbumOutput::ScrollOffset(_) => AppMsg::Ignore,
+                AlbumOutput::EditLocation(visuals) => AppMsg::EditLocation(visuals),
+                AlbumOutput::EditDateTime(visuals) => AppMsg::EditDateTime(visuals),
+                AlbumOutput::AddToAlbum(visuals) => AppMsg::AddToAlbum(visuals),
+                AlbumOutput::MoveToTrash(visuals) => AppMsg::MoveToTrash(visuals),
+                AlbumOutput::EditAlbum(_, _) => AppMsg::Ignore,
             });
 
         state.subscribe(motion_page.sender(), |_| AlbumInput::Refresh);
         adaptive_layout.subscribe(motion_page.sender(), |layout| AlbumInput::Adapt(*layout));
 
+        let favourites_page = Album::builder()
+            .launch((state.clone(), active_view.clone(), ViewName::Favourites, AlbumFilter::Favourites))
+            .forward(sender.input_sender(), |msg| match msg {
+                AlbumOutput::Selected(id, filter) => AppMsg::View(id, filter),
+                AlbumOutput::ScrollOffset(_) => AppMsg::Ignore,
+                AlbumOutput::EditLocation(visuals) => AppMsg::EditLocation(visuals),
+                AlbumOutput::EditDateTime(visuals) => AppMsg::EditDateTime(visuals),
+                AlbumOutput::AddToAlbum(visuals) => AppMsg::AddToAlbum(visuals),
+                AlbumOutput::MoveToTrash(visuals) => AppMsg::MoveToTrash(visuals),
+                AlbumOutput::EditAlbum(_, _) => AppMsg::Ignore,
+            });
+
+        state.subscribe(favourites_page.sender(), |_| AlbumInput::Refresh);
+        adaptive_layout.subscribe(favourites_page.sender(), |layout| AlbumInput::Adapt(*layout));
+
+        let user_albums_page = UserAlbums::builder()
+            .launch((state.clone(), albums_repo.clone(), active_view.clone()))
+            .forward(sender.input_sender(), |msg| match msg {
+                UserAlbumsOutput::Selected(album_id) => AppMsg::ViewUserAlbum(album_id),
+            });
+
+        // Covers are thumbnails of visual items, so refresh when they change.
+        state.subscribe(user_albums_page.sender(), |_| UserAlbumsInput::Refresh);
+        adaptive_layout.subscribe(user_albums_page.sender(), |layout| UserAlbumsInput::Adapt(*layout));
+
+        let user_album = UserAlbum::builder()
+            .launch((state.clone(), albums_repo.clone(), active_view.clone()))
+            .forward(sender.input_sender(), |msg| match msg {
+                UserAlbumOutput::Selected(id, filter) => AppMsg::View(id, filter),
+                UserAlbumOutput::Changed => AppMsg::AlbumsChanged,
+                UserAlbumOutput::Deleted => AppMsg::UserAlbumDeleted,
+                UserAlbumOutput::EditLocation(visuals) => AppMsg::EditLocation(visuals),
+                UserAlbumOutput::EditDateTime(visuals) => AppMsg::EditDateTime(visuals),
+                UserAlbumOutput::AddToAlbum(visuals) => AppMsg::AddToAlbum(visuals),
+                UserAlbumOutput::MoveToTrash(visuals) => AppMsg::MoveToTrash(visuals),
+            });
+
+        state.subscribe(user_album.sender(), |_| UserAlbumInput::Refresh);
+        adaptive_layout.subscribe(user_album.sender(), |layout| UserAlbumInput::Adapt(*layout));
+
+        let smart_albums_page = SmartAlbums::builder()
+            .launch((state.clone(), smart_albums_repo.clone(), people_repo.clone(), tags_repo.clone(), active_view.clone()))
+            .forward(sender.input_sender(), |msg| match msg {
+                SmartAlbumsOutput::Selected(smart_album_id) => AppMsg::ViewSmartAlbum(smart_album_id),
+                SmartAlbumsOutput::Create => AppMsg::CreateSmartAlbum,
+            });
+
+        // Counts of items are for the current library, so refresh when it changes.
+        state.subscribe(smart_albums_page.sender(), |_| SmartAlbumsInput::Refresh);
+
+        let smart_album = SmartAlbum::builder()
+            .launch((state.clone(), smart_albums_repo.clone(), people_repo.clone(), tags_repo.clone(), active_view.clone()))
+            .forward(sender.input_sender(), |msg| match msg {
+                SmartAlbumOutput::Selected(id, filter) => AppMsg::View(id, filter),
+                SmartAlbumOutput::Edit(smart_album_id) => AppMsg::EditSmartAlbum(smart_album_id),
+                SmartAlbumOutput::Deleted => AppMsg::SmartAlbumDeleted,
+                SmartAlbumOutput::EditLocation(visuals) => AppMsg::EditLocation(visuals),
+                SmartAlbumOutput::EditDateTime(visuals) => AppMsg::EditDateTime(visuals),
+                SmartAlbumOutput::AddToAlbum(visuals) => AppMsg::AddToAlbum(visuals),
+                SmartAlbumOutput::MoveToTrash(visuals) => AppMsg::MoveToTrash(visuals),
+            });
+
+        // Items matching the rule change as the library changes.
+        state.subscribe(smart_album.sender(), |_| SmartAlbumInput::Refresh);
+        adaptive_layout.subscribe(smart_album.sender(), |layout| SmartAlbumInput::Adapt(*layout));
+
+        let tags_page = TagsAlbum::builder()
+            .launch((tags_repo, active_view.clone()))
+            .forward(sender.input_sender(), |msg| match msg {
+                TagsAlbumOutput::Selected(title, filter) => AppMsg::ViewTag(title, filter),
+            });
+
+        // Keywords are imported as the library is scanned, so refresh when it changes.
+        state.subscribe(tags_page.sender(), |_| TagsAlbumInput::Refresh);
+
         let videos_page = Album::builder()
             .launch((state.clone(), active_view.clone(), ViewName::Videos, AlbumFilter::Videos))
             .forward(sender.input_sender(), |msg| match msg {
                 AlbumOutput::Selected(id, filter) => AppMsg::View(id, filter),
                 AlbumOutput::ScrollOffset(_) => AppMsg::Ignore,
+                AlbumOutput::EditLocation(visuals) => AppMsg::EditLocation(visuals),
+                AlbumOutput::EditDateTime(visuals) => AppMsg::EditDateTime(visuals),
+                AlbumOutput::AddToAlbum(visuals) => AppMsg::AddToAlbum(visuals),
+                AlbumOutput::MoveToTrash(visuals) => AppMsg::MoveToTrash(visuals),
+                AlbumOutput::EditAlbum(_, _) => AppMsg::Ignore,
             });
 
         state.subscribe(videos_page.sender(), |_| AlbumInput::Refresh);
@@ -599,6 +1079,7 @@ impl SimpleComponent for App {
             |msg| match msg {
                 PeopleAlbumOutput::Selected(person) => AppMsg::ViewPerson(person),
                 PeopleAlbumOutput::EnableFaceDetection => AppMsg::ScanPicturesForFaces,
+                PeopleAlbumOutput::Query(filter) => AppMsg::ViewPeopleQuery(filter),
             },
         );
 
@@ -610,21 +1091,57 @@ impl SimpleComponent for App {
                 PersonAlbumOutput::Selected(id, filter) => AppMsg::View(id, filter),
                 PersonAlbumOutput::Deleted => AppMsg::PersonDeleted,
                 PersonAlbumOutput::Renamed => AppMsg::PersonRenamed,
+                PersonAlbumOutput::Merged => AppMsg::PersonMerged,
+                PersonAlbumOutput::EditLocation(visuals) => AppMsg::EditLocation(visuals),
+                PersonAlbumOutput::EditDateTime(visuals) => AppMsg::EditDateTime(visuals),
+                PersonAlbumOutput::AddToAlbum(visuals) => AppMsg::AddToAlbum(visuals),
+                PersonAlbumOutput::MoveToTrash(visuals) => AppMsg::MoveToTrash(visuals),
+                PersonAlbumOutput::ViewMap(title, filter) => AppMsg::ViewMap(title, filter),
             });
 
         state.subscribe(person_album.sender(), |_| PersonAlbumInput::Refresh);
         adaptive_layout.subscribe(person_album.sender(), |layout| PersonAlbumInput::Adapt(*layout));
 
         let places_page = PlacesAlbum::builder()
-            .launch((state.clone(), active_view.clone()))
+            .launch((state.clone(), places_repo.clone(), active_view.clone(), ViewName::Places, AlbumFilter::All, settings_state.clone()))
             .forward(sender.input_sender(), |msg| match msg {
                 PlacesAlbumOutput::View(visual_id) => AppMsg::View(visual_id.clone(), AlbumFilter::One(visual_id)),
                 PlacesAlbumOutput::GeographicArea(cell_index) => AppMsg::ViewGeographicArea(cell_index),
+                PlacesAlbumOutput::Place(name, filter) => AppMsg::ViewPlace(name, filter),
             });
 
         state.subscribe(places_page.sender(), |_| PlacesAlbumInput::Refresh);
         adaptive_layout.subscribe(places_page.sender(), |layout| PlacesAlbumInput::Adapt(*layout));
 
+        let events_album = EventsAlbum::builder()
+            .launch((state.clone(), places_repo.clone(), active_view.clone()))
+            .forward(sender.input_sender(), |msg| match msg {
+                EventsAlbumOutput::EventSelected(title, filter) => AppMsg::ViewEvent(title, filter),
+            });
+
+        state.subscribe(events_album.sender(), |_| EventsAlbumInput::Refresh);
+        adaptive_layout.subscribe(events_album.sender(), |layout| EventsAlbumInput::Adapt(*layout));
+
+        let on_this_day_album = OnThisDayAlbum::builder()
+            .launch((state.clone(), people_repo.clone(), active_view.clone()))
+            .forward(sender.input_sender(), |msg| match msg {
+                OnThisDayAlbumOutput::Selected(id, filter) => AppMsg::View(id, filter),
+            });
+
+        state.subscribe(on_this_day_album.sender(), |_| OnThisDayAlbumInput::Refresh);
+        adaptive_layout.subscribe(on_this_day_album.sender(), |layout| OnThisDayAlbumInput::Adapt(*layout));
+
+        let map_album = PlacesAlbum::builder()
+            .launch((state.clone(), places_repo.clone(), active_view.clone(), ViewName::Map, AlbumFilter::None, settings_state.clone()))
+            .forward(sender.input_sender(), |msg| match msg {
+                PlacesAlbumOutput::View(visual_id) => AppMsg::View(visual_id.clone(), AlbumFilter::One(visual_id)),
+                PlacesAlbumOutput::GeographicArea(cell_index) => AppMsg::ViewGeographicArea(cell_index),
+                PlacesAlbumOutput::Place(name, filter) => AppMsg::ViewPlace(name, filter),
+            });
+
+        state.subscribe(map_album.sender(), |_| PlacesAlbumInput::Refresh);
+        adaptive_layout.subscribe(map_album.sender(), |layout| PlacesAlbumInput::Adapt(*layout));
+
         let folders_album = FoldersAlbum::builder()
             .launch((state.clone(), active_view.clone()))
             .forward(
@@ -642,6 +1159,11 @@ impl SimpleComponent for App {
             .forward(sender.input_sender(), |msg| match msg {
                 AlbumOutput::Selected(id, filter) => AppMsg::View(id, filter),
                 AlbumOutput::ScrollOffset(_) => AppMsg::Ignore,
+                AlbumOutput::EditLocation(visuals) => AppMsg::EditLocation(visuals),
+                AlbumOutput::EditDateTime(visuals) => AppMsg::EditDateTime(visuals),
+                AlbumOutput::AddToAlbum(visuals) => AppMsg::AddToAlbum(visuals),
+                AlbumOutput::MoveToTrash(visuals) => AppMsg::MoveToTrash(visuals),
+                AlbumOutput::EditAlbum(_, _) => AppMsg::Ignore,
             });
 
         state.subscribe(folder_album.sender(), |_| AlbumInput::Refresh);
@@ -653,8 +1175,53 @@ impl SimpleComponent for App {
             .launch((settings_state.clone(), root.clone()))
             .detach();
 
+        let geotag_dialog = GeotagDialog::builder()
+            .launch((state.clone(), places_repo.clone(), root.clone()))
+            .forward(sender.input_sender(), |msg| match msg {
+                GeotagOutput::Geotagged(_) => AppMsg::LocationsChanged,
+            });
+
+        let infer_locations_dialog = InferLocationsDialog::builder()
+            .launch((state.clone(), places_repo.clone(), root.clone()))
+            .forward(sender.input_sender(), |msg| match msg {
+                InferLocationsOutput::LocationsChanged => AppMsg::LocationsChanged,
+            });
+
+        let location_editor = LocationEditorDialog::builder()
+            .launch((places_repo.clone(), settings_state.clone(), root.clone()))
+            .forward(sender.input_sender(), |msg| match msg {
+                LocationEditorOutput::LocationsChanged => AppMsg::LocationsChanged,
+            });
+
+        let date_time_editor = DateTimeEditorDialog::builder()
+            .launch((visual_repo, root.clone()))
+            .forward(sender.input_sender(), |msg| match msg {
+                DateTimeEditorOutput::DatesChanged => AppMsg::DatesChanged,
+            });
+
+        let album_chooser = AlbumChooserDialog::builder()
+            .launch((albums_repo, root.clone()))
+            .forward(sender.input_sender(), |msg| match msg {
+                AlbumChooserOutput::AlbumsChanged => AppMsg::AlbumsChanged,
+            });
+
+        let smart_album_editor = SmartAlbumEditorDialog::builder()
+            .launch((smart_albums_repo, people_repo.clone(), root.clone()))
+            .forward(sender.input_sender(), |msg| match msg {
+                SmartAlbumEditorOutput::Created(smart_album_id) => AppMsg::SmartAlbumCreated(smart_album_id),
+                SmartAlbumEditorOutput::Changed(smart_album_id) => AppMsg::SmartAlbumChanged(smart_album_id),
+            });
+
+        let privacy_zones_dialog = PrivacyZonesDialog::builder()
+            .launch((places_repo.clone(), root.clone()))
+            .forward(sender.input_sender(), |msg| match msg {
+                PrivacyZonesOutput::ZonesChanged => AppMsg::PrivacyZonesChanged,
+            });
+
         let picture_navigation_view = adw::NavigationView::builder().build();
 
+        let toast_overlay = adw::ToastOverlay::new();
+
         let main_navigation = adw::OverlaySplitView::builder().build();
 
         let main_stack = gtk::Stack::new();
@@ -663,20 +1230,43 @@ impl SimpleComponent for App {
 
         let spinner = gtk::Spinner::builder().visible(false).build();
 
+        let selection_button = gtk::ToggleButton::new();
+
+        let album_selection_button = gtk::ToggleButton::new();
+
+        let album_title = gtk::Label::new(Some(&fl!("folder-album")));
+
+        let map_title = gtk::Label::new(Some(&fl!("places-page")));
+
         let banner = adw::Banner::new("-");
 
         let model = Self {
             adaptive_layout,
             bootstrap,
             video_transcode,
+            move_to_trash,
 
             about_dialog,
             preferences_dialog,
+            geotag_dialog,
+            infer_locations_dialog,
+            location_editor,
+            date_time_editor,
+            album_chooser,
+            privacy_zones_dialog,
+            smart_album_editor,
 
             library,
+            on_this_day_card,
 
             view_nav,
             motion_page,
+            favourites_page,
+            user_albums_page,
+            user_album,
+            smart_albums_page,
+            smart_album,
+            tags_page,
             videos_page,
             people_page,
             person_album,
@@ -685,13 +1275,24 @@ impl SimpleComponent for App {
             show_selfies,
             folders_album,
             folder_album,
+            album_title: album_title.clone(),
+            album_filter: AlbumFilter::None,
+            search_repo,
+            places_repo,
+            events_album,
+            on_this_day_album,
+            map_album,
+            map_title: map_title.clone(),
 
             main_navigation: main_navigation.clone(),
             main_stack: main_stack.clone(),
 
             picture_navigation_view: picture_navigation_view.clone(),
+            toast_overlay: toast_overlay.clone(),
             header_bar: header_bar.clone(),
             spinner: spinner.clone(),
+            selection_button: selection_button.clone(),
+            album_selection_button: album_selection_button.clone(),
 
             bootstrap_progress,
             transcode_progress,
@@ -717,8 +1318,32 @@ impl SimpleComponent for App {
             })
         };
 
+        let geotag_action = {
+            let sender = model.geotag_dialog.sender().clone();
+            RelmAction::<GeotagAction>::new_stateless(move |_| {
+                sender.send(GeotagInput::Present).unwrap();
+            })
+        };
+
+        let infer_locations_action = {
+            let sender = model.infer_locations_dialog.sender().clone();
+            RelmAction::<InferLocationsAction>::new_stateless(move |_| {
+                sender.send(InferLocationsInput::Present).unwrap();
+            })
+        };
+
+        let privacy_zones_action = {
+            let sender = model.privacy_zones_dialog.sender().clone();
+            RelmAction::<PrivacyZonesAction>::new_stateless(move |_| {
+                sender.send(PrivacyZonesInput::Present).unwrap();
+            })
+        };
+
         actions.add_action(about_action);
         actions.add_action(preferences_action);
+        actions.add_action(geotag_action);
+        actions.add_action(infer_locations_action);
+        actions.add_action(privacy_zones_action);
 
         actions.register_for_widget(&widgets.main_window);
 
@@ -783,6 +1408,12 @@ impl SimpleComponent for App {
                     self.header_bar.set_title_widget(title.as_ref());
                 }
 
+                // Only albums of photos and videos support selecting items.
+                self.end_selection();
+                self.selection_button.set_visible(matches!(child_name,
+                    ViewName::Library | ViewName::Videos | ViewName::Selfies | ViewName::Animated
+                    | ViewName::Favourites));
+
                 // figure out which view to activate
                 match child_name {
                     ViewName::Library | ViewName::All | ViewName::Month | ViewName::Year => {
@@ -794,11 +1425,20 @@ impl SimpleComponent for App {
                     ViewName::Videos => self.videos_page.emit(AlbumInput::Activate),
                     ViewName::Selfies => self.selfies_page.emit(AlbumInput::Activate),
                     ViewName::Animated => self.motion_page.emit(AlbumInput::Activate),
+                    ViewName::Favourites => self.favourites_page.emit(AlbumInput::Activate),
+                    ViewName::Albums => self.user_albums_page.emit(UserAlbumsInput::Activate),
+                    ViewName::UserAlbum => self.user_album.emit(UserAlbumInput::Activate),
+                    ViewName::SmartAlbums => self.smart_albums_page.emit(SmartAlbumsInput::Activate),
+                    ViewName::SmartAlbum => self.smart_album.emit(SmartAlbumInput::Activate),
+                    ViewName::Tags => self.tags_page.emit(TagsAlbumInput::Activate),
                     ViewName::Folders => self.folders_album.emit(FoldersAlbumInput::Activate),
                     ViewName::Folder => self.folder_album.emit(AlbumInput::Activate),
                     ViewName::People => self.people_page.emit(PeopleAlbumInput::Activate),
                     ViewName::Person => self.person_album.emit(PersonAlbumInput::Activate),
                     ViewName::Places => self.places_page.emit(PlacesAlbumInput::Activate),
+                    ViewName::Map => self.map_album.emit(PlacesAlbumInput::Activate),
+                    ViewName::Events => self.events_album.emit(EventsAlbumInput::Activate),
+                    ViewName::OnThisDay => self.on_this_day_album.emit(OnThisDayAlbumInput::Activate),
                     ViewName::Nothing => event!(Level::WARN, "Nothing activated... which should not happen"),
                 }
             },
@@ -813,17 +1453,76 @@ impl SimpleComponent for App {
                 self.view_nav.emit(ViewNavInput::Hidden);
             },
             AppMsg::ViewFolder(path) => {
+                self.end_selection();
+                self.album_title.set_label(&fl!("folder-album"));
+                self.album_filter = AlbumFilter::Folder(path.clone());
                 self.folder_album.emit(AlbumInput::Activate);
                 self.folder_album.emit(AlbumInput::Filter(AlbumFilter::Folder(path)));
                 self.picture_navigation_view.push_by_tag("album");
             },
             AppMsg::ViewGeographicArea(cell_index) => {
+                self.end_selection();
+                self.album_title.set_label(&fl!("places-page"));
+                self.album_filter = AlbumFilter::GeographicArea(cell_index);
                 self.folder_album.emit(AlbumInput::Activate);
                 self.folder_album.emit(AlbumInput::Filter(AlbumFilter::GeographicArea(cell_index)));
                 self.picture_navigation_view.push_by_tag("album");
 
             },
+            AppMsg::ViewPeopleQuery(filter) => {
+                self.end_selection();
+                info!("Viewing people query");
+                self.album_title.set_label(&fl!("people-page"));
+                self.album_filter = filter.clone();
+                self.folder_album.emit(AlbumInput::Activate);
+                self.folder_album.emit(AlbumInput::Filter(filter));
+                self.picture_navigation_view.push_by_tag("album");
+            },
+            AppMsg::ViewPlace(name, filter) => {
+                self.end_selection();
+                info!("Viewing place: {}", name);
+                self.album_title.set_label(&name);
+                self.album_filter = filter.clone();
+                self.folder_album.emit(AlbumInput::Activate);
+                self.folder_album.emit(AlbumInput::Filter(filter));
+                self.picture_navigation_view.push_by_tag("album");
+            },
+            AppMsg::ViewEvent(title, filter) => {
+                self.end_selection();
+                info!("Viewing event: {}", title);
+                self.album_title.set_label(&title);
+                self.album_filter = filter.clone();
+                self.folder_album.emit(AlbumInput::Activate);
+                self.folder_album.emit(AlbumInput::Filter(filter));
+                self.picture_navigation_view.push_by_tag("album");
+            },
+            AppMsg::ViewTag(title, filter) => {
+                self.end_selection();
+                info!("Viewing tag: {}", title);
+                self.album_title.set_label(&title);
+                self.album_filter = filter.clone();
+                self.folder_album.emit(AlbumInput::Activate);
+                self.folder_album.emit(AlbumInput::Filter(filter));
+                self.picture_navigation_view.push_by_tag("album");
+            },
+            AppMsg::ViewMap(title, filter) => {
+                self.end_selection();
+                info!("Viewing map: {}", title);
+                self.map_title.set_label(&title);
+                // Filter before activating so the map is only drawn once.
+                self.map_album.emit(PlacesAlbumInput::Filter(filter));
+                self.map_album.emit(PlacesAlbumInput::Activate);
+                self.picture_navigation_view.push_by_tag("map_album");
+            },
+            AppMsg::ViewOnThisDay => {
+                self.main_stack.set_visible_child_name(ViewName::OnThisDay.into());
+            },
+            AppMsg::ViewAlbumMap => {
+                let title = self.album_title.label().to_string();
+                sender.input(AppMsg::ViewMap(title, self.album_filter.clone()));
+            },
             AppMsg::ViewPerson(person) => {
+                self.end_selection();
                 //info!("picture_ids = {:?}", picture_ids);
                 info!("Viewing person: {}", person.person_id);
                 self.person_album.emit(PersonAlbumInput::Activate);
@@ -837,6 +1536,9 @@ impl SimpleComponent for App {
             AppMsg::PersonRenamed => {
                 self.people_page.emit(PeopleAlbumInput::Refresh);
             },
+            AppMsg::PersonMerged => {
+                self.people_page.emit(PeopleAlbumInput::Refresh);
+            },
             AppMsg::TaskStarted(task_name) => {
                 self.spinner.start();
                 self.spinner.set_visible(!self.main_navigation.shows_sidebar());
@@ -864,12 +1566,18 @@ impl SimpleComponent for App {
                     TaskName::Thumbnail(MediaType::Video) => {
                         self.banner.set_title(&fl!("banner-thumbnails-videos"));
                     },
-                    TaskName::DetectFaces => {
+                    TaskName::DetectFaces(MediaType::Photo) => {
                         self.banner.set_title(&fl!("banner-detect-faces-photos"));
                     },
+                    TaskName::DetectFaces(MediaType::Video) => {
+                        self.banner.set_title(&fl!("banner-detect-faces-videos"));
+                    },
                     TaskName::RecognizeFaces => {
                         self.banner.set_title(&fl!("banner-recognize-faces-photos"));
                     },
+                    TaskName::Geocode => {
+                        self.banner.set_title(&fl!("banner-geocode"));
+                    },
                     TaskName::Clean(MediaType::Photo) => {
                         self.banner.set_title(&fl!("banner-clean-photos"));
                     },
@@ -895,6 +1603,200 @@ impl SimpleComponent for App {
                 info!("Scan pictures for faces");
                 self.bootstrap.emit(BootstrapInput::ScanPicturesForFaces);
             },
+            AppMsg::LocationsChanged => {
+                info!("Locations changed");
+                self.end_selection();
+                self.bootstrap.emit(BootstrapInput::Geocode);
+            },
+            AppMsg::PrivacyZonesChanged => {
+                info!("Privacy zones changed");
+                self.places_page.emit(PlacesAlbumInput::PrivacyZonesChanged);
+                self.map_album.emit(PlacesAlbumInput::PrivacyZonesChanged);
+                self.events_album.emit(EventsAlbumInput::Refresh);
+            },
+            AppMsg::EditLocation(visuals) => {
+                info!("Setting location of {} items", visuals.len());
+                self.location_editor.emit(LocationEditorInput::Present(visuals));
+            },
+            AppMsg::DatesChanged => {
+                info!("Dates changed");
+                self.end_selection();
+                self.bootstrap.emit(BootstrapInput::Refresh);
+            },
+            AppMsg::EditDateTime(visuals) => {
+                info!("Setting date and time of {} items", visuals.len());
+                self.date_time_editor.emit(DateTimeEditorInput::Present(visuals));
+            },
+            AppMsg::FavouritesChanged => {
+                info!("Favourites changed");
+                self.bootstrap.emit(BootstrapInput::Refresh);
+            },
+            AppMsg::AddToAlbum(visuals) => {
+                info!("Choosing albums for {} items", visuals.len());
+                self.album_chooser.emit(AlbumChooserInput::Present(visuals));
+            },
+            AppMsg::MoveToTrash(visuals) => {
+                info!("Moving {} items to trash", visuals.len());
+                self.move_to_trash.emit(MoveToTrashInput::Trash(visuals));
+            },
+            AppMsg::Trashed(trashed) => {
+                self.end_selection();
+
+                let is_viewing = self.picture_navigation_view
+                    .visible_page()
+                    .and_then(|page| page.tag())
+                    .is_some_and(|tag| tag.as_str() == "picture");
+
+                if is_viewing {
+                    self.picture_navigation_view.pop();
+                }
+
+                if trashed.is_empty() {
+                    self.toast_overlay.add_toast(adw::Toast::new(&fl!("trash-toast", "failed")));
+                    return;
+                }
+
+                sender.input(AppMsg::TrashChanged);
+
+                let toast = adw::Toast::builder()
+                    .title(fl!("trash-toast", count = trashed.len()))
+                    .button_label(fl!("trash-toast", "undo"))
+                    .build();
+
+                // Toasts are also dismissed after the button is clicked, so remember
+                // whether to restore the items or forget them.
+                let is_undone = Rc::new(Cell::new(false));
+
+                {
+                    let sender = sender.clone();
+                    let trashed = trashed.clone();
+                    let is_undone = is_undone.clone();
+                    toast.connect_button_clicked(move |_| {
+                        is_undone.set(true);
+                        sender.input(AppMsg::RestoreFromTrash(trashed.clone()));
+                    });
+                }
+
+                toast.connect_dismissed(move |_| {
+                    if !is_undone.get() {
+                        sender.input(AppMsg::PurgeTrash(trashed.clone()));
+                    }
+                });
+
+                self.toast_overlay.add_toast(toast);
+            },
+            AppMsg::RestoreFromTrash(trashed) => {
+                info!("Restoring {} files from trash", trashed.len());
+                self.move_to_trash.emit(MoveToTrashInput::Restore(trashed));
+            },
+            AppMsg::RestoredFromTrash(count, total) => {
+                info!("Restored {} of {} files from trash", count, total);
+                if count < total {
+                    let toast = adw::Toast::new(&fl!("trash-toast", "restore-failed", count = total - count));
+                    self.toast_overlay.add_toast(toast);
+                }
+                if count > 0 {
+                    sender.input(AppMsg::TrashChanged);
+                }
+            },
+            AppMsg::PurgeTrash(trashed) => {
+                self.move_to_trash.emit(MoveToTrashInput::Purge(trashed));
+            },
+            AppMsg::TrashChanged => {
+                self.bootstrap.emit(BootstrapInput::Refresh);
+                self.user_albums_page.emit(UserAlbumsInput::Refresh);
+                self.user_album.emit(UserAlbumInput::Reload);
+                self.people_page.emit(PeopleAlbumInput::Refresh);
+                sender.input(AppMsg::TagsChanged);
+            },
+            AppMsg::AlbumsChanged => {
+                info!("Albums changed");
+                self.user_albums_page.emit(UserAlbumsInput::Refresh);
+                self.user_album.emit(UserAlbumInput::Reload);
+            },
+            AppMsg::ViewUserAlbum(album_id) => {
+                self.end_selection();
+                info!("Viewing album: {}", album_id);
+                self.user_album.emit(UserAlbumInput::Activate);
+                self.user_album.emit(UserAlbumInput::View(album_id));
+                self.picture_navigation_view.push_by_tag("user_album");
+            },
+            AppMsg::UserAlbumDeleted => {
+                self.picture_navigation_view.pop();
+                self.user_albums_page.emit(UserAlbumsInput::Refresh);
+            },
+            AppMsg::CreateSmartAlbum => {
+                self.smart_album_editor.emit(SmartAlbumEditorInput::Create);
+            },
+            AppMsg::EditSmartAlbum(smart_album_id) => {
+                self.smart_album_editor.emit(SmartAlbumEditorInput::Edit(smart_album_id));
+            },
+            AppMsg::SmartAlbumCreated(smart_album_id) => {
+                info!("Smart album {} created", smart_album_id);
+                self.smart_albums_page.emit(SmartAlbumsInput::Refresh);
+                sender.input(AppMsg::ViewSmartAlbum(smart_album_id));
+            },
+            AppMsg::SmartAlbumChanged(smart_album_id) => {
+                info!("Smart album {} changed", smart_album_id);
+                self.smart_albums_page.emit(SmartAlbumsInput::Refresh);
+                self.smart_album.emit(SmartAlbumInput::Reload);
+            },
+            AppMsg::ViewSmartAlbum(smart_album_id) => {
+                self.end_selection();
+                info!("Viewing smart album: {}", smart_album_id);
+                self.smart_album.emit(SmartAlbumInput::Activate);
+                self.smart_album.emit(SmartAlbumInput::View(smart_album_id));
+                self.picture_navigation_view.push_by_tag("smart_album");
+            },
+            AppMsg::SmartAlbumDeleted => {
+                self.picture_navigation_view.pop();
+                self.smart_albums_page.emit(SmartAlbumsInput::Refresh);
+            },
+            AppMsg::TagsChanged => {
+                info!("Tags changed");
+                self.tags_page.emit(TagsAlbumInput::Refresh);
+                self.smart_albums_page.emit(SmartAlbumsInput::Refresh);
+                self.smart_album.emit(SmartAlbumInput::Refresh);
+            },
+            AppMsg::CaptionsChanged => {
+                info!("Captions changed");
+                self.bootstrap.emit(BootstrapInput::Refresh);
+            },
+            AppMsg::Search(text) => {
+                if text.trim().is_empty() {
+                    return;
+                }
+
+                self.end_selection();
+                info!("Searching for: {}", text);
+                let zones = self.places_repo.all_privacy_zones().unwrap_or_else(|e| {
+                    error!("Failed loading privacy zones: {:?}", e);
+                    places::PrivacyZones::default()
+                });
+
+                let (picture_ids, video_ids) = match self.search_repo.search(&text, &zones) {
+                    Ok(ids) => ids,
+                    Err(e) => {
+                        error!("Failed searching: {:?}", e);
+                        return;
+                    },
+                };
+
+                let filter = AlbumFilter::Any(picture_ids, video_ids);
+                self.album_title.set_label(&fl!("search-results", query = text.trim()));
+                self.album_filter = filter.clone();
+                self.folder_album.emit(AlbumInput::Activate);
+                self.folder_album.emit(AlbumInput::Filter(filter));
+                self.picture_navigation_view.push_by_tag("album");
+            },
+            AppMsg::SelectionMode(enabled) => {
+                self.library.emit(LibraryInput::SelectionMode(enabled));
+                self.videos_page.emit(AlbumInput::SelectionMode(enabled));
+                self.motion_page.emit(AlbumInput::SelectionMode(enabled));
+                self.favourites_page.emit(AlbumInput::SelectionMode(enabled));
+                self.selfies_page.emit(AlbumInput::SelectionMode(enabled));
+                self.folder_album.emit(AlbumInput::SelectionMode(enabled));
+            },
             AppMsg::Adapt(adaptive::Layout::Narrow) => {
                 self.main_navigation.set_collapsed(true);
                 self.main_navigation.set_show_sidebar(false);
@@ -926,6 +1828,12 @@ impl App {
             show_selfies: gio_settings.boolean("show-selfies"),
             face_detection_mode: FaceDetectionMode::from_str(&gio_settings.string("face-detection-mode"))
                 .unwrap_or(FaceDetectionMode::Off),
+            offline_map: Some(gio_settings.string("offline-map-path"))
+                .filter(|path| !path.is_empty())
+                .map(|path| PathBuf::from(path.as_str())),
+            online_map_fallback: gio_settings.boolean("online-map-fallback"),
+            show_on_this_day: gio_settings.boolean("show-on-this-day"),
+            import_ratings_as_favourites: gio_settings.boolean("import-ratings-as-favourites"),
         })
     }
 
@@ -934,8 +1842,21 @@ impl App {
         let gio_settings = gio::Settings::new(APP_ID);
         gio_settings.set_boolean("show-selfies", settings.show_selfies)?;
         gio_settings.set_string("face-detection-mode", settings.face_detection_mode.as_ref())?;
+        let offline_map = settings.offline_map.as_ref()
+            .map(|path| path.to_string_lossy().to_string())
+            .unwrap_or_default();
+        gio_settings.set_string("offline-map-path", &offline_map)?;
+        gio_settings.set_boolean("online-map-fallback", settings.online_map_fallback)?;
+        gio_settings.set_boolean("show-on-this-day", settings.show_on_this_day)?;
+        gio_settings.set_boolean("import-ratings-as-favourites", settings.import_ratings_as_favourites)?;
         Ok(())
     }
+
+    /// Leave selection mode. Toggling the buttons off notifies the albums.
+    fn end_selection(&self) {
+        self.selection_button.set_active(false);
+        self.album_selection_button.set_active(false);
+    }
 }
 
 impl AppWidgets {
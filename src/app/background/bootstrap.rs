@@ -10,14 +10,20 @@ use relm4::{
 };
 
 use crate::config::APP_ID;
+use crate::config::PKGDATADIR;
 use fotema_core::database;
+use fotema_core::favourites;
 use fotema_core::photo;
 use fotema_core::video;
 use fotema_core::visual;
 use fotema_core::people;
+use fotema_core::places;
+use fotema_core::search;
+use fotema_core::tags;
 use fotema_core::PictureId;
 use fotema_core::machine_learning::face_extractor::ExtractMode;
 
+use std::path::PathBuf;
 use std::sync::{Arc, Mutex};
 use std::time::Instant;
 use std::collections::VecDeque;
@@ -27,6 +33,8 @@ use tracing::info;
 use super::{
     load_library::{LoadLibrary, LoadLibraryInput},
 
+    geocode::{Geocode, GeocodeInput, GeocodeOutput},
+
     photo_clean::{PhotoClean, PhotoCleanInput, PhotoCleanOutput},
     photo_detect_faces::{PhotoDetectFaces, PhotoDetectFacesInput, PhotoDetectFacesOutput},
     photo_enrich::{PhotoEnrich, PhotoEnrichInput, PhotoEnrichOutput},
@@ -36,6 +44,7 @@ use super::{
     photo_extract_motion::{PhotoExtractMotion, PhotoExtractMotionInput, PhotoExtractMotionOutput},
 
     video_clean::{VideoClean, VideoCleanInput, VideoCleanOutput},
+    video_detect_faces::{VideoDetectFaces, VideoDetectFacesInput, VideoDetectFacesOutput},
     video_enrich::{VideoEnrich, VideoEnrichInput, VideoEnrichOutput},
     video_scan::{VideoScan, VideoScanInput, VideoScanOutput},
     video_thumbnail::{VideoThumbnail, VideoThumbnailInput, VideoThumbnailOutput},
@@ -63,8 +72,9 @@ pub enum TaskName {
     MotionPhoto,
     Thumbnail(MediaType),
     Clean(MediaType),
-    DetectFaces,
+    DetectFaces(MediaType),
     RecognizeFaces,
+    Geocode,
 }
 
 #[derive(Debug)]
@@ -77,6 +87,12 @@ pub enum BootstrapInput {
     ScanPictureForFaces(PictureId),
     ScanPicturesForFaces,
 
+    /// Queue task for looking up places of new or changed locations.
+    Geocode,
+
+    /// Reload library, such as after dates and times have been corrected.
+    Refresh,
+
     /// A background task has started.
     TaskStarted(TaskName),
 
@@ -120,7 +136,10 @@ pub struct Bootstrap {
 
     photo_extract_motion: Arc<WorkerController<PhotoExtractMotion>>,
 
+    geocode: Arc<WorkerController<Geocode>>,
+
     photo_detect_faces: Arc<WorkerController<PhotoDetectFaces>>,
+    video_detect_faces: Arc<WorkerController<VideoDetectFaces>>,
     photo_recognize_faces: Arc<WorkerController<PhotoRecognizeFaces>>,
 
     /// Pending ordered tasks to process
@@ -179,6 +198,11 @@ impl Bootstrap {
         self.enqueue(Box::new(move || sender.emit(PhotoExtractMotionInput::Start)));
     }
 
+    fn add_task_geocode(&mut self) {
+        let sender = self.geocode.sender().clone();
+        self.enqueue(Box::new(move || sender.emit(GeocodeInput::Start)));
+    }
+
     fn add_task_photo_detect_faces(&mut self) {
         let sender = self.photo_detect_faces.sender().clone();
         let mode = match self.settings_state.read().face_detection_mode {
@@ -191,6 +215,18 @@ impl Bootstrap {
         }
     }
 
+    fn add_task_video_detect_faces(&mut self) {
+        let sender = self.video_detect_faces.sender().clone();
+        let mode = match self.settings_state.read().face_detection_mode {
+            FaceDetectionMode::Off => None,
+            FaceDetectionMode::Mobile => Some(ExtractMode::Lightweight),
+            FaceDetectionMode::Desktop => Some(ExtractMode::Heavyweight),
+        };
+        if let Some(mode) = mode {
+            self.enqueue(Box::new(move || sender.emit(VideoDetectFacesInput::DetectForAllVideos(mode))));
+        }
+    }
+
     fn add_task_photo_detect_faces_for_one(&mut self, picture_id: PictureId) {
         let sender = self.photo_detect_faces.sender().clone();
         self.enqueue(Box::new(move || sender.emit(PhotoDetectFacesInput::DetectForOnePicture(picture_id))));
@@ -271,8 +307,12 @@ impl Worker for Bootstrap {
         )
         .unwrap();
 
+        let places_repo = places::Repository::open(con.clone()).unwrap();
+
+        let search_repo = search::Repository::open(con.clone()).unwrap();
+
         let load_library = LoadLibrary::builder()
-            .detach_worker((visual_repo.clone(), state))
+            .detach_worker((visual_repo.clone(), search_repo, state))
             .detach();
 
         let photo_scan = PhotoScan::builder()
@@ -289,8 +329,11 @@ impl Worker for Bootstrap {
                 VideoScanOutput::Completed => BootstrapInput::TaskCompleted(TaskName::Scan(MediaType::Video), None),
             });
 
+        let favourites_repo = favourites::Repository::open(con.clone()).unwrap();
+        let tags_repo = tags::Repository::open(con.clone()).unwrap();
+
         let photo_enrich = PhotoEnrich::builder()
-            .detach_worker(photo_repo.clone())
+            .detach_worker((photo_repo.clone(), favourites_repo, tags_repo, settings_state.clone()))
             .forward(sender.input_sender(), |msg| match msg {
                 PhotoEnrichOutput::Started => BootstrapInput::TaskStarted(TaskName::Enrich(MediaType::Photo)),
                 PhotoEnrichOutput::Completed(count) => BootstrapInput::TaskCompleted(TaskName::Enrich(MediaType::Photo), Some(count)),
@@ -310,6 +353,13 @@ impl Worker for Bootstrap {
                 PhotoExtractMotionOutput::Completed(count) => BootstrapInput::TaskCompleted(TaskName::MotionPhoto, Some(count)),
             });
 
+        let geocode = Geocode::builder()
+            .detach_worker((PathBuf::from(PKGDATADIR).join("geonames"), places_repo))
+            .forward(sender.input_sender(), |msg| match msg {
+                GeocodeOutput::Started => BootstrapInput::TaskStarted(TaskName::Geocode),
+                GeocodeOutput::Completed(count) => BootstrapInput::TaskCompleted(TaskName::Geocode, Some(count)),
+            });
+
         let photo_thumbnail = PhotoThumbnail::builder()
             .detach_worker((photo_thumbnailer.clone(), photo_repo.clone(), progress_monitor.clone()))
             .forward(sender.input_sender(), |msg| match msg {
@@ -339,10 +389,17 @@ impl Worker for Bootstrap {
             });
 
         let photo_detect_faces = PhotoDetectFaces::builder()
-            .detach_worker((data_dir, people_repo.clone(), progress_monitor.clone()))
+            .detach_worker((data_dir.clone(), people_repo.clone(), progress_monitor.clone()))
             .forward(sender.input_sender(), |msg| match msg {
-                PhotoDetectFacesOutput::Started => BootstrapInput::TaskStarted(TaskName::DetectFaces),
-                PhotoDetectFacesOutput::Completed(count) => BootstrapInput::TaskCompleted(TaskName::DetectFaces, Some(count)),
+                PhotoDetectFacesOutput::Started => BootstrapInput::TaskStarted(TaskName::DetectFaces(MediaType::Photo)),
+                PhotoDetectFacesOutput::Completed(count) => BootstrapInput::TaskCompleted(TaskName::DetectFaces(MediaType::Photo), Some(count)),
+            });
+
+        let video_detect_faces = VideoDetectFaces::builder()
+            .detach_worker((data_dir, cache_dir.clone(), people_repo.clone(), progress_monitor.clone()))
+            .forward(sender.input_sender(), |msg| match msg {
+                VideoDetectFacesOutput::Started => BootstrapInput::TaskStarted(TaskName::DetectFaces(MediaType::Video)),
+                VideoDetectFacesOutput::Completed(count) => BootstrapInput::TaskCompleted(TaskName::DetectFaces(MediaType::Video), Some(count)),
             });
 
         let photo_recognize_faces = PhotoRecognizeFaces::builder()
@@ -362,11 +419,13 @@ impl Worker for Bootstrap {
             photo_enrich: Arc::new(photo_enrich),
             video_enrich:Arc::new(video_enrich),
             photo_extract_motion: Arc::new(photo_extract_motion),
+            geocode: Arc::new(geocode),
             photo_clean: Arc::new(photo_clean),
             video_clean: Arc::new(video_clean),
             photo_thumbnail: Arc::new(photo_thumbnail),
             video_thumbnail: Arc::new(video_thumbnail),
             photo_detect_faces: Arc::new(photo_detect_faces),
+            video_detect_faces: Arc::new(video_detect_faces),
             photo_recognize_faces: Arc::new(photo_recognize_faces),
             pending_tasks: Arc::new(Mutex::new(VecDeque::new())),
             is_running: false,
@@ -382,7 +441,9 @@ impl Worker for Bootstrap {
         bootstrap.add_task_photo_clean();
         bootstrap.add_task_video_clean();
         bootstrap.add_task_photo_extract_motion();
+        bootstrap.add_task_geocode();
         bootstrap.add_task_photo_detect_faces();
+        bootstrap.add_task_video_detect_faces();
         bootstrap.add_task_photo_recognize_faces();
 
         bootstrap
@@ -418,9 +479,26 @@ impl Worker for Bootstrap {
             BootstrapInput::ScanPicturesForFaces => {
                 info!("Queueing task to scan all pictures for faces");
                 self.add_task_photo_detect_faces();
+                self.add_task_video_detect_faces();
                 self.add_task_photo_recognize_faces();
                 self.run_if_idle();
             },
+            BootstrapInput::Geocode => {
+                info!("Queueing task to geocode locations");
+                // Locations are visible in the library, so reload even if no places are found.
+                self.library_stale = true;
+                self.add_task_geocode();
+                self.run_if_idle();
+            },
+            BootstrapInput::Refresh => {
+                if self.is_running {
+                    info!("Refreshing library when running tasks have completed");
+                    self.library_stale = true;
+                } else {
+                    info!("Refreshing library");
+                    self.load_library.emit(LoadLibraryInput::Refresh);
+                }
+            },
             BootstrapInput::TaskStarted(task_name) => {
                 info!("Task started: {:?}", task_name);
                 let _  = sender.output(BootstrapOutput::TaskStarted(task_name));
@@ -0,0 +1,171 @@
+// SPDX-FileCopyrightText: © 2024 David Bliss
+//
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use relm4::prelude::*;
+use relm4::Worker;
+use anyhow::*;
+use std::path::PathBuf;
+use fotema_core::places;
+use fotema_core::time::{self, OffsetSource};
+use chrono::{FixedOffset, Local, NaiveDateTime, Offset, TimeZone};
+
+use tracing::{error, info};
+
+#[derive(Debug)]
+pub enum GeocodeInput {
+    Start,
+}
+
+#[derive(Debug)]
+pub enum GeocodeOutput {
+    // Reverse geocoding started.
+    Started,
+
+    // Reverse geocoding completed
+    Completed(usize),
+}
+
+pub struct Geocode {
+    /// Directory containing GeoNames dataset.
+    dataset_dir: PathBuf,
+
+    // Danger! Don't hold the repo mutex for too long as it blocks viewing images.
+    repo: places::Repository,
+}
+
+impl Geocode {
+
+    fn geocode(
+        dataset_dir: PathBuf,
+        mut repo: places::Repository,
+        sender: &ComponentSender<Geocode>) -> Result<()>
+     {
+        let start = std::time::Instant::now();
+
+        let (pictures, videos) = repo.find_need_geocode()?;
+
+        let count = pictures.len() + videos.len();
+        info!("Found {} photos and videos as candidates for reverse geocoding", count);
+
+        // Short-circuit before sending progress messages to stop
+        // banner from appearing and disappearing.
+        if count == 0 {
+            // Metadata might have been scanned again, so there may still be capture times
+            // to find offsets for.
+            Self::find_capture_offsets(&mut repo)?;
+            let _ = sender.output(GeocodeOutput::Completed(count));
+            return Ok(());
+        }
+
+        let _ = sender.output(GeocodeOutput::Started);
+
+        // Loading the dataset takes a moment, so only do it if there is work to do.
+        let geocoder = places::Geocoder::open(&dataset_dir)?;
+
+        let pictures = pictures
+            .into_iter()
+            .filter_map(|(picture_id, location)| geocoder.lookup(location).map(|place| (picture_id, place)))
+            .collect();
+
+        let videos = videos
+            .into_iter()
+            .filter_map(|(video_id, location)| geocoder.lookup(location).map(|place| (video_id, place)))
+            .collect();
+
+        repo.add_places(pictures, videos)?;
+
+        info!("Reverse geocoded {} photos and videos in {} seconds.", count, start.elapsed().as_secs());
+
+        // Places have time zones, so offsets can only be found after geocoding.
+        Self::find_capture_offsets(&mut repo)?;
+
+        if let Err(e) = sender.output(GeocodeOutput::Completed(count)) {
+            error!("Failed sending GeocodeOutput::Completed: {:?}", e);
+        }
+
+        Ok(())
+    }
+
+    /// Find offsets from UTC for the times pictures and videos were taken, from the
+    /// time zone of the place they were taken. Items with a local time but without a
+    /// place are assumed to have been taken in the computer's time zone.
+    fn find_capture_offsets(repo: &mut places::Repository) -> Result<()> {
+        let (pictures, videos) = repo.find_need_capture_offset()?;
+        let local_videos = repo.find_videos_need_local_capture_offset()?;
+
+        if pictures.is_empty() && videos.is_empty() && local_videos.is_empty() {
+            return Ok(());
+        }
+
+        let pictures = pictures
+            .into_iter()
+            .filter_map(|(picture_id, local, timezone)| {
+                Self::offset_at_local(local, timezone).map(|(offset, source)| (picture_id, offset, source))
+            })
+            .collect::<Vec<_>>();
+
+        let local_videos = local_videos
+            .into_iter()
+            .filter_map(|(video_id, local, timezone)| {
+                Self::offset_at_local(local, timezone).map(|(offset, source)| (video_id, offset, source))
+            });
+
+        let videos = videos
+            .into_iter()
+            .filter_map(|(video_id, utc, timezone)| {
+                time::offset_at_utc(&timezone, utc).map(|offset| (video_id, offset, OffsetSource::Location))
+            })
+            .chain(local_videos)
+            .collect::<Vec<_>>();
+
+        info!("Found capture time offsets for {} photos and {} videos", pictures.len(), videos.len());
+
+        repo.set_capture_offsets(pictures, videos)
+    }
+
+    /// Offset from UTC of a local time in the time zone of a place, or in the
+    /// computer's time zone if the place isn't known.
+    fn offset_at_local(local: NaiveDateTime, timezone: Option<String>) -> Option<(FixedOffset, OffsetSource)> {
+        timezone
+            .and_then(|tz| time::offset_at_local(&tz, local))
+            .map(|offset| (offset, OffsetSource::Location))
+            .or_else(|| {
+                let offset = Local.offset_from_local_datetime(&local).earliest()?;
+                Some((offset.fix(), OffsetSource::Assumed))
+            })
+    }
+}
+
+impl Worker for Geocode {
+    type Init = (PathBuf, places::Repository);
+    type Input = GeocodeInput;
+    type Output = GeocodeOutput;
+
+    fn init((dataset_dir, repo): Self::Init, _sender: ComponentSender<Self>) -> Self  {
+        Geocode {
+            dataset_dir,
+            repo,
+        }
+    }
+
+
+    fn update(&mut self, msg: Self::Input, sender: ComponentSender<Self>) {
+        match msg {
+            GeocodeInput::Start => {
+                info!("Reverse geocoding photos and videos...");
+                let dataset_dir = self.dataset_dir.clone();
+                let repo = self.repo.clone();
+
+                rayon::spawn(move || {
+                    if let Err(e) = Geocode::geocode(dataset_dir, repo, &sender) {
+                        error!("Failed to reverse geocode: {}", e);
+
+                        // Must still complete so that later background tasks run.
+                        let _ = sender.output(GeocodeOutput::Completed(0));
+                    }
+                });
+            }
+        };
+    }
+}
@@ -5,6 +5,7 @@
 use relm4::prelude::*;
 use relm4::Worker;
 use crate::app::SharedState;
+use fotema_core::search;
 use fotema_core::visual::Repository;
 use fotema_core::Visual;
 use std::sync::Arc;
@@ -18,16 +19,17 @@ pub enum LoadLibraryInput {
 
 pub struct LoadLibrary {
     repo: Repository,
+    search_repo: search::Repository,
     state: SharedState,
 }
 
 impl Worker for LoadLibrary {
-    type Init = (Repository, SharedState);
+    type Init = (Repository, search::Repository, SharedState);
     type Input = LoadLibraryInput;
     type Output = ();
 
-    fn init((repo, state): Self::Init, _sender: ComponentSender<Self>) -> Self {
-        Self { repo, state }
+    fn init((repo, search_repo, state): Self::Init, _sender: ComponentSender<Self>) -> Self {
+        Self { repo, search_repo, state }
     }
 
     fn update(&mut self, msg: LoadLibraryInput, _sender: ComponentSender<Self>) {
@@ -44,7 +46,7 @@ impl Worker for LoadLibrary {
 }
 
 impl LoadLibrary {
-    fn load(&self) -> Result<()> {
+    fn load(&mut self) -> Result<()> {
         let mut all = self
             .repo
             .all()?
@@ -52,6 +54,11 @@ impl LoadLibrary {
             .map(Arc::new)
             .collect::<Vec<Arc<Visual>>>();
 
+        // Search results should match what is shown, so index whatever was loaded.
+        if let Err(e) = self.search_repo.update_index(&all) {
+            error!("Failed updating search index: {}", e);
+        }
+
         let mut index = self.state.write();
         index.clear();
         index.append(&mut all);
@@ -3,7 +3,9 @@
 // SPDX-License-Identifier: GPL-3.0-or-later
 
 pub mod bootstrap;
+pub mod geocode;
 pub mod load_library;
+pub mod move_to_trash;
 
 pub mod photo_clean;
 pub mod photo_detect_faces;
@@ -14,6 +16,7 @@ pub mod photo_scan;
 pub mod photo_thumbnail;
 
 pub mod video_clean;
+pub mod video_detect_faces;
 pub mod video_enrich;
 pub mod video_scan;
 pub mod video_thumbnail;
@@ -0,0 +1,75 @@
+// SPDX-FileCopyrightText: © 2024 David Bliss
+//
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use relm4::prelude::*;
+use relm4::Worker;
+
+use fotema_core::trash::{Trash, Trashed};
+use fotema_core::Visual;
+use tracing::{error, info};
+
+use std::sync::Arc;
+
+#[derive(Debug)]
+pub enum MoveToTrashInput {
+    /// Move files of items to the trash.
+    Trash(Vec<Arc<Visual>>),
+
+    /// Restore files from the trash to their original paths.
+    Restore(Trashed),
+
+    /// Forget items that can no longer be restored.
+    Purge(Trashed),
+}
+
+#[derive(Debug)]
+pub enum MoveToTrashOutput {
+    // Items have been moved to the trash.
+    Trashed(Trashed),
+
+    // Files have been restored from the trash. Has count of restored files,
+    // and count of files that should have been restored.
+    Restored(usize, usize),
+}
+
+pub struct MoveToTrash {
+    trash: Trash,
+}
+
+impl Worker for MoveToTrash {
+    type Init = Trash;
+    type Input = MoveToTrashInput;
+    type Output = MoveToTrashOutput;
+
+    fn init(trash: Self::Init, _sender: ComponentSender<Self>) -> Self {
+        Self { trash }
+    }
+
+    fn update(&mut self, msg: Self::Input, sender: ComponentSender<Self>) {
+        match msg {
+            MoveToTrashInput::Trash(visuals) => {
+                info!("Moving {} items to trash", visuals.len());
+                let trashed = self.trash.trash(&visuals).unwrap_or_else(|e| {
+                    error!("Failed moving to trash: {:?}", e);
+                    Trashed::default()
+                });
+                let _ = sender.output(MoveToTrashOutput::Trashed(trashed));
+            },
+            MoveToTrashInput::Restore(trashed) => {
+                info!("Restoring {} files from trash", trashed.len());
+                let count = self.trash.restore(&trashed).unwrap_or_else(|e| {
+                    error!("Failed restoring from trash: {:?}", e);
+                    0
+                });
+                let _ = sender.output(MoveToTrashOutput::Restored(count, trashed.len()));
+            },
+            MoveToTrashInput::Purge(trashed) => {
+                info!("Purging {} files from trash", trashed.len());
+                if let Err(e) = self.trash.purge(&trashed) {
+                    error!("Failed purging from trash: {:?}", e);
+                }
+            },
+        };
+    }
+}
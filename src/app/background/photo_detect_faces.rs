@@ -19,6 +19,7 @@ use fotema_core::people;
 use fotema_core::photo::PictureId;
 
 use crate::app::components::progress_monitor::{
+    MediaType,
     ProgressMonitor,
     ProgressMonitorInput,
     TaskName,
@@ -90,7 +91,7 @@ impl PhotoDetectFaces {
 
         let _ = sender.output(PhotoDetectFacesOutput::Started);
 
-        self.progress_monitor.emit(ProgressMonitorInput::Start(TaskName::DetectFaces, count));
+        self.progress_monitor.emit(ProgressMonitorInput::Start(TaskName::DetectFaces(MediaType::Photo), count));
 
         // Must build face extractor here rather than in Boostrap's init function because
         // the face detection models will be downloaded on creation and that mustn't happen
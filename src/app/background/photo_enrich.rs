@@ -6,7 +6,12 @@ use relm4::prelude::*;
 use relm4::Worker;
 use rayon::prelude::*;
 use anyhow::*;
+use fotema_core::favourites;
 use fotema_core::photo::metadata;
+use fotema_core::tags;
+use fotema_core::PictureId;
+
+use crate::app::SettingsState;
 
 use tracing::{error, info};
 
@@ -27,12 +32,21 @@ pub enum PhotoEnrichOutput {
 pub struct PhotoEnrich {
     // Danger! Don't hold the repo mutex for too long as it blocks viewing images.
     repo: fotema_core::photo::Repository,
+
+    favourites_repo: favourites::Repository,
+
+    tags_repo: tags::Repository,
+
+    settings_state: SettingsState,
 }
 
 impl PhotoEnrich {
 
     fn enrich(
         mut repo: fotema_core::photo::Repository,
+        mut favourites_repo: favourites::Repository,
+        mut tags_repo: tags::Repository,
+        import_ratings: bool,
         sender: &ComponentSender<PhotoEnrich>) -> Result<()>
      {
         let start = std::time::Instant::now();
@@ -51,7 +65,7 @@ impl PhotoEnrich {
 
         let _ = sender.output(PhotoEnrichOutput::Started);
 
-        let metadatas = unprocessed
+        let metadatas: Vec<_> = unprocessed
             .par_iter()
             .flat_map(|pic| {
                 let result = metadata::from_path(&pic.path);
@@ -59,8 +73,28 @@ impl PhotoEnrich {
             })
             .collect();
 
+        // Five stars from another photo manager is as good as a favourite.
+        let rated: Vec<PictureId> = metadatas
+            .iter()
+            .filter(|(_, m)| import_ratings && m.rating == Some(5))
+            .map(|(picture_id, _)| *picture_id)
+            .collect();
+
+        // Every picture is included so keywords removed from metadata are removed from tags.
+        let keywords: Vec<(PictureId, Vec<String>)> = metadatas
+            .iter()
+            .map(|(picture_id, m)| (*picture_id, m.keywords.clone()))
+            .collect();
+
         repo.add_metadatas(metadatas)?;
 
+        tags_repo.add_metadata_tags(keywords)?;
+
+        if !rated.is_empty() {
+            info!("Adding {} five star photos to favourites", rated.len());
+            favourites_repo.add_rated_pictures(rated)?;
+        }
+
         info!("Extracted {} photo metadatas in {} seconds.", count, start.elapsed().as_secs());
 
         if let Err(e) = sender.output(PhotoEnrichOutput::Completed(count)) {
@@ -72,13 +106,16 @@ impl PhotoEnrich {
 }
 
 impl Worker for PhotoEnrich {
-    type Init = fotema_core::photo::Repository;
+    type Init = (fotema_core::photo::Repository, favourites::Repository, tags::Repository, SettingsState);
     type Input = PhotoEnrichInput;
     type Output = PhotoEnrichOutput;
 
-    fn init(repo: Self::Init, _sender: ComponentSender<Self>) -> Self  {
+    fn init((repo, favourites_repo, tags_repo, settings_state): Self::Init, _sender: ComponentSender<Self>) -> Self  {
         PhotoEnrich {
             repo,
+            favourites_repo,
+            tags_repo,
+            settings_state,
         }
     }
 
@@ -88,10 +125,13 @@ impl Worker for PhotoEnrich {
             PhotoEnrichInput::Start => {
                 info!("Enriching photos...");
                 let repo = self.repo.clone();
+                let favourites_repo = self.favourites_repo.clone();
+                let tags_repo = self.tags_repo.clone();
+                let import_ratings = self.settings_state.read().import_ratings_as_favourites;
 
                 // Avoid runtime panic from calling block_on
                 rayon::spawn(move || {
-                    if let Err(e) = PhotoEnrich::enrich(repo, &sender) {
+                    if let Err(e) = PhotoEnrich::enrich(repo, favourites_repo, tags_repo, import_ratings, &sender) {
                         error!("Failed to update previews: {}", e);
                     }
                 });
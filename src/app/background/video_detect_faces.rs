@@ -0,0 +1,146 @@
+// SPDX-FileCopyrightText: © 2024 David Bliss
+//
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use relm4::prelude::*;
+use relm4::Worker;
+use relm4::Reducer;
+use rayon::prelude::*;
+use anyhow::*;
+use std::sync::Arc;
+use std::result::Result::Ok;
+use std::path::PathBuf;
+use tracing::{error, info};
+
+use fotema_core::machine_learning::face_extractor::FaceExtractor;
+use fotema_core::machine_learning::face_extractor::ExtractMode;
+use fotema_core::machine_learning::face_recognizer::FaceRecognizer;
+use fotema_core::people;
+use fotema_core::video::VideoId;
+
+use crate::app::components::progress_monitor::{
+    MediaType,
+    ProgressMonitor,
+    ProgressMonitorInput,
+    TaskName,
+};
+
+
+#[derive(Debug)]
+pub enum VideoDetectFacesInput {
+    DetectForAllVideos(ExtractMode),
+}
+
+#[derive(Debug)]
+pub enum VideoDetectFacesOutput {
+    // Face detection has started.
+    Started,
+
+    // Face detection has completed
+    Completed(usize),
+
+}
+
+#[derive(Clone)]
+pub struct VideoDetectFaces {
+    /// Base directory for storing video faces
+    base_dir: PathBuf,
+
+    /// Cache directory for the face recognition model
+    cache_dir: PathBuf,
+
+    // Danger! Don't hold the repo mutex for too long as it blocks viewing images.
+    repo: people::Repository,
+
+    progress_monitor: Arc<Reducer<ProgressMonitor>>,
+}
+
+impl VideoDetectFaces {
+
+    fn detect_for_all(&self, sender: ComponentSender<Self>, extract_mode: ExtractMode) -> Result<()> {
+        let start = std::time::Instant::now();
+
+        let unprocessed: Vec<(VideoId, PathBuf, Option<i32>)> = self.repo
+            .find_need_video_face_scan()?
+            .into_iter()
+            .filter(|(_, path, _)| path.exists())
+            .collect();
+
+        let count = unprocessed.len();
+        info!("Found {} videos as candidates for face detection", count);
+
+        // Short-circuit before sending progress messages to stop
+        // banner from appearing and disappearing.
+        if count == 0 {
+            let _ = sender.output(VideoDetectFacesOutput::Completed(count));
+            return Ok(());
+        }
+
+        let _ = sender.output(VideoDetectFacesOutput::Started);
+
+        self.progress_monitor.emit(ProgressMonitorInput::Start(TaskName::DetectFaces(MediaType::Video), count));
+
+        // Must build face extractor here rather than in Boostrap's init function because
+        // the face detection models will be downloaded on creation and that mustn't happen
+        // on the main thread.
+        let extractor = FaceExtractor::build(&self.base_dir)?;
+
+        // Faces in different frames are matched with face recognition,
+        // so the recognizer doesn't need any people.
+        let recognizer = FaceRecognizer::build(&self.cache_dir, vec![])?;
+
+        unprocessed
+            .par_iter()
+            .for_each(|(video_id, path, rotation)| {
+                let mut repo = self.repo.clone();
+
+                let result = extractor.extract_video_faces(video_id, path, *rotation, extract_mode, &recognizer)
+                    .and_then(|faces| repo.clone().add_video_face_scans(video_id, &faces));
+
+                if result.is_err() {
+                    error!("Failed detecting faces: Video path: {:?}. Error: {:?}", path, result);
+                    let _ = repo.mark_video_face_scan_broken(video_id);
+                }
+
+                self.progress_monitor.emit(ProgressMonitorInput::Advance);
+            });
+
+        info!("Detected faces in {} videos in {} seconds.", count, start.elapsed().as_secs());
+
+        self.progress_monitor.emit(ProgressMonitorInput::Complete);
+
+        let _ = sender.output(VideoDetectFacesOutput::Completed(count));
+
+        Ok(())
+    }
+}
+
+impl Worker for VideoDetectFaces {
+    type Init = (PathBuf, PathBuf, people::Repository, Arc<Reducer<ProgressMonitor>>);
+    type Input = VideoDetectFacesInput;
+    type Output = VideoDetectFacesOutput;
+
+    fn init((base_dir, cache_dir, repo, progress_monitor): Self::Init, _sender: ComponentSender<Self>) -> Self  {
+        VideoDetectFaces {
+            base_dir,
+            cache_dir,
+            repo,
+            progress_monitor,
+        }
+    }
+
+    fn update(&mut self, msg: Self::Input, sender: ComponentSender<Self>) {
+        match msg {
+            VideoDetectFacesInput::DetectForAllVideos(extract_mode) => {
+                info!("Extracting faces for all videos...");
+                let this = self.clone();
+
+                rayon::spawn(move || {
+                    if let Err(e) = this.detect_for_all(sender, extract_mode) {
+                        error!("Failed to extract video faces: {}", e);
+                    }
+                });
+            },
+        };
+    }
+}
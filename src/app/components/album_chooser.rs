@@ -0,0 +1,218 @@
+// SPDX-FileCopyrightText: © 2024 David Bliss
+//
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use relm4::{adw, ComponentParts, ComponentSender, RelmWidgetExt, SimpleComponent};
+use relm4::adw::prelude::*;
+use relm4::gtk;
+
+use fotema_core::albums::{self, AlbumId, AlbumItem};
+use fotema_core::Visual;
+
+use tracing::{error, info};
+
+use std::sync::Arc;
+
+use crate::fl;
+
+/// Dialog for adding photos and videos to albums curated by the user, or
+/// taking them out again. Albums can also be created here.
+pub struct AlbumChooserDialog {
+    repo: albums::Repository,
+    parent: adw::ApplicationWindow,
+    dialog: adw::Dialog,
+
+    /// Photos and videos to add to or remove from albums.
+    visuals: Vec<Arc<Visual>>,
+
+    albums_list: gtk::ListBox,
+    new_album_row: adw::EntryRow,
+}
+
+#[derive(Debug)]
+pub enum AlbumChooserInput {
+    /// Show dialog for choosing albums for photos and videos.
+    Present(Vec<Arc<Visual>>),
+
+    /// Add items to an album, or take them out of it.
+    Toggle(AlbumId, bool),
+
+    /// Create an album and add items to it.
+    Create(String),
+}
+
+#[derive(Debug)]
+pub enum AlbumChooserOutput {
+    /// Albums have been created or items of albums have changed.
+    AlbumsChanged,
+}
+
+#[relm4::component(pub)]
+impl SimpleComponent for AlbumChooserDialog {
+    type Init = (albums::Repository, adw::ApplicationWindow);
+    type Input = AlbumChooserInput;
+    type Output = AlbumChooserOutput;
+
+    view! {
+        adw::Dialog {
+            #[watch]
+            set_title: &fl!("album-chooser-dialog", count = model.visuals.len()),
+            set_content_width: 400,
+
+            #[wrap(Some)]
+            set_child = &adw::ToolbarView {
+                add_top_bar = &adw::HeaderBar,
+
+                #[wrap(Some)]
+                set_content = &gtk::ScrolledWindow {
+                    set_hscrollbar_policy: gtk::PolicyType::Never,
+                    set_propagate_natural_height: true,
+                    set_max_content_height: 480,
+
+                    gtk::Box {
+                        set_orientation: gtk::Orientation::Vertical,
+                        set_spacing: 12,
+                        set_margin_all: 12,
+
+                        gtk::ListBox {
+                            set_selection_mode: gtk::SelectionMode::None,
+                            add_css_class: "boxed-list",
+
+                            #[local_ref]
+                            new_album_row -> adw::EntryRow {
+                                set_title: &fl!("album-chooser-dialog", "new-album"),
+                                set_show_apply_button: true,
+                                connect_apply[sender] => move |row| {
+                                    sender.input(AlbumChooserInput::Create(row.text().to_string()));
+                                },
+                            },
+                        },
+
+                        #[local_ref]
+                        albums_list -> gtk::ListBox {
+                            set_selection_mode: gtk::SelectionMode::None,
+                            add_css_class: "boxed-list",
+                        },
+                    },
+                },
+            },
+        }
+    }
+
+    fn init(
+        (repo, parent): Self::Init,
+        dialog: Self::Root,
+        sender: ComponentSender<Self>,
+    ) -> ComponentParts<Self> {
+        let albums_list = gtk::ListBox::new();
+        let new_album_row = adw::EntryRow::new();
+
+        let model = Self {
+            repo,
+            parent,
+            dialog: dialog.clone(),
+            visuals: Vec::new(),
+            albums_list: albums_list.clone(),
+            new_album_row: new_album_row.clone(),
+        };
+
+        let widgets = view_output!();
+
+        ComponentParts { model, widgets }
+    }
+
+    fn update(&mut self, msg: Self::Input, sender: ComponentSender<Self>) {
+        match msg {
+            AlbumChooserInput::Present(visuals) => {
+                self.visuals = visuals;
+                self.new_album_row.set_text("");
+                self.update_albums(&sender);
+                self.dialog.present(Some(&self.parent));
+            },
+            AlbumChooserInput::Toggle(album_id, add) => {
+                let items = self.items();
+                info!("{} {} items for album {}", if add { "Adding" } else { "Removing" }, items.len(), album_id);
+
+                let result = if add {
+                    self.repo.add_items(album_id, items)
+                } else {
+                    self.repo.remove_items(album_id, items)
+                };
+
+                if let Err(e) = result {
+                    error!("Failed changing album: {:?}", e);
+                    return;
+                }
+
+                let _ = sender.output(AlbumChooserOutput::AlbumsChanged);
+            },
+            AlbumChooserInput::Create(name) => {
+                let name = name.trim();
+                if name.is_empty() {
+                    return;
+                }
+
+                info!("Creating album {} with {} items", name, self.visuals.len());
+
+                let items = self.items();
+                let result = self.repo.create(name)
+                    .and_then(|album_id| self.repo.add_items(album_id, items));
+
+                if let Err(e) = result {
+                    error!("Failed creating album: {:?}", e);
+                    return;
+                }
+
+                self.new_album_row.set_text("");
+                self.update_albums(&sender);
+                let _ = sender.output(AlbumChooserOutput::AlbumsChanged);
+            },
+        }
+    }
+}
+
+impl AlbumChooserDialog {
+    fn items(&self) -> Vec<AlbumItem> {
+        self.visuals.iter().map(|v| AlbumItem::from(v.as_ref())).collect()
+    }
+
+    fn update_albums(&mut self, sender: &ComponentSender<Self>) {
+        let albums = self.repo.all().unwrap_or_else(|e| {
+            error!("Failed loading albums: {:?}", e);
+            Vec::new()
+        });
+
+        self.albums_list.remove_all();
+        self.albums_list.set_visible(!albums.is_empty());
+
+        for album in albums {
+            // Checked if every item is already in the album.
+            let is_in_album = !self.visuals.is_empty() && self.visuals
+                .iter()
+                .all(|v| album.items.iter().any(|item| item.is_for(v)));
+
+            let check = gtk::CheckButton::builder()
+                .active(is_in_album)
+                .valign(gtk::Align::Center)
+                .build();
+
+            {
+                let sender = sender.clone();
+                let album_id = album.album_id;
+                check.connect_toggled(move |check| {
+                    sender.input(AlbumChooserInput::Toggle(album_id, check.is_active()));
+                });
+            }
+
+            let row = adw::ActionRow::builder()
+                .title(&album.name)
+                .use_markup(false)
+                .subtitle(fl!("user-albums-page", "count", count = album.items.len()))
+                .activatable_widget(&check)
+                .build();
+
+            row.add_prefix(&check);
+            self.albums_list.append(&row);
+        }
+    }
+}
@@ -3,6 +3,7 @@
 // SPDX-License-Identifier: GPL-3.0-or-later
 
 use gtk::prelude::OrientableExt;
+use fotema_core::Visual;
 use fotema_core::VisualId;
 use fotema_core::YearMonth;
 use fotema_core::visual::model::PictureOrientation;
@@ -22,6 +23,7 @@ use crate::app::adaptive;
 use crate::app::SharedState;
 use crate::app::ActiveView;
 use crate::app::ViewName;
+use crate::fl;
 use super::album_filter::AlbumFilter;
 
 use tracing::{debug, info};
@@ -57,6 +59,25 @@ pub enum AlbumInput {
 
     // Scroll offset, in pixels.
     ScrollOffset(f64),
+
+    /// Enter or leave selection mode, where activating an item toggles whether it
+    /// is selected instead of viewing it.
+    SelectionMode(bool),
+
+    /// Set location of selected items.
+    EditLocation,
+
+    /// Correct date and time of selected items.
+    EditDateTime,
+
+    /// Add selected items to albums curated by the user.
+    AddToAlbum,
+
+    /// Move selected items to the trash.
+    MoveToTrash,
+
+    /// Change selected items of an album curated by the user.
+    EditAlbum(AlbumEdit),
 }
 
 #[derive(Debug)]
@@ -66,6 +87,38 @@ pub enum AlbumOutput {
 
     // Scroll offset, in pixels.
     ScrollOffset(f64),
+
+    /// User wants to set the location of the selected photos and videos.
+    EditLocation(Vec<Arc<Visual>>),
+
+    /// User wants to correct the date and time of the selected photos and videos.
+    EditDateTime(Vec<Arc<Visual>>),
+
+    /// User wants to add the selected photos and videos to albums.
+    AddToAlbum(Vec<Arc<Visual>>),
+
+    /// User wants to move the selected photos and videos to the trash.
+    MoveToTrash(Vec<Arc<Visual>>),
+
+    /// User wants to change the selected photos and videos of an album curated by the user.
+    /// Only for albums with a UserAlbum filter.
+    EditAlbum(AlbumEdit, Vec<Arc<Visual>>),
+}
+
+/// Changes to items of an album curated by the user.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AlbumEdit {
+    /// Take items out of album.
+    Remove,
+
+    /// Move items to the start of album.
+    MoveToStart,
+
+    /// Move items to the end of album.
+    MoveToEnd,
+
+    /// Use item to represent album.
+    SetCover,
 }
 
 #[derive(Debug)]
@@ -74,6 +127,16 @@ struct PhotoGridItem {
 
     // Length of thumbnail edge to allow for resizing when layout changes.
     edge_length: I32Binding,
+
+    // If album is in selection mode, so selection check box must be shown.
+    selection_mode: BoolBinding,
+
+    // If item has been selected in selection mode.
+    selected: bool,
+
+    // Selection check box of bound widget, so selection can be toggled
+    // without rebinding.
+    selected_check: Option<gtk::CheckButton>,
 }
 
 struct PhotoGridItemWidgets {
@@ -82,6 +145,7 @@ struct PhotoGridItemWidgets {
     motion_type_icon: gtk::Image,
     duration_overlay: gtk::Frame,
     duration_label: gtk::Label,
+    selected_check: gtk::CheckButton,
 
     // If the gtk::Picture has been bound to edge_length.
     is_bound: bool,
@@ -96,6 +160,17 @@ impl RelmGridItem for PhotoGridItem {
             root = gtk::AspectFrame {
                 gtk::Frame {
                     gtk::Overlay {
+                        // Clicks toggle selection through grid view activation.
+                        #[name(selected_check)]
+                        add_overlay = &gtk::CheckButton {
+                            set_halign: gtk::Align::Start,
+                            set_valign: gtk::Align::Start,
+                            set_margin_all: 8,
+                            set_can_target: false,
+                            set_visible: false,
+                            add_css_class: "selection-mode",
+                        },
+
                         #[name(status_overlay)]
                         add_overlay =  &gtk::Frame {
                             set_halign: gtk::Align::End,
@@ -144,6 +219,7 @@ impl RelmGridItem for PhotoGridItem {
             motion_type_icon,
             duration_overlay,
             duration_label,
+            selected_check,
             is_bound: false,
         };
 
@@ -162,9 +238,13 @@ impl RelmGridItem for PhotoGridItem {
         if !widgets.is_bound {
             widgets.picture.add_write_only_binding(&self.edge_length, "width-request");
             widgets.picture.add_write_only_binding(&self.edge_length, "height-request");
+            widgets.selected_check.add_write_only_binding(&self.selection_mode, "visible");
             widgets.is_bound = true;
         }
 
+        widgets.selected_check.set_active(self.selected);
+        self.selected_check = Some(widgets.selected_check.clone());
+
         if self.visual.thumbnail_path.as_ref().is_some_and(|x| x.exists()) {
             widgets.picture.set_filename(self.visual.thumbnail_path.clone());
 
@@ -213,6 +293,8 @@ impl RelmGridItem for PhotoGridItem {
         widgets.status_overlay.set_visible(false);
         widgets.duration_overlay.set_visible(false);
         widgets.duration_label.set_label("");
+        widgets.selected_check.set_active(false);
+        self.selected_check = None;
 
         // clear orientation transformation css classes
         for orient in PictureOrientation::iter() {
@@ -228,6 +310,12 @@ pub struct Album {
     photo_grid: TypedGridView<PhotoGridItem, gtk::SingleSelection>,
     filter: AlbumFilter,
     edge_length: I32Binding,
+
+    // Selection mode for showing selection check boxes on items.
+    selection_mode: BoolBinding,
+
+    // Count of selected items.
+    selected_count: usize,
 }
 
 #[relm4::component(pub)]
@@ -237,27 +325,130 @@ impl SimpleComponent for Album {
     type Output = AlbumOutput;
 
     view! {
-        gtk::ScrolledWindow {
-            set_vexpand: true,
+        gtk::Box {
+            set_orientation: gtk::Orientation::Vertical,
+
+            gtk::ScrolledWindow {
+                set_vexpand: true,
 
-            #[local_ref]
-            grid_view -> gtk::GridView {
-                set_orientation: gtk::Orientation::Vertical,
-                set_single_click_activate: true,
+                #[local_ref]
+                grid_view -> gtk::GridView {
+                    set_orientation: gtk::Orientation::Vertical,
+                    set_single_click_activate: true,
 
-                connect_activate[sender] => move |_, idx| {
-                    sender.input(AlbumInput::Selected(idx))
+                    connect_activate[sender] => move |_, idx| {
+                        sender.input(AlbumInput::Selected(idx))
+                    },
                 },
-            },
 
-            #[wrap(Some)]
-            set_vadjustment = &gtk::Adjustment {
-                // Emit scroll events so PersonAlbum can determine when to hide avatar.
-                // FIXME maybe just emit one event at a boundary, instead of emitting an
-                // event for every scroll?
-                connect_value_changed[sender] => move |v| sender.input(AlbumInput::ScrollOffset(v.value())),
+                #[wrap(Some)]
+                set_vadjustment = &gtk::Adjustment {
+                    // Emit scroll events so PersonAlbum can determine when to hide avatar.
+                    // FIXME maybe just emit one event at a boundary, instead of emitting an
+                    // event for every scroll?
+                    connect_value_changed[sender] => move |v| sender.input(AlbumInput::ScrollOffset(v.value())),
+                },
             },
 
+            // Actions for selected items.
+            gtk::ActionBar {
+                #[watch]
+                set_revealed: model.selection_mode.value(),
+
+                #[wrap(Some)]
+                set_center_widget = &gtk::Label {
+                    #[watch]
+                    set_label: &fl!("album-selection", count = model.selected_count),
+                },
+
+                // Actions only for albums curated by the user.
+                pack_start = &gtk::Box {
+                    set_orientation: gtk::Orientation::Horizontal,
+
+                    #[watch]
+                    set_visible: model.filter.is_ordered(),
+
+                    gtk::Button {
+                        set_icon_name: "list-remove-symbolic",
+                        set_tooltip_text: Some(&fl!("album-selection", "remove-from-album")),
+
+                        #[watch]
+                        set_sensitive: model.selected_count > 0,
+
+                        connect_clicked => AlbumInput::EditAlbum(AlbumEdit::Remove),
+                    },
+
+                    gtk::Button {
+                        set_icon_name: "go-top-symbolic",
+                        set_tooltip_text: Some(&fl!("album-selection", "move-to-start")),
+
+                        #[watch]
+                        set_sensitive: model.selected_count > 0,
+
+                        connect_clicked => AlbumInput::EditAlbum(AlbumEdit::MoveToStart),
+                    },
+
+                    gtk::Button {
+                        set_icon_name: "go-bottom-symbolic",
+                        set_tooltip_text: Some(&fl!("album-selection", "move-to-end")),
+
+                        #[watch]
+                        set_sensitive: model.selected_count > 0,
+
+                        connect_clicked => AlbumInput::EditAlbum(AlbumEdit::MoveToEnd),
+                    },
+
+                    gtk::Button {
+                        set_icon_name: "image-x-generic-symbolic",
+                        set_tooltip_text: Some(&fl!("album-selection", "set-cover")),
+
+                        #[watch]
+                        set_sensitive: model.selected_count == 1,
+
+                        connect_clicked => AlbumInput::EditAlbum(AlbumEdit::SetCover),
+                    },
+                },
+
+                pack_end = &gtk::Button {
+                    set_icon_name: "user-trash-symbolic",
+                    set_tooltip_text: Some(&fl!("album-selection", "move-to-trash")),
+
+                    #[watch]
+                    set_sensitive: model.selected_count > 0,
+
+                    connect_clicked => AlbumInput::MoveToTrash,
+                },
+
+                pack_end = &gtk::Button {
+                    set_icon_name: "list-add-symbolic",
+                    set_tooltip_text: Some(&fl!("album-selection", "add-to-album")),
+
+                    #[watch]
+                    set_sensitive: model.selected_count > 0,
+
+                    connect_clicked => AlbumInput::AddToAlbum,
+                },
+
+                pack_end = &gtk::Button {
+                    set_icon_name: "mark-location-symbolic",
+                    set_tooltip_text: Some(&fl!("album-selection", "edit-location")),
+
+                    #[watch]
+                    set_sensitive: model.selected_count > 0,
+
+                    connect_clicked => AlbumInput::EditLocation,
+                },
+
+                pack_end = &gtk::Button {
+                    set_icon_name: "x-office-calendar-symbolic",
+                    set_tooltip_text: Some(&fl!("album-selection", "edit-date-time")),
+
+                    #[watch]
+                    set_sensitive: model.selected_count > 0,
+
+                    connect_clicked => AlbumInput::EditDateTime,
+                },
+            },
         }
     }
 
@@ -276,6 +467,8 @@ impl SimpleComponent for Album {
             photo_grid,
             filter,
             edge_length: I32Binding::new(NARROW_EDGE_LENGTH),
+            selection_mode: BoolBinding::new(false),
+            selected_count: 0,
         };
 
         model.update_filter();
@@ -302,8 +495,29 @@ impl SimpleComponent for Album {
                 }
             }
             AlbumInput::Filter(filter) => {
+                // Items must be reloaded to go into or out of a manual order.
+                let rearrange = filter.is_ordered() || self.filter.is_ordered();
                 self.filter = filter;
+                self.clear_selection();
                 self.update_filter();
+                if rearrange {
+                    self.refresh();
+                }
+            }
+            AlbumInput::Selected(index) if self.selection_mode.value() => {
+                if let Some(item) = self.photo_grid.get_visible(index) {
+                    let mut item = item.borrow_mut();
+                    item.selected = !item.selected;
+                    if let Some(ref check) = item.selected_check {
+                        check.set_active(item.selected);
+                    }
+
+                    if item.selected {
+                        self.selected_count += 1;
+                    } else {
+                        self.selected_count = self.selected_count.saturating_sub(1);
+                    }
+                }
             }
             AlbumInput::Selected(index) => {
                 // Albums are filters so must use get_visible(...) over get(...), otherwise
@@ -336,6 +550,42 @@ impl SimpleComponent for Album {
             AlbumInput::ScrollOffset(offset) => {
                 let _ = sender.output(AlbumOutput::ScrollOffset(offset));
             },
+            AlbumInput::SelectionMode(enabled) => {
+                self.selection_mode.set_value(enabled);
+                if !enabled {
+                    self.clear_selection();
+                }
+            },
+            AlbumInput::EditLocation => {
+                let visuals = self.selected_visuals();
+                if !visuals.is_empty() {
+                    let _ = sender.output(AlbumOutput::EditLocation(visuals));
+                }
+            },
+            AlbumInput::EditDateTime => {
+                let visuals = self.selected_visuals();
+                if !visuals.is_empty() {
+                    let _ = sender.output(AlbumOutput::EditDateTime(visuals));
+                }
+            },
+            AlbumInput::AddToAlbum => {
+                let visuals = self.selected_visuals();
+                if !visuals.is_empty() {
+                    let _ = sender.output(AlbumOutput::AddToAlbum(visuals));
+                }
+            },
+            AlbumInput::MoveToTrash => {
+                let visuals = self.selected_visuals();
+                if !visuals.is_empty() {
+                    let _ = sender.output(AlbumOutput::MoveToTrash(visuals));
+                }
+            },
+            AlbumInput::EditAlbum(edit) => {
+                let visuals = self.selected_visuals();
+                if !visuals.is_empty() {
+                    let _ = sender.output(AlbumOutput::EditAlbum(edit, visuals));
+                }
+            },
         }
     }
 }
@@ -343,23 +593,32 @@ impl SimpleComponent for Album {
 impl Album {
 
     fn refresh(&mut self) {
-        let all = {
-            let data = self.state.read();
-            data
-                .iter()
-                .map(|visual| PhotoGridItem {
-                    visual: visual.clone(),
-                    edge_length: self.edge_length.clone(),
-                })
-                .collect::<Vec<PhotoGridItem>>()
-        };
+        let mut visuals = self.state.read().clone();
+        self.filter.arrange(&mut visuals);
+
+        let all = visuals
+            .into_iter()
+            .map(|visual| PhotoGridItem {
+                visual,
+                edge_length: self.edge_length.clone(),
+                selection_mode: self.selection_mode.clone(),
+                selected: false,
+                selected_check: None,
+            })
+            .collect::<Vec<PhotoGridItem>>();
 
         self.photo_grid.clear();
+        self.selected_count = 0;
 
         //self.photo_grid.add_filter(move |item| (self.photo_grid_filter)(&item.picture));
         self.photo_grid.extend_from_iter(all);
 
-        self.go_to_last();
+        // Albums in a manual order start from the first item rather than the latest.
+        if self.filter.is_ordered() {
+            self.go_to_first();
+        } else {
+            self.go_to_last();
+        }
     }
 
     fn go_to_first(&mut self) {
@@ -410,6 +669,28 @@ impl Album {
         }
     }
 
+    /// Selected items that are visible with the current filter.
+    fn selected_visuals(&self) -> Vec<Arc<Visual>> {
+        (0..self.photo_grid.len())
+            .filter_map(|index| self.photo_grid.get_visible(index))
+            .filter(|item| item.borrow().selected)
+            .map(|item| item.borrow().visual.clone())
+            .collect()
+    }
+
+    fn clear_selection(&mut self) {
+        for index in 0..self.photo_grid.len() {
+            if let Some(item) = self.photo_grid.get(index) {
+                let mut item = item.borrow_mut();
+                item.selected = false;
+                if let Some(ref check) = item.selected_check {
+                    check.set_active(false);
+                }
+            }
+        }
+        self.selected_count = 0;
+    }
+
     fn update_filter(&mut self) {
         self.photo_grid.clear_filters();
         let filter = self.filter.clone();
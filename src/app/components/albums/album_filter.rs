@@ -3,11 +3,16 @@
 // SPDX-License-Identifier: GPL-3.0-or-later
 
 use std::path::PathBuf;
+use std::sync::Arc;
 
+use fotema_core::albums::AlbumItem;
+use fotema_core::smart_albums::{Rule, RuleItems};
 use fotema_core::Visual;
 use h3o::CellIndex;
 use fotema_core::VisualId;
 use fotema_core::PictureId;
+use fotema_core::VideoId;
+use fotema_core::Year;
 
 // An album is a view applied over the whole collection of messages.
 // An AlbumFilter defines the filter to apply to produce an album.
@@ -31,15 +36,31 @@ pub enum AlbumFilter {
     // Show only motion photos (live photos)
     Motion,
 
+    // Show only favourites
+    Favourites,
+
     // Show photos only for folder
     Folder(PathBuf),
 
     // Show photos in a geographic area
     GeographicArea(CellIndex),
 
-    /// Show photos who's picture_id is in a set. Used for person filtering.
+    // Show photos taken in a year
+    Year(Year),
+
+    /// Show photos who's picture_id or videos who's video_id is in a set.
+    /// Used for person filtering.
     /// FIXME should probably be a Set of some kind... but that mucks up PartialEq and Eq.
-    Any(Vec<PictureId>)
+    Any(Vec<PictureId>, Vec<VideoId>),
+
+    /// Show photos and videos of an album curated by the user, in the
+    /// order chosen by the user.
+    UserAlbum(Vec<AlbumItem>),
+
+    /// Show photos and videos matching the rule of a smart album, with the
+    /// pictures and videos of each person and tag the rule refers to.
+    /// Wrapped in Arc because the filter is cloned for every item.
+    Smart(Arc<Rule>, Arc<RuleItems>),
 }
 
 impl AlbumFilter {
@@ -50,6 +71,7 @@ impl AlbumFilter {
             AlbumFilter::All => true,
             AlbumFilter::Folder(path) => v.parent_path == path,
             AlbumFilter::Motion => v.is_motion_photo(),
+            AlbumFilter::Favourites => v.is_favourite,
             AlbumFilter::Selfies => v.is_selfie(),
             AlbumFilter::Videos => v.is_video_only() && !v.is_motion_photo(),
             AlbumFilter::GeographicArea(cell_index) => {
@@ -60,7 +82,25 @@ impl AlbumFilter {
                     false
                 }
             },
-            AlbumFilter::Any(picture_ids) => v.picture_id.is_some_and(|id| picture_ids.contains(&id)),
+            AlbumFilter::Year(year) => v.year_month().year == year,
+            AlbumFilter::Any(picture_ids, video_ids) => {
+                v.picture_id.is_some_and(|id| picture_ids.contains(&id))
+                    || v.video_id.is_some_and(|id| video_ids.contains(&id))
+            },
+            AlbumFilter::UserAlbum(items) => items.iter().any(|item| item.is_for(v)),
+            AlbumFilter::Smart(rule, items) => rule.matches(v, &items),
+        }
+    }
+
+    /// Does this filter have its own order instead of the order items were taken in?
+    pub fn is_ordered(&self) -> bool {
+        matches!(self, AlbumFilter::UserAlbum(_))
+    }
+
+    /// Sort items into the order of the filter, if it has one.
+    pub fn arrange(&self, visuals: &mut [Arc<Visual>]) {
+        if let AlbumFilter::UserAlbum(items) = self {
+            visuals.sort_by_key(|v| items.iter().position(|item| item.is_for(v)));
         }
     }
 }
@@ -0,0 +1,383 @@
+// SPDX-FileCopyrightText: © 2024 David Bliss
+//
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use gtk::prelude::OrientableExt;
+
+use fotema_core::events::{self, Clustering, Moment};
+use fotema_core::places;
+use fotema_core::visual::model::PictureOrientation;
+use fotema_core::Visual;
+use strum::IntoEnumIterator;
+
+use itertools::Itertools;
+
+use relm4::gtk;
+use relm4::gtk::gdk;
+use relm4::gtk::gdk_pixbuf;
+use relm4::gtk::prelude::WidgetExt;
+use relm4::typed_view::grid::{RelmGridItem, TypedGridView};
+use relm4::*;
+use relm4::binding::*;
+
+use std::collections::HashMap;
+use std::path;
+use std::sync::Arc;
+
+use tracing::{error, info};
+
+use crate::adaptive;
+use crate::app::SharedState;
+use crate::app::ActiveView;
+use crate::app::ViewName;
+use crate::app::components::albums::album_filter::AlbumFilter;
+use crate::fl;
+
+const NARROW_EDGE_LENGTH: i32 = 170;
+const WIDE_EDGE_LENGTH: i32 = 200;
+
+#[derive(Debug)]
+struct PhotoGridItem {
+    /// Name of place where event happened, or the date if the place isn't known.
+    title: String,
+
+    /// Dates of event.
+    subtitle: String,
+
+    /// Event happened far from the usual location.
+    is_trip: bool,
+
+    // Event album cover
+    picture: Arc<Visual>,
+
+    /// Filter for items in event.
+    filter: AlbumFilter,
+
+    // Length of thumbnail edge to allow for resizing when layout changes.
+    edge_length: I32Binding,
+}
+
+struct Widgets {
+    picture: gtk::Picture,
+    title: gtk::Label,
+    subtitle: gtk::Label,
+    trip: gtk::Frame,
+
+    // If the gtk::Picture has been bound to edge_length.
+    is_bound: bool,
+}
+
+#[derive(Debug)]
+pub enum EventsAlbumInput {
+    Activate,
+
+    // Reload photos from database
+    Refresh,
+
+    EventSelected(u32), // Index into photo grid vector
+
+    // Adapt to layout
+    Adapt(adaptive::Layout),
+}
+
+#[derive(Debug)]
+pub enum EventsAlbumOutput {
+    /// User has selected an event to view as an album. Title and filter.
+    EventSelected(String, AlbumFilter),
+}
+
+impl RelmGridItem for PhotoGridItem {
+    type Root = gtk::Box;
+    type Widgets = Widgets;
+
+    fn setup(_item: &gtk::ListItem) -> (gtk::Box, Widgets) {
+        relm4::view! {
+           my_box = gtk::Box {
+                set_orientation: gtk::Orientation::Vertical,
+                gtk::AspectFrame {
+                    gtk::Frame {
+                        gtk::Overlay {
+                            #[name(trip)]
+                            add_overlay = &gtk::Frame {
+                                set_halign: gtk::Align::Start,
+                                set_valign: gtk::Align::Start,
+                                set_margin_start: 8,
+                                set_margin_top: 8,
+                                add_css_class: "photo-grid-year-frame",
+
+                                #[wrap(Some)]
+                                set_child = &gtk::Label {
+                                    set_label: &fl!("events-album-event", "trip"),
+                                    add_css_class: "photo-grid-year-label",
+                                },
+                            },
+
+                            #[wrap(Some)]
+                            #[name(picture)]
+                            set_child = &gtk::Picture {
+                                set_can_shrink: true,
+                                set_width_request: NARROW_EDGE_LENGTH,
+                                set_height_request: NARROW_EDGE_LENGTH,
+                            }
+                        }
+                    }
+                },
+
+                #[name(title)]
+                gtk::Label {
+                    add_css_class: "caption-heading",
+                    set_margin_top: 4,
+                    set_ellipsize: gtk::pango::EllipsizeMode::End,
+                },
+
+                #[name(subtitle)]
+                gtk::Label {
+                    add_css_class: "caption",
+                    add_css_class: "dim-label",
+                    set_margin_bottom: 12,
+                },
+            }
+        }
+
+        let widgets = Widgets {
+            picture,
+            title,
+            subtitle,
+            trip,
+            is_bound: false,
+        };
+
+        (my_box, widgets)
+    }
+
+    fn bind(&mut self, widgets: &mut Self::Widgets, _root: &mut Self::Root) {
+        widgets.title.set_text(&self.title);
+        widgets.subtitle.set_text(&self.subtitle);
+        widgets.trip.set_visible(self.is_trip);
+
+        // If we repeatedly bind, then Fotema will die with the following error:
+        // (fotema:2): GLib-GObject-CRITICAL **: 13:26:14.297: Too many GWeakRef registered
+        // GLib-GObject:ERROR:../gobject/gbinding.c:805:g_binding_constructed: assertion failed: (source != NULL)
+        // Bail out! GLib-GObject:ERROR:../gobject/gbinding.c:805:g_binding_constructed: assertion failed: (source != NULL)
+        if !widgets.is_bound {
+            widgets.picture.add_write_only_binding(&self.edge_length, "width-request");
+            widgets.picture.add_write_only_binding(&self.edge_length, "height-request");
+            widgets.is_bound = true;
+        }
+
+        if self.picture.thumbnail_path.as_ref().is_some_and(|x| x.exists())
+        {
+            widgets
+                .picture
+                .set_filename(self.picture.thumbnail_path.clone());
+
+            // Add CSS class for orientation
+            let orientation = self.picture.thumbnail_orientation();
+            widgets.picture.add_css_class(orientation.as_ref());
+        } else {
+            let pb = gdk_pixbuf::Pixbuf::from_resource_at_scale(
+                "/app/fotema/Fotema/icons/scalable/actions/image-missing-symbolic.svg",
+                200, 200, true
+            ).unwrap();
+            let img = gdk::Texture::for_pixbuf(&pb);
+            widgets.picture.set_paintable(Some(&img));
+        }
+    }
+
+    fn unbind(&mut self, widgets: &mut Self::Widgets, _root: &mut Self::Root) {
+        widgets.picture.set_filename(None::<&path::Path>);
+        // clear orientation transformation css classes
+        for orient in PictureOrientation::iter() {
+            widgets.picture.remove_css_class(orient.as_ref());
+        }
+    }
+}
+
+/// Album of events, such as a party or a trip, found by grouping photos and videos
+/// taken close together in time and place.
+pub struct EventsAlbum {
+    state: SharedState,
+    repo: places::Repository,
+    active_view: ActiveView,
+    photo_grid: TypedGridView<PhotoGridItem, gtk::NoSelection>,
+    edge_length: I32Binding,
+}
+
+#[relm4::component(pub)]
+impl SimpleComponent for EventsAlbum {
+    type Init = (SharedState, places::Repository, ActiveView);
+    type Input = EventsAlbumInput;
+    type Output = EventsAlbumOutput;
+
+    view! {
+        gtk::ScrolledWindow {
+            set_vexpand: true,
+
+            #[local_ref]
+            pictures_box -> gtk::GridView {
+                set_orientation: gtk::Orientation::Vertical,
+                set_single_click_activate: true,
+
+                connect_activate[sender] => move |_, idx| {
+                    sender.input(EventsAlbumInput::EventSelected(idx))
+                }
+            }
+        }
+    }
+
+    fn init(
+        (state, repo, active_view): Self::Init,
+        _root: Self::Root,
+        sender: ComponentSender<Self>,
+    ) -> ComponentParts<Self> {
+        let photo_grid = TypedGridView::new();
+
+        let model = EventsAlbum {
+            state,
+            repo,
+            active_view,
+            photo_grid,
+            edge_length: I32Binding::new(NARROW_EDGE_LENGTH),
+        };
+
+        let pictures_box = &model.photo_grid.view;
+
+        let widgets = view_output!();
+
+        ComponentParts { model, widgets }
+    }
+
+    fn update(&mut self, msg: Self::Input, sender: ComponentSender<Self>) {
+        match msg {
+            EventsAlbumInput::Activate => {
+                *self.active_view.write() = ViewName::Events;
+                if self.photo_grid.is_empty() {
+                    self.refresh();
+                }
+            },
+            EventsAlbumInput::Refresh => {
+                if *self.active_view.read() == ViewName::Events {
+                    info!("Events view is active so refreshing");
+                    self.refresh();
+                } else {
+                    info!("Events view is inactive so clearing");
+                    self.photo_grid.clear();
+                }
+            },
+            EventsAlbumInput::EventSelected(index) => {
+                if let Some(item) = self.photo_grid.get(index) {
+                    let item = item.borrow();
+                    info!("Event selected: {}", item.title);
+                    let _ = sender.output(EventsAlbumOutput::EventSelected(item.title.clone(), item.filter.clone()));
+                }
+            },
+            EventsAlbumInput::Adapt(adaptive::Layout::Narrow) => {
+                self.edge_length.set_value(NARROW_EDGE_LENGTH);
+            },
+            EventsAlbumInput::Adapt(adaptive::Layout::Wide) => {
+                self.edge_length.set_value(WIDE_EDGE_LENGTH);
+            },
+        }
+    }
+}
+
+impl EventsAlbum {
+    fn refresh(&mut self) {
+        let visuals = {
+            let data = self.state.read();
+            data.iter()
+                .sorted_by_key(|x| x.ordering_ts)
+                .cloned()
+                .collect_vec()
+        };
+
+        let places: HashMap<String, places::Place> = self.repo.all_visual_places()
+            .unwrap_or_else(|e| {
+                error!("Failed loading places: {}", e);
+                vec![]
+            })
+            .into_iter()
+            .map(|(visual_id, place)| (visual_id.id().clone(), place))
+            .collect();
+
+        let zones = self.repo.all_privacy_zones()
+            .unwrap_or_else(|e| {
+                error!("Failed loading privacy zones: {}", e);
+                places::PrivacyZones::default()
+            });
+
+        let moments = visuals.iter()
+            .map(|x| Moment { timestamp: x.ordering_ts, location: x.location })
+            .collect_vec();
+
+        let clustering = Clustering::default();
+        let usual_location = events::usual_location(visuals.iter().filter_map(|x| x.location));
+
+        let events = clustering.cluster(&moments)
+            .into_iter()
+            .map(|range| {
+                let items = &visuals[range];
+
+                let locations = items.iter().filter_map(|x| x.location).collect_vec();
+                let is_trip = usual_location.is_some_and(|usual| clustering.is_trip(usual, &locations));
+
+                // Name the event after the place most of it happened.
+                // Items inside a privacy zone don't give away where they were taken.
+                let event_places = items.iter()
+                    .filter(|x| zones.reveal(x.location).is_some())
+                    .filter_map(|x| places.get(x.visual_id.id()))
+                    .collect_vec();
+
+                let place = event_places.iter()
+                    .counts_by(|place| place.place_id.id())
+                    .into_iter()
+                    .max_by_key(|(place_id, count)| (*count, *place_id))
+                    .and_then(|(place_id, _)| event_places.iter().find(|place| place.place_id.id() == place_id));
+
+                let first = items.first().expect("Events can't be empty");
+                let last = items.last().expect("Events can't be empty");
+
+                let start = first.local_ts().date_naive();
+                let end = last.local_ts().date_naive();
+
+                let subtitle = if start == end {
+                    start.format("%Y-%m-%d").to_string()
+                } else {
+                    fl!("events-album-event", "date-range",
+                        start = start.format("%Y-%m-%d").to_string(),
+                        end = end.format("%Y-%m-%d").to_string())
+                };
+
+                let title = place
+                    .map(|place| place.city.clone())
+                    .unwrap_or_else(|| subtitle.clone());
+
+                let picture_ids = items.iter().filter_map(|x| x.picture_id).collect();
+                let video_ids = items.iter().filter_map(|x| x.video_id).collect();
+
+                PhotoGridItem {
+                    title,
+                    subtitle,
+                    is_trip,
+                    // Middle item is more likely to be typical of the event than the first.
+                    picture: items[items.len() / 2].clone(),
+                    filter: AlbumFilter::Any(picture_ids, video_ids),
+                    edge_length: self.edge_length.clone(),
+                }
+            })
+            .collect_vec();
+
+        info!("Found {} events", events.len());
+
+        self.photo_grid.clear();
+        self.photo_grid.extend_from_iter(events);
+
+        if !self.photo_grid.is_empty() {
+            self.photo_grid.view.scroll_to(
+                self.photo_grid.len() - 1,
+                gtk::ListScrollFlags::SELECT,
+                None,
+            );
+        }
+    }
+}
@@ -4,9 +4,17 @@
 
 pub mod album;
 pub mod album_filter;
+pub mod events_album;
 pub mod folders_album;
 pub mod months_album;
+pub mod on_this_day_album;
+pub mod offline_map;
 pub mod people_album;
 pub mod person_album;
 pub mod places_album;
+pub mod smart_album;
+pub mod smart_albums;
+pub mod tags_album;
+pub mod user_album;
+pub mod user_albums;
 pub mod years_album;
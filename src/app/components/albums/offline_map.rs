@@ -0,0 +1,122 @@
+// SPDX-FileCopyrightText: © 2024 David Bliss
+//
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//! Map source for viewing maps offline with tiles from a local tile archive.
+
+use fotema_core::map_tiles::{self, TileArchive};
+
+use relm4::gtk::{gio, glib};
+use relm4::gtk::glib::subclass::prelude::*;
+
+use shumate;
+use shumate::prelude::*;
+
+use anyhow::*;
+use std::path::Path;
+use std::sync::Arc;
+
+mod imp {
+    use super::*;
+    use shumate::subclass::prelude::*;
+    use std::cell::OnceCell;
+    use std::future::Future;
+    use std::pin::Pin;
+
+    #[derive(Default)]
+    pub struct OfflineDataSource {
+        pub archive: OnceCell<Arc<dyn TileArchive>>,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for OfflineDataSource {
+        const NAME: &'static str = "FotemaOfflineDataSource";
+        type Type = super::OfflineDataSource;
+        type ParentType = shumate::DataSource;
+    }
+
+    impl ObjectImpl for OfflineDataSource {}
+
+    impl DataSourceImpl for OfflineDataSource {
+        fn tile_data_future(
+            &self,
+            x: i32,
+            y: i32,
+            zoom_level: i32,
+        ) -> Pin<Box<dyn Future<Output = std::result::Result<glib::Bytes, glib::Error>> + 'static>> {
+            let archive = self.archive.get().cloned();
+
+            Box::pin(async move {
+                let Some(archive) = archive else {
+                    return Err(glib::Error::new(gio::IOErrorEnum::NotInitialized, "No tile archive"));
+                };
+
+                // Reading tiles hits the disk, so keep it off the main thread.
+                let result = gio::spawn_blocking(move || {
+                    archive.tile(zoom_level as u8, x as u32, y as u32)
+                }).await;
+
+                match result {
+                    std::result::Result::Ok(std::result::Result::Ok(Some(data))) => {
+                        std::result::Result::Ok(glib::Bytes::from_owned(data))
+                    },
+                    std::result::Result::Ok(std::result::Result::Ok(None)) => {
+                        Err(glib::Error::new(gio::IOErrorEnum::NotFound, "No tile in archive"))
+                    },
+                    std::result::Result::Ok(Err(e)) => {
+                        Err(glib::Error::new(gio::IOErrorEnum::Failed, &e.to_string()))
+                    },
+                    Err(_) => {
+                        Err(glib::Error::new(gio::IOErrorEnum::Failed, "Failed reading tile"))
+                    },
+                }
+            })
+        }
+    }
+}
+
+glib::wrapper! {
+    /// Data source of tiles read from a local tile archive.
+    pub struct OfflineDataSource(ObjectSubclass<imp::OfflineDataSource>)
+        @extends shumate::DataSource;
+}
+
+impl OfflineDataSource {
+    pub fn new(archive: Arc<dyn TileArchive>) -> Self {
+        let data_source: Self = glib::Object::new();
+        let _ = data_source.imp().archive.set(archive);
+        data_source
+    }
+}
+
+/// Identifier of offline map source.
+pub const MAP_SOURCE_OFFLINE: &str = "fotema-offline";
+
+/// Tiles in raster archives are almost always 256 pixels square.
+const TILE_SIZE: u32 = 256;
+
+/// Open a map source backed by a local MBTiles or PMTiles archive.
+pub fn open_map_source(path: &Path) -> Result<shumate::MapSource> {
+    let archive: Arc<dyn TileArchive> = Arc::from(map_tiles::open(path)?);
+
+    let name = path
+        .file_stem()
+        .map(|x| x.to_string_lossy().to_string())
+        .unwrap_or_else(|| String::from(MAP_SOURCE_OFFLINE));
+
+    let data_source = OfflineDataSource::new(archive.clone());
+
+    let renderer = shumate::RasterRenderer::new_full(
+        MAP_SOURCE_OFFLINE,
+        &name,
+        "",
+        "",
+        u32::from(archive.min_zoom()),
+        u32::from(archive.max_zoom()),
+        TILE_SIZE,
+        shumate::MapProjection::Mercator,
+        &data_source,
+    );
+
+    Ok(renderer.upcast())
+}
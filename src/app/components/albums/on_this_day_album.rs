@@ -0,0 +1,301 @@
+// SPDX-FileCopyrightText: © 2024 David Bliss
+//
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use fotema_core::memories::{self, Interest};
+use fotema_core::people;
+use fotema_core::{Visual, VisualId};
+
+use chrono::{Datelike, NaiveDate};
+use itertools::Itertools;
+
+use relm4::adw;
+use relm4::gtk;
+use relm4::gtk::gdk;
+use relm4::gtk::gdk_pixbuf;
+use relm4::gtk::prelude::*;
+use relm4::*;
+use relm4::binding::*;
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use tracing::{error, info};
+
+use crate::adaptive;
+use crate::app::SharedState;
+use crate::app::ActiveView;
+use crate::app::ViewName;
+use crate::app::components::albums::album_filter::AlbumFilter;
+use crate::fl;
+
+const NARROW_EDGE_LENGTH: i32 = 100;
+const WIDE_EDGE_LENGTH: i32 = 150;
+
+#[derive(Debug)]
+pub enum OnThisDayAlbumInput {
+    Activate,
+
+    // Reload photos from database
+    Refresh,
+
+    // Adapt to layout
+    Adapt(adaptive::Layout),
+
+    // Memory selected from one of the years
+    Selected(VisualId),
+}
+
+#[derive(Debug)]
+pub enum OnThisDayAlbumOutput {
+    /// User has selected photo or video to view
+    Selected(VisualId, AlbumFilter),
+}
+
+/// Photos and videos taken on today's month and day in previous years, grouped by year.
+pub struct OnThisDayAlbum {
+    state: SharedState,
+    people_repo: people::Repository,
+    active_view: ActiveView,
+    years_box: gtk::Box,
+    edge_length: I32Binding,
+
+    /// Day memories were last found for. Memories change at midnight.
+    day: Option<NaiveDate>,
+
+    /// All memories, so the viewer can move between years.
+    filter: AlbumFilter,
+}
+
+#[relm4::component(pub)]
+impl SimpleComponent for OnThisDayAlbum {
+    type Init = (SharedState, people::Repository, ActiveView);
+    type Input = OnThisDayAlbumInput;
+    type Output = OnThisDayAlbumOutput;
+
+    view! {
+        gtk::Box {
+            set_orientation: gtk::Orientation::Vertical,
+
+            adw::StatusPage {
+                set_vexpand: true,
+                set_icon_name: Some("arrow-circular-top-left-symbolic"),
+                set_title: &fl!("on-this-day-album", "empty-title"),
+                set_description: Some(&fl!("on-this-day-album", "empty-description")),
+
+                #[watch]
+                set_visible: model.filter == AlbumFilter::None,
+            },
+
+            gtk::ScrolledWindow {
+                set_vexpand: true,
+                set_hscrollbar_policy: gtk::PolicyType::Never,
+
+                #[watch]
+                set_visible: model.filter != AlbumFilter::None,
+
+                #[local_ref]
+                years_box -> gtk::Box {
+                    set_orientation: gtk::Orientation::Vertical,
+                    set_spacing: 24,
+                    set_margin_all: 12,
+                },
+            },
+        }
+    }
+
+    fn init(
+        (state, people_repo, active_view): Self::Init,
+        _root: Self::Root,
+        sender: ComponentSender<Self>,
+    ) -> ComponentParts<Self> {
+        let years_box = gtk::Box::new(gtk::Orientation::Vertical, 0);
+
+        let model = OnThisDayAlbum {
+            state,
+            people_repo,
+            active_view,
+            years_box: years_box.clone(),
+            edge_length: I32Binding::new(NARROW_EDGE_LENGTH),
+            day: None,
+            filter: AlbumFilter::None,
+        };
+
+        let widgets = view_output!();
+
+        ComponentParts { model, widgets }
+    }
+
+    fn update(&mut self, msg: Self::Input, sender: ComponentSender<Self>) {
+        match msg {
+            OnThisDayAlbumInput::Activate => {
+                *self.active_view.write() = ViewName::OnThisDay;
+                let today = chrono::Local::now().date_naive();
+                if self.day != Some(today) {
+                    self.refresh(&sender);
+                }
+            },
+            OnThisDayAlbumInput::Refresh => {
+                if *self.active_view.read() == ViewName::OnThisDay {
+                    info!("On this day view is active so refreshing");
+                    self.refresh(&sender);
+                } else {
+                    info!("On this day view is inactive so clearing");
+                    self.day = None;
+                }
+            },
+            OnThisDayAlbumInput::Selected(visual_id) => {
+                let _ = sender.output(OnThisDayAlbumOutput::Selected(visual_id, self.filter.clone()));
+            },
+            OnThisDayAlbumInput::Adapt(adaptive::Layout::Narrow) => {
+                self.edge_length.set_value(NARROW_EDGE_LENGTH);
+            },
+            OnThisDayAlbumInput::Adapt(adaptive::Layout::Wide) => {
+                self.edge_length.set_value(WIDE_EDGE_LENGTH);
+            },
+        }
+    }
+}
+
+impl OnThisDayAlbum {
+    fn refresh(&mut self, sender: &ComponentSender<Self>) {
+        let today = chrono::Local::now().date_naive();
+        self.day = Some(today);
+
+        while let Some(child) = self.years_box.first_child() {
+            self.years_box.remove(&child);
+        }
+
+        let candidates = {
+            let data = self.state.read();
+            data.iter()
+                .filter(|x| memories::is_on_this_day(x.local_ts().date_naive(), today))
+                .cloned()
+                .collect_vec()
+        };
+
+        info!("{} items taken on this day in previous years", candidates.len());
+
+        if candidates.is_empty() {
+            self.filter = AlbumFilter::None;
+            return;
+        }
+
+        // Prefer memories with people in them, and better shots.
+        let face_counts = self.people_repo.count_faces().unwrap_or_else(|e| {
+            error!("Failed counting faces: {}", e);
+            vec![]
+        });
+
+        let mut picture_faces = HashMap::new();
+        let mut video_faces = HashMap::new();
+        for (picture_id, video_id, people, faces) in face_counts {
+            if let Some(picture_id) = picture_id {
+                picture_faces.insert(picture_id.id(), (people, faces));
+            } else if let Some(video_id) = video_id {
+                video_faces.insert(video_id.id(), (people, faces));
+            }
+        }
+
+        let interest = |visual: &Visual| {
+            let (people, faces) = visual.picture_id
+                .and_then(|id| picture_faces.get(&id.id()))
+                .or_else(|| visual.video_id.and_then(|id| video_faces.get(&id.id())))
+                .copied()
+                .unwrap_or_default();
+
+            Interest {
+                people,
+                faces,
+                is_picture: visual.picture_id.is_some(),
+                is_favourite: visual.is_favourite,
+                has_camera: visual.camera.is_some(),
+                is_screenshot: visual.path().is_some_and(|x| memories::is_screenshot(x)),
+            }
+        };
+
+        let by_year = candidates
+            .into_iter()
+            .sorted_by_key(|x| x.ordering_ts)
+            .chunk_by(|x| x.local_ts().year());
+
+        let mut years = Vec::new();
+        for (year, visuals) in by_year.into_iter() {
+            let visuals = visuals.map(|x| {
+                let interest = interest(x.as_ref());
+                (x, interest)
+            }).collect_vec();
+
+            years.push((year, memories::pick_best(visuals, memories::MAX_PER_YEAR)));
+        }
+
+        let picture_ids = years.iter().flat_map(|(_, vs)| vs.iter().filter_map(|x| x.picture_id)).collect();
+        let video_ids = years.iter().flat_map(|(_, vs)| vs.iter().filter_map(|x| x.video_id)).collect();
+        self.filter = AlbumFilter::Any(picture_ids, video_ids);
+
+        // Most recent year first.
+        for (year, visuals) in years.into_iter().rev() {
+            let heading = gtk::Label::builder()
+                .label(fl!("on-this-day-album", "years-ago", years = today.year() - year, year = year))
+                .halign(gtk::Align::Start)
+                .css_classes(["title-4"])
+                .build();
+
+            let flow_box = gtk::FlowBox::builder()
+                .selection_mode(gtk::SelectionMode::None)
+                .activate_on_single_click(true)
+                .homogeneous(true)
+                .min_children_per_line(2)
+                .max_children_per_line(12)
+                .row_spacing(6)
+                .column_spacing(6)
+                .build();
+
+            for visual in visuals.iter() {
+                flow_box.append(&self.to_thumbnail(visual));
+            }
+
+            {
+                let sender = sender.clone();
+                let visual_ids = visuals.iter().map(|x| x.visual_id.clone()).collect_vec();
+                flow_box.connect_child_activated(move |_, child| {
+                    if let Some(visual_id) = visual_ids.get(child.index() as usize) {
+                        sender.input(OnThisDayAlbumInput::Selected(visual_id.clone()));
+                    }
+                });
+            }
+
+            let section = gtk::Box::new(gtk::Orientation::Vertical, 12);
+            section.append(&heading);
+            section.append(&flow_box);
+            self.years_box.append(&section);
+        }
+    }
+
+    fn to_thumbnail(&self, visual: &Arc<Visual>) -> gtk::Frame {
+        let picture = if visual.thumbnail_path.as_ref().is_some_and(|x| x.exists()) {
+            let picture = gtk::Picture::for_filename(visual.thumbnail_path.as_ref().expect("Must have path"));
+
+            // Add CSS class for orientation
+            let orientation = visual.thumbnail_orientation();
+            picture.add_css_class(orientation.as_ref());
+            picture
+        } else {
+            let pb = gdk_pixbuf::Pixbuf::from_resource_at_scale(
+                "/app/fotema/Fotema/icons/scalable/actions/image-missing-symbolic.svg",
+                200, 200, true
+            ).unwrap();
+            let img = gdk::Texture::for_pixbuf(&pb);
+            gtk::Picture::for_paintable(&img)
+        };
+
+        picture.set_can_shrink(true);
+        picture.set_content_fit(gtk::ContentFit::Cover);
+        picture.add_write_only_binding(&self.edge_length, "width-request");
+        picture.add_write_only_binding(&self.edge_length, "height-request");
+
+        gtk::Frame::builder()
+            .child(&picture)
+            .build()
+    }
+}
@@ -19,9 +19,10 @@ use crate::app::ActiveView;
 use crate::app::ViewName;
 use crate::app::SettingsState;
 use crate::app::FaceDetectionMode;
+use crate::app::components::albums::album_filter::AlbumFilter;
 use crate::fl;
 
-use tracing::{debug, info};
+use tracing::{debug, error, info};
 
 const NARROW_EDGE_LENGTH: i32 = 170;
 const WIDE_EDGE_LENGTH: i32 = 200;
@@ -61,6 +62,12 @@ pub enum PeopleAlbumInput {
     EnableForMobile,
 
     EnableForDesktop,
+
+    /// Show dialog for finding photos with several people.
+    QueryDialog,
+
+    /// Find photos and videos matching a query over people.
+    Query(people::PeopleQuery),
 }
 
 #[derive(Debug)]
@@ -68,8 +75,18 @@ pub enum PeopleAlbumOutput {
     Selected(people::Person),
 
     EnableFaceDetection,
+
+    /// View photos and videos matching a query over people.
+    Query(AlbumFilter),
 }
 
+/// How a person takes part in a query over people.
+/// Index matches position in the query dialog's drop down.
+const QUERY_IGNORE: u32 = 0;
+const QUERY_WITH: u32 = 1;
+const QUERY_OR_WITH: u32 = 2;
+const QUERY_WITHOUT: u32 = 3;
+
 impl RelmGridItem for PhotoGridItem {
     type Root = gtk::Box;
     type Widgets = Widgets;
@@ -137,6 +154,7 @@ pub struct PeopleAlbum {
     settings_state: SettingsState,
     photo_grid: TypedGridView<PhotoGridItem, gtk::SingleSelection>,
     avatars: gtk::ScrolledWindow,
+    query_bar: gtk::Box,
     status: adw::StatusPage,
     edge_length: I32Binding,
 }
@@ -151,6 +169,21 @@ impl SimpleComponent for PeopleAlbum {
         gtk::Box {
             set_orientation: gtk::Orientation::Vertical,
 
+            #[local_ref]
+            query_bar -> gtk::Box {
+                set_orientation: gtk::Orientation::Horizontal,
+                set_halign: gtk::Align::End,
+                set_margin_all: 8,
+
+                gtk::Button {
+                    set_icon_name: "system-search-symbolic",
+                    set_label: &fl!("people-query-button"),
+                    add_css_class: "flat",
+                    set_tooltip_text: Some(&fl!("people-query-button", "tooltip")),
+                    connect_clicked => PeopleAlbumInput::QueryDialog,
+                },
+            },
+
             #[local_ref]
             avatars -> gtk::ScrolledWindow {
                 set_vexpand: true,
@@ -216,17 +249,21 @@ impl SimpleComponent for PeopleAlbum {
 
         let avatars = gtk::ScrolledWindow::builder().build();
 
+        let query_bar = gtk::Box::builder().build();
+
         let model = PeopleAlbum {
             repo,
             active_view,
             settings_state,
             photo_grid,
             avatars: avatars.clone(),
+            query_bar: query_bar.clone(),
             status: status.clone(),
             edge_length: I32Binding::new(NARROW_EDGE_LENGTH),
         };
 
         let pictures_box = &model.photo_grid.view;
+        let query_bar = &model.query_bar;
 
         let widgets = view_output!();
 
@@ -277,7 +314,126 @@ impl SimpleComponent for PeopleAlbum {
                 self.refresh();
                 let _ = sender.output(PeopleAlbumOutput::EnableFaceDetection);
             },
+            PeopleAlbumInput::QueryDialog => {
+                let people = self.repo.all_people().unwrap_or_default();
+
+                let people_list = gtk::ListBox::builder()
+                    .selection_mode(gtk::SelectionMode::None)
+                    .css_classes(["boxed-list"])
+                    .build();
+
+                let options = [
+                    fl!("people-query-dialog", "ignore"),
+                    fl!("people-query-dialog", "with"),
+                    fl!("people-query-dialog", "or-with"),
+                    fl!("people-query-dialog", "without"),
+                ];
+                let options: Vec<&str> = options.iter().map(|x| x.as_str()).collect();
+
+                let mut choices = Vec::with_capacity(people.len());
+
+                for person in people.into_iter() {
+                    let avatar = adw::Avatar::builder()
+                        .size(32)
+                        .show_initials(true)
+                        .text(&person.name)
+                        .build();
+
+                    let img = gdk::Texture::from_filename(&person.thumbnail_path).ok();
+                    avatar.set_custom_image(img.as_ref());
+
+                    let choice = gtk::DropDown::from_strings(&options);
+                    choice.set_valign(gtk::Align::Center);
+                    choice.set_selected(QUERY_IGNORE);
+
+                    let row = adw::ActionRow::builder()
+                        .title(&person.name)
+                        .build();
+                    row.add_prefix(&avatar);
+                    row.add_suffix(&choice);
+                    people_list.append(&row);
+
+                    choices.push((person.person_id, choice));
+                }
+
+                let group_size = adw::SpinRow::with_range(0.0, 50.0, 1.0);
+                group_size.set_title(&fl!("people-query-dialog", "group-size"));
+                group_size.set_subtitle(&fl!("people-query-dialog", "group-size-subtitle"));
+
+                let group_list = gtk::ListBox::builder()
+                    .selection_mode(gtk::SelectionMode::None)
+                    .css_classes(["boxed-list"])
+                    .build();
+                group_list.append(&group_size);
+
+                let content = gtk::Box::builder()
+                    .orientation(gtk::Orientation::Vertical)
+                    .spacing(12)
+                    .build();
+                content.append(&people_list);
+                content.append(&group_list);
+
+                let scrolled = gtk::ScrolledWindow::builder()
+                    .hscrollbar_policy(gtk::PolicyType::Never)
+                    .propagate_natural_height(true)
+                    .max_content_height(400)
+                    .child(&content)
+                    .build();
+
+                let dialog = adw::AlertDialog::builder()
+                    .heading(fl!("people-query-dialog", "heading"))
+                    .body(fl!("people-query-dialog", "body"))
+                    .close_response("cancel")
+                    .default_response("find")
+                    .extra_child(&scrolled)
+                    .build();
+
+                dialog.add_response("cancel", &fl!("people-query-dialog", "cancel-button"));
+                dialog.add_response("find", &fl!("people-query-dialog", "find-button"));
+                dialog.set_response_appearance("find", adw::ResponseAppearance::Suggested);
+
+                dialog.connect_response(None, move |_, response| {
+                    if response != "find" {
+                        return;
+                    }
 
+                    let mut query = people::PeopleQuery::default();
+                    for (person_id, choice) in choices.iter() {
+                        match choice.selected() {
+                            QUERY_WITH => query.all_of.push(*person_id),
+                            QUERY_OR_WITH => query.any_of.push(*person_id),
+                            QUERY_WITHOUT => query.none_of.push(*person_id),
+                            _ => {},
+                        }
+                    }
+
+                    let min_people = group_size.value() as usize;
+                    if min_people > 0 {
+                        query.min_people = Some(min_people);
+                    }
+
+                    sender.input(PeopleAlbumInput::Query(query));
+                });
+
+                if let Some(root) = gtk::Widget::root(self.avatars.widget_ref()) {
+                    dialog.present(Some(&root));
+                } else {
+                    error!("Couldn't get root widget!");
+                }
+            },
+            PeopleAlbumInput::Query(query) => {
+                info!("Finding items for people query: {:?}", query);
+                if query.is_empty() {
+                    return;
+                }
+
+                match self.repo.find_visuals_for_people(&query) {
+                    Ok((picture_ids, video_ids)) => {
+                        let _ = sender.output(PeopleAlbumOutput::Query(AlbumFilter::Any(picture_ids, video_ids)));
+                    },
+                    Err(e) => error!("Failed querying people: {}", e),
+                }
+            },
         }
     }
 }
@@ -287,6 +443,7 @@ impl PeopleAlbum {
 
         if self.settings_state.read().face_detection_mode == FaceDetectionMode::Off {
             self.avatars.set_visible(false);
+            self.query_bar.set_visible(false);
             self.status.set_visible(true);
             self.status.set_title(&fl!("people-page-status-off", "title"));
             self.status.set_description(Some(&fl!("people-page-status-off", "description")));
@@ -314,6 +471,7 @@ impl PeopleAlbum {
 
         self.status.set_visible(items.is_empty());
         self.avatars.set_visible(!items.is_empty());
+        self.query_bar.set_visible(items.len() > 1);
 
         if items.is_empty() {
             if let Some(child) = self.status.child() {
@@ -3,7 +3,7 @@
 // SPDX-License-Identifier: GPL-3.0-or-later
 
 use gtk::prelude::OrientableExt;
-use fotema_core::VisualId;
+use fotema_core::{Visual, VisualId};
 use relm4::gtk;
 use relm4::gtk::prelude::*;
 use relm4::*;
@@ -11,6 +11,7 @@ use relm4::binding::*;
 use relm4::adw;
 use relm4::adw::prelude::*;
 use relm4::gtk::gdk;
+use relm4::gtk::glib;
 use relm4::actions::{RelmAction, RelmActionGroup};
 
 use crate::app::adaptive;
@@ -24,8 +25,12 @@ use crate::app::components::albums:: {
 
 use fotema_core::people;
 use fotema_core::PictureId;
+use fotema_core::VideoId;
+use chrono::NaiveDate;
 use crate::fl;
 
+use std::sync::Arc;
+
 use tracing::{error, info};
 
 const NARROW_EDGE_LENGTH: i32 = 50;
@@ -39,6 +44,12 @@ relm4::new_stateless_action!(RenameAction, PersonActionGroup, "rename");
 // Delete a person
 relm4::new_stateless_action!(DeleteAction, PersonActionGroup, "delete");
 
+// Merge another person into this person
+relm4::new_stateless_action!(MergeAction, PersonActionGroup, "merge");
+
+// Set birthday of a person
+relm4::new_stateless_action!(BirthdayAction, PersonActionGroup, "birthday");
+
 #[derive(Debug)]
 pub enum PersonAlbumInput {
 
@@ -71,6 +82,43 @@ pub enum PersonAlbumInput {
 
     /// Actually delete person.
     Delete,
+
+    /// Start merge person flow by choosing the other person.
+    MergeDialog,
+
+    /// Choose surviving name and thumbnail for merge with other person.
+    MergeWithDialog(people::Person),
+
+    /// Actually merge other person into this person.
+    /// Booleans are for keeping the other person's name and thumbnail.
+    Merge(people::Person, bool, bool),
+
+    /// Start set birthday flow.
+    BirthdayDialog,
+
+    /// Actually set or clear birthday.
+    Birthday(Option<NaiveDate>),
+
+    /// Age chosen for browsing album. Index into age choices.
+    AgeSelected(u32),
+
+    /// Enter or leave selection mode.
+    SelectionMode(bool),
+
+    /// Set location of items selected in underlying album.
+    EditLocation(Vec<Arc<Visual>>),
+
+    /// Correct date and time of items selected in underlying album.
+    EditDateTime(Vec<Arc<Visual>>),
+
+    /// Add items selected in underlying album to albums.
+    AddToAlbum(Vec<Arc<Visual>>),
+
+    /// Move items selected in underlying album to the trash.
+    MoveToTrash(Vec<Arc<Visual>>),
+
+    /// Show items of person on a map.
+    ViewMap,
 }
 
 #[derive(Debug)]
@@ -83,15 +131,41 @@ pub enum PersonAlbumOutput {
 
     /// Person renamed.
     Renamed,
+
+    /// Another person merged into this person.
+    Merged,
+
+    /// User wants to set the location of the selected photos and videos.
+    EditLocation(Vec<Arc<Visual>>),
+
+    /// User wants to correct the date and time of the selected photos and videos.
+    EditDateTime(Vec<Arc<Visual>>),
+
+    /// User wants to add the selected photos and videos to albums.
+    AddToAlbum(Vec<Arc<Visual>>),
+
+    /// User wants to move the selected photos and videos to the trash.
+    MoveToTrash(Vec<Arc<Visual>>),
+
+    /// User wants to see where photos and videos of the person were taken. Title and filter.
+    ViewMap(String, AlbumFilter),
 }
 
+/// Ages that a person album can be browsed by. None is for all ages.
+type AgeChoice = Option<u32>;
+
 pub struct PersonAlbum {
+    state: SharedState,
     repo: people::Repository,
     person: Option<people::Person>,
     picture_ids: Vec<PictureId>,
+    video_ids: Vec<VideoId>,
     album: Controller<Album>,
     avatar: adw::Avatar,
     title: gtk::Label,
+    ages: gtk::DropDown,
+    selection_button: gtk::ToggleButton,
+    age_choices: Vec<AgeChoice>,
     active_view: ActiveView,
     edge_length: I32Binding,
 }
@@ -107,6 +181,8 @@ impl SimpleComponent for PersonAlbum {
             section! {
                 // FIXME I would like to have the person's name in these menu items.
                 &fl!("person-menu-rename") => RenameAction,
+                &fl!("person-menu-birthday") => BirthdayAction,
+                &fl!("person-menu-merge") => MergeAction,
                 &fl!("person-menu-delete") => DeleteAction,
             }
         }
@@ -125,6 +201,21 @@ impl SimpleComponent for PersonAlbum {
                     set_icon_name: "open-menu-symbolic",
                     set_menu_model: Some(&primary_menu),
                 },
+
+                #[local_ref]
+                pack_end = &selection_button -> gtk::ToggleButton {
+                    set_icon_name: "selection-mode-symbolic",
+                    set_tooltip_text: Some(&fl!("selection-mode", "tooltip")),
+                    connect_toggled[sender] => move |button| {
+                        sender.input(PersonAlbumInput::SelectionMode(button.is_active()));
+                    },
+                },
+
+                pack_end = &gtk::Button {
+                    set_icon_name: "find-location-symbolic",
+                    set_tooltip_text: Some(&fl!("show-on-map", "tooltip")),
+                    connect_clicked => PersonAlbumInput::ViewMap,
+                },
             },
 
             #[wrap(Some)]
@@ -136,6 +227,15 @@ impl SimpleComponent for PersonAlbum {
                 #[local_ref]
                 avatar -> adw::Avatar,
 
+                #[local_ref]
+                ages -> gtk::DropDown {
+                    set_halign: gtk::Align::Center,
+                    set_visible: false,
+                    connect_selected_notify[sender] => move |dd| {
+                        sender.input(PersonAlbumInput::AgeSelected(dd.selected()));
+                    },
+                },
+
                 model.album.widget(),
             }
         }
@@ -156,19 +256,34 @@ impl SimpleComponent for PersonAlbum {
             .forward(sender.input_sender(), |msg| match msg {
                 AlbumOutput::Selected(id, _) => PersonAlbumInput::Selected(id),
                 AlbumOutput::ScrollOffset(offset) => PersonAlbumInput::ScrollOffset(offset),
+                AlbumOutput::EditLocation(visuals) => PersonAlbumInput::EditLocation(visuals),
+                AlbumOutput::EditDateTime(visuals) => PersonAlbumInput::EditDateTime(visuals),
+                AlbumOutput::AddToAlbum(visuals) => PersonAlbumInput::AddToAlbum(visuals),
+                AlbumOutput::MoveToTrash(visuals) => PersonAlbumInput::MoveToTrash(visuals),
+                // Person albums aren't curated by the user, so have no album edits.
+                AlbumOutput::EditAlbum(_, _) => PersonAlbumInput::Refresh,
             });
 
         let title = gtk::Label::builder()
             .build();
 
+        let ages = gtk::DropDown::builder().build();
+
+        let selection_button = gtk::ToggleButton::new();
+
         let model = PersonAlbum {
+            state: state.clone(),
             repo,
             person: None,
             avatar: avatar.clone(),
             title: title.clone(),
+            ages: ages.clone(),
+            selection_button: selection_button.clone(),
+            age_choices: vec![],
             album,
             active_view,
             picture_ids: vec![],
+            video_ids: vec![],
             edge_length: I32Binding::new(NARROW_EDGE_LENGTH),
         };
 
@@ -191,8 +306,24 @@ impl SimpleComponent for PersonAlbum {
             })
         };
 
+        let merge_action = {
+            let sender = sender.clone();
+            RelmAction::<MergeAction>::new_stateless(move |_| {
+                sender.input(PersonAlbumInput::MergeDialog);
+            })
+        };
+
+        let birthday_action = {
+            let sender = sender.clone();
+            RelmAction::<BirthdayAction>::new_stateless(move |_| {
+                sender.input(PersonAlbumInput::BirthdayDialog);
+            })
+        };
+
         actions.add_action(rename_action);
         actions.add_action(delete_action);
+        actions.add_action(merge_action);
+        actions.add_action(birthday_action);
         actions.register_for_widget(&root);
 
         ComponentParts { model, widgets }
@@ -209,6 +340,7 @@ impl SimpleComponent for PersonAlbum {
             }
             PersonAlbumInput::View(person) => {
                 info!("Viewing album for person: {}", person.person_id);
+                self.selection_button.set_active(false);
 
                 let img = gdk::Texture::from_filename(&person.thumbnail_path).ok();
                 self.avatar.set_custom_image(img.as_ref());
@@ -219,16 +351,117 @@ impl SimpleComponent for PersonAlbum {
                 }
 
                 self.picture_ids = self.repo.find_pictures_for_person(person.person_id).unwrap_or_default();
-                info!("Person {} has {} items to view.", person.person_id, self.picture_ids.len());
+                self.video_ids = self.repo.find_videos_for_person(person.person_id).unwrap_or_default();
+                info!("Person {} has {} items to view.", person.person_id, self.picture_ids.len() + self.video_ids.len());
                 self.album.sender().emit(AlbumInput::Activate);
-                self.album.sender().emit(AlbumInput::Filter(AlbumFilter::Any(self.picture_ids.clone())));
+                self.album.sender().emit(AlbumInput::Filter(AlbumFilter::Any(self.picture_ids.clone(), self.video_ids.clone())));
                 self.album.sender().emit(AlbumInput::GoToFirst);
 
                 self.title.set_label(&person.name);
                 self.person = Some(person);
+                self.update_ages();
             }
             PersonAlbumInput::Selected(visual_id) => {
-                let _ = sender.output(PersonAlbumOutput::Selected(visual_id, AlbumFilter::Any(self.picture_ids.clone())));
+                let _ = sender.output(PersonAlbumOutput::Selected(visual_id, self.filter_for_age()));
+            },
+            PersonAlbumInput::SelectionMode(enabled) => {
+                self.album.sender().emit(AlbumInput::SelectionMode(enabled));
+            },
+            PersonAlbumInput::EditLocation(visuals) => {
+                let _ = sender.output(PersonAlbumOutput::EditLocation(visuals));
+            },
+            PersonAlbumInput::EditDateTime(visuals) => {
+                let _ = sender.output(PersonAlbumOutput::EditDateTime(visuals));
+            },
+            PersonAlbumInput::AddToAlbum(visuals) => {
+                let _ = sender.output(PersonAlbumOutput::AddToAlbum(visuals));
+            },
+            PersonAlbumInput::MoveToTrash(visuals) => {
+                let _ = sender.output(PersonAlbumOutput::MoveToTrash(visuals));
+            },
+            PersonAlbumInput::ViewMap => {
+                let Some(ref person) = self.person else {
+                    return;
+                };
+                // Respect the chosen age, if any.
+                let _ = sender.output(PersonAlbumOutput::ViewMap(person.name.clone(), self.filter_for_age()));
+            },
+            PersonAlbumInput::AgeSelected(index) => {
+                info!("Age choice {} selected", index);
+                self.album.sender().emit(AlbumInput::Filter(self.filter_for_age()));
+                self.album.sender().emit(AlbumInput::GoToFirst);
+            },
+            PersonAlbumInput::BirthdayDialog => {
+                let Some(ref person) = self.person else {
+                    info!("Asked to set birthday of person, but no person for album");
+                    return;
+                };
+                info!("Starting birthday flow for person: {}", person.person_id);
+
+                let calendar = gtk::Calendar::new();
+
+                if let Some(birth_date) = person.birth_date {
+                    use chrono::Datelike;
+                    let date = glib::DateTime::from_local(
+                        birth_date.year(),
+                        birth_date.month() as i32,
+                        birth_date.day() as i32,
+                        0, 0, 0.0,
+                    );
+                    if let Ok(date) = date {
+                        calendar.select_day(&date);
+                    }
+                }
+
+                let dialog = adw::AlertDialog::builder()
+                    .heading(fl!("person-birthday-dialog", "heading", name = person.name.clone()))
+                    .body(fl!("person-birthday-dialog", "body"))
+                    .close_response("cancel")
+                    .default_response("set")
+                    .extra_child(&calendar)
+                    .build();
+
+                dialog.add_response("cancel", &fl!("person-birthday-dialog", "cancel-button"));
+                dialog.add_response("clear", &fl!("person-birthday-dialog", "clear-button"));
+                dialog.add_response("set", &fl!("person-birthday-dialog", "set-button"));
+                dialog.set_response_appearance("set", adw::ResponseAppearance::Suggested);
+                dialog.set_response_enabled("clear", person.birth_date.is_some());
+
+                dialog.connect_response(None, move |_, response| {
+                    if response == "set" {
+                        let date = calendar.date();
+                        let birth_date = NaiveDate::from_ymd_opt(
+                            date.year(),
+                            date.month() as u32,
+                            date.day_of_month() as u32,
+                        );
+                        sender.input(PersonAlbumInput::Birthday(birth_date));
+                    } else if response == "clear" {
+                        sender.input(PersonAlbumInput::Birthday(None));
+                    }
+                });
+
+                if let Some(root) = gtk::Widget::root(self.avatar.widget_ref()) {
+                    dialog.present(Some(&root));
+                } else {
+                    error!("Couldn't get root widget!");
+                }
+            },
+            PersonAlbumInput::Birthday(birth_date) => {
+                let Some(ref mut person) = self.person else {
+                    info!("Asked to set birthday of person, but no person for album");
+                    return;
+                };
+
+                info!("Setting birthday of {} to {:?}", person.person_id, birth_date);
+
+                if let Err(e) = self.repo.set_birth_date(person.person_id, birth_date) {
+                    error!("Failed to set birthday: {}", e);
+                    return;
+                }
+
+                person.birth_date = birth_date;
+                self.update_ages();
             },
             PersonAlbumInput::Adapt(layout @ adaptive::Layout::Narrow) => {
                 self.edge_length.set_value(NARROW_EDGE_LENGTH);
@@ -363,9 +596,276 @@ impl SimpleComponent for PersonAlbum {
                 }
                 self.person = None;
                 self.picture_ids.clear();
+                self.video_ids.clear();
                 let _ = sender.output(PersonAlbumOutput::Deleted);
             },
+            PersonAlbumInput::MergeDialog => {
+                let Some(ref person) = self.person else {
+                    info!("Asked to merge person, but no person for album");
+                    return;
+                };
+                info!("Starting merge flow for person: {}", person.person_id);
+
+                let others: Vec<people::Person> = self.repo.all_people()
+                    .unwrap_or_default()
+                    .into_iter()
+                    .filter(|p| p.person_id != person.person_id)
+                    .collect();
+
+                let people_list = gtk::ListBox::builder()
+                    .selection_mode(gtk::SelectionMode::None)
+                    .css_classes(["boxed-list"])
+                    .build();
+
+                for other in others.iter() {
+                    let avatar = person_avatar(other);
+                    let row = adw::ActionRow::builder()
+                        .title(&other.name)
+                        .activatable(true)
+                        .build();
+                    row.add_prefix(&avatar);
+                    people_list.append(&row);
+                }
+
+                let scrolled = gtk::ScrolledWindow::builder()
+                    .hscrollbar_policy(gtk::PolicyType::Never)
+                    .propagate_natural_height(true)
+                    .max_content_height(400)
+                    .child(&people_list)
+                    .build();
+
+                let dialog = adw::AlertDialog::builder()
+                    .heading(fl!("person-merge-dialog", "heading", name = person.name.clone()))
+                    .body(fl!("person-merge-dialog", "body"))
+                    .close_response("cancel")
+                    .extra_child(&scrolled)
+                    .build();
+
+                dialog.add_response("cancel", &fl!("person-merge-dialog", "cancel-button"));
+                dialog.set_default_response(Some("cancel"));
+
+                {
+                    let dialog = dialog.clone();
+                    let sender = sender.clone();
+                    people_list.connect_row_activated(move |_, row| {
+                        let Some(other) = usize::try_from(row.index()).ok().and_then(|i| others.get(i)) else {
+                            return;
+                        };
+                        dialog.close();
+                        sender.input(PersonAlbumInput::MergeWithDialog(other.clone()));
+                    });
+                }
+
+                if let Some(root) = gtk::Widget::root(self.avatar.widget_ref()) {
+                    dialog.present(Some(&root));
+                } else {
+                    error!("Couldn't get root widget!");
+                }
+            },
+            PersonAlbumInput::MergeWithDialog(other) => {
+                let Some(ref person) = self.person else {
+                    info!("Asked to merge person, but no person for album");
+                    return;
+                };
+                info!("Choosing name and thumbnail to merge {} into {}", other.person_id, person.person_id);
+
+                // Name of surviving person
+                let name_list = gtk::ListBox::builder()
+                    .selection_mode(gtk::SelectionMode::None)
+                    .css_classes(["boxed-list"])
+                    .build();
+
+                let keep_this_name = gtk::CheckButton::builder().active(true).build();
+                let keep_other_name = gtk::CheckButton::builder().group(&keep_this_name).build();
+                name_list.append(&choice_row(&person.name, &keep_this_name));
+                name_list.append(&choice_row(&other.name, &keep_other_name));
+
+                // Thumbnail of surviving person
+                let thumbnail_list = gtk::ListBox::builder()
+                    .selection_mode(gtk::SelectionMode::None)
+                    .css_classes(["boxed-list"])
+                    .build();
+
+                let keep_this_thumbnail = gtk::CheckButton::builder().active(true).build();
+                let keep_other_thumbnail = gtk::CheckButton::builder().group(&keep_this_thumbnail).build();
+
+                let row = choice_row(&person.name, &keep_this_thumbnail);
+                row.add_suffix(&person_avatar(person));
+                thumbnail_list.append(&row);
+
+                let row = choice_row(&other.name, &keep_other_thumbnail);
+                row.add_suffix(&person_avatar(&other));
+                thumbnail_list.append(&row);
+
+                let content = gtk::Box::builder()
+                    .orientation(gtk::Orientation::Vertical)
+                    .spacing(12)
+                    .build();
+
+                content.append(&gtk::Label::builder()
+                    .label(fl!("person-merge-with-dialog", "name-title"))
+                    .halign(gtk::Align::Start)
+                    .css_classes(["heading"])
+                    .build());
+                content.append(&name_list);
+
+                content.append(&gtk::Label::builder()
+                    .label(fl!("person-merge-with-dialog", "thumbnail-title"))
+                    .halign(gtk::Align::Start)
+                    .css_classes(["heading"])
+                    .build());
+                content.append(&thumbnail_list);
+
+                let dialog = adw::AlertDialog::builder()
+                    .heading(fl!("person-merge-with-dialog", "heading"))
+                    .body(fl!("person-merge-with-dialog", "body",
+                        this_name = person.name.clone(),
+                        other_name = other.name.clone()))
+                    .close_response("cancel")
+                    .default_response("merge")
+                    .extra_child(&content)
+                    .build();
+
+                dialog.add_response("cancel", &fl!("person-merge-with-dialog", "cancel-button"));
+                dialog.set_default_response(Some("cancel"));
+                dialog.set_close_response("cancel");
+
+                dialog.add_response("merge", &fl!("person-merge-with-dialog", "merge-button"));
+                dialog.set_response_appearance("merge", adw::ResponseAppearance::Destructive);
+
+                dialog.connect_response(None, move |_, response| {
+                    if response == "merge" {
+                        sender.input(PersonAlbumInput::Merge(
+                            other.clone(),
+                            keep_other_name.is_active(),
+                            keep_other_thumbnail.is_active(),
+                        ));
+                    }
+                });
+
+                if let Some(root) = gtk::Widget::root(self.avatar.widget_ref()) {
+                    dialog.present(Some(&root));
+                } else {
+                    error!("Couldn't get root widget!");
+                }
+            },
+            PersonAlbumInput::Merge(other, keep_other_name, keep_other_thumbnail) => {
+                let Some(ref person) = self.person else {
+                    info!("Asked to merge person, but no person for album");
+                    return;
+                };
+                info!("Merging person {} into {}", other.person_id, person.person_id);
+
+                let name = if keep_other_name { &other.name } else { &person.name };
+                let thumbnail_of = if keep_other_thumbnail { other.person_id } else { person.person_id };
+
+                if let Err(e) = self.repo.merge_people(other.person_id, person.person_id, name, thumbnail_of) {
+                    error!("Failed to merge people: {}", e);
+                    return;
+                }
+
+                match self.repo.get_person(person.person_id) {
+                    Ok(Some(merged)) => sender.input(PersonAlbumInput::View(merged)),
+                    Ok(None) => error!("Merged person {} has vanished", person.person_id),
+                    Err(e) => error!("Failed to load merged person: {}", e),
+                }
+
+                let _ = sender.output(PersonAlbumOutput::Merged);
+            },
         }
     }
 }
 
+impl PersonAlbum {
+    /// Rebuild the choice of ages for browsing the album. Ages are only available
+    /// when the person's birthday is known.
+    fn update_ages(&mut self) {
+        self.age_choices.clear();
+
+        let Some(ref person) = self.person else {
+            self.ages.set_visible(false);
+            return;
+        };
+
+        if person.birth_date.is_some() {
+            let filter = AlbumFilter::Any(self.picture_ids.clone(), self.video_ids.clone());
+
+            let mut ages: Vec<u32> = {
+                let data = self.state.read();
+                data.iter()
+                    .filter(|v| filter.clone().filter(v))
+                    .filter_map(|v| person.age_at(v.local_ts().date_naive()))
+                    .collect()
+            };
+
+            ages.sort_unstable();
+            ages.dedup();
+
+            self.age_choices.push(None);
+            self.age_choices.extend(ages.into_iter().map(Some));
+        }
+
+        let labels: Vec<String> = self.age_choices
+            .iter()
+            .map(|choice| match choice {
+                None => fl!("person-album-age", "all"),
+                Some(age) => fl!("person-album-age", "age", name = person.name.clone(), age = *age),
+            })
+            .collect();
+
+        let labels: Vec<&str> = labels.iter().map(|x| x.as_str()).collect();
+        self.ages.set_model(Some(&gtk::StringList::new(&labels)));
+        self.ages.set_selected(0);
+
+        // Only worth browsing by age if there is more than one age.
+        self.ages.set_visible(self.age_choices.len() > 2);
+    }
+
+    /// Filter for all items of person at the selected age.
+    fn filter_for_age(&self) -> AlbumFilter {
+        let age = self.age_choices
+            .get(self.ages.selected() as usize)
+            .cloned()
+            .flatten();
+
+        let (Some(age), Some(person)) = (age, self.person.as_ref()) else {
+            return AlbumFilter::Any(self.picture_ids.clone(), self.video_ids.clone());
+        };
+
+        let filter = AlbumFilter::Any(self.picture_ids.clone(), self.video_ids.clone());
+
+        let data = self.state.read();
+        let at_age: Vec<_> = data.iter()
+            .filter(|v| filter.clone().filter(v))
+            .filter(|v| person.age_at(v.local_ts().date_naive()) == Some(age))
+            .collect();
+
+        let picture_ids = at_age.iter().filter_map(|v| v.picture_id).collect();
+        let video_ids = at_age.iter().filter_map(|v| v.video_id).collect();
+
+        AlbumFilter::Any(picture_ids, video_ids)
+    }
+}
+
+/// Small avatar for a person to show in lists.
+fn person_avatar(person: &people::Person) -> adw::Avatar {
+    let avatar = adw::Avatar::builder()
+        .size(32)
+        .show_initials(true)
+        .text(&person.name)
+        .build();
+
+    let img = gdk::Texture::from_filename(&person.thumbnail_path).ok();
+    avatar.set_custom_image(img.as_ref());
+    avatar
+}
+
+/// List row with a check button for choosing between alternatives.
+fn choice_row(title: &str, check: &gtk::CheckButton) -> adw::ActionRow {
+    let row = adw::ActionRow::builder()
+        .title(title)
+        .build();
+    row.add_prefix(check);
+    row.set_activatable_widget(Some(check));
+    row
+}
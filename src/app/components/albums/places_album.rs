@@ -8,6 +8,8 @@ use itertools::Itertools;
 
 use relm4::gtk;
 use relm4::gtk::prelude::FrameExt;
+use relm4::adw;
+use relm4::adw::prelude::*;
 use relm4::gtk::prelude::WidgetExt;
 use relm4::gtk::gdk_pixbuf;
 use relm4::gtk::gdk;
@@ -19,8 +21,13 @@ use tracing::{debug,error,info};
 use crate::adaptive;
 use crate::app::SharedState;
 use crate::app::ActiveView;
+use crate::app::SettingsState;
 use crate::app::ViewName;
+use crate::app::components::albums::album_filter::AlbumFilter;
+use crate::app::components::albums::offline_map;
+use crate::fl;
 use fotema_core::{Visual, VisualId};
+use fotema_core::places;
 
 use h3o;
 use h3o::CellIndex;
@@ -31,6 +38,7 @@ use shumate::MAP_SOURCE_OSM_MAPNIK;
 
 use std::sync::Arc;
 use std::collections::HashMap;
+use std::path::PathBuf;
 
 const NARROW_EDGE_LENGTH: i32 = 60;
 const WIDE_EDGE_LENGTH: i32 = 100;
@@ -40,6 +48,8 @@ const MAX_ZOOM_LEVEL: u32 = 17;
 
 const DEFAULT_ZOOM_LEVEL: f64 = 7.0;
 
+const PATH_STROKE_WIDTH: f64 = 4.0;
+
 #[derive(Debug)]
 pub enum PlacesAlbumInput {
     Activate,
@@ -55,6 +65,24 @@ pub enum PlacesAlbumInput {
 
     // Map has been dragged
     Move,
+
+    // Place selected from list of places
+    PlaceSelected(places::Place),
+
+    // Country selected from list of places. Country code and name.
+    CountrySelected(String, String),
+
+    // Preferences have changed, possibly including the map tile source.
+    SettingsChanged,
+
+    // Privacy zones have been added or removed.
+    PrivacyZonesChanged,
+
+    // Show only items matching filter on the map.
+    Filter(AlbumFilter),
+
+    // A group of items in a cell has been clicked.
+    CellSelected(CellIndex),
 }
 
 #[derive(Debug)]
@@ -64,6 +92,10 @@ pub enum PlacesAlbumOutput {
 
     // User has selected a group of items grouped in a cell index to view as an album
     GeographicArea(CellIndex),
+
+    // User has selected a named place, such as a city or country, or a group
+    // of items in a cell of a filtered map to view as an album.
+    Place(String, AlbumFilter),
 }
 
 /// Item to represent all photos in a cell
@@ -78,9 +110,21 @@ pub struct CellItem {
 
 pub struct PlacesAlbum {
     state: SharedState,
+    repo: places::Repository,
     active_view: ActiveView,
+    view_name: ViewName,
+
+    /// Items to show on the map. The places page shows everything, but a map can
+    /// also be shown for a person, folder, year, etc.
+    filter: AlbumFilter,
+
+    settings_state: SettingsState,
     edge_length: I32Binding,
 
+    /// Countries and cities to browse, next to the map
+    places_list: gtk::ListBox,
+    places_sidebar: gtk::ScrolledWindow,
+
     /// Map of visual items
     map: shumate::SimpleMap,
     viewport: shumate::Viewport,
@@ -88,6 +132,15 @@ pub struct PlacesAlbum {
     /// Layer containing thumbnails
     marker_layer: shumate::MarkerLayer,
 
+    /// Path taken each day, drawn underneath the thumbnails.
+    path_layers: Vec<shumate::PathLayer>,
+
+    /// Layer of offline tiles drawn over online tiles, if both are enabled.
+    offline_layer: Option<shumate::MapLayer>,
+
+    /// Offline tile archive and online fallback settings currently applied to the map.
+    map_settings: Option<(Option<PathBuf>, bool)>,
+
     /// Current resolution being viewed
     resolution: h3o::Resolution,
 
@@ -98,17 +151,40 @@ pub struct PlacesAlbum {
     /// Cell nearest centre of map
     centre_cell: h3o::CellIndex,
 
+    /// Items located in a privacy zone are not pinned to the map.
+    privacy_zones: places::PrivacyZones,
+
     need_refresh: bool,
+
+    /// Layout is wide enough to show places next to the map
+    is_wide: bool,
 }
 
 #[relm4::component(pub)]
 impl SimpleComponent for PlacesAlbum {
-    type Init = (SharedState, ActiveView);
+    type Init = (SharedState, places::Repository, ActiveView, ViewName, AlbumFilter, SettingsState);
     type Input = PlacesAlbumInput;
     type Output = PlacesAlbumOutput;
 
     view! {
        gtk::Box {
+        set_orientation: gtk::Orientation::Horizontal,
+
+        #[local_ref]
+        places_sidebar -> gtk::ScrolledWindow {
+            set_hscrollbar_policy: gtk::PolicyType::Never,
+            set_width_request: 280,
+            set_visible: false,
+
+            #[local_ref]
+            places_list -> gtk::ListBox {
+                set_selection_mode: gtk::SelectionMode::None,
+                set_valign: gtk::Align::Start,
+                set_margin_all: 12,
+                add_css_class: "boxed-list",
+            },
+        },
+
        #[local_ref]
         map_widget -> shumate::SimpleMap{
             set_vexpand: true,
@@ -118,7 +194,7 @@ impl SimpleComponent for PlacesAlbum {
     }
 
     fn init(
-        (state, active_view): Self::Init,
+        (state, repo, active_view, view_name, filter, settings_state): Self::Init,
         _root: Self::Root,
         sender: ComponentSender<Self>,
     ) -> ComponentParts<Self> {
@@ -132,16 +208,12 @@ impl SimpleComponent for PlacesAlbum {
             scale.set_unit(shumate::Unit::Metric);
         }
 
-        // Use OpenStreetMap as the source
-        let registry = shumate::MapSourceRegistry::with_defaults();
-        let map_source = registry.by_id(MAP_SOURCE_OSM_MAPNIK);
-        let map = map_widget.map().unwrap();
+        settings_state.subscribe(sender.input_sender(), |_| PlacesAlbumInput::SettingsChanged);
 
-        map_widget.set_map_source(map_source.as_ref());
+        let map = map_widget.map().unwrap();
 
-        // Reference map source used by MarkerLayer
+        // Map source is set from preferences once the model is built.
         let viewport = map_widget.viewport().unwrap();
-        viewport.set_reference_map_source(map_source.as_ref());
         viewport.set_min_zoom_level(MIN_ZOOM_LEVEL);
         viewport.set_max_zoom_level(MAX_ZOOM_LEVEL);
         viewport.set_zoom_level(DEFAULT_ZOOM_LEVEL);
@@ -167,14 +239,27 @@ impl SimpleComponent for PlacesAlbum {
 
         map.add_layer(&marker_layer);
 
-        let model = PlacesAlbum {
+        let places_list = gtk::ListBox::new();
+        let places_sidebar = gtk::ScrolledWindow::new();
+
+        let mut model = PlacesAlbum {
             state,
+            repo,
             active_view,
+            view_name,
+            filter,
+            settings_state,
+            places_list: places_list.clone(),
+            places_sidebar: places_sidebar.clone(),
             need_refresh: true,
+            is_wide: false,
             edge_length: I32Binding::new(NARROW_EDGE_LENGTH),
             map: map_widget.clone(),
             viewport,
             marker_layer,
+            path_layers: Vec::new(),
+            offline_layer: None,
+            map_settings: None,
 
             // NOTE will immediately be overridden when map is first rendered
             resolution: h3o::Resolution::Zero,
@@ -185,8 +270,12 @@ impl SimpleComponent for PlacesAlbum {
             centre_cell: h3o::LatLng::new(0.0, 0.0)
                 .expect("0/0 is a valid lat/lng")
                 .to_cell(h3o::Resolution::Zero),
+
+            privacy_zones: places::PrivacyZones::default(),
         };
 
+        model.update_map_source();
+
         let widgets = view_output!();
         ComponentParts { model, widgets }
     }
@@ -194,26 +283,91 @@ impl SimpleComponent for PlacesAlbum {
     fn update(&mut self, msg: Self::Input, sender: ComponentSender<Self>) {
         match msg {
             PlacesAlbumInput::Activate => {
-                *self.active_view.write() = ViewName::Places;
+                *self.active_view.write() = self.view_name;
                 if self.need_refresh {
                     self.refresh(&sender);
                 }
             }
             PlacesAlbumInput::Refresh => {
-                if *self.active_view.read() == ViewName::Places {
-                    info!("Places view is active so refreshing");
+                if *self.active_view.read() == self.view_name {
+                    info!("{:?} view is active so refreshing", self.view_name);
                     self.refresh(&sender);
                 } else {
-                    info!("Places view is inactive so clearing");
+                    info!("{:?} view is inactive so clearing", self.view_name);
                     self.marker_layer.remove_all();
                     self.need_refresh = true;
                 }
             }
+            PlacesAlbumInput::Filter(filter) => {
+                self.filter = filter;
+                if *self.active_view.read() == self.view_name {
+                    self.refresh(&sender);
+                } else {
+                    self.marker_layer.remove_all();
+                    self.need_refresh = true;
+                }
+            },
+            PlacesAlbumInput::CellSelected(cell_index) => {
+                if self.filter == AlbumFilter::All {
+                    let _ = sender.output(PlacesAlbumOutput::GeographicArea(cell_index));
+                    return;
+                }
+
+                // Only show the items in the cell that are also in the filtered set.
+                let (picture_ids, video_ids): (Vec<_>, Vec<_>) = {
+                    let data = self.state.read();
+                    let items = data.iter()
+                        .filter(|x| self.filter.clone().filter(x))
+                        .filter(|x| self.privacy_zones.reveal(x.location)
+                            .is_some_and(|location| location.to_cell(cell_index.resolution()) == cell_index))
+                        .collect_vec();
+
+                    (
+                        items.iter().flat_map(|x| x.picture_id).collect(),
+                        items.iter().flat_map(|x| x.video_id).collect(),
+                    )
+                };
+
+                let _ = sender.output(PlacesAlbumOutput::Place(fl!("places-page"), AlbumFilter::Any(picture_ids, video_ids)));
+            },
             PlacesAlbumInput::Adapt(adaptive::Layout::Narrow) => {
                 self.edge_length.set_value(NARROW_EDGE_LENGTH);
+                self.is_wide = false;
+                self.update_places_sidebar_visibility();
             },
             PlacesAlbumInput::Adapt(adaptive::Layout::Wide) => {
                 self.edge_length.set_value(WIDE_EDGE_LENGTH);
+                self.is_wide = true;
+                self.update_places_sidebar_visibility();
+            },
+            PlacesAlbumInput::PlaceSelected(place) => {
+                info!("Viewing place: {}", place);
+                match self.repo.find_visuals_for_place(place.place_id, &self.privacy_zones) {
+                    Ok((picture_ids, video_ids)) => {
+                        let _ = sender.output(PlacesAlbumOutput::Place(place.city, AlbumFilter::Any(picture_ids, video_ids)));
+                    },
+                    Err(e) => error!("Failed finding items for place: {}", e),
+                }
+            },
+            PlacesAlbumInput::CountrySelected(country_code, country) => {
+                info!("Viewing country: {}", country_code);
+                match self.repo.find_visuals_for_country(&country_code, &self.privacy_zones) {
+                    Ok((picture_ids, video_ids)) => {
+                        let _ = sender.output(PlacesAlbumOutput::Place(country, AlbumFilter::Any(picture_ids, video_ids)));
+                    },
+                    Err(e) => error!("Failed finding items for country: {}", e),
+                }
+            },
+            PlacesAlbumInput::SettingsChanged => {
+                self.update_map_source();
+            },
+            PlacesAlbumInput::PrivacyZonesChanged => {
+                if *self.active_view.read() == self.view_name {
+                    self.refresh(&sender);
+                } else {
+                    self.marker_layer.remove_all();
+                    self.need_refresh = true;
+                }
             },
             PlacesAlbumInput::Zoom => {
                 let zoom_level = self.viewport.zoom_level();
@@ -231,6 +385,67 @@ impl SimpleComponent for PlacesAlbum {
 
 impl PlacesAlbum {
 
+    /// Use offline tiles from a local tile archive if one is configured, otherwise
+    /// use OpenStreetMap. If both are enabled, then OpenStreetMap tiles are drawn
+    /// underneath the offline tiles to fill in any gaps in the tile archive.
+    fn update_map_source(&mut self) {
+        let (offline_map, online_map_fallback) = {
+            let settings = self.settings_state.read();
+            (settings.offline_map.clone(), settings.online_map_fallback)
+        };
+
+        let map_settings = Some((offline_map.clone(), online_map_fallback));
+        if self.map_settings == map_settings {
+            return;
+        }
+        self.map_settings = map_settings;
+
+        let map = self.map.map().unwrap();
+
+        if let Some(layer) = self.offline_layer.take() {
+            map.remove_layer(&layer);
+        }
+
+        let offline = offline_map.as_ref().and_then(|path| {
+            info!("Using offline map tiles from {:?}", path);
+            offline_map::open_map_source(path)
+                .inspect_err(|e| error!("Failed opening offline map tiles: {}", e))
+                .ok()
+        });
+
+        let online = if offline_map.is_none() || online_map_fallback {
+            shumate::MapSourceRegistry::with_defaults().by_id(MAP_SOURCE_OSM_MAPNIK)
+        } else {
+            None
+        };
+
+        match (offline, online) {
+            (Some(offline), Some(online)) => {
+                self.map.set_map_source(Some(&online));
+
+                // Reference map source used by MarkerLayer
+                self.viewport.set_reference_map_source(Some(&online));
+
+                // Offline tiles must be drawn under any paths as well as the thumbnails.
+                let layer = shumate::MapLayer::new(&offline, &self.viewport);
+                match self.path_layers.first() {
+                    Some(path_layer) => map.insert_layer_behind(&layer, Some(path_layer)),
+                    None => map.insert_layer_behind(&layer, Some(&self.marker_layer)),
+                }
+                self.offline_layer = Some(layer);
+            },
+            (Some(offline), None) => {
+                self.map.set_map_source(Some(&offline));
+                self.viewport.set_reference_map_source(Some(&offline));
+            },
+            (None, online) => {
+                // Without an online fallback a broken tile archive leaves the map blank.
+                self.map.set_map_source(online.as_ref());
+                self.viewport.set_reference_map_source(online.as_ref());
+            },
+        }
+    }
+
     /// Maps a Shumate zoom level to a H3O resolution
     /// FIXME this is pretty coarse. Would be good map by scale or by fractional zoom levels.
     fn zoom_to_resolution(zoom_level: f64) -> h3o::Resolution {
@@ -266,14 +481,20 @@ impl PlacesAlbum {
         debug!("Resolution zoomed from {} to {}", self.resolution, resolution);
 
         self.resolution = *resolution;
+        self.update_cells();
+    }
 
+    /// Group items with a location into cells at the current resolution.
+    fn update_cells(&mut self) {
+        let resolution = &self.resolution;
         let data = self.state.read();
         self.cells.clear();
 
         // Build a map of cell indexes to cell items for current resolution
         data.iter()
-            // only want visual items with location
-            .filter(|x| x.location.is_some())
+            .filter(|x| self.filter.clone().filter(x))
+            // only want visual items with location outside of privacy zones
+            .filter(|x| self.privacy_zones.reveal(x.location).is_some())
             // make visual items in same cell adjacent
             .sorted_by_key(|x| x.location.map(|y| y.to_cell(*resolution)))
             // group visual items in same cell
@@ -317,10 +538,14 @@ impl PlacesAlbum {
         debug!("Centre cell moved from {} to {}", self.centre_cell, centre_cell);
 
         self.centre_cell = centre_cell;
+        self.update_markers(sender);
+    }
 
+    /// Add thumbnails for cells near the centre of the map.
+    fn update_markers(&mut self, sender: &ComponentSender<Self>) {
         // Get neighbouring cells. Hopefully enough to fully cover the map,
         // but not so many that the UI stutters.
-        let nearby = centre_cell.grid_disk::<Vec<_>>(5);
+        let nearby = self.centre_cell.grid_disk::<Vec<_>>(5);
         debug!("{} cells near centre", nearby.len());
 
         // WARNING reusing the marker layer by removing all markers and then adding new ones
@@ -369,8 +594,16 @@ impl PlacesAlbum {
     }
 
     fn refresh(&mut self, sender: &ComponentSender<Self>) {
+        self.privacy_zones = self.repo.all_privacy_zones().unwrap_or_else(|e| {
+            error!("Failed loading privacy zones: {}", e);
+            places::PrivacyZones::default()
+        });
+
         let data = self.state.read().clone();
-        let data = data.iter().filter(|x| x.location.is_some()).collect_vec();
+        let data = data.iter()
+            .filter(|x| self.filter.clone().filter(x))
+            .filter(|x| self.privacy_zones.reveal(x.location).is_some())
+            .collect_vec();
 
         info!("{} items with location data", data.len());
 
@@ -382,11 +615,147 @@ impl PlacesAlbum {
         }
 
         self.viewport.set_zoom_level(DEFAULT_ZOOM_LEVEL);
-        self.update_on_zoom(&PlacesAlbum::zoom_to_resolution(DEFAULT_ZOOM_LEVEL));
-        self.update_on_move(sender);
+
+        // Always rebuild cells and thumbnails because items or privacy zones might
+        // have changed even if the resolution and centre of the map haven't.
+        self.resolution = PlacesAlbum::zoom_to_resolution(DEFAULT_ZOOM_LEVEL);
+        self.update_cells();
+
+        if let Ok(centre_point) = h3o::LatLng::new(self.viewport.latitude(), self.viewport.longitude()) {
+            self.centre_cell = centre_point.to_cell(self.resolution);
+        }
+        self.update_markers(sender);
+
+        // The path taken over the whole library isn't meaningful, so only draw
+        // paths for filtered sets, such as a trip or a person's photos.
+        if self.filter == AlbumFilter::All {
+            self.update_paths(&[]);
+        } else {
+            self.update_paths(&data);
+        }
+
+        self.update_places_list(sender);
         self.need_refresh = false;
     }
 
+    /// Draw a line through the locations of each day's items, in the order they were taken.
+    fn update_paths(&mut self, data: &[&Arc<Visual>]) {
+        let map = self.map.map().expect("Must have map");
+
+        for layer in self.path_layers.drain(..) {
+            map.remove_layer(&layer);
+        }
+
+        let by_day = data.iter()
+            .filter(|x| x.location.is_some())
+            .sorted_by_key(|x| x.ordering_ts)
+            .chunk_by(|x| x.local_ts().date_naive());
+
+        for (_, visuals) in by_day.into_iter() {
+            let locations = visuals
+                .filter_map(|x| x.location)
+                .dedup()
+                .collect_vec();
+
+            // A path needs somewhere to go.
+            if locations.len() < 2 {
+                continue;
+            }
+
+            let layer = shumate::PathLayer::new(&self.viewport);
+            layer.set_stroke_width(PATH_STROKE_WIDTH);
+
+            for location in locations {
+                layer.add_node(&shumate::Coordinate::new_full(location.lat(), location.lng()));
+            }
+
+            map.insert_layer_behind(&layer, Some(&self.marker_layer));
+            self.path_layers.push(layer);
+        }
+
+        debug!("{} paths added to the map", self.path_layers.len());
+    }
+
+    /// Rebuild list of countries, each expanding to the cities in that country.
+    fn update_places_list(&mut self, sender: &ComponentSender<Self>) {
+        self.places_list.remove_all();
+
+        // Places are for the whole library, so don't show them next to a filtered map.
+        if self.filter != AlbumFilter::All {
+            self.update_places_sidebar_visibility();
+            return;
+        }
+
+        let all_places = self.repo.all_places(&self.privacy_zones).unwrap_or_else(|e| {
+            error!("Failed loading places: {}", e);
+            vec![]
+        });
+
+        // Places are already ordered by country.
+        let by_country = all_places
+            .into_iter()
+            .chunk_by(|(place, _)| place.country_code.clone());
+
+        for (country_code, places) in by_country.into_iter() {
+            let places = places.collect_vec();
+            let Some((first, _)) = places.first() else {
+                continue;
+            };
+
+            let country = first.country.clone();
+            let country_count: usize = places.iter().map(|(_, count)| count).sum();
+
+            let expander = adw::ExpanderRow::builder()
+                .title(&country)
+                .subtitle(fl!("places-page-list", "count", count = country_count))
+                .build();
+
+            let row = adw::ActionRow::builder()
+                .title(fl!("places-page-list", "all-of-country"))
+                .activatable(true)
+                .build();
+            {
+                let sender = sender.clone();
+                let country_code = country_code.clone();
+                let country = country.clone();
+                row.connect_activated(move |_| {
+                    sender.input(PlacesAlbumInput::CountrySelected(country_code.clone(), country.clone()));
+                });
+            }
+            expander.add_row(&row);
+
+            for (place, count) in places {
+                let row = adw::ActionRow::builder()
+                    .title(&place.city)
+                    .subtitle(place.region.clone().unwrap_or_default())
+                    .activatable(true)
+                    .build();
+
+                row.add_suffix(&gtk::Label::builder()
+                    .label(count.to_string())
+                    .css_classes(["dim-label"])
+                    .build());
+
+                let sender = sender.clone();
+                row.connect_activated(move |_| {
+                    sender.input(PlacesAlbumInput::PlaceSelected(place.clone()));
+                });
+
+                expander.add_row(&row);
+            }
+
+            self.places_list.append(&expander);
+        }
+
+        self.update_places_sidebar_visibility();
+    }
+
+    /// Only show places next to the map if there is room and there are places to show.
+    fn update_places_sidebar_visibility(&self) {
+        let has_places = self.places_list.first_child().is_some();
+        self.places_sidebar.set_visible(self.is_wide && has_places);
+    }
+
     /// Make thumbnail to put onto map
     fn to_pin_thumbnail(&self, visual: &Visual, count: Option<usize>, sender: &ComponentSender<PlacesAlbum>) -> gtk::Frame {
         let picture = if visual.thumbnail_path.as_ref().is_some_and(|x| x.exists()) {
@@ -454,7 +823,7 @@ impl PlacesAlbum {
                 if count > 1 {
                     info!("Viewing album containing: {}", visual.visual_id);
                     if let Some(cell_index) = visual.location.map(|loc| loc.to_cell(resolution)) {
-                        let _ = sender.output(PlacesAlbumOutput::GeographicArea(cell_index));
+                        sender.input(PlacesAlbumInput::CellSelected(cell_index));
                     }
                 } else {
                     info!("Viewing item: {}", visual.visual_id);
@@ -0,0 +1,398 @@
+// SPDX-FileCopyrightText: © 2024 David Bliss
+//
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use fotema_core::{Visual, VisualId};
+use fotema_core::people;
+use fotema_core::smart_albums;
+use fotema_core::smart_albums::{LinkedItems, Rule, RuleItems, SmartAlbumId};
+use fotema_core::tags;
+use relm4::gtk;
+use relm4::gtk::prelude::*;
+use relm4::*;
+use relm4::adw;
+use relm4::adw::prelude::*;
+use relm4::actions::{RelmAction, RelmActionGroup};
+
+use crate::app::adaptive;
+use crate::app::SharedState;
+use crate::app::ActiveView;
+use crate::app::ViewName;
+use crate::app::components::albums:: {
+    album::{Album, AlbumInput, AlbumOutput},
+    album_filter::AlbumFilter,
+};
+
+use crate::fl;
+
+use std::sync::Arc;
+
+use tracing::{error, info};
+
+relm4::new_action_group!(SmartAlbumActionGroup, "smart_album");
+
+// Change the name and rule of a smart album
+relm4::new_stateless_action!(EditAction, SmartAlbumActionGroup, "edit");
+
+// Delete a smart album
+relm4::new_stateless_action!(DeleteAction, SmartAlbumActionGroup, "delete");
+
+/// Album filter for the rule of a smart album.
+/// The pictures and videos of people and tags are looked up now, so the filter should be
+/// rebuilt when people are recognised in more items or tags change.
+pub fn rule_filter(rule: &Rule, people_repo: &people::Repository, tags_repo: &tags::Repository) -> AlbumFilter {
+    let people = rule
+        .people()
+        .into_iter()
+        .map(|person_id| {
+            let items = LinkedItems {
+                picture_ids: people_repo
+                    .find_pictures_for_person(person_id)
+                    .unwrap_or_default()
+                    .into_iter()
+                    .collect(),
+                video_ids: people_repo
+                    .find_videos_for_person(person_id)
+                    .unwrap_or_default()
+                    .into_iter()
+                    .collect(),
+            };
+            (person_id, items)
+        })
+        .collect();
+
+    let tags = rule
+        .tags()
+        .into_iter()
+        .map(|path| {
+            let items = LinkedItems {
+                picture_ids: tags_repo
+                    .find_pictures_for_tag(&path)
+                    .unwrap_or_default()
+                    .into_iter()
+                    .collect(),
+                video_ids: tags_repo
+                    .find_videos_for_tag(&path)
+                    .unwrap_or_default()
+                    .into_iter()
+                    .collect(),
+            };
+            (path, items)
+        })
+        .collect();
+
+    AlbumFilter::Smart(Arc::new(rule.clone()), Arc::new(RuleItems { people, tags }))
+}
+
+#[derive(Debug)]
+pub enum SmartAlbumInput {
+
+    /// Album is visible
+    Activate,
+
+    // State has been updated
+    Refresh,
+
+    /// View a smart album
+    View(SmartAlbumId),
+
+    /// Smart album has been changed elsewhere, such as in the editor.
+    Reload,
+
+    /// Adapt to layout
+    Adapt(adaptive::Layout),
+
+    /// Picture selected in underlying album
+    Selected(VisualId),
+
+    /// Enter or leave selection mode.
+    SelectionMode(bool),
+
+    /// Set location of items selected in underlying album.
+    EditLocation(Vec<Arc<Visual>>),
+
+    /// Correct date and time of items selected in underlying album.
+    EditDateTime(Vec<Arc<Visual>>),
+
+    /// Add items selected in underlying album to albums curated by the user.
+    AddToAlbum(Vec<Arc<Visual>>),
+
+    /// Move items selected in underlying album to the trash.
+    MoveToTrash(Vec<Arc<Visual>>),
+
+    /// Change name and rule of smart album.
+    Edit,
+
+    /// Start delete smart album flow.
+    DeleteDialog,
+
+    /// Actually delete smart album.
+    Delete,
+
+    Ignore,
+}
+
+#[derive(Debug)]
+pub enum SmartAlbumOutput {
+    /// User has selected photo or video in grid view
+    Selected(VisualId, AlbumFilter),
+
+    /// User wants to change the name and rule of a smart album.
+    Edit(SmartAlbumId),
+
+    /// Smart album deleted.
+    Deleted,
+
+    /// User wants to set the location of the selected photos and videos.
+    EditLocation(Vec<Arc<Visual>>),
+
+    /// User wants to correct the date and time of the selected photos and videos.
+    EditDateTime(Vec<Arc<Visual>>),
+
+    /// User wants to add the selected photos and videos to albums.
+    AddToAlbum(Vec<Arc<Visual>>),
+
+    /// User wants to move the selected photos and videos to the trash.
+    MoveToTrash(Vec<Arc<Visual>>),
+}
+
+/// Photos and videos matching the rule of a smart album.
+pub struct SmartAlbum {
+    repo: smart_albums::Repository,
+    people_repo: people::Repository,
+    tags_repo: tags::Repository,
+    smart_album: Option<smart_albums::SmartAlbum>,
+    filter: AlbumFilter,
+    grid: Controller<Album>,
+    title: gtk::Label,
+    selection_button: gtk::ToggleButton,
+    active_view: ActiveView,
+}
+
+#[relm4::component(pub)]
+impl SimpleComponent for SmartAlbum {
+    type Init = (SharedState, smart_albums::Repository, people::Repository, tags::Repository, ActiveView);
+    type Input = SmartAlbumInput;
+    type Output = SmartAlbumOutput;
+
+    menu! {
+        primary_menu: {
+            section! {
+                &fl!("smart-album-menu-edit") => EditAction,
+                &fl!("smart-album-menu-delete") => DeleteAction,
+            }
+        }
+    }
+
+    view! {
+        adw::ToolbarView {
+            add_top_bar = &adw::HeaderBar {
+                #[wrap(Some)]
+                #[local_ref]
+                set_title_widget = &title -> gtk::Label {
+                    add_css_class: "title",
+                },
+
+                pack_end = &gtk::MenuButton {
+                    set_icon_name: "open-menu-symbolic",
+                    set_menu_model: Some(&primary_menu),
+                },
+
+                #[local_ref]
+                pack_end = &selection_button -> gtk::ToggleButton {
+                    set_icon_name: "selection-mode-symbolic",
+                    set_tooltip_text: Some(&fl!("selection-mode", "tooltip")),
+                    connect_toggled[sender] => move |button| {
+                        sender.input(SmartAlbumInput::SelectionMode(button.is_active()));
+                    },
+                },
+            },
+
+            #[wrap(Some)]
+            set_content = model.grid.widget(),
+        }
+    }
+
+    fn init(
+        (state, repo, people_repo, tags_repo, active_view): Self::Init,
+        root: Self::Root,
+        sender: ComponentSender<Self>,
+    ) -> ComponentParts<Self> {
+        let grid = Album::builder()
+            .launch((state, active_view.clone(), ViewName::SmartAlbum, AlbumFilter::None))
+            .forward(sender.input_sender(), |msg| match msg {
+                AlbumOutput::Selected(id, _) => SmartAlbumInput::Selected(id),
+                AlbumOutput::ScrollOffset(_) => SmartAlbumInput::Ignore,
+                AlbumOutput::EditLocation(visuals) => SmartAlbumInput::EditLocation(visuals),
+                AlbumOutput::EditDateTime(visuals) => SmartAlbumInput::EditDateTime(visuals),
+                AlbumOutput::AddToAlbum(visuals) => SmartAlbumInput::AddToAlbum(visuals),
+                AlbumOutput::MoveToTrash(visuals) => SmartAlbumInput::MoveToTrash(visuals),
+                // Items of a smart album are chosen by its rule, so can't be edited.
+                AlbumOutput::EditAlbum(_, _) => SmartAlbumInput::Ignore,
+            });
+
+        let title = gtk::Label::builder().build();
+
+        let selection_button = gtk::ToggleButton::new();
+
+        let model = SmartAlbum {
+            repo,
+            people_repo,
+            tags_repo,
+            smart_album: None,
+            filter: AlbumFilter::None,
+            grid,
+            title: title.clone(),
+            selection_button: selection_button.clone(),
+            active_view,
+        };
+
+        let widgets = view_output!();
+
+        let mut actions = RelmActionGroup::<SmartAlbumActionGroup>::new();
+
+        let edit_action = {
+            let sender = sender.clone();
+            RelmAction::<EditAction>::new_stateless(move |_| {
+                sender.input(SmartAlbumInput::Edit);
+            })
+        };
+
+        let delete_action = {
+            let sender = sender.clone();
+            RelmAction::<DeleteAction>::new_stateless(move |_| {
+                sender.input(SmartAlbumInput::DeleteDialog);
+            })
+        };
+
+        actions.add_action(edit_action);
+        actions.add_action(delete_action);
+        actions.register_for_widget(&root);
+
+        ComponentParts { model, widgets }
+    }
+
+    fn update(&mut self, msg: Self::Input, sender: ComponentSender<Self>) {
+        match msg {
+            SmartAlbumInput::Activate => {
+                *self.active_view.write() = ViewName::SmartAlbum;
+                self.grid.emit(AlbumInput::Activate);
+            },
+            SmartAlbumInput::Refresh => {
+                // People might have been recognised in new items, or tags changed.
+                self.update_filter();
+                self.grid.emit(AlbumInput::Refresh);
+            },
+            SmartAlbumInput::View(smart_album_id) => {
+                info!("Viewing smart album: {}", smart_album_id);
+                self.selection_button.set_active(false);
+                self.load(smart_album_id);
+            },
+            SmartAlbumInput::Reload => {
+                if let Some(smart_album_id) = self.smart_album.as_ref().map(|x| x.smart_album_id) {
+                    self.load(smart_album_id);
+                }
+            },
+            SmartAlbumInput::Adapt(layout) => {
+                self.grid.emit(AlbumInput::Adapt(layout));
+            },
+            SmartAlbumInput::Selected(visual_id) => {
+                let _ = sender.output(SmartAlbumOutput::Selected(visual_id, self.filter.clone()));
+            },
+            SmartAlbumInput::SelectionMode(enabled) => {
+                self.grid.emit(AlbumInput::SelectionMode(enabled));
+            },
+            SmartAlbumInput::EditLocation(visuals) => {
+                let _ = sender.output(SmartAlbumOutput::EditLocation(visuals));
+            },
+            SmartAlbumInput::EditDateTime(visuals) => {
+                let _ = sender.output(SmartAlbumOutput::EditDateTime(visuals));
+            },
+            SmartAlbumInput::AddToAlbum(visuals) => {
+                let _ = sender.output(SmartAlbumOutput::AddToAlbum(visuals));
+            },
+            SmartAlbumInput::MoveToTrash(visuals) => {
+                let _ = sender.output(SmartAlbumOutput::MoveToTrash(visuals));
+            },
+            SmartAlbumInput::Edit => {
+                if let Some(ref smart_album) = self.smart_album {
+                    let _ = sender.output(SmartAlbumOutput::Edit(smart_album.smart_album_id));
+                }
+            },
+            SmartAlbumInput::DeleteDialog => {
+                let Some(ref smart_album) = self.smart_album else {
+                    info!("Asked to delete smart album, but no smart album to delete");
+                    return;
+                };
+
+                let dialog = adw::AlertDialog::builder()
+                    .heading(fl!("smart-album-delete-dialog", "heading", name = smart_album.name.clone()))
+                    .body(fl!("smart-album-delete-dialog", "body"))
+                    .close_response("cancel")
+                    .default_response("cancel")
+                    .build();
+
+                dialog.add_response("cancel", &fl!("smart-album-delete-dialog", "cancel-button"));
+                dialog.add_response("delete", &fl!("smart-album-delete-dialog", "delete-button"));
+                dialog.set_response_appearance("delete", adw::ResponseAppearance::Destructive);
+
+                dialog.connect_response(None, move |_, response| {
+                    if response == "delete" {
+                        sender.input(SmartAlbumInput::Delete);
+                    }
+                });
+
+                if let Some(root) = gtk::Widget::root(self.title.widget_ref()) {
+                    dialog.present(Some(&root));
+                } else {
+                    error!("Couldn't get root widget!");
+                }
+            },
+            SmartAlbumInput::Delete => {
+                let Some(smart_album) = self.smart_album.take() else {
+                    info!("Asked to delete smart album, but no smart album to delete");
+                    return;
+                };
+
+                info!("Deleting smart album {}", smart_album.smart_album_id);
+
+                if let Err(e) = self.repo.delete(smart_album.smart_album_id) {
+                    error!("Failed to delete smart album: {}", e);
+                    self.smart_album = Some(smart_album);
+                    return;
+                }
+
+                let _ = sender.output(SmartAlbumOutput::Deleted);
+            },
+            SmartAlbumInput::Ignore => {},
+        }
+    }
+}
+
+impl SmartAlbum {
+    /// Load smart album from database and show items matching its rule.
+    fn load(&mut self, smart_album_id: SmartAlbumId) {
+        match self.repo.get(smart_album_id) {
+            Ok(Some(smart_album)) => {
+                self.title.set_label(&smart_album.name);
+                self.smart_album = Some(smart_album);
+                self.update_filter();
+            },
+            Ok(None) => {
+                info!("Smart album {} no longer exists", smart_album_id);
+                self.smart_album = None;
+                self.update_filter();
+            },
+            Err(e) => error!("Failed to load smart album: {}", e),
+        }
+    }
+
+    fn update_filter(&mut self) {
+        self.filter = self.smart_album
+            .as_ref()
+            .map(|smart_album| rule_filter(&smart_album.rule, &self.people_repo, &self.tags_repo))
+            .unwrap_or(AlbumFilter::None);
+
+        self.grid.emit(AlbumInput::Filter(self.filter.clone()));
+    }
+}
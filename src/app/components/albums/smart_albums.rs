@@ -0,0 +1,184 @@
+// SPDX-FileCopyrightText: © 2024 David Bliss
+//
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use fotema_core::people;
+use fotema_core::smart_albums;
+use fotema_core::smart_albums::SmartAlbumId;
+use fotema_core::tags;
+
+use relm4::adw;
+use relm4::adw::prelude::*;
+use relm4::gtk;
+use relm4::*;
+
+use crate::app::ActiveView;
+use crate::app::SharedState;
+use crate::app::ViewName;
+use crate::fl;
+
+use super::smart_album::rule_filter;
+
+use tracing::info;
+
+#[derive(Debug)]
+pub enum SmartAlbumsInput {
+    Activate,
+
+    // Reload smart albums from database
+    Refresh,
+}
+
+#[derive(Debug)]
+pub enum SmartAlbumsOutput {
+    /// User wants to view a smart album.
+    Selected(SmartAlbumId),
+
+    /// User wants to create a smart album.
+    Create,
+}
+
+/// Albums whose items are chosen by rules.
+pub struct SmartAlbums {
+    state: SharedState,
+    repo: smart_albums::Repository,
+    people_repo: people::Repository,
+    tags_repo: tags::Repository,
+    active_view: ActiveView,
+    albums_list: gtk::ListBox,
+    albums_view: gtk::ScrolledWindow,
+    status: adw::StatusPage,
+}
+
+#[relm4::component(pub)]
+impl SimpleComponent for SmartAlbums {
+    type Init = (SharedState, smart_albums::Repository, people::Repository, tags::Repository, ActiveView);
+    type Input = SmartAlbumsInput;
+    type Output = SmartAlbumsOutput;
+
+    view! {
+        gtk::Box {
+            set_orientation: gtk::Orientation::Vertical,
+
+            gtk::Box {
+                set_orientation: gtk::Orientation::Horizontal,
+                set_halign: gtk::Align::End,
+                set_margin_all: 8,
+
+                gtk::Button {
+                    set_icon_name: "list-add-symbolic",
+                    set_label: &fl!("smart-albums-page", "create-button"),
+                    add_css_class: "flat",
+                    connect_clicked[sender] => move |_| {
+                        let _ = sender.output(SmartAlbumsOutput::Create);
+                    },
+                },
+            },
+
+            #[local_ref]
+            albums_view -> gtk::ScrolledWindow {
+                set_vexpand: true,
+                set_hscrollbar_policy: gtk::PolicyType::Never,
+
+                adw::Clamp {
+                    set_margin_all: 12,
+
+                    #[local_ref]
+                    albums_list -> gtk::ListBox {
+                        set_valign: gtk::Align::Start,
+                        set_selection_mode: gtk::SelectionMode::None,
+                        add_css_class: "boxed-list",
+                    },
+                },
+            },
+
+            #[local_ref]
+            status -> adw::StatusPage {
+                set_valign: gtk::Align::Start,
+                set_vexpand: true,
+                set_visible: false,
+                set_icon_name: Some("edit-find-symbolic"),
+                set_title: &fl!("smart-albums-page", "empty-title"),
+                set_description: Some(&fl!("smart-albums-page", "empty-description")),
+            },
+        },
+    }
+
+    fn init(
+        (state, repo, people_repo, tags_repo, active_view): Self::Init,
+        _root: Self::Root,
+        sender: ComponentSender<Self>,
+    ) -> ComponentParts<Self> {
+        let albums_list = gtk::ListBox::new();
+        let albums_view = gtk::ScrolledWindow::new();
+        let status = adw::StatusPage::new();
+
+        let model = SmartAlbums {
+            state,
+            repo,
+            people_repo,
+            tags_repo,
+            active_view,
+            albums_list: albums_list.clone(),
+            albums_view: albums_view.clone(),
+            status: status.clone(),
+        };
+
+        let widgets = view_output!();
+
+        ComponentParts { model, widgets }
+    }
+
+    fn update(&mut self, msg: Self::Input, sender: ComponentSender<Self>) {
+        match msg {
+            SmartAlbumsInput::Activate => {
+                info!("Activating smart albums view");
+                *self.active_view.write() = ViewName::SmartAlbums;
+                self.refresh(&sender);
+            },
+            SmartAlbumsInput::Refresh => {
+                self.refresh(&sender);
+            },
+        }
+    }
+}
+
+impl SmartAlbums {
+    fn refresh(&mut self, sender: &ComponentSender<Self>) {
+        let albums = self.repo.all().unwrap_or_default();
+
+        self.status.set_visible(albums.is_empty());
+        self.albums_view.set_visible(!albums.is_empty());
+
+        self.albums_list.remove_all();
+
+        for album in albums {
+            // Items are counted now so the count is as current as the library.
+            let filter = rule_filter(&album.rule, &self.people_repo, &self.tags_repo);
+            let count = self.state
+                .read()
+                .iter()
+                .filter(|v| filter.clone().filter(v))
+                .count();
+
+            let row = adw::ActionRow::builder()
+                .title(&album.name)
+                .use_markup(false)
+                .subtitle(fl!("smart-albums-page", "count", count = count))
+                .activatable(true)
+                .build();
+
+            row.add_suffix(&gtk::Image::from_icon_name("go-next-symbolic"));
+
+            {
+                let sender = sender.clone();
+                let smart_album_id = album.smart_album_id;
+                row.connect_activated(move |_| {
+                    let _ = sender.output(SmartAlbumsOutput::Selected(smart_album_id));
+                });
+            }
+
+            self.albums_list.append(&row);
+        }
+    }
+}
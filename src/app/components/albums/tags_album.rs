@@ -0,0 +1,200 @@
+// SPDX-FileCopyrightText: © 2024 David Bliss
+//
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use fotema_core::tags;
+use fotema_core::tags::TagId;
+
+use relm4::adw;
+use relm4::adw::prelude::*;
+use relm4::gtk;
+use relm4::*;
+
+use crate::app::ActiveView;
+use crate::app::ViewName;
+use crate::app::components::albums::album_filter::AlbumFilter;
+use crate::fl;
+
+use std::collections::{HashMap, HashSet};
+
+use tracing::info;
+
+#[derive(Debug)]
+pub enum TagsAlbumInput {
+    Activate,
+
+    // Reload tags from database
+    Refresh,
+
+    /// View items of tag with path.
+    View(String),
+}
+
+#[derive(Debug)]
+pub enum TagsAlbumOutput {
+    /// User wants to view the items of a tag, including those of nested tags.
+    /// Has title for album and filter for items.
+    Selected(String, AlbumFilter),
+}
+
+/// Browser for keyword tags, with nested tags inside their parents.
+pub struct TagsAlbum {
+    repo: tags::Repository,
+    active_view: ActiveView,
+    tags_list: gtk::ListBox,
+    tags_view: gtk::ScrolledWindow,
+    status: adw::StatusPage,
+}
+
+#[relm4::component(pub)]
+impl SimpleComponent for TagsAlbum {
+    type Init = (tags::Repository, ActiveView);
+    type Input = TagsAlbumInput;
+    type Output = TagsAlbumOutput;
+
+    view! {
+        gtk::Box {
+            set_orientation: gtk::Orientation::Vertical,
+
+            #[local_ref]
+            tags_view -> gtk::ScrolledWindow {
+                set_vexpand: true,
+                set_hscrollbar_policy: gtk::PolicyType::Never,
+
+                adw::Clamp {
+                    set_margin_all: 12,
+
+                    #[local_ref]
+                    tags_list -> gtk::ListBox {
+                        set_valign: gtk::Align::Start,
+                        set_selection_mode: gtk::SelectionMode::None,
+                        add_css_class: "boxed-list",
+                    },
+                },
+            },
+
+            #[local_ref]
+            status -> adw::StatusPage {
+                set_valign: gtk::Align::Start,
+                set_vexpand: true,
+                set_visible: false,
+                set_icon_name: Some("tag-symbolic"),
+                set_title: &fl!("tags-page", "empty-title"),
+                set_description: Some(&fl!("tags-page", "empty-description")),
+            },
+        },
+    }
+
+    fn init(
+        (repo, active_view): Self::Init,
+        _root: Self::Root,
+        sender: ComponentSender<Self>,
+    ) -> ComponentParts<Self> {
+        let tags_list = gtk::ListBox::new();
+        let tags_view = gtk::ScrolledWindow::new();
+        let status = adw::StatusPage::new();
+
+        let model = TagsAlbum {
+            repo,
+            active_view,
+            tags_list: tags_list.clone(),
+            tags_view: tags_view.clone(),
+            status: status.clone(),
+        };
+
+        let widgets = view_output!();
+
+        ComponentParts { model, widgets }
+    }
+
+    fn update(&mut self, msg: Self::Input, sender: ComponentSender<Self>) {
+        match msg {
+            TagsAlbumInput::Activate => {
+                info!("Activating tags view");
+                *self.active_view.write() = ViewName::Tags;
+                self.refresh(&sender);
+            },
+            TagsAlbumInput::Refresh => {
+                self.refresh(&sender);
+            },
+            TagsAlbumInput::View(path) => {
+                let picture_ids = self.repo.find_pictures_for_tag(&path).unwrap_or_default();
+                let video_ids = self.repo.find_videos_for_tag(&path).unwrap_or_default();
+                let title = tags::model::display_path(&path);
+                let filter = AlbumFilter::Any(picture_ids, video_ids);
+                let _ = sender.output(TagsAlbumOutput::Selected(title, filter));
+            },
+        }
+    }
+}
+
+impl TagsAlbum {
+    fn refresh(&mut self, sender: &ComponentSender<Self>) {
+        let tags = self.repo.all().unwrap_or_default();
+
+        self.status.set_visible(tags.is_empty());
+        self.tags_view.set_visible(!tags.is_empty());
+
+        self.tags_list.remove_all();
+
+        let parents: HashSet<TagId> = tags.iter().filter_map(|tag| tag.parent_tag_id).collect();
+
+        // Tags are ordered by path, so parents are added before their children.
+        let mut expanders: HashMap<TagId, adw::ExpanderRow> = HashMap::new();
+
+        for tag in tags {
+            let count = fl!("tags-page", "count", count = tag.item_count);
+
+            let row: gtk::Widget = if parents.contains(&tag.tag_id) {
+                let row = adw::ExpanderRow::builder()
+                    .title(&tag.name)
+                    .use_markup(false)
+                    .subtitle(count)
+                    .build();
+
+                let open_button = gtk::Button::builder()
+                    .valign(gtk::Align::Center)
+                    .icon_name("go-next-symbolic")
+                    .tooltip_text(fl!("tags-page", "open-button"))
+                    .css_classes(["flat"])
+                    .build();
+
+                {
+                    let sender = sender.clone();
+                    let path = tag.path.clone();
+                    open_button.connect_clicked(move |_| {
+                        sender.input(TagsAlbumInput::View(path.clone()));
+                    });
+                }
+
+                row.add_suffix(&open_button);
+                expanders.insert(tag.tag_id, row.clone());
+                row.upcast()
+            } else {
+                let row = adw::ActionRow::builder()
+                    .title(&tag.name)
+                    .use_markup(false)
+                    .subtitle(count)
+                    .activatable(true)
+                    .build();
+
+                row.add_suffix(&gtk::Image::from_icon_name("go-next-symbolic"));
+
+                {
+                    let sender = sender.clone();
+                    let path = tag.path.clone();
+                    row.connect_activated(move |_| {
+                        sender.input(TagsAlbumInput::View(path.clone()));
+                    });
+                }
+
+                row.upcast()
+            };
+
+            match tag.parent_tag_id.and_then(|id| expanders.get(&id)) {
+                Some(parent) => parent.add_row(&row),
+                None => self.tags_list.append(&row),
+            }
+        }
+    }
+}
@@ -0,0 +1,442 @@
+// SPDX-FileCopyrightText: © 2024 David Bliss
+//
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use fotema_core::{Visual, VisualId};
+use fotema_core::albums;
+use fotema_core::albums::{AlbumId, AlbumItem, Placement};
+use relm4::gtk;
+use relm4::gtk::prelude::*;
+use relm4::*;
+use relm4::adw;
+use relm4::adw::prelude::*;
+use relm4::actions::{RelmAction, RelmActionGroup};
+
+use crate::app::adaptive;
+use crate::app::SharedState;
+use crate::app::ActiveView;
+use crate::app::ViewName;
+use crate::app::components::albums:: {
+    album::{Album, AlbumEdit, AlbumInput, AlbumOutput},
+    album_filter::AlbumFilter,
+};
+
+use crate::fl;
+
+use std::sync::Arc;
+
+use tracing::{error, info};
+
+relm4::new_action_group!(UserAlbumActionGroup, "user_album");
+
+// Rename an album
+relm4::new_stateless_action!(RenameAction, UserAlbumActionGroup, "rename");
+
+// Delete an album
+relm4::new_stateless_action!(DeleteAction, UserAlbumActionGroup, "delete");
+
+#[derive(Debug)]
+pub enum UserAlbumInput {
+
+    /// Album is visible
+    Activate,
+
+    // State has been updated
+    Refresh,
+
+    /// View an album
+    View(AlbumId),
+
+    /// Items of albums have been changed elsewhere, such as in the viewer.
+    Reload,
+
+    /// Adapt to layout
+    Adapt(adaptive::Layout),
+
+    /// Picture selected in underlying album
+    Selected(VisualId),
+
+    /// Enter or leave selection mode.
+    SelectionMode(bool),
+
+    /// Set location of items selected in underlying album.
+    EditLocation(Vec<Arc<Visual>>),
+
+    /// Correct date and time of items selected in underlying album.
+    EditDateTime(Vec<Arc<Visual>>),
+
+    /// Add items selected in underlying album to other albums.
+    AddToAlbum(Vec<Arc<Visual>>),
+
+    /// Move items selected in underlying album to the trash.
+    MoveToTrash(Vec<Arc<Visual>>),
+
+    /// Change items selected in underlying album.
+    EditAlbum(AlbumEdit, Vec<Arc<Visual>>),
+
+    /// Start rename album flow
+    RenameDialog,
+
+    /// Actually rename album
+    Rename(String),
+
+    /// Start delete album flow.
+    DeleteDialog,
+
+    /// Actually delete album.
+    Delete,
+
+    Ignore,
+}
+
+#[derive(Debug)]
+pub enum UserAlbumOutput {
+    /// User has selected photo or video in grid view
+    Selected(VisualId, AlbumFilter),
+
+    /// Album renamed or its items changed.
+    Changed,
+
+    /// Album deleted.
+    Deleted,
+
+    /// User wants to set the location of the selected photos and videos.
+    EditLocation(Vec<Arc<Visual>>),
+
+    /// User wants to correct the date and time of the selected photos and videos.
+    EditDateTime(Vec<Arc<Visual>>),
+
+    /// User wants to add the selected photos and videos to albums.
+    AddToAlbum(Vec<Arc<Visual>>),
+
+    /// User wants to move the selected photos and videos to the trash.
+    MoveToTrash(Vec<Arc<Visual>>),
+}
+
+/// Photos and videos of an album curated by the user.
+pub struct UserAlbum {
+    repo: albums::Repository,
+    album: Option<albums::Album>,
+    grid: Controller<Album>,
+    title: gtk::Label,
+    selection_button: gtk::ToggleButton,
+    active_view: ActiveView,
+}
+
+#[relm4::component(pub)]
+impl SimpleComponent for UserAlbum {
+    type Init = (SharedState, albums::Repository, ActiveView);
+    type Input = UserAlbumInput;
+    type Output = UserAlbumOutput;
+
+    menu! {
+        primary_menu: {
+            section! {
+                &fl!("user-album-menu-rename") => RenameAction,
+                &fl!("user-album-menu-delete") => DeleteAction,
+            }
+        }
+    }
+
+    view! {
+        adw::ToolbarView {
+            add_top_bar = &adw::HeaderBar {
+                #[wrap(Some)]
+                #[local_ref]
+                set_title_widget = &title -> gtk::Label {
+                    add_css_class: "title",
+                },
+
+                pack_end = &gtk::MenuButton {
+                    set_icon_name: "open-menu-symbolic",
+                    set_menu_model: Some(&primary_menu),
+                },
+
+                #[local_ref]
+                pack_end = &selection_button -> gtk::ToggleButton {
+                    set_icon_name: "selection-mode-symbolic",
+                    set_tooltip_text: Some(&fl!("selection-mode", "tooltip")),
+                    connect_toggled[sender] => move |button| {
+                        sender.input(UserAlbumInput::SelectionMode(button.is_active()));
+                    },
+                },
+            },
+
+            #[wrap(Some)]
+            set_content = model.grid.widget(),
+        }
+    }
+
+    fn init(
+        (state, repo, active_view): Self::Init,
+        root: Self::Root,
+        sender: ComponentSender<Self>,
+    ) -> ComponentParts<Self> {
+        let grid = Album::builder()
+            .launch((state, active_view.clone(), ViewName::UserAlbum, AlbumFilter::None))
+            .forward(sender.input_sender(), |msg| match msg {
+                AlbumOutput::Selected(id, _) => UserAlbumInput::Selected(id),
+                AlbumOutput::ScrollOffset(_) => UserAlbumInput::Ignore,
+                AlbumOutput::EditLocation(visuals) => UserAlbumInput::EditLocation(visuals),
+                AlbumOutput::EditDateTime(visuals) => UserAlbumInput::EditDateTime(visuals),
+                AlbumOutput::AddToAlbum(visuals) => UserAlbumInput::AddToAlbum(visuals),
+                AlbumOutput::MoveToTrash(visuals) => UserAlbumInput::MoveToTrash(visuals),
+                AlbumOutput::EditAlbum(edit, visuals) => UserAlbumInput::EditAlbum(edit, visuals),
+            });
+
+        let title = gtk::Label::builder().build();
+
+        let selection_button = gtk::ToggleButton::new();
+
+        let model = UserAlbum {
+            repo,
+            album: None,
+            grid,
+            title: title.clone(),
+            selection_button: selection_button.clone(),
+            active_view,
+        };
+
+        let widgets = view_output!();
+
+        let mut actions = RelmActionGroup::<UserAlbumActionGroup>::new();
+
+        let rename_action = {
+            let sender = sender.clone();
+            RelmAction::<RenameAction>::new_stateless(move |_| {
+                sender.input(UserAlbumInput::RenameDialog);
+            })
+        };
+
+        let delete_action = {
+            let sender = sender.clone();
+            RelmAction::<DeleteAction>::new_stateless(move |_| {
+                sender.input(UserAlbumInput::DeleteDialog);
+            })
+        };
+
+        actions.add_action(rename_action);
+        actions.add_action(delete_action);
+        actions.register_for_widget(&root);
+
+        ComponentParts { model, widgets }
+    }
+
+    fn update(&mut self, msg: Self::Input, sender: ComponentSender<Self>) {
+        match msg {
+            UserAlbumInput::Activate => {
+                *self.active_view.write() = ViewName::UserAlbum;
+                self.grid.emit(AlbumInput::Activate);
+            },
+            UserAlbumInput::Refresh => {
+                self.grid.emit(AlbumInput::Refresh);
+            },
+            UserAlbumInput::View(album_id) => {
+                info!("Viewing album: {}", album_id);
+                self.selection_button.set_active(false);
+                self.load(album_id);
+            },
+            UserAlbumInput::Reload => {
+                if let Some(album_id) = self.album.as_ref().map(|album| album.album_id) {
+                    self.load(album_id);
+                }
+            },
+            UserAlbumInput::Adapt(layout) => {
+                self.grid.emit(AlbumInput::Adapt(layout));
+            },
+            UserAlbumInput::Selected(visual_id) => {
+                let _ = sender.output(UserAlbumOutput::Selected(visual_id, self.filter()));
+            },
+            UserAlbumInput::SelectionMode(enabled) => {
+                self.grid.emit(AlbumInput::SelectionMode(enabled));
+            },
+            UserAlbumInput::EditLocation(visuals) => {
+                let _ = sender.output(UserAlbumOutput::EditLocation(visuals));
+            },
+            UserAlbumInput::EditDateTime(visuals) => {
+                let _ = sender.output(UserAlbumOutput::EditDateTime(visuals));
+            },
+            UserAlbumInput::AddToAlbum(visuals) => {
+                let _ = sender.output(UserAlbumOutput::AddToAlbum(visuals));
+            },
+            UserAlbumInput::MoveToTrash(visuals) => {
+                let _ = sender.output(UserAlbumOutput::MoveToTrash(visuals));
+            },
+            UserAlbumInput::EditAlbum(edit, visuals) => {
+                let Some(ref album) = self.album else {
+                    return;
+                };
+
+                let album_id = album.album_id;
+                let selected: Vec<AlbumItem> = visuals.iter().map(|v| AlbumItem::from(v.as_ref())).collect();
+
+                info!("{:?} for {} items of album {}", edit, selected.len(), album_id);
+
+                let result = match edit {
+                    AlbumEdit::Remove => self.repo.remove_items(album_id, selected),
+                    AlbumEdit::MoveToStart => {
+                        let items = albums::model::move_items(&album.items, &selected, Placement::Start);
+                        self.repo.set_order(album_id, items)
+                    },
+                    AlbumEdit::MoveToEnd => {
+                        let items = albums::model::move_items(&album.items, &selected, Placement::End);
+                        self.repo.set_order(album_id, items)
+                    },
+                    AlbumEdit::SetCover => self.repo.set_cover(album_id, selected.first().copied()),
+                };
+
+                if let Err(e) = result {
+                    error!("Failed to change album: {}", e);
+                    return;
+                }
+
+                self.selection_button.set_active(false);
+                self.load(album_id);
+                let _ = sender.output(UserAlbumOutput::Changed);
+            },
+            UserAlbumInput::RenameDialog => {
+                let Some(ref album) = self.album else {
+                    info!("Asked to rename album, but no album to rename");
+                    return;
+                };
+
+                let album_name = gtk::Entry::builder()
+                    .text(&album.name)
+                    .placeholder_text(fl!("album-rename-dialog", "placeholder"))
+                    .build();
+
+                let dialog = adw::AlertDialog::builder()
+                    .heading(fl!("album-rename-dialog", "heading"))
+                    .close_response("cancel")
+                    .default_response("rename")
+                    .extra_child(&album_name)
+                    .build();
+
+                dialog.add_response("cancel", &fl!("album-rename-dialog", "cancel-button"));
+                dialog.add_response("rename", &fl!("album-rename-dialog", "rename-button"));
+                dialog.set_response_appearance("rename", adw::ResponseAppearance::Suggested);
+
+                {
+                    let album_name = album_name.clone();
+                    let sender = sender.clone();
+                    dialog.connect_response(None, move |_, response| {
+                        if response == "rename" {
+                            sender.input(UserAlbumInput::Rename(album_name.text().into()));
+                        }
+                    });
+                }
+
+                {
+                    let album_name = album_name.clone();
+                    let sender = sender.clone();
+                    let dialog = dialog.clone();
+                    album_name.clone().connect_activate(move |_| {
+                        dialog.close();
+                        sender.input(UserAlbumInput::Rename(album_name.text().into()));
+                    });
+                }
+
+                if let Some(root) = gtk::Widget::root(self.title.widget_ref()) {
+                    dialog.present(Some(&root));
+                    album_name.grab_focus();
+                } else {
+                    error!("Couldn't get root widget!");
+                }
+            },
+            UserAlbumInput::Rename(name) => {
+                let Some(ref mut album) = self.album else {
+                    info!("Asked to rename album, but no album to rename");
+                    return;
+                };
+
+                let name = name.trim();
+                if name.is_empty() {
+                    return;
+                }
+
+                info!("Renaming album {} to {}", album.name, name);
+
+                if let Err(e) = self.repo.rename(album.album_id, name) {
+                    error!("Failed to rename album: {}", e);
+                    return;
+                }
+
+                self.title.set_label(name);
+                album.name = name.to_string();
+                let _ = sender.output(UserAlbumOutput::Changed);
+            },
+            UserAlbumInput::DeleteDialog => {
+                let Some(ref album) = self.album else {
+                    info!("Asked to delete album, but no album to delete");
+                    return;
+                };
+
+                let dialog = adw::AlertDialog::builder()
+                    .heading(fl!("album-delete-dialog", "heading", name = album.name.clone()))
+                    .body(fl!("album-delete-dialog", "body"))
+                    .close_response("cancel")
+                    .default_response("cancel")
+                    .build();
+
+                dialog.add_response("cancel", &fl!("album-delete-dialog", "cancel-button"));
+                dialog.add_response("delete", &fl!("album-delete-dialog", "delete-button"));
+                dialog.set_response_appearance("delete", adw::ResponseAppearance::Destructive);
+
+                dialog.connect_response(None, move |_, response| {
+                    if response == "delete" {
+                        sender.input(UserAlbumInput::Delete);
+                    }
+                });
+
+                if let Some(root) = gtk::Widget::root(self.title.widget_ref()) {
+                    dialog.present(Some(&root));
+                } else {
+                    error!("Couldn't get root widget!");
+                }
+            },
+            UserAlbumInput::Delete => {
+                let Some(album) = self.album.take() else {
+                    info!("Asked to delete album, but no album to delete");
+                    return;
+                };
+
+                info!("Deleting album {}", album.album_id);
+
+                if let Err(e) = self.repo.delete(album.album_id) {
+                    error!("Failed to delete album: {}", e);
+                    self.album = Some(album);
+                    return;
+                }
+
+                let _ = sender.output(UserAlbumOutput::Deleted);
+            },
+            UserAlbumInput::Ignore => {},
+        }
+    }
+}
+
+impl UserAlbum {
+    /// Load album from database and show its items.
+    fn load(&mut self, album_id: AlbumId) {
+        match self.repo.get(album_id) {
+            Ok(Some(album)) => {
+                self.title.set_label(&album.name);
+                self.album = Some(album);
+                self.grid.emit(AlbumInput::Filter(self.filter()));
+            },
+            Ok(None) => {
+                info!("Album {} no longer exists", album_id);
+                self.album = None;
+                self.grid.emit(AlbumInput::Filter(AlbumFilter::None));
+            },
+            Err(e) => error!("Failed to load album: {}", e),
+        }
+    }
+
+    fn filter(&self) -> AlbumFilter {
+        self.album
+            .as_ref()
+            .map(|album| AlbumFilter::UserAlbum(album.items.clone()))
+            .unwrap_or(AlbumFilter::None)
+    }
+}
@@ -0,0 +1,374 @@
+// SPDX-FileCopyrightText: © 2024 David Bliss
+//
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use gtk::prelude::OrientableExt;
+
+use fotema_core::albums;
+use fotema_core::albums::AlbumId;
+use fotema_core::visual::model::PictureOrientation;
+use fotema_core::Visual;
+
+use relm4::gtk;
+use relm4::gtk::prelude::*;
+use relm4::gtk::gdk;
+use relm4::gtk::gdk_pixbuf;
+use relm4::typed_view::grid::{RelmGridItem, TypedGridView};
+use relm4::*;
+use relm4::binding::*;
+use strum::IntoEnumIterator;
+
+use std::path::Path;
+use std::sync::Arc;
+
+use crate::adaptive;
+use crate::app::ActiveView;
+use crate::app::SharedState;
+use crate::app::ViewName;
+use crate::fl;
+
+use tracing::{debug, error, info};
+
+const NARROW_EDGE_LENGTH: i32 = 150;
+const WIDE_EDGE_LENGTH: i32 = 200;
+
+#[derive(Debug)]
+struct AlbumGridItem {
+    album: albums::Album,
+
+    /// Item representing album.
+    cover: Option<Arc<Visual>>,
+
+    // Length of thumbnail edge to allow for resizing when layout changes.
+    edge_length: I32Binding,
+}
+
+struct Widgets {
+    picture: gtk::Picture,
+
+    label: gtk::Label,
+
+    count: gtk::Label,
+
+    // If the gtk::Picture has been bound to edge_length.
+    is_bound: bool,
+}
+
+#[derive(Debug)]
+pub enum UserAlbumsInput {
+    Activate,
+
+    // Reload albums from database
+    Refresh,
+
+    Selected(u32), // Index into album grid vector
+
+    // Adapt to layout
+    Adapt(adaptive::Layout),
+
+    /// Start create album flow.
+    CreateDialog,
+
+    /// Actually create album.
+    Create(String),
+}
+
+#[derive(Debug)]
+pub enum UserAlbumsOutput {
+    /// User wants to view an album.
+    Selected(AlbumId),
+}
+
+impl RelmGridItem for AlbumGridItem {
+    type Root = gtk::Box;
+    type Widgets = Widgets;
+
+    fn setup(_item: &gtk::ListItem) -> (gtk::Box, Widgets) {
+        relm4::view! {
+           my_box = gtk::Box {
+                set_orientation: gtk::Orientation::Vertical,
+                set_margin_all: 6,
+
+                gtk::Frame {
+                    #[name(picture)]
+                    gtk::Picture {
+                        set_can_shrink: true,
+                        set_content_fit: gtk::ContentFit::Cover,
+                        set_width_request: NARROW_EDGE_LENGTH,
+                        set_height_request: NARROW_EDGE_LENGTH,
+                    },
+                },
+
+                #[name(label)]
+                gtk::Label {
+                    add_css_class: "caption-heading",
+                    set_margin_top: 4,
+                    set_ellipsize: gtk::pango::EllipsizeMode::End,
+                },
+
+                #[name(count)]
+                gtk::Label {
+                    add_css_class: "caption",
+                    add_css_class: "dim-label",
+                    set_margin_bottom: 12,
+                },
+            }
+        }
+
+        let widgets = Widgets {
+            picture,
+            label,
+            count,
+            is_bound: false,
+        };
+
+        (my_box, widgets)
+    }
+
+    fn bind(&mut self, widgets: &mut Self::Widgets, _root: &mut Self::Root) {
+        widgets.label.set_text(&self.album.name);
+        widgets.count.set_text(&fl!("user-albums-page", "count", count = self.album.items.len()));
+
+        // If we repeatedly bind, then Fotema will die with the following error:
+        // (fotema:2): GLib-GObject-CRITICAL **: 13:26:14.297: Too many GWeakRef registered
+        // GLib-GObject:ERROR:../gobject/gbinding.c:805:g_binding_constructed: assertion failed: (source != NULL)
+        // Bail out! GLib-GObject:ERROR:../gobject/gbinding.c:805:g_binding_constructed: assertion failed: (source != NULL)
+        if !widgets.is_bound {
+            widgets.picture.add_write_only_binding(&self.edge_length, "width-request");
+            widgets.picture.add_write_only_binding(&self.edge_length, "height-request");
+            widgets.is_bound = true;
+        }
+
+        let thumbnail = self.cover
+            .as_ref()
+            .filter(|cover| cover.thumbnail_path.as_ref().is_some_and(|x| x.exists()));
+
+        if let Some(cover) = thumbnail {
+            widgets.picture.set_filename(cover.thumbnail_path.clone());
+
+            // Add CSS class for orientation
+            let orientation = cover.thumbnail_orientation();
+            widgets.picture.add_css_class(orientation.as_ref());
+        } else {
+            let pb = gdk_pixbuf::Pixbuf::from_resource_at_scale(
+                "/app/fotema/Fotema/icons/scalable/actions/image-missing-symbolic.svg",
+                200, 200, true
+            ).unwrap();
+            let img = gdk::Texture::for_pixbuf(&pb);
+            widgets.picture.set_paintable(Some(&img));
+        }
+    }
+
+    fn unbind(&mut self, widgets: &mut Self::Widgets, _root: &mut Self::Root) {
+        widgets.picture.set_filename(None::<&Path>);
+
+        // clear orientation transformation css classes
+        for orient in PictureOrientation::iter() {
+            widgets.picture.remove_css_class(orient.as_ref());
+        }
+    }
+}
+
+/// Albums curated by the user.
+pub struct UserAlbums {
+    state: SharedState,
+    repo: albums::Repository,
+    active_view: ActiveView,
+    album_grid: TypedGridView<AlbumGridItem, gtk::SingleSelection>,
+    albums_view: gtk::ScrolledWindow,
+    status: adw::StatusPage,
+    edge_length: I32Binding,
+}
+
+#[relm4::component(pub)]
+impl SimpleComponent for UserAlbums {
+    type Init = (SharedState, albums::Repository, ActiveView);
+    type Input = UserAlbumsInput;
+    type Output = UserAlbumsOutput;
+
+    view! {
+        gtk::Box {
+            set_orientation: gtk::Orientation::Vertical,
+
+            gtk::Box {
+                set_orientation: gtk::Orientation::Horizontal,
+                set_halign: gtk::Align::End,
+                set_margin_all: 8,
+
+                gtk::Button {
+                    set_icon_name: "list-add-symbolic",
+                    set_label: &fl!("user-albums-page", "create-button"),
+                    add_css_class: "flat",
+                    connect_clicked => UserAlbumsInput::CreateDialog,
+                },
+            },
+
+            #[local_ref]
+            albums_view -> gtk::ScrolledWindow {
+                set_vexpand: true,
+
+                #[local_ref]
+                album_grid_view -> gtk::GridView {
+                    set_orientation: gtk::Orientation::Vertical,
+                    set_single_click_activate: true,
+
+                    connect_activate[sender] => move |_, idx| {
+                        sender.input(UserAlbumsInput::Selected(idx))
+                    }
+                }
+            },
+
+            #[local_ref]
+            status -> adw::StatusPage {
+                set_valign: gtk::Align::Start,
+                set_vexpand: true,
+                set_visible: false,
+                set_icon_name: Some("image-alt-symbolic"),
+                set_title: &fl!("user-albums-page", "empty-title"),
+                set_description: Some(&fl!("user-albums-page", "empty-description")),
+            },
+        },
+    }
+
+    fn init(
+        (state, repo, active_view): Self::Init,
+        _root: Self::Root,
+        sender: ComponentSender<Self>,
+    ) -> ComponentParts<Self> {
+        let album_grid = TypedGridView::new();
+
+        let albums_view = gtk::ScrolledWindow::builder().build();
+
+        let status = adw::StatusPage::new();
+
+        let model = UserAlbums {
+            state,
+            repo,
+            active_view,
+            album_grid,
+            albums_view: albums_view.clone(),
+            status: status.clone(),
+            edge_length: I32Binding::new(NARROW_EDGE_LENGTH),
+        };
+
+        let album_grid_view = &model.album_grid.view;
+
+        let widgets = view_output!();
+
+        ComponentParts { model, widgets }
+    }
+
+    fn update(&mut self, msg: Self::Input, sender: ComponentSender<Self>) {
+        match msg {
+            UserAlbumsInput::Activate => {
+                info!("Activating albums view");
+                *self.active_view.write() = ViewName::Albums;
+                self.refresh();
+            },
+            UserAlbumsInput::Refresh => {
+                self.refresh();
+            },
+            UserAlbumsInput::Selected(index) => {
+                debug!("Album selected index: {}", index);
+                if let Some(item) = self.album_grid.get_visible(index) {
+                    let album_id = item.borrow().album.album_id;
+                    let _ = sender.output(UserAlbumsOutput::Selected(album_id));
+                }
+            },
+            UserAlbumsInput::Adapt(adaptive::Layout::Narrow) => {
+                self.edge_length.set_value(NARROW_EDGE_LENGTH);
+            },
+            UserAlbumsInput::Adapt(adaptive::Layout::Wide) => {
+                self.edge_length.set_value(WIDE_EDGE_LENGTH);
+            },
+            UserAlbumsInput::CreateDialog => {
+                let album_name = gtk::Entry::builder()
+                    .placeholder_text(fl!("album-create-dialog", "placeholder"))
+                    .build();
+
+                let dialog = adw::AlertDialog::builder()
+                    .heading(fl!("album-create-dialog", "heading"))
+                    .close_response("cancel")
+                    .default_response("create")
+                    .extra_child(&album_name)
+                    .build();
+
+                dialog.add_response("cancel", &fl!("album-create-dialog", "cancel-button"));
+                dialog.add_response("create", &fl!("album-create-dialog", "create-button"));
+                dialog.set_response_appearance("create", adw::ResponseAppearance::Suggested);
+
+                {
+                    let album_name = album_name.clone();
+                    let sender = sender.clone();
+                    dialog.connect_response(None, move |_, response| {
+                        if response == "create" {
+                            sender.input(UserAlbumsInput::Create(album_name.text().into()));
+                        }
+                    });
+                }
+
+                {
+                    let album_name = album_name.clone();
+                    let sender = sender.clone();
+                    let dialog = dialog.clone();
+                    album_name.clone().connect_activate(move |_| {
+                        dialog.close();
+                        sender.input(UserAlbumsInput::Create(album_name.text().into()));
+                    });
+                }
+
+                if let Some(root) = gtk::Widget::root(self.albums_view.widget_ref()) {
+                    dialog.present(Some(&root));
+                    album_name.grab_focus();
+                } else {
+                    error!("Couldn't get root widget!");
+                }
+            },
+            UserAlbumsInput::Create(name) => {
+                let name = name.trim();
+                if name.is_empty() {
+                    return;
+                }
+
+                info!("Creating album {}", name);
+                match self.repo.create(name) {
+                    Ok(album_id) => {
+                        self.refresh();
+                        let _ = sender.output(UserAlbumsOutput::Selected(album_id));
+                    },
+                    Err(e) => error!("Failed to create album: {}", e),
+                }
+            },
+        }
+    }
+}
+
+impl UserAlbums {
+    fn refresh(&mut self) {
+        let albums = self.repo.all().unwrap_or_default();
+
+        let items = {
+            let visuals = self.state.read();
+            albums
+                .into_iter()
+                .map(|album| {
+                    let cover = album.cover_or_first()
+                        .and_then(|item| visuals.iter().find(|v| item.is_for(v)))
+                        .cloned();
+
+                    AlbumGridItem {
+                        album,
+                        cover,
+                        edge_length: self.edge_length.clone(),
+                    }
+                })
+                .collect::<Vec<AlbumGridItem>>()
+        };
+
+        self.status.set_visible(items.is_empty());
+        self.albums_view.set_visible(!items.is_empty());
+
+        self.album_grid.clear();
+        self.album_grid.extend_from_iter(items);
+    }
+}
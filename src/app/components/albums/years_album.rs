@@ -14,6 +14,8 @@ use fotema_core::Year;
 use relm4::gtk;
 use relm4::gtk::gdk;
 use relm4::gtk::gdk_pixbuf;
+use relm4::gtk::glib;
+use relm4::gtk::prelude::{ButtonExt, ObjectExt};
 use relm4::gtk::prelude::FrameExt;
 use relm4::gtk::prelude::WidgetExt;
 use relm4::typed_view::grid::{RelmGridItem, TypedGridView};
@@ -29,6 +31,7 @@ use crate::adaptive;
 use crate::app::SharedState;
 use crate::app::ActiveView;
 use crate::app::ViewName;
+use crate::fl;
 
 const NARROW_EDGE_LENGTH: i32 = 170;
 const WIDE_EDGE_LENGTH: i32 = 200;
@@ -39,6 +42,9 @@ struct PhotoGridItem {
 
     // Length of thumbnail edge to allow for resizing when layout changes.
     edge_length: I32Binding,
+
+    // For showing the year on a map.
+    sender: relm4::Sender<YearsAlbumInput>,
 }
 #[derive(Debug)]
 pub enum YearsAlbumInput {
@@ -52,16 +58,25 @@ pub enum YearsAlbumInput {
 
     // Adapt to layout
     Adapt(adaptive::Layout),
+
+    /// User wants to see a year on a map
+    ViewMap(Year),
 }
 
 #[derive(Debug)]
 pub enum YearsAlbumOutput {
     YearSelected(Year),
+
+    ViewMap(Year),
 }
 
 struct Widgets {
     picture: gtk::Picture,
     label: gtk::Label,
+    map_button: gtk::Button,
+
+    // Handler for map button of currently bound year.
+    map_handler: Option<glib::SignalHandlerId>,
 
     // If the gtk::Picture has been bound to edge_length.
      is_bound: bool,
@@ -90,6 +105,18 @@ impl RelmGridItem for PhotoGridItem {
                             },
                         },
 
+                        #[name(map_button)]
+                        add_overlay = &gtk::Button {
+                            set_halign: gtk::Align::End,
+                            set_valign: gtk::Align::Start,
+                            set_margin_end: 8,
+                            set_margin_top: 8,
+                            set_icon_name: "find-location-symbolic",
+                            set_tooltip_text: Some(&fl!("show-on-map", "tooltip")),
+                            add_css_class: "osd",
+                            add_css_class: "circular",
+                        },
+
                         #[wrap(Some)]
                         #[name(picture)]
                         set_child = &gtk::Picture {
@@ -105,6 +132,8 @@ impl RelmGridItem for PhotoGridItem {
         let widgets = Widgets {
             picture,
             label,
+            map_button,
+            map_handler: None,
             is_bound: false,
         };
 
@@ -116,6 +145,12 @@ impl RelmGridItem for PhotoGridItem {
             .label
             .set_text(format!("{}", self.picture.year()).as_str());
 
+        let year = self.picture.year_month().year;
+        let sender = self.sender.clone();
+        widgets.map_handler = Some(widgets.map_button.connect_clicked(move |_| {
+            sender.emit(YearsAlbumInput::ViewMap(year));
+        }));
+
         // If we repeatedly bind, then Fotema will die with the following error:
         // (fotema:2): GLib-GObject-CRITICAL **: 13:26:14.297: Too many GWeakRef registered
         // GLib-GObject:ERROR:../gobject/gbinding.c:805:g_binding_constructed: assertion failed: (source != NULL)
@@ -145,6 +180,10 @@ impl RelmGridItem for PhotoGridItem {
     }
 
     fn unbind(&mut self, widgets: &mut Self::Widgets, _root: &mut Self::Root) {
+        if let Some(handler) = widgets.map_handler.take() {
+            widgets.map_button.disconnect(handler);
+        }
+
         widgets.picture.set_filename(None::<&path::Path>);
         // clear orientation transformation css classes
         for orient in PictureOrientation::iter() {
@@ -158,6 +197,7 @@ pub struct YearsAlbum {
     active_view: ActiveView,
     photo_grid: TypedGridView<PhotoGridItem, gtk::NoSelection>,
     edge_length: I32Binding,
+    sender: relm4::Sender<YearsAlbumInput>,
 }
 
 #[relm4::component(pub)]
@@ -197,6 +237,7 @@ impl SimpleComponent for YearsAlbum {
             active_view,
             photo_grid,
             edge_length: I32Binding::new(NARROW_EDGE_LENGTH),
+            sender: sender.input_sender().clone(),
         };
 
         let photo_grid_view = &model.photo_grid.view;
@@ -228,6 +269,10 @@ impl SimpleComponent for YearsAlbum {
                     let _ = sender.output(YearsAlbumOutput::YearSelected(date.year));
                 }
             },
+            YearsAlbumInput::ViewMap(year) => {
+                info!("Viewing year {} on map", year);
+                let _ = sender.output(YearsAlbumOutput::ViewMap(year));
+            },
             YearsAlbumInput::Adapt(adaptive::Layout::Narrow) => {
                 self.edge_length.set_value(NARROW_EDGE_LENGTH);
             },
@@ -248,6 +293,7 @@ impl YearsAlbum {
                 .map(|picture| PhotoGridItem {
                     picture: picture.clone(),
                     edge_length: self.edge_length.clone(),
+                    sender: self.sender.clone(),
                 })
                 .collect::<Vec<PhotoGridItem>>()
         };
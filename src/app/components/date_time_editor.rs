@@ -0,0 +1,317 @@
+// SPDX-FileCopyrightText: © 2024 David Bliss
+//
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use relm4::{adw, ComponentParts, ComponentSender, RelmWidgetExt, SimpleComponent};
+use relm4::adw::prelude::*;
+use relm4::gtk;
+
+use fotema_core::time::{self, TimeShift};
+use fotema_core::visual;
+use fotema_core::Visual;
+
+use chrono::{DateTime, FixedOffset, NaiveDateTime, TimeZone, Timelike, Utc};
+
+use tracing::{error, info};
+
+use std::sync::Arc;
+
+use crate::fl;
+
+const DATE_TIME_FORMAT: &str = "%Y-%m-%d %H:%M:%S";
+
+/// Dialog for correcting the dates and times of photos and videos taken with a camera
+/// with a wrong clock. One item can be given a new date and time, and many items can
+/// be shifted by the same amount, such as "+1 year -3 hours".
+/// Corrected dates and times take precedence over those from metadata.
+pub struct DateTimeEditorDialog {
+    repo: visual::Repository,
+    parent: adw::ApplicationWindow,
+    dialog: adw::Dialog,
+
+    /// Photos and videos to correct the dates and times of.
+    visuals: Vec<Arc<Visual>>,
+
+    /// New local date and time when correcting one item.
+    date_time: Option<NaiveDateTime>,
+
+    /// Amount to shift dates and times by.
+    shift: Option<TimeShift>,
+
+    date_time_row: adw::EntryRow,
+    shift_row: adw::EntryRow,
+}
+
+#[derive(Debug)]
+pub enum DateTimeEditorInput {
+    /// Show dialog for correcting the dates and times of photos and videos.
+    Present(Vec<Arc<Visual>>),
+
+    /// Date and time typed or pasted.
+    UpdateDateTime(String),
+
+    /// Shift typed or pasted.
+    UpdateShift(String),
+
+    /// Save dates and times.
+    Apply,
+
+    /// Go back to dates and times from metadata.
+    Reset,
+}
+
+#[derive(Debug)]
+pub enum DateTimeEditorOutput {
+    /// Dates and times have been set.
+    DatesChanged,
+}
+
+#[relm4::component(pub)]
+impl SimpleComponent for DateTimeEditorDialog {
+    type Init = (visual::Repository, adw::ApplicationWindow);
+    type Input = DateTimeEditorInput;
+    type Output = DateTimeEditorOutput;
+
+    view! {
+        adw::Dialog {
+            set_title: &fl!("date-time-editor-dialog"),
+            set_content_width: 480,
+
+            #[wrap(Some)]
+            set_child = &adw::ToolbarView {
+                add_top_bar = &adw::HeaderBar,
+
+                #[wrap(Some)]
+                set_content = &gtk::Box {
+                    set_orientation: gtk::Orientation::Vertical,
+                    set_spacing: 12,
+                    set_margin_all: 12,
+
+                    gtk::ListBox {
+                        set_selection_mode: gtk::SelectionMode::None,
+                        add_css_class: "boxed-list",
+
+                        #[watch]
+                        set_visible: model.visuals.len() == 1,
+
+                        #[local_ref]
+                        date_time_row -> adw::EntryRow {
+                            set_title: &fl!("date-time-editor-dialog", "date-time"),
+                            connect_changed[sender] => move |row| {
+                                sender.input(DateTimeEditorInput::UpdateDateTime(row.text().to_string()));
+                            },
+                        },
+                    },
+
+                    gtk::Label {
+                        set_label: &fl!("date-time-editor-dialog", "date-time-help"),
+                        set_halign: gtk::Align::Start,
+                        set_wrap: true,
+                        add_css_class: "dim-label",
+
+                        #[watch]
+                        set_visible: model.visuals.len() == 1,
+                    },
+
+                    gtk::ListBox {
+                        set_selection_mode: gtk::SelectionMode::None,
+                        add_css_class: "boxed-list",
+
+                        #[local_ref]
+                        shift_row -> adw::EntryRow {
+                            set_title: &fl!("date-time-editor-dialog", "shift"),
+                            connect_changed[sender] => move |row| {
+                                sender.input(DateTimeEditorInput::UpdateShift(row.text().to_string()));
+                            },
+                        },
+                    },
+
+                    gtk::Label {
+                        set_label: &fl!("date-time-editor-dialog", "shift-help"),
+                        set_halign: gtk::Align::Start,
+                        set_wrap: true,
+                        add_css_class: "dim-label",
+                    },
+                },
+
+                add_bottom_bar = &gtk::Box {
+                    set_orientation: gtk::Orientation::Horizontal,
+                    set_halign: gtk::Align::Center,
+                    set_spacing: 12,
+                    set_margin_all: 12,
+
+                    gtk::Button {
+                        set_label: &fl!("date-time-editor-dialog", "reset"),
+                        add_css_class: "pill",
+
+                        #[watch]
+                        set_sensitive: !model.visuals.is_empty(),
+
+                        connect_clicked => DateTimeEditorInput::Reset,
+                    },
+
+                    gtk::Button {
+                        #[watch]
+                        set_label: &fl!("date-time-editor-dialog", "apply", count = model.visuals.len()),
+                        add_css_class: "pill",
+                        add_css_class: "suggested-action",
+
+                        #[watch]
+                        set_sensitive: model.is_changed(),
+
+                        connect_clicked => DateTimeEditorInput::Apply,
+                    },
+                },
+            },
+        }
+    }
+
+    fn init(
+        (repo, parent): Self::Init,
+        dialog: Self::Root,
+        sender: ComponentSender<Self>,
+    ) -> ComponentParts<Self> {
+        let date_time_row = adw::EntryRow::new();
+        let shift_row = adw::EntryRow::new();
+
+        let model = Self {
+            repo,
+            parent,
+            dialog: dialog.clone(),
+            visuals: Vec::new(),
+            date_time: None,
+            shift: None,
+            date_time_row: date_time_row.clone(),
+            shift_row: shift_row.clone(),
+        };
+
+        let widgets = view_output!();
+
+        ComponentParts { model, widgets }
+    }
+
+    fn update(&mut self, msg: Self::Input, sender: ComponentSender<Self>) {
+        match msg {
+            DateTimeEditorInput::Present(visuals) => {
+                self.visuals = visuals;
+                self.date_time = None;
+                self.shift = None;
+
+                // Start from the current date and time of a single item.
+                let text = self.original()
+                    .map(|ts| ts.format(DATE_TIME_FORMAT).to_string())
+                    .unwrap_or_default();
+
+                self.date_time_row.set_text(&text);
+                self.shift_row.set_text("");
+
+                self.dialog.present(Some(&self.parent));
+            },
+            DateTimeEditorInput::UpdateDateTime(text) => {
+                self.date_time = time::parse_local_date_time(&text);
+                if text.trim().is_empty() || self.date_time.is_some() {
+                    self.date_time_row.remove_css_class("error");
+                } else {
+                    self.date_time_row.add_css_class("error");
+                }
+            },
+            DateTimeEditorInput::UpdateShift(text) => {
+                self.shift = time::parse_time_shift(&text);
+                if text.trim().is_empty() || self.shift.is_some() {
+                    self.shift_row.remove_css_class("error");
+                } else {
+                    self.shift_row.add_css_class("error");
+                }
+
+                // Show the shifted date and time of a single item.
+                let shifted = self.original()
+                    .zip(self.shift)
+                    .and_then(|(ts, shift)| shift.apply(ts.naive_local()));
+
+                if let Some(shifted) = shifted {
+                    self.date_time_row.set_text(&shifted.format(DATE_TIME_FORMAT).to_string());
+                }
+            },
+            DateTimeEditorInput::Apply => {
+                let changes: Vec<(Arc<Visual>, DateTime<Utc>)> = self.visuals
+                    .iter()
+                    .filter_map(|visual| self.corrected(visual).map(|ts| (visual.clone(), ts)))
+                    .collect();
+
+                let pictures = changes
+                    .iter()
+                    .filter_map(|(visual, ts)| visual.picture_id.map(|id| (id, *ts)))
+                    .collect::<Vec<_>>();
+
+                // Live photos have a picture and a video, so correct both.
+                let videos = changes
+                    .iter()
+                    .filter_map(|(visual, ts)| visual.video_id.map(|id| (id, *ts)))
+                    .collect::<Vec<_>>();
+
+                info!("Setting date and time of {} photos and {} videos", pictures.len(), videos.len());
+
+                if let Err(e) = self.repo.set_date_time_overrides(pictures, videos) {
+                    error!("Failed saving dates and times: {:?}", e);
+                    return;
+                }
+
+                self.visuals.clear();
+                self.dialog.close();
+                let _ = sender.output(DateTimeEditorOutput::DatesChanged);
+            },
+            DateTimeEditorInput::Reset => {
+                let pictures = self.visuals.iter().filter_map(|visual| visual.picture_id).collect();
+                let videos = self.visuals.iter().filter_map(|visual| visual.video_id).collect();
+
+                info!("Restoring original dates and times of {} items", self.visuals.len());
+
+                if let Err(e) = self.repo.remove_date_time_overrides(pictures, videos) {
+                    error!("Failed restoring dates and times: {:?}", e);
+                    return;
+                }
+
+                self.visuals.clear();
+                self.dialog.close();
+                let _ = sender.output(DateTimeEditorOutput::DatesChanged);
+            },
+        }
+    }
+}
+
+impl DateTimeEditorDialog {
+    /// Current date and time when correcting a single item.
+    fn original(&self) -> Option<DateTime<FixedOffset>> {
+        match self.visuals.as_slice() {
+            [visual] => Some(visual.local_ts()),
+            _ => None,
+        }
+    }
+
+    fn is_changed(&self) -> bool {
+        if let Some(original) = self.original() {
+            // Entry only shows whole seconds.
+            let original = original.naive_local().with_nanosecond(0);
+            self.date_time.is_some_and(|ts| Some(ts) != original)
+        } else {
+            !self.visuals.is_empty() && self.shift.is_some_and(|shift| !shift.is_zero())
+        }
+    }
+
+    /// Corrected date and time of an item, keeping the offset from UTC of the
+    /// time zone it was taken in.
+    fn corrected(&self, visual: &Visual) -> Option<DateTime<Utc>> {
+        let original = visual.local_ts();
+
+        let local = if self.visuals.len() == 1 {
+            self.date_time?
+        } else {
+            self.shift?.apply(original.naive_local())?
+        };
+
+        original.offset()
+            .from_local_datetime(&local)
+            .single()
+            .map(|ts| ts.to_utc())
+    }
+}
@@ -0,0 +1,322 @@
+// SPDX-FileCopyrightText: © 2024 David Bliss
+//
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use relm4::{adw, ComponentParts, ComponentSender, RelmWidgetExt, SimpleComponent};
+use relm4::adw::prelude::*;
+use relm4::gtk;
+use relm4::gtk::gio;
+
+use fotema_core::places::{self, LocationSource, Track};
+use fotema_core::Visual;
+
+use chrono::TimeDelta;
+use h3o::LatLng;
+
+use tracing::{error, info};
+
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use crate::app::SharedState;
+use crate::fl;
+
+/// Don't flood the preview with rows when geotagging a large library.
+const MAX_PREVIEW_ROWS: usize = 200;
+
+const DEFAULT_MAX_GAP_MINUTES: f64 = 10.0;
+
+/// Dialog for geotagging photos without a location from a GPS track, such as
+/// one recorded by a GPS logger carried alongside a camera without GPS.
+pub struct GeotagDialog {
+    state: SharedState,
+    repo: places::Repository,
+    parent: adw::ApplicationWindow,
+    dialog: adw::Dialog,
+
+    /// Path of track file.
+    track_path: Option<PathBuf>,
+
+    track: Track,
+
+    /// Minutes the camera clock is ahead of the GPS clock.
+    clock_offset: i64,
+
+    /// Maximum minutes between a photo and a track point for the photo to be geotagged.
+    max_gap: i64,
+
+    /// Count of photos without a location.
+    candidate_count: usize,
+
+    /// Photos that matched the track and the location they matched.
+    matches: Vec<(Arc<Visual>, LatLng)>,
+
+    preview_list: gtk::ListBox,
+}
+
+#[derive(Debug)]
+pub enum GeotagInput {
+    /// Show the geotag dialog.
+    Present,
+
+    /// Show file chooser for track file.
+    ChooseTrack,
+
+    /// Load track from file.
+    LoadTrack(PathBuf),
+
+    UpdateClockOffset(i64),
+
+    UpdateMaxGap(i64),
+
+    /// Save matched locations.
+    Apply,
+}
+
+#[derive(Debug)]
+pub enum GeotagOutput {
+    /// Locations have been added to photos. Count of photos geotagged.
+    Geotagged(usize),
+}
+
+#[relm4::component(pub)]
+impl SimpleComponent for GeotagDialog {
+    type Init = (SharedState, places::Repository, adw::ApplicationWindow);
+    type Input = GeotagInput;
+    type Output = GeotagOutput;
+
+    view! {
+        adw::Dialog {
+            set_title: &fl!("geotag-dialog"),
+            set_content_width: 480,
+            set_content_height: 640,
+
+            #[wrap(Some)]
+            set_child = &adw::ToolbarView {
+                add_top_bar = &adw::HeaderBar,
+
+                #[wrap(Some)]
+                set_content = &adw::PreferencesPage {
+                    add = &adw::PreferencesGroup {
+                        adw::ActionRow {
+                            set_title: &fl!("geotag-dialog", "track"),
+
+                            #[watch]
+                            set_subtitle: &model.track_path.as_ref()
+                                .and_then(|path| path.file_name())
+                                .map(|name| name.to_string_lossy().to_string())
+                                .unwrap_or_else(|| fl!("geotag-dialog", "no-track")),
+
+                            add_suffix = &gtk::Button {
+                                set_icon_name: "document-open-symbolic",
+                                set_tooltip_text: Some(&fl!("geotag-dialog", "choose-track")),
+                                set_valign: gtk::Align::Center,
+                                add_css_class: "flat",
+                                connect_clicked => GeotagInput::ChooseTrack,
+                            },
+                        },
+                    },
+
+                    add = &adw::PreferencesGroup {
+                        adw::SpinRow::with_range(-1440.0, 1440.0, 1.0) {
+                            set_title: &fl!("geotag-dialog", "clock-offset"),
+                            set_subtitle: &fl!("geotag-dialog", "clock-offset-subtitle"),
+                            set_value: 0.0,
+                            connect_value_notify[sender] => move |row| {
+                                sender.input(GeotagInput::UpdateClockOffset(row.value() as i64));
+                            },
+                        },
+
+                        adw::SpinRow::with_range(1.0, 240.0, 1.0) {
+                            set_title: &fl!("geotag-dialog", "max-gap"),
+                            set_subtitle: &fl!("geotag-dialog", "max-gap-subtitle"),
+                            set_value: DEFAULT_MAX_GAP_MINUTES,
+                            connect_value_notify[sender] => move |row| {
+                                sender.input(GeotagInput::UpdateMaxGap(row.value() as i64));
+                            },
+                        },
+                    },
+
+                    add = &adw::PreferencesGroup {
+                        set_title: &fl!("geotag-dialog", "preview"),
+
+                        #[watch]
+                        set_description: Some(&fl!("geotag-dialog", "preview-count",
+                            count = model.matches.len(),
+                            total = model.candidate_count)),
+
+                        #[local_ref]
+                        preview_list -> gtk::ListBox {
+                            set_selection_mode: gtk::SelectionMode::None,
+                            add_css_class: "boxed-list",
+                        },
+                    },
+                },
+
+                add_bottom_bar = &gtk::Button {
+                    set_label: &fl!("geotag-dialog", "apply"),
+                    set_halign: gtk::Align::Center,
+                    set_margin_all: 12,
+                    add_css_class: "pill",
+                    add_css_class: "suggested-action",
+
+                    #[watch]
+                    set_sensitive: !model.matches.is_empty(),
+
+                    connect_clicked => GeotagInput::Apply,
+                },
+            },
+        }
+    }
+
+    fn init(
+        (state, repo, parent): Self::Init,
+        dialog: Self::Root,
+        sender: ComponentSender<Self>,
+    ) -> ComponentParts<Self> {
+
+        let preview_list = gtk::ListBox::new();
+
+        let model = Self {
+            state,
+            repo,
+            parent,
+            dialog: dialog.clone(),
+            track_path: None,
+            track: Track::default(),
+            clock_offset: 0,
+            max_gap: DEFAULT_MAX_GAP_MINUTES as i64,
+            candidate_count: 0,
+            matches: Vec::new(),
+            preview_list: preview_list.clone(),
+        };
+
+        let widgets = view_output!();
+
+        ComponentParts { model, widgets }
+    }
+
+    fn update(&mut self, msg: Self::Input, sender: ComponentSender<Self>) {
+        match msg {
+            GeotagInput::Present => {
+                self.update_matches();
+                self.dialog.present(Some(&self.parent));
+            },
+            GeotagInput::ChooseTrack => {
+                let filter = gtk::FileFilter::new();
+                filter.set_name(Some(&fl!("geotag-dialog", "filter")));
+                filter.add_suffix("gpx");
+                filter.add_suffix("kml");
+                filter.add_suffix("geojson");
+                filter.add_suffix("json");
+
+                let filters = gio::ListStore::new::<gtk::FileFilter>();
+                filters.append(&filter);
+
+                let file_dialog = gtk::FileDialog::builder()
+                    .title(fl!("geotag-dialog", "choose-track"))
+                    .modal(true)
+                    .filters(&filters)
+                    .build();
+
+                file_dialog.open(Some(&self.parent), None::<&gio::Cancellable>, move |result| {
+                    match result {
+                        Ok(file) => {
+                            if let Some(path) = file.path() {
+                                sender.input(GeotagInput::LoadTrack(path));
+                            }
+                        },
+                        Err(e) => error!("No track chosen: {}", e),
+                    }
+                });
+            },
+            GeotagInput::LoadTrack(path) => {
+                match Track::open(&path) {
+                    Ok(track) => {
+                        self.track = track;
+                        self.track_path = Some(path);
+                    },
+                    Err(e) => {
+                        error!("Failed loading track: {:?}", e);
+                        self.track = Track::default();
+                        self.track_path = None;
+                    },
+                }
+                self.update_matches();
+            },
+            GeotagInput::UpdateClockOffset(minutes) => {
+                self.clock_offset = minutes;
+                self.update_matches();
+            },
+            GeotagInput::UpdateMaxGap(minutes) => {
+                self.max_gap = minutes;
+                self.update_matches();
+            },
+            GeotagInput::Apply => {
+                let locations = self.matches
+                    .iter()
+                    .filter_map(|(visual, location)| visual.picture_id.map(|id| (id, *location)))
+                    .collect::<Vec<_>>();
+
+                let count = locations.len();
+                info!("Geotagging {} photos from track", count);
+
+                if let Err(e) = self.repo.set_picture_locations(LocationSource::Track, locations) {
+                    error!("Failed saving locations from track: {:?}", e);
+                    return;
+                }
+
+                self.matches.clear();
+                self.dialog.close();
+                let _ = sender.output(GeotagOutput::Geotagged(count));
+            },
+        }
+    }
+}
+
+impl GeotagDialog {
+    /// Match photos without a location against the track and show a preview of the matches.
+    fn update_matches(&mut self) {
+        let clock_offset = TimeDelta::minutes(self.clock_offset);
+        let max_gap = TimeDelta::minutes(self.max_gap);
+
+        let candidates: Vec<Arc<Visual>> = self.state.read()
+            .iter()
+            .filter(|v| v.picture_id.is_some() && v.location.is_none())
+            .cloned()
+            .collect();
+
+        self.candidate_count = candidates.len();
+
+        self.matches = candidates
+            .into_iter()
+            .filter_map(|visual| {
+                // Camera clock ahead of GPS clock, so subtract to get GPS time.
+                let timestamp = visual.ordering_ts - clock_offset;
+                self.track.locate(timestamp, max_gap).map(|location| (visual, location))
+            })
+            .collect();
+
+        self.preview_list.remove_all();
+
+        for (visual, location) in self.matches.iter().take(MAX_PREVIEW_ROWS) {
+            let name = visual.path()
+                .and_then(|path| path.file_name())
+                .map(|name| name.to_string_lossy().to_string())
+                .unwrap_or_default();
+
+            let row = adw::ActionRow::builder()
+                .title(name)
+                .use_markup(false)
+                .subtitle(format!(
+                    "{} · {:.5}, {:.5}",
+                    visual.ordering_ts.format("%Y-%m-%d %H:%M:%S"),
+                    location.lat(),
+                    location.lng(),
+                ))
+                .build();
+
+            self.preview_list.append(&row);
+        }
+    }
+}
@@ -0,0 +1,283 @@
+// SPDX-FileCopyrightText: © 2024 David Bliss
+//
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use relm4::{adw, ComponentParts, ComponentSender, RelmWidgetExt, SimpleComponent};
+use relm4::adw::prelude::*;
+use relm4::gtk;
+
+use fotema_core::places::{self, Inference, LocationSource};
+
+use chrono::TimeDelta;
+
+use tracing::{error, info};
+
+use std::collections::HashMap;
+
+use crate::app::SharedState;
+use crate::fl;
+
+/// Don't flood the list with rows when inferring locations for a large library.
+/// Proposals beyond this are still saved, but can't be rejected individually.
+const MAX_PROPOSAL_ROWS: usize = 200;
+
+const DEFAULT_MAX_GAP_MINUTES: f64 = 30.0;
+
+/// Dialog proposing locations for photos without one, based on photos and videos
+/// taken shortly before or after. Accepted locations are stored as inferred locations,
+/// which can be reverted.
+pub struct InferLocationsDialog {
+    state: SharedState,
+    repo: places::Repository,
+    parent: adw::ApplicationWindow,
+    dialog: adw::Dialog,
+
+    /// Maximum minutes between a photo and a located photo or video.
+    max_gap: i64,
+
+    /// Proposed locations, with whether the proposal is accepted.
+    /// Proposals without a row in the list have no check button and are always accepted.
+    proposals: Vec<(Inference, Option<gtk::CheckButton>)>,
+
+    /// Count of photos with a previously accepted inferred location.
+    inferred_count: usize,
+
+    proposals_list: gtk::ListBox,
+}
+
+#[derive(Debug)]
+pub enum InferLocationsInput {
+    /// Show the dialog.
+    Present,
+
+    UpdateMaxGap(i64),
+
+    /// Save accepted proposals.
+    Apply,
+
+    /// Remove all inferred locations.
+    RevertAll,
+}
+
+#[derive(Debug)]
+pub enum InferLocationsOutput {
+    /// Inferred locations have been added or removed.
+    LocationsChanged,
+}
+
+#[relm4::component(pub)]
+impl SimpleComponent for InferLocationsDialog {
+    type Init = (SharedState, places::Repository, adw::ApplicationWindow);
+    type Input = InferLocationsInput;
+    type Output = InferLocationsOutput;
+
+    view! {
+        adw::Dialog {
+            set_title: &fl!("infer-locations-dialog"),
+            set_content_width: 480,
+            set_content_height: 640,
+
+            #[wrap(Some)]
+            set_child = &adw::ToolbarView {
+                add_top_bar = &adw::HeaderBar,
+
+                #[wrap(Some)]
+                set_content = &adw::PreferencesPage {
+                    add = &adw::PreferencesGroup {
+                        adw::SpinRow::with_range(1.0, 720.0, 1.0) {
+                            set_title: &fl!("infer-locations-dialog", "max-gap"),
+                            set_subtitle: &fl!("infer-locations-dialog", "max-gap-subtitle"),
+                            set_value: DEFAULT_MAX_GAP_MINUTES,
+                            connect_value_notify[sender] => move |row| {
+                                sender.input(InferLocationsInput::UpdateMaxGap(row.value() as i64));
+                            },
+                        },
+                    },
+
+                    add = &adw::PreferencesGroup {
+                        set_title: &fl!("infer-locations-dialog", "proposals"),
+
+                        #[watch]
+                        set_description: Some(&fl!("infer-locations-dialog", "proposals-count",
+                            count = model.proposals.len())),
+
+                        #[local_ref]
+                        proposals_list -> gtk::ListBox {
+                            set_selection_mode: gtk::SelectionMode::None,
+                            add_css_class: "boxed-list",
+                        },
+                    },
+
+                    add = &adw::PreferencesGroup {
+                        set_title: &fl!("infer-locations-dialog", "inferred"),
+
+                        adw::ActionRow {
+                            #[watch]
+                            set_title: &fl!("infer-locations-dialog", "inferred-count",
+                                count = model.inferred_count),
+
+                            add_suffix = &gtk::Button {
+                                set_label: &fl!("infer-locations-dialog", "revert-all"),
+                                set_valign: gtk::Align::Center,
+                                add_css_class: "destructive-action",
+
+                                #[watch]
+                                set_sensitive: model.inferred_count > 0,
+
+                                connect_clicked => InferLocationsInput::RevertAll,
+                            },
+                        },
+                    },
+                },
+
+                add_bottom_bar = &gtk::Button {
+                    set_label: &fl!("infer-locations-dialog", "apply"),
+                    set_halign: gtk::Align::Center,
+                    set_margin_all: 12,
+                    add_css_class: "pill",
+                    add_css_class: "suggested-action",
+
+                    #[watch]
+                    set_sensitive: !model.proposals.is_empty(),
+
+                    connect_clicked => InferLocationsInput::Apply,
+                },
+            },
+        }
+    }
+
+    fn init(
+        (state, repo, parent): Self::Init,
+        dialog: Self::Root,
+        sender: ComponentSender<Self>,
+    ) -> ComponentParts<Self> {
+
+        let proposals_list = gtk::ListBox::new();
+
+        let model = Self {
+            state,
+            repo,
+            parent,
+            dialog: dialog.clone(),
+            max_gap: DEFAULT_MAX_GAP_MINUTES as i64,
+            proposals: Vec::new(),
+            inferred_count: 0,
+            proposals_list: proposals_list.clone(),
+        };
+
+        let widgets = view_output!();
+
+        ComponentParts { model, widgets }
+    }
+
+    fn update(&mut self, msg: Self::Input, sender: ComponentSender<Self>) {
+        match msg {
+            InferLocationsInput::Present => {
+                self.update_proposals();
+                self.dialog.present(Some(&self.parent));
+            },
+            InferLocationsInput::UpdateMaxGap(minutes) => {
+                self.max_gap = minutes;
+                self.update_proposals();
+            },
+            InferLocationsInput::Apply => {
+                let locations = self.proposals
+                    .iter()
+                    .filter(|(_, check)| check.as_ref().is_none_or(|c| c.is_active()))
+                    .map(|(inference, _)| (inference.picture_id, inference.location))
+                    .collect::<Vec<_>>();
+
+                info!("Saving {} inferred locations", locations.len());
+
+                if let Err(e) = self.repo.set_picture_locations(LocationSource::Inferred, locations) {
+                    error!("Failed saving inferred locations: {:?}", e);
+                    return;
+                }
+
+                self.dialog.close();
+                let _ = sender.output(InferLocationsOutput::LocationsChanged);
+            },
+            InferLocationsInput::RevertAll => {
+                match self.repo.remove_picture_locations(LocationSource::Inferred, None) {
+                    Ok(count) => {
+                        info!("Reverted {} inferred locations", count);
+                        self.update_proposals();
+                        let _ = sender.output(InferLocationsOutput::LocationsChanged);
+                    },
+                    Err(e) => error!("Failed reverting inferred locations: {:?}", e),
+                }
+            },
+        }
+    }
+}
+
+impl InferLocationsDialog {
+    fn update_proposals(&mut self) {
+        self.inferred_count = self.repo
+            .count_picture_locations(LocationSource::Inferred)
+            .unwrap_or(0);
+
+        let inferences = match self.repo.find_need_inference() {
+            Ok((known, unknown)) => {
+                places::infer::infer_locations(known, unknown, TimeDelta::minutes(self.max_gap))
+            },
+            Err(e) => {
+                error!("Failed finding locations to infer: {:?}", e);
+                Vec::new()
+            },
+        };
+
+        // File names for proposal rows
+        let names: HashMap<i64, String> = self.state.read()
+            .iter()
+            .filter_map(|v| {
+                let picture_id = v.picture_id?;
+                let name = v.picture_path.as_ref()?.file_name()?.to_string_lossy().to_string();
+                Some((picture_id.id(), name))
+            })
+            .collect();
+
+        self.proposals_list.remove_all();
+        self.proposals.clear();
+
+        let hidden_count = inferences.len().saturating_sub(MAX_PROPOSAL_ROWS);
+
+        for (index, inference) in inferences.into_iter().enumerate() {
+            if index >= MAX_PROPOSAL_ROWS {
+                self.proposals.push((inference, None));
+                continue;
+            }
+
+            let check = gtk::CheckButton::builder()
+                .active(true)
+                .valign(gtk::Align::Center)
+                .build();
+
+            let name = names.get(&inference.picture_id.id()).cloned().unwrap_or_default();
+
+            let row = adw::ActionRow::builder()
+                .title(name)
+                .use_markup(false)
+                .subtitle(fl!("infer-locations-dialog", "proposal",
+                    latitude = format!("{:.5}", inference.location.lat()),
+                    longitude = format!("{:.5}", inference.location.lng()),
+                    minutes = inference.gap.num_minutes()))
+                .activatable_widget(&check)
+                .build();
+
+            row.add_prefix(&check);
+
+            self.proposals_list.append(&row);
+            self.proposals.push((inference, Some(check)));
+        }
+
+        if hidden_count > 0 {
+            let row = adw::ActionRow::builder()
+                .title(fl!("infer-locations-dialog", "more-proposals", count = hidden_count))
+                .activatable(false)
+                .build();
+
+            self.proposals_list.append(&row);
+        }
+    }
+}
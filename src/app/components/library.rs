@@ -2,7 +2,7 @@
 //
 // SPDX-License-Identifier: GPL-3.0-or-later
 
-use fotema_core::{VisualId, YearMonth};
+use fotema_core::{Visual, VisualId, Year, YearMonth};
 
 use relm4::*;
 use relm4::adw;
@@ -42,11 +42,40 @@ pub enum LibraryInput {
     GoToYear(i32),
 
     View(VisualId),
+
+    // Enter or leave selection mode
+    SelectionMode(bool),
+
+    EditLocation(Vec<Arc<Visual>>),
+
+    EditDateTime(Vec<Arc<Visual>>),
+
+    AddToAlbum(Vec<Arc<Visual>>),
+
+    MoveToTrash(Vec<Arc<Visual>>),
+
+    // Show items from a year on a map
+    ViewMap(Year),
 }
 
 #[derive(Debug)]
 pub enum LibraryOutput {
     View(VisualId),
+
+    // Set location of selected items
+    EditLocation(Vec<Arc<Visual>>),
+
+    // Correct date and time of selected items
+    EditDateTime(Vec<Arc<Visual>>),
+
+    // Add selected items to albums
+    AddToAlbum(Vec<Arc<Visual>>),
+
+    // Move selected items to the trash
+    MoveToTrash(Vec<Arc<Visual>>),
+
+    // Show items matching filter on a map. Title and filter.
+    ViewMap(String, AlbumFilter),
 }
 
 
@@ -94,6 +123,11 @@ impl SimpleComponent for Library {
             .forward(sender.input_sender(), |msg| match msg {
                 AlbumOutput::Selected(id, _) => LibraryInput::View(id),
                 AlbumOutput::ScrollOffset(_) => LibraryInput::Ignore,
+                AlbumOutput::EditLocation(visuals) => LibraryInput::EditLocation(visuals),
+                AlbumOutput::EditDateTime(visuals) => LibraryInput::EditDateTime(visuals),
+                AlbumOutput::AddToAlbum(visuals) => LibraryInput::AddToAlbum(visuals),
+                AlbumOutput::MoveToTrash(visuals) => LibraryInput::MoveToTrash(visuals),
+                AlbumOutput::EditAlbum(_, _) => LibraryInput::Ignore,
             });
 
         state.subscribe(all_album.sender(), |_| AlbumInput::Refresh);
@@ -113,6 +147,7 @@ impl SimpleComponent for Library {
             .launch((state.clone(), active_view.clone()))
             .forward(sender.input_sender(),|msg| match msg {
                 YearsAlbumOutput::YearSelected(year) => LibraryInput::GoToYear(year),
+                YearsAlbumOutput::ViewMap(year) => LibraryInput::ViewMap(year),
             },
         );
 
@@ -163,6 +198,29 @@ impl SimpleComponent for Library {
             LibraryInput::View(id) => {
                 let _ = sender.output(LibraryOutput::View(id));
             },
+            LibraryInput::SelectionMode(enabled) => {
+                // Only the all photos view supports selecting items.
+                if enabled {
+                    self.stack.set_visible_child_name(LibraryViewName::All.into());
+                    self.all_album.emit(AlbumInput::Activate);
+                }
+                self.all_album.emit(AlbumInput::SelectionMode(enabled));
+            },
+            LibraryInput::EditLocation(visuals) => {
+                let _ = sender.output(LibraryOutput::EditLocation(visuals));
+            },
+            LibraryInput::EditDateTime(visuals) => {
+                let _ = sender.output(LibraryOutput::EditDateTime(visuals));
+            },
+            LibraryInput::AddToAlbum(visuals) => {
+                let _ = sender.output(LibraryOutput::AddToAlbum(visuals));
+            },
+            LibraryInput::MoveToTrash(visuals) => {
+                let _ = sender.output(LibraryOutput::MoveToTrash(visuals));
+            },
+            LibraryInput::ViewMap(year) => {
+                let _ = sender.output(LibraryOutput::ViewMap(year.to_string(), AlbumFilter::Year(year)));
+            },
         }
     }
 }
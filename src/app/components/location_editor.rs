@@ -0,0 +1,326 @@
+// SPDX-FileCopyrightText: © 2024 David Bliss
+//
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use relm4::{adw, ComponentParts, ComponentSender, RelmWidgetExt, SimpleComponent};
+use relm4::adw::prelude::*;
+use relm4::gtk;
+
+use fotema_core::places::{self, LocationSource};
+use fotema_core::Visual;
+
+use h3o::LatLng;
+
+use shumate;
+use shumate::prelude::*;
+use shumate::MAP_SOURCE_OSM_MAPNIK;
+
+use tracing::{error, info};
+
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use crate::app::components::albums::offline_map;
+use crate::app::SettingsState;
+use crate::fl;
+
+/// Zoom level for showing an existing location.
+const LOCATION_ZOOM_LEVEL: f64 = 12.0;
+
+const MIN_ZOOM_LEVEL: u32 = 2;
+const MAX_ZOOM_LEVEL: u32 = 17;
+
+/// Dialog for manually setting the location of one or more photos and videos,
+/// either by clicking on a map or by pasting in coordinates.
+/// Manual locations are never replaced by locations from metadata.
+pub struct LocationEditorDialog {
+    repo: places::Repository,
+    settings_state: SettingsState,
+    parent: adw::ApplicationWindow,
+    dialog: adw::Dialog,
+
+    /// Photos and videos to set the location of.
+    visuals: Vec<Arc<Visual>>,
+
+    /// Location chosen on the map or from coordinates.
+    location: Option<LatLng>,
+
+    /// Offline map and online fallback settings of last map source update.
+    map_settings: Option<(Option<PathBuf>, bool)>,
+
+    map: shumate::SimpleMap,
+    viewport: shumate::Viewport,
+    marker: shumate::Point,
+    coordinates_row: adw::EntryRow,
+}
+
+#[derive(Debug)]
+pub enum LocationEditorInput {
+    /// Show dialog for setting the location of photos and videos.
+    Present(Vec<Arc<Visual>>),
+
+    /// Map clicked at widget coordinates.
+    MapClicked(f64, f64),
+
+    /// Coordinates typed or pasted.
+    UpdateCoordinates(String),
+
+    /// Save location.
+    Apply,
+}
+
+#[derive(Debug)]
+pub enum LocationEditorOutput {
+    /// Locations have been set.
+    LocationsChanged,
+}
+
+#[relm4::component(pub)]
+impl SimpleComponent for LocationEditorDialog {
+    type Init = (places::Repository, SettingsState, adw::ApplicationWindow);
+    type Input = LocationEditorInput;
+    type Output = LocationEditorOutput;
+
+    view! {
+        adw::Dialog {
+            set_title: &fl!("location-editor-dialog"),
+            set_content_width: 640,
+            set_content_height: 640,
+
+            #[wrap(Some)]
+            set_child = &adw::ToolbarView {
+                add_top_bar = &adw::HeaderBar,
+
+                #[wrap(Some)]
+                set_content = &gtk::Box {
+                    set_orientation: gtk::Orientation::Vertical,
+                    set_spacing: 12,
+                    set_margin_all: 12,
+
+                    gtk::ListBox {
+                        set_selection_mode: gtk::SelectionMode::None,
+                        add_css_class: "boxed-list",
+
+                        #[local_ref]
+                        coordinates_row -> adw::EntryRow {
+                            set_title: &fl!("location-editor-dialog", "coordinates"),
+                            connect_changed[sender] => move |row| {
+                                sender.input(LocationEditorInput::UpdateCoordinates(row.text().to_string()));
+                            },
+                        },
+                    },
+
+                    gtk::Label {
+                        set_label: &fl!("location-editor-dialog", "map-help"),
+                        set_halign: gtk::Align::Start,
+                        set_wrap: true,
+                        add_css_class: "dim-label",
+                    },
+
+                    #[local_ref]
+                    map_widget -> shumate::SimpleMap {
+                        set_vexpand: true,
+                        set_hexpand: true,
+                    },
+                },
+
+                add_bottom_bar = &gtk::Button {
+                    #[watch]
+                    set_label: &fl!("location-editor-dialog", "apply", count = model.visuals.len()),
+                    set_halign: gtk::Align::Center,
+                    set_margin_all: 12,
+                    add_css_class: "pill",
+                    add_css_class: "suggested-action",
+
+                    #[watch]
+                    set_sensitive: model.location.is_some() && !model.visuals.is_empty(),
+
+                    connect_clicked => LocationEditorInput::Apply,
+                },
+            },
+        }
+    }
+
+    fn init(
+        (repo, settings_state, parent): Self::Init,
+        dialog: Self::Root,
+        sender: ComponentSender<Self>,
+    ) -> ComponentParts<Self> {
+
+        let map_widget = shumate::SimpleMap::new();
+
+        if let Some(scale) = map_widget.scale() {
+            scale.set_unit(shumate::Unit::Metric);
+        }
+
+        let map = map_widget.map().unwrap();
+
+        let viewport = map_widget.viewport().unwrap();
+        viewport.set_min_zoom_level(MIN_ZOOM_LEVEL);
+        viewport.set_max_zoom_level(MAX_ZOOM_LEVEL);
+
+        let gesture = gtk::GestureClick::new();
+        {
+            let sender = sender.clone();
+            gesture.connect_released(move |_, _, x, y| {
+                sender.input(LocationEditorInput::MapClicked(x, y));
+            });
+        }
+        map.add_controller(gesture);
+
+        let marker_layer = shumate::MarkerLayer::new(&viewport);
+        map.add_layer(&marker_layer);
+
+        let marker = shumate::Point::new();
+        marker.set_visible(false);
+        marker_layer.add_marker(&marker);
+
+        let coordinates_row = adw::EntryRow::new();
+
+        let model = Self {
+            repo,
+            settings_state,
+            parent,
+            dialog: dialog.clone(),
+            visuals: Vec::new(),
+            location: None,
+            map_settings: None,
+            map: map_widget.clone(),
+            viewport,
+            marker,
+            coordinates_row: coordinates_row.clone(),
+        };
+
+        let widgets = view_output!();
+
+        ComponentParts { model, widgets }
+    }
+
+    fn update(&mut self, msg: Self::Input, sender: ComponentSender<Self>) {
+        match msg {
+            LocationEditorInput::Present(visuals) => {
+                self.update_map_source();
+
+                // Start from an existing location, if any.
+                let location = visuals.iter().find_map(|visual| visual.location);
+                self.visuals = visuals;
+                self.location = None;
+
+                if let Some(location) = location {
+                    self.viewport.set_zoom_level(LOCATION_ZOOM_LEVEL);
+                    self.set_location(location, true);
+                } else {
+                    self.marker.set_visible(false);
+                    self.coordinates_row.set_text("");
+                }
+
+                self.dialog.present(Some(&self.parent));
+            },
+            LocationEditorInput::MapClicked(x, y) => {
+                let map = self.map.map().expect("Must have map");
+                let (latitude, longitude) = self.viewport.widget_coords_to_location(&map, x, y);
+                match LatLng::new(latitude, longitude) {
+                    Ok(location) => self.set_location(location, false),
+                    Err(e) => error!("Invalid location from map: {}", e),
+                }
+            },
+            LocationEditorInput::UpdateCoordinates(text) => {
+                let location = places::parse_coordinates(&text);
+                if text.trim().is_empty() || location.is_some() {
+                    self.coordinates_row.remove_css_class("error");
+                } else {
+                    self.coordinates_row.add_css_class("error");
+                }
+
+                // Text is also updated when the map is clicked, so ignore
+                // coordinates that are only rounded from the current location.
+                let is_same = location.zip(self.location).is_some_and(|(a, b)| {
+                    (a.lat() - b.lat()).abs() < 1e-5 && (a.lng() - b.lng()).abs() < 1e-5
+                });
+
+                if let (Some(location), false) = (location, is_same) {
+                    self.set_location(location, true);
+                }
+            },
+            LocationEditorInput::Apply => {
+                let Some(location) = self.location else {
+                    return;
+                };
+
+                let pictures = self.visuals
+                    .iter()
+                    .filter_map(|visual| visual.picture_id.map(|id| (id, location)))
+                    .collect::<Vec<_>>();
+
+                // Live photos have a picture and a video, so set the location of both.
+                let videos = self.visuals
+                    .iter()
+                    .filter_map(|visual| visual.video_id.map(|id| (id, location)))
+                    .collect::<Vec<_>>();
+
+                info!("Setting location of {} photos and {} videos", pictures.len(), videos.len());
+
+                if let Err(e) = self.repo.set_picture_locations(LocationSource::Manual, pictures) {
+                    error!("Failed saving photo locations: {:?}", e);
+                    return;
+                }
+
+                if let Err(e) = self.repo.set_video_locations(LocationSource::Manual, videos) {
+                    error!("Failed saving video locations: {:?}", e);
+                    return;
+                }
+
+                self.visuals.clear();
+                self.dialog.close();
+                let _ = sender.output(LocationEditorOutput::LocationsChanged);
+            },
+        }
+    }
+}
+
+impl LocationEditorDialog {
+    /// Move marker to location and show coordinates.
+    fn set_location(&mut self, location: LatLng, center: bool) {
+        self.location = Some(location);
+        self.marker.set_location(location.lat(), location.lng());
+        self.marker.set_visible(true);
+
+        if center {
+            let map = self.map.map().expect("Must have map");
+            map.center_on(location.lat(), location.lng());
+        }
+
+        self.coordinates_row.set_text(&format!("{:.5}, {:.5}", location.lat(), location.lng()));
+    }
+
+    /// Use offline tiles if a tile archive is configured, otherwise use OpenStreetMap.
+    fn update_map_source(&mut self) {
+        let (offline_map, online_map_fallback) = {
+            let settings = self.settings_state.read();
+            (settings.offline_map.clone(), settings.online_map_fallback)
+        };
+
+        let map_settings = Some((offline_map.clone(), online_map_fallback));
+        if self.map_settings == map_settings {
+            return;
+        }
+        self.map_settings = map_settings;
+
+        let offline = offline_map.as_ref().and_then(|path| {
+            offline_map::open_map_source(path)
+                .inspect_err(|e| error!("Failed opening offline map tiles: {}", e))
+                .ok()
+        });
+
+        let source = offline.or_else(|| {
+            if offline_map.is_none() || online_map_fallback {
+                shumate::MapSourceRegistry::with_defaults().by_id(MAP_SOURCE_OSM_MAPNIK)
+            } else {
+                None
+            }
+        });
+
+        self.map.set_map_source(source.as_ref());
+        self.viewport.set_reference_map_source(source.as_ref());
+    }
+}
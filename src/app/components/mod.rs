@@ -3,9 +3,17 @@
 // SPDX-License-Identifier: GPL-3.0-or-later
 
 pub mod about;
+pub mod album_chooser;
+pub mod date_time_editor;
+pub mod geotag;
+pub mod infer_locations;
+pub mod location_editor;
 pub mod preferences;
+pub mod privacy_zones;
+pub mod smart_album_editor;
 pub mod albums;
 pub mod library;
+pub mod on_this_day_card;
 pub mod progress_monitor;
 pub mod progress_panel;
 pub mod viewer;
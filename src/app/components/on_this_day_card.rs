@@ -0,0 +1,216 @@
+// SPDX-FileCopyrightText: © 2024 David Bliss
+//
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use fotema_core::memories;
+use fotema_core::visual::model::PictureOrientation;
+use fotema_core::Visual;
+use strum::IntoEnumIterator;
+
+use chrono::Datelike;
+use itertools::Itertools;
+
+use relm4::gtk;
+use relm4::gtk::prelude::*;
+use relm4::*;
+
+use std::path;
+use std::sync::Arc;
+
+use crate::app::SharedState;
+use crate::app::SettingsState;
+use crate::fl;
+
+const THUMBNAIL_EDGE_LENGTH: i32 = 64;
+
+#[derive(Debug)]
+pub enum OnThisDayCardInput {
+    // Reload photos from database
+    Refresh,
+
+    // Settings have changed
+    SettingsChanged,
+
+    // Hide card until Fotema is restarted
+    Dismiss,
+
+    // View memories
+    Open,
+}
+
+#[derive(Debug)]
+pub enum OnThisDayCardOutput {
+    /// User wants to view the "On this day" memories.
+    Open,
+}
+
+/// Card at the top of the library inviting the user to look at photos and videos
+/// taken on today's month and day in previous years.
+pub struct OnThisDayCard {
+    state: SharedState,
+    settings_state: SettingsState,
+    thumbnail: gtk::Picture,
+
+    /// Count of memories and of years they are from.
+    counts: Option<(usize, usize)>,
+
+    is_enabled: bool,
+    is_dismissed: bool,
+}
+
+#[relm4::component(pub)]
+impl SimpleComponent for OnThisDayCard {
+    type Init = (SharedState, SettingsState);
+    type Input = OnThisDayCardInput;
+    type Output = OnThisDayCardOutput;
+
+    view! {
+        gtk::Box {
+            set_orientation: gtk::Orientation::Horizontal,
+            set_spacing: 12,
+            set_margin_all: 12,
+            add_css_class: "card",
+
+            #[watch]
+            set_visible: model.is_enabled && !model.is_dismissed && model.counts.is_some(),
+
+            gtk::Frame {
+                set_margin_all: 6,
+
+                #[local_ref]
+                thumbnail -> gtk::Picture {
+                    set_can_shrink: true,
+                    set_content_fit: gtk::ContentFit::Cover,
+                    set_width_request: THUMBNAIL_EDGE_LENGTH,
+                    set_height_request: THUMBNAIL_EDGE_LENGTH,
+                },
+            },
+
+            gtk::Box {
+                set_orientation: gtk::Orientation::Vertical,
+                set_valign: gtk::Align::Center,
+                set_hexpand: true,
+
+                gtk::Label {
+                    set_label: &fl!("on-this-day-card"),
+                    set_halign: gtk::Align::Start,
+                    add_css_class: "heading",
+                },
+
+                gtk::Label {
+                    set_halign: gtk::Align::Start,
+                    set_ellipsize: gtk::pango::EllipsizeMode::End,
+                    add_css_class: "dim-label",
+
+                    #[watch]
+                    set_label: &model.counts
+                        .map(|(count, years)| fl!("on-this-day-card", "subtitle", count = count, years = years))
+                        .unwrap_or_default(),
+                },
+            },
+
+            gtk::Button {
+                set_label: &fl!("on-this-day-card", "view"),
+                set_valign: gtk::Align::Center,
+                add_css_class: "pill",
+                connect_clicked => OnThisDayCardInput::Open,
+            },
+
+            gtk::Button {
+                set_icon_name: "window-close-symbolic",
+                set_tooltip_text: Some(&fl!("on-this-day-card", "dismiss")),
+                set_valign: gtk::Align::Center,
+                set_margin_end: 6,
+                add_css_class: "flat",
+                add_css_class: "circular",
+                connect_clicked => OnThisDayCardInput::Dismiss,
+            },
+        }
+    }
+
+    fn init(
+        (state, settings_state): Self::Init,
+        _root: Self::Root,
+        sender: ComponentSender<Self>,
+    ) -> ComponentParts<Self> {
+        settings_state.subscribe(sender.input_sender(), |_| OnThisDayCardInput::SettingsChanged);
+
+        let thumbnail = gtk::Picture::new();
+
+        let is_enabled = settings_state.read().show_on_this_day;
+
+        let model = OnThisDayCard {
+            state,
+            settings_state,
+            thumbnail: thumbnail.clone(),
+            counts: None,
+            is_enabled,
+            is_dismissed: false,
+        };
+
+        let widgets = view_output!();
+
+        ComponentParts { model, widgets }
+    }
+
+    fn update(&mut self, msg: Self::Input, sender: ComponentSender<Self>) {
+        match msg {
+            OnThisDayCardInput::Refresh => {
+                self.refresh();
+            },
+            OnThisDayCardInput::SettingsChanged => {
+                self.is_enabled = self.settings_state.read().show_on_this_day;
+            },
+            OnThisDayCardInput::Dismiss => {
+                self.is_dismissed = true;
+            },
+            OnThisDayCardInput::Open => {
+                let _ = sender.output(OnThisDayCardOutput::Open);
+            },
+        }
+    }
+}
+
+impl OnThisDayCard {
+    fn refresh(&mut self) {
+        let today = chrono::Local::now().date_naive();
+
+        let visuals: Vec<Arc<Visual>> = {
+            let data = self.state.read();
+            data.iter()
+                .filter(|x| memories::is_on_this_day(x.local_ts().date_naive(), today))
+                .cloned()
+                .collect()
+        };
+
+        let years = visuals.iter()
+            .counts_by(|x| x.local_ts().year());
+
+        if years.is_empty() {
+            self.counts = None;
+            self.thumbnail.set_filename(None::<&path::Path>);
+            return;
+        }
+
+        // The memories view shows only the best of each year.
+        let count: usize = years.values().map(|count| (*count).min(memories::MAX_PER_YEAR)).sum();
+        self.counts = Some((count, years.len()));
+
+        // clear orientation transformation css classes
+        for orient in PictureOrientation::iter() {
+            self.thumbnail.remove_css_class(orient.as_ref());
+        }
+
+        let cover = visuals.iter()
+            .filter(|x| x.thumbnail_path.as_ref().is_some_and(|x| x.exists()))
+            .max_by_key(|x| x.ordering_ts);
+
+        if let Some(cover) = cover {
+            self.thumbnail.set_filename(cover.thumbnail_path.clone());
+            let orientation = cover.thumbnail_orientation();
+            self.thumbnail.add_css_class(orientation.as_ref());
+        } else {
+            self.thumbnail.set_filename(None::<&path::Path>);
+        }
+    }
+}
@@ -5,8 +5,11 @@
 use relm4::{adw, ComponentParts, ComponentSender, SimpleComponent};
 use relm4::adw::prelude::*;
 use relm4::gtk;
+use relm4::gtk::gio;
 
-use tracing::info;
+use tracing::{error, info};
+
+use std::path::PathBuf;
 
 use crate::fl;
 use crate::app::{Settings, SettingsState};
@@ -34,6 +37,18 @@ pub enum PreferencesInput {
     UpdateShowSelfies(bool),
 
     UpdateFaceDetectionMode(FaceDetectionMode),
+
+    /// Show file chooser for offline map tile archive.
+    ChooseOfflineMap,
+
+    /// Set or clear offline map tile archive.
+    UpdateOfflineMap(Option<PathBuf>),
+
+    UpdateOnlineMapFallback(bool),
+
+    UpdateShowOnThisDay(bool),
+
+    UpdateImportRatingsAsFavourites(bool),
 }
 
 #[relm4::component(pub)]
@@ -62,6 +77,30 @@ impl SimpleComponent for PreferencesDialog {
 		                },
                     },
 
+                    adw::SwitchRow {
+                        set_title: &fl!("prefs-views-on-this-day"),
+                        set_subtitle: &fl!("prefs-views-on-this-day", "subtitle"),
+
+                        #[watch]
+                        set_active: model.settings.show_on_this_day,
+
+                        connect_active_notify[sender] => move |switch| {
+                            let _ = sender.input_sender().send(PreferencesInput::UpdateShowOnThisDay(switch.is_active()));
+                        },
+                    },
+
+                    adw::SwitchRow {
+                        set_title: &fl!("prefs-views-ratings"),
+                        set_subtitle: &fl!("prefs-views-ratings", "subtitle"),
+
+                        #[watch]
+                        set_active: model.settings.import_ratings_as_favourites,
+
+                        connect_active_notify[sender] => move |switch| {
+                            let _ = sender.input_sender().send(PreferencesInput::UpdateImportRatingsAsFavourites(switch.is_active()));
+                        },
+                    },
+
                     #[local_ref]
                     face_detection_mode_row -> adw::ComboRow {
                         set_title: &fl!("prefs-views-faces"),
@@ -72,6 +111,55 @@ impl SimpleComponent for PreferencesDialog {
                         },
                     }
                 },
+
+                add = &adw::PreferencesGroup {
+                    set_title: &fl!("prefs-map-section"),
+                    set_description: Some(&fl!("prefs-map-section", "description")),
+
+                    adw::ActionRow {
+                        set_title: &fl!("prefs-map-offline"),
+
+                        #[watch]
+                        set_subtitle: &model.settings.offline_map.as_ref()
+                            .map(|path| path.to_string_lossy().to_string())
+                            .unwrap_or_else(|| fl!("prefs-map-offline", "subtitle")),
+
+                        add_suffix = &gtk::Button {
+                            set_icon_name: "document-open-symbolic",
+                            set_tooltip_text: Some(&fl!("prefs-map-offline", "choose")),
+                            set_valign: gtk::Align::Center,
+                            add_css_class: "flat",
+                            connect_clicked => PreferencesInput::ChooseOfflineMap,
+                        },
+
+                        add_suffix = &gtk::Button {
+                            set_icon_name: "edit-clear-symbolic",
+                            set_tooltip_text: Some(&fl!("prefs-map-offline", "clear")),
+                            set_valign: gtk::Align::Center,
+                            add_css_class: "flat",
+
+                            #[watch]
+                            set_sensitive: model.settings.offline_map.is_some(),
+
+                            connect_clicked => PreferencesInput::UpdateOfflineMap(None),
+                        },
+                    },
+
+                    adw::SwitchRow {
+                        set_title: &fl!("prefs-map-online-fallback"),
+                        set_subtitle: &fl!("prefs-map-online-fallback", "subtitle"),
+
+                        #[watch]
+                        set_active: model.settings.online_map_fallback,
+
+                        #[watch]
+                        set_sensitive: model.settings.offline_map.is_some(),
+
+                        connect_active_notify[sender] => move |switch| {
+                            let _ = sender.input_sender().send(PreferencesInput::UpdateOnlineMapFallback(switch.is_active()));
+                        },
+                    },
+                },
             }
         }
     }
@@ -108,7 +196,7 @@ impl SimpleComponent for PreferencesDialog {
         ComponentParts { model, widgets }
     }
 
-    fn update(&mut self, msg: Self::Input, _sender: ComponentSender<Self>) {
+    fn update(&mut self, msg: Self::Input, sender: ComponentSender<Self>) {
         match msg {
             PreferencesInput::Present => {
                 self.settings = self.settings_state.read().clone();
@@ -134,6 +222,52 @@ impl SimpleComponent for PreferencesDialog {
                 self.settings.face_detection_mode = mode;
                 *self.settings_state.write() = self.settings.clone();
             },
+            PreferencesInput::ChooseOfflineMap => {
+                let filter = gtk::FileFilter::new();
+                filter.set_name(Some(&fl!("prefs-map-offline", "filter")));
+                filter.add_suffix("mbtiles");
+                filter.add_suffix("pmtiles");
+
+                let filters = gio::ListStore::new::<gtk::FileFilter>();
+                filters.append(&filter);
+
+                let file_dialog = gtk::FileDialog::builder()
+                    .title(fl!("prefs-map-offline", "choose"))
+                    .modal(true)
+                    .filters(&filters)
+                    .build();
+
+                file_dialog.open(Some(&self.parent), None::<&gio::Cancellable>, move |result| {
+                    match result {
+                        Ok(file) => {
+                            if let Some(path) = file.path() {
+                                sender.input(PreferencesInput::UpdateOfflineMap(Some(path)));
+                            }
+                        },
+                        Err(e) => error!("No offline map chosen: {}", e),
+                    }
+                });
+            },
+            PreferencesInput::UpdateOfflineMap(path) => {
+                info!("Update offline map: {:?}", path);
+                self.settings.offline_map = path;
+                *self.settings_state.write() = self.settings.clone();
+            },
+            PreferencesInput::UpdateOnlineMapFallback(online_map_fallback) => {
+                info!("Update online map fallback: {}", online_map_fallback);
+                self.settings.online_map_fallback = online_map_fallback;
+                *self.settings_state.write() = self.settings.clone();
+            },
+            PreferencesInput::UpdateShowOnThisDay(show_on_this_day) => {
+                info!("Update show on this day: {}", show_on_this_day);
+                self.settings.show_on_this_day = show_on_this_day;
+                *self.settings_state.write() = self.settings.clone();
+            },
+            PreferencesInput::UpdateImportRatingsAsFavourites(import_ratings) => {
+                info!("Update import ratings as favourites: {}", import_ratings);
+                self.settings.import_ratings_as_favourites = import_ratings;
+                *self.settings_state.write() = self.settings.clone();
+            },
         }
     }
 }
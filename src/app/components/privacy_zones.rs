@@ -0,0 +1,358 @@
+// SPDX-FileCopyrightText: © 2024 David Bliss
+//
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use relm4::{adw, ComponentParts, ComponentSender, RelmWidgetExt, SimpleComponent};
+use relm4::adw::prelude::*;
+use relm4::gtk;
+
+use fotema_core::places::{self, PrivacyZoneId, PrivacyZones, ZoneArea};
+
+use h3o::{LatLng, Resolution};
+
+use tracing::{error, info};
+
+use crate::fl;
+
+const DEFAULT_RADIUS_METRES: f64 = 200.0;
+
+const DEFAULT_RESOLUTION: f64 = 9.0;
+
+const DEFAULT_RINGS: f64 = 1.0;
+
+/// Shape of a new privacy zone.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ZoneShape {
+    Circle,
+    Cells,
+}
+
+/// Dialog for adding and removing privacy zones, such as home or work.
+/// Items inside a privacy zone aren't shown on the map and their location
+/// isn't shown in the properties of the item.
+pub struct PrivacyZonesDialog {
+    repo: places::Repository,
+    parent: adw::ApplicationWindow,
+    dialog: adw::Dialog,
+
+    zones: PrivacyZones,
+    zones_list: gtk::ListBox,
+
+    /// Name of new zone.
+    name: String,
+    name_row: adw::EntryRow,
+
+    /// Centre of new zone.
+    centre: Option<LatLng>,
+    centre_row: adw::EntryRow,
+
+    shape: ZoneShape,
+
+    /// Radius of new circular zone, in metres.
+    radius: f64,
+
+    /// Resolution of cells of new zone.
+    resolution: u8,
+
+    /// Rings of cells around the centre cell of a new zone.
+    rings: u32,
+}
+
+#[derive(Debug)]
+pub enum PrivacyZonesInput {
+    /// Show the dialog.
+    Present,
+
+    UpdateName(String),
+
+    UpdateCentre(String),
+
+    /// Index into shape choices.
+    UpdateShape(u32),
+
+    UpdateRadius(f64),
+
+    UpdateResolution(u8),
+
+    UpdateRings(u32),
+
+    /// Add new zone.
+    Add,
+
+    Remove(PrivacyZoneId),
+}
+
+#[derive(Debug)]
+pub enum PrivacyZonesOutput {
+    /// Privacy zones have been added or removed.
+    ZonesChanged,
+}
+
+#[relm4::component(pub)]
+impl SimpleComponent for PrivacyZonesDialog {
+    type Init = (places::Repository, adw::ApplicationWindow);
+    type Input = PrivacyZonesInput;
+    type Output = PrivacyZonesOutput;
+
+    view! {
+        adw::Dialog {
+            set_title: &fl!("privacy-zones-dialog"),
+            set_content_width: 480,
+            set_content_height: 640,
+
+            #[wrap(Some)]
+            set_child = &adw::ToolbarView {
+                add_top_bar = &adw::HeaderBar,
+
+                #[wrap(Some)]
+                set_content = &adw::PreferencesPage {
+                    add = &adw::PreferencesGroup {
+                        set_title: &fl!("privacy-zones-dialog", "zones"),
+                        set_description: Some(&fl!("privacy-zones-dialog", "zones-description")),
+
+                        #[local_ref]
+                        zones_list -> gtk::ListBox {
+                            set_selection_mode: gtk::SelectionMode::None,
+                            add_css_class: "boxed-list",
+
+                            #[watch]
+                            set_visible: !model.zones.is_empty(),
+                        },
+                    },
+
+                    add = &adw::PreferencesGroup {
+                        set_title: &fl!("privacy-zones-dialog", "new-zone"),
+
+                        #[local_ref]
+                        name_row -> adw::EntryRow {
+                            set_title: &fl!("privacy-zones-dialog", "name"),
+                            connect_changed[sender] => move |row| {
+                                sender.input(PrivacyZonesInput::UpdateName(row.text().to_string()));
+                            },
+                        },
+
+                        #[local_ref]
+                        centre_row -> adw::EntryRow {
+                            set_title: &fl!("privacy-zones-dialog", "centre"),
+                            connect_changed[sender] => move |row| {
+                                sender.input(PrivacyZonesInput::UpdateCentre(row.text().to_string()));
+                            },
+                        },
+
+                        adw::ComboRow {
+                            set_title: &fl!("privacy-zones-dialog", "shape"),
+                            set_model: Some(&gtk::StringList::new(&[
+                                &fl!("privacy-zones-dialog", "shape-circle"),
+                                &fl!("privacy-zones-dialog", "shape-cells"),
+                            ])),
+                            connect_selected_notify[sender] => move |row| {
+                                sender.input(PrivacyZonesInput::UpdateShape(row.selected()));
+                            },
+                        },
+
+                        adw::SpinRow::with_range(10.0, 10_000.0, 10.0) {
+                            set_title: &fl!("privacy-zones-dialog", "radius"),
+                            set_subtitle: &fl!("privacy-zones-dialog", "radius-subtitle"),
+                            set_value: DEFAULT_RADIUS_METRES,
+
+                            #[watch]
+                            set_visible: model.shape == ZoneShape::Circle,
+
+                            connect_value_notify[sender] => move |row| {
+                                sender.input(PrivacyZonesInput::UpdateRadius(row.value()));
+                            },
+                        },
+
+                        adw::SpinRow::with_range(5.0, 12.0, 1.0) {
+                            set_title: &fl!("privacy-zones-dialog", "resolution"),
+                            set_subtitle: &fl!("privacy-zones-dialog", "resolution-subtitle"),
+                            set_value: DEFAULT_RESOLUTION,
+
+                            #[watch]
+                            set_visible: model.shape == ZoneShape::Cells,
+
+                            connect_value_notify[sender] => move |row| {
+                                sender.input(PrivacyZonesInput::UpdateResolution(row.value() as u8));
+                            },
+                        },
+
+                        adw::SpinRow::with_range(0.0, 5.0, 1.0) {
+                            set_title: &fl!("privacy-zones-dialog", "rings"),
+                            set_subtitle: &fl!("privacy-zones-dialog", "rings-subtitle"),
+                            set_value: DEFAULT_RINGS,
+
+                            #[watch]
+                            set_visible: model.shape == ZoneShape::Cells,
+
+                            connect_value_notify[sender] => move |row| {
+                                sender.input(PrivacyZonesInput::UpdateRings(row.value() as u32));
+                            },
+                        },
+                    },
+                },
+
+                add_bottom_bar = &gtk::Button {
+                    set_label: &fl!("privacy-zones-dialog", "add"),
+                    set_halign: gtk::Align::Center,
+                    set_margin_all: 12,
+                    add_css_class: "pill",
+                    add_css_class: "suggested-action",
+
+                    #[watch]
+                    set_sensitive: !model.name.trim().is_empty() && model.centre.is_some(),
+
+                    connect_clicked => PrivacyZonesInput::Add,
+                },
+            },
+        }
+    }
+
+    fn init(
+        (repo, parent): Self::Init,
+        dialog: Self::Root,
+        sender: ComponentSender<Self>,
+    ) -> ComponentParts<Self> {
+
+        let zones_list = gtk::ListBox::new();
+        let name_row = adw::EntryRow::new();
+        let centre_row = adw::EntryRow::new();
+
+        let model = Self {
+            repo,
+            parent,
+            dialog: dialog.clone(),
+            zones: PrivacyZones::default(),
+            zones_list: zones_list.clone(),
+            name: String::new(),
+            name_row: name_row.clone(),
+            centre: None,
+            centre_row: centre_row.clone(),
+            shape: ZoneShape::Circle,
+            radius: DEFAULT_RADIUS_METRES,
+            resolution: DEFAULT_RESOLUTION as u8,
+            rings: DEFAULT_RINGS as u32,
+        };
+
+        let widgets = view_output!();
+
+        ComponentParts { model, widgets }
+    }
+
+    fn update(&mut self, msg: Self::Input, sender: ComponentSender<Self>) {
+        match msg {
+            PrivacyZonesInput::Present => {
+                self.update_zones(&sender);
+                self.dialog.present(Some(&self.parent));
+            },
+            PrivacyZonesInput::UpdateName(name) => {
+                self.name = name;
+            },
+            PrivacyZonesInput::UpdateCentre(text) => {
+                self.centre = places::parse_coordinates(&text);
+                if text.trim().is_empty() || self.centre.is_some() {
+                    self.centre_row.remove_css_class("error");
+                } else {
+                    self.centre_row.add_css_class("error");
+                }
+            },
+            PrivacyZonesInput::UpdateShape(index) => {
+                self.shape = if index == 1 { ZoneShape::Cells } else { ZoneShape::Circle };
+            },
+            PrivacyZonesInput::UpdateRadius(radius) => {
+                self.radius = radius;
+            },
+            PrivacyZonesInput::UpdateResolution(resolution) => {
+                self.resolution = resolution;
+            },
+            PrivacyZonesInput::UpdateRings(rings) => {
+                self.rings = rings;
+            },
+            PrivacyZonesInput::Add => {
+                let Some(centre) = self.centre else {
+                    return;
+                };
+
+                let area = match self.shape {
+                    ZoneShape::Circle => ZoneArea::Circle { centre, radius: self.radius },
+                    ZoneShape::Cells => {
+                        let Ok(resolution) = Resolution::try_from(self.resolution) else {
+                            error!("Invalid resolution: {}", self.resolution);
+                            return;
+                        };
+                        let cells = centre.to_cell(resolution).grid_disk::<Vec<_>>(self.rings);
+                        ZoneArea::Cells(cells)
+                    },
+                };
+
+                let name = self.name.trim().to_string();
+                info!("Adding privacy zone: {}", name);
+
+                if let Err(e) = self.repo.add_privacy_zone(&name, &area) {
+                    error!("Failed adding privacy zone: {:?}", e);
+                    return;
+                }
+
+                self.name_row.set_text("");
+                self.centre_row.set_text("");
+                self.update_zones(&sender);
+                let _ = sender.output(PrivacyZonesOutput::ZonesChanged);
+            },
+            PrivacyZonesInput::Remove(zone_id) => {
+                info!("Removing privacy zone: {}", zone_id);
+
+                if let Err(e) = self.repo.remove_privacy_zone(zone_id) {
+                    error!("Failed removing privacy zone: {:?}", e);
+                    return;
+                }
+
+                self.update_zones(&sender);
+                let _ = sender.output(PrivacyZonesOutput::ZonesChanged);
+            },
+        }
+    }
+}
+
+impl PrivacyZonesDialog {
+    fn update_zones(&mut self, sender: &ComponentSender<Self>) {
+        self.zones = self.repo.all_privacy_zones().unwrap_or_else(|e| {
+            error!("Failed loading privacy zones: {:?}", e);
+            PrivacyZones::default()
+        });
+
+        self.zones_list.remove_all();
+
+        for zone in self.zones.iter() {
+            let subtitle = match zone.area {
+                ZoneArea::Circle { centre, radius } => fl!("privacy-zones-dialog", "circle-summary",
+                    radius = radius.round() as u32,
+                    latitude = format!("{:.5}", centre.lat()),
+                    longitude = format!("{:.5}", centre.lng())),
+                ZoneArea::Cells(ref cells) => fl!("privacy-zones-dialog", "cells-summary",
+                    count = cells.len(),
+                    resolution = cells.first().map(|cell| u8::from(cell.resolution())).unwrap_or_default()),
+            };
+
+            let row = adw::ActionRow::builder()
+                .title(&zone.name)
+                .use_markup(false)
+                .subtitle(subtitle)
+                .build();
+
+            let remove = gtk::Button::builder()
+                .icon_name("user-trash-symbolic")
+                .tooltip_text(fl!("privacy-zones-dialog", "remove"))
+                .valign(gtk::Align::Center)
+                .css_classes(["flat"])
+                .build();
+
+            {
+                let sender = sender.clone();
+                let zone_id = zone.zone_id;
+                remove.connect_clicked(move |_| sender.input(PrivacyZonesInput::Remove(zone_id)));
+            }
+
+            row.add_suffix(&remove);
+            self.zones_list.append(&row);
+        }
+    }
+}
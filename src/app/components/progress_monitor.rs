@@ -19,7 +19,7 @@ pub enum TaskName {
     Thumbnail(MediaType),
     Transcode,
     MotionPhoto,
-    DetectFaces,
+    DetectFaces(MediaType),
     RecognizeFaces,
 
     /// FIXME figure out if 'Idle' will be used.
@@ -77,9 +77,12 @@ impl SimpleComponent for ProgressPanel {
                         TaskName::MotionPhoto => {
                             self.progress_bar.set_text(Some(&fl!("progress-motion-photo")));
                         },
-                        TaskName::DetectFaces => {
+                        TaskName::DetectFaces(MediaType::Photo) => {
                             self.progress_bar.set_text(Some(&fl!("progress-detect-faces-photos")));
                         },
+                        TaskName::DetectFaces(MediaType::Video) => {
+                            self.progress_bar.set_text(Some(&fl!("progress-detect-faces-videos")));
+                        },
                         TaskName::RecognizeFaces => {
                             self.progress_bar.set_text(Some(&fl!("progress-recognize-faces-photos")));
                         },
@@ -0,0 +1,573 @@
+// SPDX-FileCopyrightText: © 2024 David Bliss
+//
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use relm4::{adw, ComponentParts, ComponentSender, RelmWidgetExt, SimpleComponent};
+use relm4::adw::prelude::*;
+use relm4::gtk;
+
+use fotema_core::people;
+use fotema_core::places;
+use fotema_core::smart_albums::{self, Criterion, DateRange, MediaType, Rule, RuleKind, SmartAlbumId};
+use fotema_core::tags;
+
+use h3o::{LatLng, Resolution};
+
+use tracing::{error, info, warn};
+
+use std::path::PathBuf;
+use std::str::FromStr;
+
+use crate::fl;
+
+/// Criteria that can be chosen, in the order they are shown.
+const CRITERIA: [RuleKind; 10] = [
+    RuleKind::Folder,
+    RuleKind::DateRange,
+    RuleKind::Person,
+    RuleKind::Place,
+    RuleKind::MediaType,
+    RuleKind::Camera,
+    RuleKind::Tag,
+    RuleKind::Selfie,
+    RuleKind::Motion,
+    RuleKind::Favourite,
+];
+
+const MEDIA_TYPES: [MediaType; 2] = [MediaType::Photo, MediaType::Video];
+
+const DEFAULT_RESOLUTION: f64 = 7.0;
+
+/// Widgets for editing one criterion of a rule.
+struct CriterionRow {
+    /// Identifies row for removal.
+    key: u32,
+
+    row: gtk::ListBoxRow,
+
+    /// Index into CRITERIA.
+    kind: gtk::DropDown,
+
+    /// Is or is not.
+    negate: gtk::DropDown,
+
+    /// Text value for folders, date ranges, places, cameras, and tags.
+    value: gtk::Entry,
+
+    /// Index into people of dialog.
+    person: gtk::DropDown,
+
+    /// Index into MEDIA_TYPES.
+    media_type: gtk::DropDown,
+
+    /// Resolution of cell for places.
+    resolution: gtk::SpinButton,
+}
+
+impl CriterionRow {
+    fn new(key: u32, people: &[people::Person], sender: &ComponentSender<SmartAlbumEditorDialog>) -> Self {
+        let kind_labels: Vec<String> = CRITERIA.iter().map(|kind| criterion_label(*kind)).collect();
+        let kind_labels: Vec<&str> = kind_labels.iter().map(|x| x.as_str()).collect();
+        let kind = gtk::DropDown::from_strings(&kind_labels);
+        kind.set_hexpand(true);
+
+        let negate = gtk::DropDown::from_strings(&[
+            &fl!("smart-album-editor", "is"),
+            &fl!("smart-album-editor", "is-not"),
+        ]);
+
+        let remove = gtk::Button::builder()
+            .icon_name("user-trash-symbolic")
+            .tooltip_text(fl!("smart-album-editor", "remove-criterion"))
+            .css_classes(["flat"])
+            .build();
+
+        {
+            let sender = sender.clone();
+            remove.connect_clicked(move |_| sender.input(SmartAlbumEditorInput::RemoveCriterion(key)));
+        }
+
+        let value = gtk::Entry::builder().hexpand(true).build();
+
+        let person_names: Vec<&str> = people.iter().map(|p| p.name.as_str()).collect();
+        let person = gtk::DropDown::from_strings(&person_names);
+        person.set_hexpand(true);
+
+        let media_type = gtk::DropDown::from_strings(&[
+            &fl!("smart-album-editor", "media-type-photo"),
+            &fl!("smart-album-editor", "media-type-video"),
+        ]);
+        media_type.set_hexpand(true);
+
+        let resolution = gtk::SpinButton::with_range(5.0, 12.0, 1.0);
+        resolution.set_value(DEFAULT_RESOLUTION);
+        resolution.set_tooltip_text(Some(&fl!("smart-album-editor", "place-resolution")));
+
+        let top = gtk::Box::new(gtk::Orientation::Horizontal, 6);
+        top.append(&kind);
+        top.append(&negate);
+        top.append(&remove);
+
+        let bottom = gtk::Box::new(gtk::Orientation::Horizontal, 6);
+        bottom.append(&value);
+        bottom.append(&person);
+        bottom.append(&media_type);
+        bottom.append(&resolution);
+
+        let content = gtk::Box::new(gtk::Orientation::Vertical, 6);
+        content.set_margin_all(6);
+        content.append(&top);
+        content.append(&bottom);
+
+        let row = gtk::ListBoxRow::builder()
+            .activatable(false)
+            .child(&content)
+            .build();
+
+        let criterion_row = Self {
+            key,
+            row,
+            kind,
+            negate,
+            value,
+            person,
+            media_type,
+            resolution,
+        };
+
+        {
+            let value = criterion_row.value.clone();
+            let person = criterion_row.person.clone();
+            let media_type = criterion_row.media_type.clone();
+            let resolution = criterion_row.resolution.clone();
+            let bottom = bottom.clone();
+            criterion_row.kind.connect_selected_notify(move |kind| {
+                let kind = CRITERIA.get(kind.selected() as usize).copied().unwrap_or(RuleKind::Folder);
+                show_value_widgets(kind, &bottom, &value, &person, &media_type, &resolution);
+            });
+        }
+
+        criterion_row.value.connect_changed(|entry| entry.remove_css_class("error"));
+
+        show_value_widgets(
+            RuleKind::Folder,
+            &bottom,
+            &criterion_row.value,
+            &criterion_row.person,
+            &criterion_row.media_type,
+            &criterion_row.resolution,
+        );
+
+        criterion_row
+    }
+
+    fn selected_kind(&self) -> RuleKind {
+        CRITERIA.get(self.kind.selected() as usize).copied().unwrap_or(RuleKind::Folder)
+    }
+
+    /// Show a criterion, which might be negated.
+    fn set_rule(&self, rule: &Rule, people: &[people::Person]) {
+        let (criterion, is_negated) = match rule {
+            Rule::Criterion(criterion) => (criterion, false),
+            Rule::Not(rule) => match rule.as_ref() {
+                Rule::Criterion(criterion) => (criterion, true),
+                _ => {
+                    warn!("Cannot edit nested rule: {:?}", rule);
+                    return;
+                },
+            },
+            _ => {
+                warn!("Cannot edit nested rule: {:?}", rule);
+                return;
+            },
+        };
+
+        let kind = criterion.kind();
+        if let Some(index) = CRITERIA.iter().position(|x| *x == kind) {
+            self.kind.set_selected(index as u32);
+        }
+
+        self.negate.set_selected(if is_negated { 1 } else { 0 });
+
+        match criterion {
+            Criterion::Folder(path) => self.value.set_text(&path.to_string_lossy()),
+            Criterion::DateRange(range) => self.value.set_text(&range.to_string()),
+            Criterion::Person(person_id) => {
+                if let Some(index) = people.iter().position(|p| p.person_id == *person_id) {
+                    self.person.set_selected(index as u32);
+                }
+            },
+            Criterion::Place(cell) => {
+                let centre = LatLng::from(*cell);
+                self.value.set_text(&format!("{:.5}, {:.5}", centre.lat(), centre.lng()));
+                self.resolution.set_value(u8::from(cell.resolution()).into());
+            },
+            Criterion::MediaType(media_type) => {
+                if let Some(index) = MEDIA_TYPES.iter().position(|x| x == media_type) {
+                    self.media_type.set_selected(index as u32);
+                }
+            },
+            Criterion::Camera(text) => self.value.set_text(text),
+            Criterion::Tag(path) => self.value.set_text(path),
+            Criterion::Selfie | Criterion::Motion | Criterion::Favourite => {},
+        }
+    }
+
+    /// Rule for row, or None if the value isn't valid.
+    fn rule(&self, people: &[people::Person]) -> Option<Rule> {
+        let text = self.value.text().trim().to_string();
+
+        let criterion = match self.selected_kind() {
+            RuleKind::Folder => {
+                let path = PathBuf::from(&text);
+                path.is_absolute().then_some(Criterion::Folder(path))
+            },
+            RuleKind::DateRange => DateRange::from_str(&text).ok().map(Criterion::DateRange),
+            RuleKind::Person => people
+                .get(self.person.selected() as usize)
+                .map(|p| Criterion::Person(p.person_id)),
+            RuleKind::Place => {
+                let resolution = Resolution::try_from(self.resolution.value() as u8).ok();
+                places::parse_coordinates(&text)
+                    .zip(resolution)
+                    .map(|(centre, resolution)| Criterion::Place(centre.to_cell(resolution)))
+            },
+            RuleKind::MediaType => MEDIA_TYPES
+                .get(self.media_type.selected() as usize)
+                .map(|x| Criterion::MediaType(*x)),
+            RuleKind::Camera => (!text.is_empty()).then_some(Criterion::Camera(text)),
+            RuleKind::Tag => tags::model::normalise_path(&text).map(Criterion::Tag),
+            RuleKind::Selfie => Some(Criterion::Selfie),
+            RuleKind::Motion => Some(Criterion::Motion),
+            RuleKind::Favourite => Some(Criterion::Favourite),
+            RuleKind::All | RuleKind::Any | RuleKind::Not => None,
+        };
+
+        let Some(criterion) = criterion else {
+            self.value.add_css_class("error");
+            return None;
+        };
+
+        let rule = Rule::Criterion(criterion);
+        if self.negate.selected() == 1 {
+            Some(Rule::Not(Box::new(rule)))
+        } else {
+            Some(rule)
+        }
+    }
+}
+
+/// Only show the widgets needed for the value of a kind of criterion.
+fn show_value_widgets(
+    kind: RuleKind,
+    container: &gtk::Box,
+    value: &gtk::Entry,
+    person: &gtk::DropDown,
+    media_type: &gtk::DropDown,
+    resolution: &gtk::SpinButton,
+) {
+    let has_text = matches!(kind, RuleKind::Folder | RuleKind::DateRange | RuleKind::Place | RuleKind::Camera | RuleKind::Tag);
+    value.set_visible(has_text);
+    value.set_placeholder_text(Some(&criterion_placeholder(kind)));
+    person.set_visible(kind == RuleKind::Person);
+    media_type.set_visible(kind == RuleKind::MediaType);
+    resolution.set_visible(kind == RuleKind::Place);
+    container.set_visible(!matches!(kind, RuleKind::Selfie | RuleKind::Motion | RuleKind::Favourite));
+}
+
+fn criterion_label(kind: RuleKind) -> String {
+    match kind {
+        RuleKind::Folder => fl!("smart-album-editor", "criterion-folder"),
+        RuleKind::DateRange => fl!("smart-album-editor", "criterion-date-range"),
+        RuleKind::Person => fl!("smart-album-editor", "criterion-person"),
+        RuleKind::Place => fl!("smart-album-editor", "criterion-place"),
+        RuleKind::MediaType => fl!("smart-album-editor", "criterion-media-type"),
+        RuleKind::Camera => fl!("smart-album-editor", "criterion-camera"),
+        RuleKind::Tag => fl!("smart-album-editor", "criterion-tag"),
+        RuleKind::Selfie => fl!("smart-album-editor", "criterion-selfie"),
+        RuleKind::Motion => fl!("smart-album-editor", "criterion-motion"),
+        RuleKind::Favourite => fl!("smart-album-editor", "criterion-favourite"),
+        RuleKind::All | RuleKind::Any | RuleKind::Not => String::new(),
+    }
+}
+
+fn criterion_placeholder(kind: RuleKind) -> String {
+    match kind {
+        RuleKind::Folder => fl!("smart-album-editor", "folder-placeholder"),
+        RuleKind::DateRange => fl!("smart-album-editor", "date-range-placeholder"),
+        RuleKind::Place => fl!("smart-album-editor", "place-placeholder"),
+        RuleKind::Camera => fl!("smart-album-editor", "camera-placeholder"),
+        RuleKind::Tag => fl!("smart-album-editor", "tag-placeholder"),
+        _ => String::new(),
+    }
+}
+
+/// Dialog for creating a smart album or changing its name and rule.
+/// The rule is a list of criteria that must all match, or any of which must match.
+pub struct SmartAlbumEditorDialog {
+    repo: smart_albums::Repository,
+    people_repo: people::Repository,
+    parent: adw::ApplicationWindow,
+    dialog: adw::Dialog,
+
+    /// Smart album being edited, or None when creating a smart album.
+    smart_album_id: Option<SmartAlbumId>,
+
+    /// People that can be chosen for person criteria.
+    people: Vec<people::Person>,
+
+    name_row: adw::EntryRow,
+
+    /// Match all or any criteria.
+    match_row: adw::ComboRow,
+
+    criteria_list: gtk::ListBox,
+    criteria: Vec<CriterionRow>,
+
+    /// Key for next criterion row.
+    next_key: u32,
+}
+
+#[derive(Debug)]
+pub enum SmartAlbumEditorInput {
+    /// Show dialog for creating a smart album.
+    Create,
+
+    /// Show dialog for editing a smart album.
+    Edit(SmartAlbumId),
+
+    AddCriterion,
+
+    RemoveCriterion(u32),
+
+    Save,
+}
+
+#[derive(Debug)]
+pub enum SmartAlbumEditorOutput {
+    /// Smart album has been created.
+    Created(SmartAlbumId),
+
+    /// Name or rule of smart album has been changed.
+    Changed(SmartAlbumId),
+}
+
+#[relm4::component(pub)]
+impl SimpleComponent for SmartAlbumEditorDialog {
+    type Init = (smart_albums::Repository, people::Repository, adw::ApplicationWindow);
+    type Input = SmartAlbumEditorInput;
+    type Output = SmartAlbumEditorOutput;
+
+    view! {
+        adw::Dialog {
+            #[watch]
+            set_title: &model.title(),
+            set_content_width: 480,
+            set_content_height: 640,
+
+            #[wrap(Some)]
+            set_child = &adw::ToolbarView {
+                add_top_bar = &adw::HeaderBar,
+
+                #[wrap(Some)]
+                set_content = &adw::PreferencesPage {
+                    add = &adw::PreferencesGroup {
+                        #[local_ref]
+                        name_row -> adw::EntryRow {
+                            set_title: &fl!("smart-album-editor", "name"),
+                        },
+
+                        #[local_ref]
+                        match_row -> adw::ComboRow {
+                            set_title: &fl!("smart-album-editor", "match"),
+                            set_model: Some(&gtk::StringList::new(&[
+                                &fl!("smart-album-editor", "match-all"),
+                                &fl!("smart-album-editor", "match-any"),
+                            ])),
+                        },
+                    },
+
+                    add = &adw::PreferencesGroup {
+                        set_title: &fl!("smart-album-editor", "criteria"),
+
+                        #[wrap(Some)]
+                        set_header_suffix = &gtk::Button {
+                            set_icon_name: "list-add-symbolic",
+                            set_tooltip_text: Some(&fl!("smart-album-editor", "add-criterion")),
+                            add_css_class: "flat",
+                            connect_clicked => SmartAlbumEditorInput::AddCriterion,
+                        },
+
+                        #[local_ref]
+                        criteria_list -> gtk::ListBox {
+                            set_selection_mode: gtk::SelectionMode::None,
+                            add_css_class: "boxed-list",
+                        },
+                    },
+                },
+
+                add_bottom_bar = &gtk::Button {
+                    set_label: &fl!("smart-album-editor", "save"),
+                    set_halign: gtk::Align::Center,
+                    set_margin_all: 12,
+                    add_css_class: "pill",
+                    add_css_class: "suggested-action",
+                    connect_clicked => SmartAlbumEditorInput::Save,
+                },
+            },
+        }
+    }
+
+    fn init(
+        (repo, people_repo, parent): Self::Init,
+        dialog: Self::Root,
+        sender: ComponentSender<Self>,
+    ) -> ComponentParts<Self> {
+        let name_row = adw::EntryRow::new();
+        let match_row = adw::ComboRow::new();
+        let criteria_list = gtk::ListBox::new();
+
+        let model = Self {
+            repo,
+            people_repo,
+            parent,
+            dialog: dialog.clone(),
+            smart_album_id: None,
+            people: Vec::new(),
+            name_row: name_row.clone(),
+            match_row: match_row.clone(),
+            criteria_list: criteria_list.clone(),
+            criteria: Vec::new(),
+            next_key: 0,
+        };
+
+        let widgets = view_output!();
+
+        ComponentParts { model, widgets }
+    }
+
+    fn update(&mut self, msg: Self::Input, sender: ComponentSender<Self>) {
+        match msg {
+            SmartAlbumEditorInput::Create => {
+                self.reset();
+                self.smart_album_id = None;
+                self.add_criterion(&sender);
+                self.dialog.present(Some(&self.parent));
+                self.name_row.grab_focus();
+            },
+            SmartAlbumEditorInput::Edit(smart_album_id) => {
+                let smart_album = match self.repo.get(smart_album_id) {
+                    Ok(Some(smart_album)) => smart_album,
+                    Ok(None) => {
+                        info!("Smart album {} no longer exists", smart_album_id);
+                        return;
+                    },
+                    Err(e) => {
+                        error!("Failed loading smart album: {:?}", e);
+                        return;
+                    },
+                };
+
+                self.reset();
+                self.smart_album_id = Some(smart_album_id);
+                self.name_row.set_text(&smart_album.name);
+
+                // The editor only creates a single group of criteria.
+                let (is_any, rules) = match smart_album.rule {
+                    Rule::All(rules) => (false, rules),
+                    Rule::Any(rules) => (true, rules),
+                    rule => (false, vec![rule]),
+                };
+
+                self.match_row.set_selected(if is_any { 1 } else { 0 });
+
+                for rule in rules {
+                    self.add_criterion(&sender);
+                    if let Some(row) = self.criteria.last() {
+                        row.set_rule(&rule, &self.people);
+                    }
+                }
+
+                self.dialog.present(Some(&self.parent));
+            },
+            SmartAlbumEditorInput::AddCriterion => {
+                self.add_criterion(&sender);
+            },
+            SmartAlbumEditorInput::RemoveCriterion(key) => {
+                if let Some(index) = self.criteria.iter().position(|row| row.key == key) {
+                    let row = self.criteria.remove(index);
+                    self.criteria_list.remove(&row.row);
+                }
+            },
+            SmartAlbumEditorInput::Save => {
+                let name = self.name_row.text().trim().to_string();
+                if name.is_empty() {
+                    self.name_row.add_css_class("error");
+                    return;
+                }
+                self.name_row.remove_css_class("error");
+
+                // Check every row so all invalid values are highlighted.
+                let rules: Vec<Option<Rule>> = self.criteria.iter().map(|row| row.rule(&self.people)).collect();
+                let Some(rules) = rules.into_iter().collect::<Option<Vec<Rule>>>() else {
+                    return;
+                };
+
+                let rule = if self.match_row.selected() == 1 {
+                    Rule::Any(rules)
+                } else {
+                    Rule::All(rules)
+                };
+
+                let result = if let Some(smart_album_id) = self.smart_album_id {
+                    info!("Updating smart album {}", smart_album_id);
+                    self.repo.update(smart_album_id, &name, &rule)
+                        .map(|_| SmartAlbumEditorOutput::Changed(smart_album_id))
+                } else {
+                    info!("Creating smart album {}", name);
+                    self.repo.create(&name, &rule)
+                        .map(SmartAlbumEditorOutput::Created)
+                };
+
+                match result {
+                    Ok(output) => {
+                        self.dialog.close();
+                        let _ = sender.output(output);
+                    },
+                    Err(e) => error!("Failed saving smart album: {:?}", e),
+                }
+            },
+        }
+    }
+}
+
+impl SmartAlbumEditorDialog {
+    fn title(&self) -> String {
+        if self.smart_album_id.is_some() {
+            fl!("smart-album-editor", "edit-title")
+        } else {
+            fl!("smart-album-editor", "create-title")
+        }
+    }
+
+    /// Clear the dialog and reload people.
+    fn reset(&mut self) {
+        self.people = self.people_repo.all_people().unwrap_or_else(|e| {
+            error!("Failed loading people: {:?}", e);
+            Vec::new()
+        });
+
+        self.name_row.set_text("");
+        self.name_row.remove_css_class("error");
+        self.match_row.set_selected(0);
+        self.criteria_list.remove_all();
+        self.criteria.clear();
+    }
+
+    fn add_criterion(&mut self, sender: &ComponentSender<Self>) {
+        let row = CriterionRow::new(self.next_key, &self.people, sender);
+        self.next_key += 1;
+        self.criteria_list.append(&row.row);
+        self.criteria.push(row);
+    }
+}
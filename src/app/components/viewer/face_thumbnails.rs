@@ -11,6 +11,7 @@ use relm4::actions::{RelmAction, RelmActionGroup};
 use crate::fl;
 use fotema_core::people;
 use fotema_core::PictureId;
+use fotema_core::VideoId;
 use fotema_core::FaceId;
 use fotema_core::PersonId;
 
@@ -40,7 +41,10 @@ pub enum FaceThumbnailsInput {
     /// View an item.
     View(PictureId),
 
-    /// Reload face and person data for current picture
+    /// View a video.
+    ViewVideo(VideoId),
+
+    /// Reload face and person data for current picture or video
     Refresh,
 
     /// The photo/video page has been hidden so any playing media should stop.
@@ -73,6 +77,8 @@ pub struct FaceThumbnails {
 
     picture_id: Option<PictureId>,
 
+    video_id: Option<VideoId>,
+
     face_thumbnails: gtk::Box,
 
     person_dialog: adw::Dialog,
@@ -114,6 +120,7 @@ impl SimpleAsyncComponent for FaceThumbnails {
 
         let model = Self {
             picture_id: None,
+            video_id: None,
             people_repo,
             face_thumbnails: widgets.face_thumbnails.clone(),
             person_dialog,
@@ -131,19 +138,27 @@ impl SimpleAsyncComponent for FaceThumbnails {
             },
             FaceThumbnailsInput::View(picture_id) => {
                 self.picture_id = Some(picture_id);
+                self.video_id = None;
+                sender.input(FaceThumbnailsInput::Refresh);
+            },
+            FaceThumbnailsInput::ViewVideo(video_id) => {
+                self.picture_id = None;
+                self.video_id = Some(video_id);
                 sender.input(FaceThumbnailsInput::Refresh);
             },
             FaceThumbnailsInput::Refresh => {
 
                 self.face_thumbnails.remove_all();
 
-                let Some(picture_id) = self.picture_id else {
+                let result = if let Some(picture_id) = self.picture_id {
+                    info!("Showing faces for {}", picture_id);
+                    self.people_repo.find_faces(&picture_id)
+                } else if let Some(video_id) = self.video_id {
+                    info!("Showing faces for video {}", video_id);
+                    self.people_repo.find_faces_for_video(&video_id)
+                } else {
                     return;
                 };
-
-                info!("Showing faces for {}", picture_id);
-
-                let result = self.people_repo.find_faces(&picture_id);
                 if let Err(e) = result {
                     error!("Failed getting faces: {}", e);
                     return;
@@ -5,7 +5,15 @@
 /// Properties view for a photo.
 ///Inspired by how Loupe displays its property view.
 
+use fotema_core::PictureId;
+use fotema_core::VideoId;
 use fotema_core::VisualId;
+use fotema_core::people;
+use fotema_core::places;
+use fotema_core::places::LocationSource;
+use fotema_core::tags;
+use fotema_core::time::TimestampSource;
+use fotema_core::visual;
 use gtk::prelude::OrientableExt;
 
 use relm4::gtk;
@@ -22,24 +30,66 @@ use chrono::{DateTime, Utc};
 use crate::app::SharedState;
 use crate::fl;
 
-use tracing::{event, Level};
+use tracing::{error, event, info, Level};
 
 #[derive(Debug)]
 pub enum ViewInfoInput {
     Photo(VisualId, ImageInfo),
     Video(VisualId),
     OpenFolder,
+
+    /// Remove inferred location of current photo.
+    RevertLocation,
+
+    /// Add tag typed into tag entry to current item.
+    AddTag,
+
+    /// Remove tag from current item.
+    RemoveTag(String),
+
+    /// Save caption typed into caption entry for current item.
+    SaveCaption,
+}
+
+#[derive(Debug)]
+pub enum ViewInfoOutput {
+    /// Location of an item has changed.
+    LocationsChanged,
+
+    /// Tags of an item have changed.
+    TagsChanged,
+
+    /// Caption of an item has changed.
+    CaptionsChanged,
 }
 
 pub struct ViewInfo {
     state: SharedState,
 
+    people_repo: people::Repository,
+
+    places_repo: places::Repository,
+
+    tags_repo: tags::Repository,
+
+    visual_repo: visual::Repository,
+
     path: Option<PathBuf>,
+    picture_id: Option<PictureId>,
+    video_id: Option<VideoId>,
     folder: adw::ActionRow,
     file_name: adw::ActionRow,
+    place: adw::ActionRow,
+    location: adw::ActionRow,
+    location_revert: gtk::Button,
+
+    // Title from metadata, and caption that the user can edit.
+    title: adw::ActionRow,
+    caption_entry: adw::EntryRow,
 
     // FIXME what timestamps to show for live photos that have an image an a video?
     date_time_details: adw::PreferencesGroup,
+    taken_at: adw::ActionRow,
     created_at: adw::ActionRow,
     modified_at: adw::ActionRow,
 
@@ -60,14 +110,23 @@ pub struct ViewInfo {
     video_file_size: adw::ActionRow,
     video_originally_created_at: adw::ActionRow,
     video_duration: adw::ActionRow,
+
+    // People recognized in photo or video, with their age at the time.
+    people_details: adw::PreferencesGroup,
+    people_rows: Vec<adw::ActionRow>,
+
+    // Keyword tags of photo or video, with an entry for adding more.
+    tags_details: adw::PreferencesGroup,
+    tags_rows: Vec<adw::ActionRow>,
+    tag_entry: adw::EntryRow,
 }
 
 
 #[relm4::component(pub)]
 impl SimpleComponent for ViewInfo {
-    type Init = SharedState;
+    type Init = (SharedState, people::Repository, places::Repository, tags::Repository, visual::Repository);
     type Input = ViewInfoInput;
-    type Output = ();
+    type Output = ViewInfoOutput;
 
     view! {
         gtk::ScrolledWindow {
@@ -77,6 +136,23 @@ impl SimpleComponent for ViewInfo {
                 set_margin_all: 12,
                 set_spacing: 12,
 
+                adw::PreferencesGroup {
+                    #[local_ref]
+                    title -> adw::ActionRow {
+                        set_title: &fl!("infobar-title"),
+                        add_css_class: "property",
+                        set_subtitle_selectable: true,
+                        set_use_markup: false,
+                    },
+
+                    #[local_ref]
+                    caption_entry -> adw::EntryRow {
+                        set_title: &fl!("infobar-caption"),
+                        set_show_apply_button: true,
+                        connect_apply => ViewInfoInput::SaveCaption,
+                    },
+                },
+
                 adw::PreferencesGroup {
                     #[local_ref]
                     folder -> adw::ActionRow {
@@ -99,10 +175,50 @@ impl SimpleComponent for ViewInfo {
                         add_css_class: "property",
                         set_subtitle_selectable: true,
                     },
+
+                    #[local_ref]
+                    place -> adw::ActionRow {
+                        set_title: &fl!("infobar-place"),
+                        add_css_class: "property",
+                        set_subtitle_selectable: true,
+                    },
+
+                    #[local_ref]
+                    location -> adw::ActionRow {
+                        set_title: &fl!("infobar-location"),
+                        add_css_class: "property",
+                        set_subtitle_selectable: true,
+                    },
+                },
+
+                #[local_ref]
+                people_details -> adw::PreferencesGroup {
+                    set_title: &fl!("infobar-people"),
+                    set_visible: false,
+                },
+
+                #[local_ref]
+                tags_details -> adw::PreferencesGroup {
+                    set_title: &fl!("infobar-tags"),
+                    set_description: Some(&fl!("infobar-tags", "description")),
+
+                    #[local_ref]
+                    tag_entry -> adw::EntryRow {
+                        set_title: &fl!("infobar-tags", "add"),
+                        set_show_apply_button: true,
+                        connect_apply => ViewInfoInput::AddTag,
+                    },
                 },
 
                 #[local_ref]
                 date_time_details -> adw::PreferencesGroup {
+                    #[local_ref]
+                    taken_at -> adw::ActionRow {
+                        set_title: &fl!("infobar-taken"),
+                        add_css_class: "property",
+                        set_subtitle_selectable: true,
+                    },
+
                     #[local_ref]
                     created_at -> adw::ActionRow {
                         set_title: &fl!("infobar-file-created"),
@@ -216,15 +332,36 @@ impl SimpleComponent for ViewInfo {
     }
 
     fn init(
-        state: Self::Init,
+        (state, people_repo, places_repo, tags_repo, visual_repo): Self::Init,
         _root: Self::Root,
-        _sender: ComponentSender<Self>,
+        sender: ComponentSender<Self>,
     ) -> ComponentParts<Self> {
 
         let folder = adw::ActionRow::new();
         let file_name = adw::ActionRow::new();
+        let place = adw::ActionRow::new();
+        let location = adw::ActionRow::new();
+
+        // Only inferred locations can be reverted.
+        let location_revert = gtk::Button::builder()
+            .valign(gtk::Align::Center)
+            .icon_name("edit-undo-symbolic")
+            .tooltip_text(fl!("infobar-location", "revert"))
+            .css_classes(["flat"])
+            .visible(false)
+            .build();
+
+        {
+            let sender = sender.clone();
+            location_revert.connect_clicked(move |_| sender.input(ViewInfoInput::RevertLocation));
+        }
+        location.add_suffix(&location_revert);
+
+        let title = adw::ActionRow::new();
+        let caption_entry = adw::EntryRow::new();
 
         let date_time_details = adw::PreferencesGroup::new();
+        let taken_at = adw::ActionRow::new();
         let created_at = adw::ActionRow::new();
         let modified_at = adw::ActionRow::new();
 
@@ -246,14 +383,31 @@ impl SimpleComponent for ViewInfo {
         let video_file_size = adw::ActionRow::new();
         let video_originally_created_at = adw::ActionRow::new();
 
+        let people_details = adw::PreferencesGroup::new();
+
+        let tags_details = adw::PreferencesGroup::new();
+        let tag_entry = adw::EntryRow::new();
+
         let model = ViewInfo {
             state,
+            people_repo,
+            places_repo,
+            tags_repo,
+            visual_repo,
 
             folder: folder.clone(),
             file_name: file_name.clone(),
+            place: place.clone(),
+            location: location.clone(),
+            location_revert: location_revert.clone(),
+            title: title.clone(),
+            caption_entry: caption_entry.clone(),
             path: None,
+            picture_id: None,
+            video_id: None,
 
             date_time_details: date_time_details.clone(),
+            taken_at: taken_at.clone(),
             created_at: created_at.clone(),
             modified_at: modified_at.clone(),
 
@@ -274,6 +428,13 @@ impl SimpleComponent for ViewInfo {
             video_codec: video_codec.clone(),
             video_audio_codec: video_audio_codec.clone(),
             video_dimensions: video_dimensions.clone(),
+
+            people_details: people_details.clone(),
+            people_rows: vec![],
+
+            tags_details: tags_details.clone(),
+            tags_rows: vec![],
+            tag_entry: tag_entry.clone(),
         };
 
         let widgets = view_output!();
@@ -281,8 +442,65 @@ impl SimpleComponent for ViewInfo {
         ComponentParts { model, widgets }
     }
 
-    fn update(&mut self, msg: Self::Input, _sender: ComponentSender<Self>) {
+    fn update(&mut self, msg: Self::Input, sender: ComponentSender<Self>) {
         match msg {
+            ViewInfoInput::RevertLocation => {
+                let Some(picture_id) = self.picture_id else {
+                    return;
+                };
+
+                info!("Reverting inferred location of picture {}", picture_id);
+                match self.places_repo.remove_picture_locations(LocationSource::Inferred, Some(vec![picture_id])) {
+                    Ok(_) => {
+                        Self::update_row(&self.location, None::<String>);
+                        Self::update_row(&self.place, None::<String>);
+                        self.location_revert.set_visible(false);
+                        let _ = sender.output(ViewInfoOutput::LocationsChanged);
+                    },
+                    Err(e) => error!("Failed reverting location: {:?}", e),
+                }
+            },
+            ViewInfoInput::AddTag => {
+                let path = self.tag_entry.text().to_string();
+                if path.trim().is_empty() || (self.picture_id.is_none() && self.video_id.is_none()) {
+                    return;
+                }
+
+                info!("Adding tag {} to picture {:?} and video {:?}", path, self.picture_id, self.video_id);
+                match self.tags_repo.add_tag(self.picture_id, self.video_id, &path) {
+                    Ok(_) => {
+                        self.tag_entry.set_text("");
+                        self.update_tag_details(&sender);
+                        let _ = sender.output(ViewInfoOutput::TagsChanged);
+                    },
+                    Err(e) => error!("Failed adding tag: {:?}", e),
+                }
+            },
+            ViewInfoInput::RemoveTag(path) => {
+                info!("Removing tag {} from picture {:?} and video {:?}", path, self.picture_id, self.video_id);
+                match self.tags_repo.remove_tag(self.picture_id, self.video_id, &path) {
+                    Ok(_) => {
+                        self.update_tag_details(&sender);
+                        let _ = sender.output(ViewInfoOutput::TagsChanged);
+                    },
+                    Err(e) => error!("Failed removing tag: {:?}", e),
+                }
+            },
+            ViewInfoInput::SaveCaption => {
+                if self.picture_id.is_none() && self.video_id.is_none() {
+                    return;
+                }
+
+                let caption = self.caption_entry.text().to_string();
+
+                info!("Setting caption of picture {:?} and video {:?}", self.picture_id, self.video_id);
+                match self.visual_repo.set_caption(self.picture_id, self.video_id, &caption) {
+                    Ok(_) => {
+                        let _ = sender.output(ViewInfoOutput::CaptionsChanged);
+                    },
+                    Err(e) => error!("Failed setting caption: {:?}", e),
+                }
+            },
             ViewInfoInput::OpenFolder => {
                 let Some(ref path) = self.path else {
                     return;
@@ -305,6 +523,8 @@ impl SimpleComponent for ViewInfo {
                 self.video_details.set_visible(false);
 
                 let _ = self.update_file_details(vis.clone());
+                self.update_people_details(vis.clone());
+                self.update_tag_details(&sender);
 
                 if vis.picture_id.is_some() {
                     let _ = self.update_photo_details(vis.clone(), image_info);
@@ -325,6 +545,8 @@ impl SimpleComponent for ViewInfo {
                 self.exif_details.set_visible(false);
 
                 let _ = self.update_file_details(vis.clone());
+                self.update_people_details(vis.clone());
+                self.update_tag_details(&sender);
 
                 if vis.video_id.is_some() {
                     let _ = self.update_video_details(vis.clone());
@@ -346,7 +568,28 @@ impl ViewInfo {
 
         Self::update_row(&self.folder, vis.folder_name());
         Self::update_row(&self.file_name, path.file_name().map(|x| x.to_string_lossy().to_string()));
+
         self.path = Some(path.to_path_buf());
+        self.picture_id = vis.picture_id;
+        self.video_id = vis.video_id;
+
+        Self::update_row(&self.title, vis.title.as_ref());
+        self.caption_entry.set_text(vis.caption.as_deref().unwrap_or_default());
+
+        // Don't reveal where an item inside a privacy zone was taken.
+        let is_private = vis.location.is_some_and(|location| {
+            self.places_repo
+                .all_privacy_zones()
+                .is_ok_and(|zones| zones.contains(location))
+        });
+
+        if is_private {
+            Self::update_row(&self.place, None::<String>);
+            Self::update_row(&self.location, None::<String>);
+            self.location_revert.set_visible(false);
+        } else {
+            self.update_location_details(&vis);
+        }
 
         // FIXME duplicated from Scanner
         let file = fs::File::open(path).map_err(|e| e.to_string())?;
@@ -366,7 +609,17 @@ impl ViewInfo {
             .map(|x| x.format("%Y-%m-%d %H:%M:%S %:z").to_string())
             .ok();
 
+        // Local time where the item was taken, which might not be the computer's time zone.
+        let taken_at = vis.local_ts().format("%Y-%m-%d %H:%M:%S %:z").to_string();
+        let taken_at = match vis.ordering_ts_source {
+            Some(TimestampSource::Manual) => fl!("infobar-taken", "manual", date_time = taken_at),
+            Some(TimestampSource::FileName) => fl!("infobar-taken", "file-name", date_time = taken_at),
+            Some(TimestampSource::FileSystem) => fl!("infobar-taken", "file-system", date_time = taken_at),
+            Some(TimestampSource::Metadata) | None => taken_at,
+        };
+
         let has_date_time_details = [
+            Self::update_row(&self.taken_at, Some(taken_at)),
             Self::update_row(&self.created_at, fs_created_at),
             Self::update_row(&self.modified_at, fs_modified_at),
         ]
@@ -378,6 +631,100 @@ impl ViewInfo {
         Ok(())
     }
 
+    /// Show location and place, including where the location came from
+    /// as it might not come from the item's own metadata.
+    fn update_location_details(&self, vis: &fotema_core::visual::Visual) {
+        let place = self.places_repo
+            .get_place(vis.picture_id, vis.video_id)
+            .ok()
+            .flatten();
+        Self::update_row(&self.place, place.map(|x| x.to_string()));
+
+        let location = vis.picture_id
+            .and_then(|picture_id| self.places_repo.get_picture_location(picture_id).ok().flatten())
+            .or_else(|| vis.video_id
+                .and_then(|video_id| self.places_repo.get_video_location(video_id).ok().flatten()))
+            .or_else(|| vis.location.map(|location| (location, LocationSource::Exif)));
+
+        let location_text = location.map(|(location, source)| {
+            let location = format!("{:.5}, {:.5}", location.lat(), location.lng());
+            match source {
+                LocationSource::Exif => location,
+                LocationSource::Track => fl!("infobar-location", "track", location = location),
+                LocationSource::Inferred => fl!("infobar-location", "inferred", location = location),
+                LocationSource::Manual => fl!("infobar-location", "manual", location = location),
+            }
+        });
+
+        Self::update_row(&self.location, location_text);
+        self.location_revert.set_visible(location.is_some_and(|(_, source)| source == LocationSource::Inferred));
+    }
+
+    fn update_people_details(&mut self, vis: Arc<fotema_core::visual::Visual>) {
+        for row in self.people_rows.drain(..) {
+            self.people_details.remove(&row);
+        }
+
+        let people = self.people_repo
+            .find_people_for_visual(vis.picture_id, vis.video_id)
+            .unwrap_or_default();
+
+        for person in people {
+            let row = adw::ActionRow::builder()
+                .title(&person.name)
+                .css_classes(["property"])
+                .subtitle_selectable(true)
+                .build();
+
+            let age = person.age_at(vis.local_ts().date_naive())
+                .map(|age| fl!("infobar-people", "age", age = age))
+                .unwrap_or_else(|| String::from(FALLBACK));
+
+            row.set_subtitle(&age);
+            self.people_details.add(&row);
+            self.people_rows.push(row);
+        }
+
+        self.people_details.set_visible(!self.people_rows.is_empty());
+    }
+
+    fn update_tag_details(&mut self, sender: &ComponentSender<Self>) {
+        for row in self.tags_rows.drain(..) {
+            self.tags_details.remove(&row);
+        }
+
+        let paths = self.tags_repo
+            .find_tags(self.picture_id, self.video_id)
+            .unwrap_or_default();
+
+        for path in paths {
+            let row = adw::ActionRow::builder()
+                .title(tags::model::display_path(&path))
+                .use_markup(false)
+                .build();
+
+            let remove_button = gtk::Button::builder()
+                .valign(gtk::Align::Center)
+                .icon_name("list-remove-symbolic")
+                .tooltip_text(fl!("infobar-tags", "remove"))
+                .css_classes(["flat"])
+                .build();
+
+            {
+                let sender = sender.clone();
+                remove_button.connect_clicked(move |_| sender.input(ViewInfoInput::RemoveTag(path.clone())));
+            }
+
+            row.add_suffix(&remove_button);
+
+            // Keep the entry for adding tags at the end of the group.
+            self.tags_details.remove(&self.tag_entry);
+            self.tags_details.add(&row);
+            self.tags_details.add(&self.tag_entry);
+            self.tags_rows.push(row);
+        }
+    }
+
     fn update_photo_details(&mut self, vis: Arc<fotema_core::visual::Visual>, image_info: &ImageInfo) -> Result<(), String> {
         let Some(ref picture_path) = vis.picture_path else {
             return Err("No picture path".to_string());
@@ -404,10 +751,13 @@ impl ViewInfo {
         if let Some(Ok(exif)) = image_info.details.exif.as_ref().map(|x| x.get_full()) {
             let metadata = fotema_core::photo::metadata::from_raw(exif).ok();
 
+            // Cameras don't always record the offset from UTC.
             let created_at: Option<String> = metadata
                 .clone()
-                .and_then(|x| x.created_at)
-                .map(|x| x.format("%Y-%m-%d %H:%M:%S %:z").to_string());
+                .and_then(|x| x.created_at.map(|local| match x.created_at_offset {
+                    Some(offset) => format!("{} {}", local.format("%Y-%m-%d %H:%M:%S"), offset),
+                    None => local.format("%Y-%m-%d %H:%M:%S").to_string(),
+                }));
 
             let modified_at: Option<String> = metadata
                 .clone()
@@ -448,7 +798,10 @@ impl ViewInfo {
 
         let created_at: Option<String> = metadata
             .created_at
-            .map(|x| x.format("%Y-%m-%d %H:%M:%S %:z").to_string());
+            .map(|x| match metadata.created_at_offset {
+                Some(offset) => x.with_timezone(&offset).format("%Y-%m-%d %H:%M:%S %:z").to_string(),
+                None => x.format("%Y-%m-%d %H:%M:%S %:z").to_string(),
+            });
 
         let duration = metadata
             .duration
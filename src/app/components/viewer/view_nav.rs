@@ -10,17 +10,22 @@ use relm4::actions::{RelmAction, RelmActionGroup};
 
 use crate::app::components::albums::album_filter::AlbumFilter;
 use super::view_one::{ViewOne, ViewOneInput, ViewOneOutput};
-use super::view_info::{ViewInfo, ViewInfoInput};
+use super::view_info::{ViewInfo, ViewInfoInput, ViewInfoOutput};
 use crate::app::components::progress_monitor::ProgressMonitor;
 use crate::app::SharedState;
 use crate::adaptive;
 use crate::fl;
 
 use fotema_core::Visual;
+use fotema_core::favourites;
 use fotema_core::people;
+use fotema_core::places;
+use fotema_core::tags;
+use fotema_core::visual;
 use fotema_core::PictureId;
 use fotema_core::VisualId;
 
+use std::path::PathBuf;
 use std::sync::Arc;
 
 use tracing::{error, info};
@@ -38,6 +43,12 @@ relm4::new_stateless_action!(IgnoreUnknownFacesAction, ViewNavActionGroup, "igno
 // Scan file for faces again using the most thorough scan possible.
 relm4::new_stateless_action!(ScanForFacesAction, ViewNavActionGroup, "scan_faces");
 
+// Manually draw a face the face detection missed.
+relm4::new_stateless_action!(AddFaceAction, ViewNavActionGroup, "add_face");
+
+// Add or remove item from favourites.
+relm4::new_stateless_action!(ToggleFavouriteAction, ViewNavActionGroup, "toggle_favourite");
+
 #[derive(Debug)]
 pub enum ViewNavInput {
     /// View an item after applying an album filter.
@@ -78,12 +89,47 @@ pub enum ViewNavInput {
 
     /// Scan for more faces.
     ScanForFaces,
+
+    /// Manually draw a face.
+    AddFace,
+
+    /// Location of an item has changed.
+    LocationsChanged,
+
+    /// Tags of an item have changed.
+    TagsChanged,
+
+    /// Caption of an item has changed.
+    CaptionsChanged,
+
+    /// Set location of current item.
+    EditLocation,
+
+    /// Correct date and time of current item.
+    EditDateTime,
+
+    /// Add or remove current item from favourites.
+    ToggleFavourite,
+
+    /// Add current item to albums, or take it out of them.
+    AddToAlbum,
+
+    /// Move current item to the trash.
+    MoveToTrash,
 }
 
 #[derive(Debug)]
 pub enum ViewNavOutput {
     TranscodeAll,
     ScanForFaces(PictureId),
+    LocationsChanged,
+    TagsChanged,
+    CaptionsChanged,
+    EditLocation(Arc<Visual>),
+    EditDateTime(Arc<Visual>),
+    FavouritesChanged,
+    AddToAlbum(Arc<Visual>),
+    MoveToTrash(Arc<Visual>),
 }
 
 pub struct ViewNav {
@@ -91,6 +137,11 @@ pub struct ViewNav {
 
     people_repo: people::Repository,
 
+    favourites_repo: favourites::Repository,
+
+    /// Is currently viewed item a favourite?
+    is_favourite: bool,
+
     // View one photo or video
     view_one: AsyncController<ViewOne>,
 
@@ -116,7 +167,7 @@ pub struct ViewNav {
 
 #[relm4::component(pub async)]
 impl SimpleAsyncComponent for ViewNav {
-    type Init = (SharedState, Arc<Reducer<ProgressMonitor>>, Arc<adaptive::LayoutState>, people::Repository);
+    type Init = (SharedState, Arc<Reducer<ProgressMonitor>>, Arc<adaptive::LayoutState>, people::Repository, places::Repository, favourites::Repository, tags::Repository, visual::Repository, PathBuf);
     type Input = ViewNavInput;
     type Output = ViewNavOutput;
 
@@ -126,6 +177,7 @@ impl SimpleAsyncComponent for ViewNav {
                 &fl!("viewer-faces-menu", "restore-ignored") => RestoreIgnoredFacesAction,
                 &fl!("viewer-faces-menu", "ignore-unknown") => IgnoreUnknownFacesAction,
                 &fl!("viewer-faces-menu", "scan") => ScanForFacesAction,
+                &fl!("viewer-faces-menu", "add") => AddFaceAction,
             }
         }
     }
@@ -136,11 +188,54 @@ impl SimpleAsyncComponent for ViewNav {
                 pack_end = &gtk::Box {
                     set_orientation: gtk::Orientation::Horizontal,
 
+                    gtk::ToggleButton {
+                        set_icon_name: "emblem-favorite-symbolic",
+
+                        #[watch]
+                        set_tooltip_text: Some(&if model.is_favourite {
+                            fl!("viewer-favourite", "remove")
+                        } else {
+                            fl!("viewer-favourite", "add")
+                        }),
+
+                        #[watch]
+                        #[block_signal(toggle_favourite_handler)]
+                        set_active: model.is_favourite,
+
+                        connect_toggled[sender] => move |_| {
+                            sender.input(ViewNavInput::ToggleFavourite);
+                        } @toggle_favourite_handler,
+                    },
+
+                    gtk::Button {
+                        set_icon_name: "list-add-symbolic",
+                        set_tooltip_text: Some(&fl!("viewer-add-to-album-tooltip")),
+                        connect_clicked => ViewNavInput::AddToAlbum,
+                    },
+
                     gtk::MenuButton {
                         set_icon_name: "sentiment-very-satisfied-symbolic",
                         set_menu_model: Some(&viewnav_menu),
                     },
 
+                    gtk::Button {
+                        set_icon_name: "mark-location-symbolic",
+                        set_tooltip_text: Some(&fl!("viewer-edit-location-tooltip")),
+                        connect_clicked => ViewNavInput::EditLocation,
+                    },
+
+                    gtk::Button {
+                        set_icon_name: "x-office-calendar-symbolic",
+                        set_tooltip_text: Some(&fl!("viewer-edit-date-time-tooltip")),
+                        connect_clicked => ViewNavInput::EditDateTime,
+                    },
+
+                    gtk::Button {
+                        set_icon_name: "user-trash-symbolic",
+                        set_tooltip_text: Some(&fl!("viewer-move-to-trash-tooltip")),
+                        connect_clicked => ViewNavInput::MoveToTrash,
+                    },
+
                     gtk::Button {
                         set_icon_name: "info-outline-symbolic",
                         set_tooltip_text: Some(&fl!("viewer-info-tooltip")),
@@ -204,7 +299,7 @@ impl SimpleAsyncComponent for ViewNav {
     }
 
     async fn init(
-        (state, transcode_progress_monitor, layout_state, people_repo): Self::Init,
+        (state, transcode_progress_monitor, layout_state, people_repo, places_repo, favourites_repo, tags_repo, visual_repo, data_dir): Self::Init,
         root: Self::Root,
         sender: AsyncComponentSender<Self>,
     ) -> AsyncComponentParts<Self>  {
@@ -212,7 +307,7 @@ impl SimpleAsyncComponent for ViewNav {
         let split_view = adw::OverlaySplitView::new();
 
         let view_one = ViewOne::builder()
-            .launch((people_repo.clone(), transcode_progress_monitor))
+            .launch((people_repo.clone(), transcode_progress_monitor, data_dir))
             .forward(sender.input_sender(), |msg| match msg {
                 ViewOneOutput::PhotoShown(id, info) => ViewNavInput::ShowPhotoInfo(id, info),
                 ViewOneOutput::VideoShown(id) => ViewNavInput::ShowVideoInfo(id),
@@ -220,8 +315,12 @@ impl SimpleAsyncComponent for ViewNav {
             });
 
         let view_info = ViewInfo::builder()
-            .launch(state.clone())
-            .detach();
+            .launch((state.clone(), people_repo.clone(), places_repo, tags_repo, visual_repo))
+            .forward(sender.input_sender(), |msg| match msg {
+                ViewInfoOutput::LocationsChanged => ViewNavInput::LocationsChanged,
+                ViewInfoOutput::TagsChanged => ViewNavInput::TagsChanged,
+                ViewInfoOutput::CaptionsChanged => ViewNavInput::CaptionsChanged,
+            });
 
         layout_state.subscribe(sender.input_sender(), |layout| ViewNavInput::Adapt(*layout));
 
@@ -231,6 +330,8 @@ impl SimpleAsyncComponent for ViewNav {
         let model = ViewNav {
             state,
             people_repo,
+            favourites_repo,
+            is_favourite: false,
             view_one,
             view_info,
             current_index: None,
@@ -263,12 +364,37 @@ impl SimpleAsyncComponent for ViewNav {
             })
         };
 
+        let add_face_action = {
+            let sender = sender.clone();
+            RelmAction::<AddFaceAction>::new_stateless(move |_| {
+                sender.input(ViewNavInput::AddFace);
+            })
+        };
+
+        let toggle_favourite_action = {
+            let sender = sender.clone();
+            RelmAction::<ToggleFavouriteAction>::new_stateless(move |_| {
+                sender.input(ViewNavInput::ToggleFavourite);
+            })
+        };
+
         let mut actions = RelmActionGroup::<ViewNavActionGroup>::new();
         actions.add_action(restore_action);
         actions.add_action(ignore_unknown_faces_action);
         actions.add_action(scan_faces_action);
+        actions.add_action(add_face_action);
+        actions.add_action(toggle_favourite_action);
         actions.register_for_widget(&root);
 
+        // Keyboard shortcut only applies while an item is being viewed.
+        let shortcuts = gtk::ShortcutController::new();
+        shortcuts.set_scope(gtk::ShortcutScope::Managed);
+        shortcuts.add_shortcut(gtk::Shortcut::new(
+            gtk::ShortcutTrigger::parse_string("<Control>d"),
+            Some(gtk::NamedAction::new("viewnav.toggle_favourite")),
+        ));
+        root.add_controller(shortcuts);
+
         let widgets = view_output!();
         AsyncComponentParts { model, widgets }
     }
@@ -291,6 +417,7 @@ impl SimpleAsyncComponent for ViewNav {
                         .filter(|v| filter.clone().filter(v))
                         .cloned()
                         .collect();
+                    filter.arrange(&mut self.filtered_items);
                 }
 
                 self.current_index = self.filtered_items
@@ -311,6 +438,13 @@ impl SimpleAsyncComponent for ViewNav {
                 let visual = &self.filtered_items[index];
                 self.current_index = Some(index);
 
+                self.is_favourite = self.favourites_repo
+                    .is_favourite(visual.picture_id, visual.video_id)
+                    .unwrap_or_else(|e| {
+                        error!("Failed checking favourite: {}", e);
+                        false
+                    });
+
                 self.update_nav_buttons();
 
                 self.view_one.emit(ViewOneInput::View(visual.clone()));
@@ -421,6 +555,71 @@ impl SimpleAsyncComponent for ViewNav {
                     let _ = sender.output(ViewNavOutput::ScanForFaces(picture_id));
                 }
             },
+            ViewNavInput::AddFace => {
+                info!("Drawing a face");
+                self.view_one.emit(ViewOneInput::DrawFace);
+            },
+            ViewNavInput::LocationsChanged => {
+                let _ = sender.output(ViewNavOutput::LocationsChanged);
+            },
+            ViewNavInput::TagsChanged => {
+                let _ = sender.output(ViewNavOutput::TagsChanged);
+            },
+            ViewNavInput::CaptionsChanged => {
+                let _ = sender.output(ViewNavOutput::CaptionsChanged);
+            },
+            ViewNavInput::EditLocation => {
+                let visual = self.current_index
+                    .and_then(|index| self.filtered_items.get(index))
+                    .cloned();
+
+                if let Some(visual) = visual {
+                    let _ = sender.output(ViewNavOutput::EditLocation(visual));
+                }
+            },
+            ViewNavInput::EditDateTime => {
+                let visual = self.current_index
+                    .and_then(|index| self.filtered_items.get(index))
+                    .cloned();
+
+                if let Some(visual) = visual {
+                    let _ = sender.output(ViewNavOutput::EditDateTime(visual));
+                }
+            },
+            ViewNavInput::AddToAlbum => {
+                let visual = self.current_index
+                    .and_then(|index| self.filtered_items.get(index))
+                    .cloned();
+
+                if let Some(visual) = visual {
+                    let _ = sender.output(ViewNavOutput::AddToAlbum(visual));
+                }
+            },
+            ViewNavInput::MoveToTrash => {
+                let visual = self.current_index
+                    .and_then(|index| self.filtered_items.get(index))
+                    .cloned();
+
+                if let Some(visual) = visual {
+                    let _ = sender.output(ViewNavOutput::MoveToTrash(visual));
+                }
+            },
+            ViewNavInput::ToggleFavourite => {
+                let Some(visual) = self.current_index.and_then(|index| self.filtered_items.get(index)) else {
+                    return;
+                };
+
+                let is_favourite = !self.is_favourite;
+                info!("Setting favourite to {} for {}", is_favourite, visual.visual_id);
+
+                if let Err(e) = self.favourites_repo.set_favourite(visual.picture_id, visual.video_id, is_favourite) {
+                    error!("Failed setting favourite: {}", e);
+                    return;
+                }
+
+                self.is_favourite = is_favourite;
+                let _ = sender.output(ViewNavOutput::FavouritesChanged);
+            },
         }
     }
 }
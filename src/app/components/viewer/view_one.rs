@@ -19,8 +19,12 @@ use crate::app::components::progress_monitor::ProgressMonitor;
 use crate::app::components::progress_panel::ProgressPanel;
 use crate::fl;
 use fotema_core::people;
+use fotema_core::machine_learning::face_extractor::{self, FaceExtractor};
 use super::face_thumbnails::{FaceThumbnails, FaceThumbnailsInput};
 
+use std::cell::Cell;
+use std::path::PathBuf;
+use std::rc::Rc;
 use std::sync::Arc;
 
 use tracing::{event, Level};
@@ -58,6 +62,13 @@ pub enum ViewOneInput {
 
     // Video has been "prepared", so duration should be available
     Prepared,
+
+    /// Start drawing bounds of a face the face detection missed.
+    DrawFace,
+
+    /// User has finished drawing a face.
+    /// Bounds are x, y, width, height relative to the picture widget.
+    FaceDrawn(f64, f64, f64, f64),
 }
 
 #[derive(Debug)]
@@ -95,11 +106,28 @@ pub struct ViewOne {
     broken_status: adw::StatusPage,
 
     face_thumbnails: AsyncController<FaceThumbnails>,
+
+    people_repo: people::Repository,
+
+    /// Base directory for face thumbnails.
+    data_dir: PathBuf,
+
+    /// Item currently being viewed.
+    visual: Option<Arc<Visual>>,
+
+    /// Width and height of current picture before orientation is applied.
+    picture_size: Option<(i32, i32)>,
+
+    /// Overlay for drawing bounds of a face.
+    face_drawing_area: gtk::DrawingArea,
+
+    /// Face bounds as currently drawn. Shared with drawing callbacks.
+    face_drawing_bounds: Rc<Cell<Option<(f64, f64, f64, f64)>>>,
 }
 
 #[relm4::component(pub async)]
 impl SimpleAsyncComponent for ViewOne {
-    type Init = (people::Repository, Arc<Reducer<ProgressMonitor>>);
+    type Init = (people::Repository, Arc<Reducer<ProgressMonitor>>, PathBuf);
     type Input = ViewOneInput;
     type Output = ViewOneOutput;
 
@@ -186,6 +214,12 @@ impl SimpleAsyncComponent for ViewOne {
                     container_add: model.face_thumbnails.widget(),
                 },
 
+                #[local_ref]
+                add_overlay = &face_drawing_area -> gtk::DrawingArea {
+                    set_visible: false,
+                    set_cursor_from_name: Some("crosshair"),
+                },
+
                 #[wrap(Some)]
                 set_child = &gtk::Box {
                     #[local_ref]
@@ -237,9 +271,9 @@ impl SimpleAsyncComponent for ViewOne {
     }
 
     async fn init(
-        (people_repo, transcode_progress_monitor): Self::Init,
+        (people_repo, transcode_progress_monitor, data_dir): Self::Init,
         root: Self::Root,
-        _sender: AsyncComponentSender<Self>,
+        sender: AsyncComponentSender<Self>,
     ) -> AsyncComponentParts<Self>  {
 
         let picture = gtk::Picture::new();
@@ -267,9 +301,56 @@ impl SimpleAsyncComponent for ViewOne {
         let broken_status = adw::StatusPage::new();
 
         let face_thumbnails = FaceThumbnails::builder()
-            .launch(people_repo)
+            .launch(people_repo.clone())
             .detach();
 
+        let face_drawing_area = gtk::DrawingArea::new();
+        let face_drawing_bounds = Rc::new(Cell::new(None));
+
+        {
+            let bounds = face_drawing_bounds.clone();
+            face_drawing_area.set_draw_func(move |_, cr, _, _| {
+                if let Some((x, y, width, height)) = bounds.get() {
+                    cr.set_source_rgba(1.0, 1.0, 1.0, 0.9);
+                    cr.set_line_width(2.0);
+                    cr.rectangle(x, y, width, height);
+                    let _ = cr.stroke();
+                }
+            });
+        }
+
+        let drag = gtk::GestureDrag::new();
+        {
+            let bounds = face_drawing_bounds.clone();
+            let area = face_drawing_area.clone();
+            drag.connect_drag_begin(move |_, x, y| {
+                bounds.set(Some((x, y, 0.0, 0.0)));
+                area.queue_draw();
+            });
+        }
+        {
+            let bounds = face_drawing_bounds.clone();
+            let area = face_drawing_area.clone();
+            drag.connect_drag_update(move |gesture, width, height| {
+                if let Some((x, y)) = gesture.start_point() {
+                    bounds.set(Some((x, y, width, height)));
+                    area.queue_draw();
+                }
+            });
+        }
+        {
+            let sender = sender.clone();
+            drag.connect_drag_end(move |gesture, width, height| {
+                if let Some((x, y)) = gesture.start_point() {
+                    // Normalize so box can be drawn from any corner
+                    let (x, width) = if width < 0.0 { (x + width, -width) } else { (x, width) };
+                    let (y, height) = if height < 0.0 { (y + height, -height) } else { (y, height) };
+                    sender.input(ViewOneInput::FaceDrawn(x, y, width, height));
+                }
+            });
+        }
+        face_drawing_area.add_controller(drag);
+
         let model = ViewOne {
             picture: picture.clone(),
             video: None,
@@ -284,6 +365,12 @@ impl SimpleAsyncComponent for ViewOne {
             transcode_progress,
             broken_status: broken_status.clone(),
             face_thumbnails,
+            people_repo,
+            data_dir,
+            visual: None,
+            picture_size: None,
+            face_drawing_area: face_drawing_area.clone(),
+            face_drawing_bounds,
         };
 
         let widgets = view_output!();
@@ -297,10 +384,17 @@ impl SimpleAsyncComponent for ViewOne {
                 self.video = None;
                 self.picture.set_paintable(None::<&gdk::Paintable>);
                 self.face_thumbnails.emit(FaceThumbnailsInput::Hide);
+                self.stop_drawing_face();
+                self.visual = None;
+                self.picture_size = None;
             },
             ViewOneInput::View(visual) => {
                 event!(Level::INFO, "Showing item for {}", visual.visual_id);
 
+                self.stop_drawing_face();
+                self.visual = Some(visual.clone());
+                self.picture_size = None;
+
                 self.picture.set_visible(false);
                 self.transcode_status.set_visible(false);
                 self.video_controls.set_visible(false);
@@ -373,6 +467,7 @@ impl SimpleAsyncComponent for ViewOne {
                     };
 
                     let texture = frame.texture();
+                    self.picture_size = Some((texture.width(), texture.height()));
 
                     self.picture.set_paintable(Some(&texture));
                     self.picture.set_visible(true);
@@ -447,9 +542,11 @@ impl SimpleAsyncComponent for ViewOne {
                     }
                 }
 
-                // Overlay faces in picture
+                // Overlay faces in picture or video
                 if let Some(ref picture_id) = visual.picture_id {
                     self.face_thumbnails.emit(FaceThumbnailsInput::View(*picture_id));
+                } else if let Some(ref video_id) = visual.video_id {
+                    self.face_thumbnails.emit(FaceThumbnailsInput::ViewVideo(*video_id));
                 } else {
                     self.face_thumbnails.emit(FaceThumbnailsInput::Hide);
                 }
@@ -549,6 +646,101 @@ impl SimpleAsyncComponent for ViewOne {
             ViewOneInput::Refresh => {
                 self.face_thumbnails.emit(FaceThumbnailsInput::Refresh);
             },
+            ViewOneInput::DrawFace => {
+                if !self.visual.as_ref().is_some_and(|v| v.is_photo_only()) || self.picture_size.is_none() {
+                    event!(Level::INFO, "Can only draw faces on photos");
+                    return;
+                }
+                self.face_drawing_bounds.set(None);
+                self.face_drawing_area.set_visible(true);
+                self.face_drawing_area.queue_draw();
+            },
+            ViewOneInput::FaceDrawn(x, y, width, height) => {
+                self.stop_drawing_face();
+
+                let Some(ref visual) = self.visual else {
+                    return;
+                };
+
+                let (Some(picture_id), Some(ref picture_path)) = (visual.picture_id, visual.picture_path.clone()) else {
+                    return;
+                };
+
+                let Some(bounds) = self.to_picture_bounds(x, y, width, height) else {
+                    event!(Level::INFO, "Face drawn outside of picture");
+                    return;
+                };
+
+                let face = FaceExtractor::extract_manual_face(&self.data_dir, &picture_id, picture_path, bounds).await;
+                let face = match face {
+                    Ok(face) => face,
+                    Err(e) => {
+                        event!(Level::ERROR, "Failed adding face: {}", e);
+                        return;
+                    }
+                };
+
+                match self.people_repo.add_manual_face(&picture_id, &face) {
+                    Ok(face_id) => {
+                        self.face_thumbnails.emit(FaceThumbnailsInput::SetPerson(face_id, face.thumbnail_path));
+                    },
+                    Err(e) => {
+                        event!(Level::ERROR, "Failed saving face: {}", e);
+                    }
+                }
+            },
         }
     }
 }
+
+impl ViewOne {
+    fn stop_drawing_face(&self) {
+        self.face_drawing_bounds.set(None);
+        self.face_drawing_area.set_visible(false);
+    }
+
+    /// Convert bounds drawn over the picture widget to bounds in the picture,
+    /// after orientation has been applied.
+    fn to_picture_bounds(&self, x: f64, y: f64, width: f64, height: f64) -> Option<face_extractor::Rect> {
+        let (picture_width, picture_height) = self.picture_size?;
+        let orientation = self.visual.as_ref()?.picture_orientation.unwrap_or_default();
+
+        // The picture is scaled to fit the widget before a CSS transformation applies
+        // the orientation, so the scale factor comes from the untransformed size.
+        let widget_width = f64::from(self.face_drawing_area.width());
+        let widget_height = f64::from(self.face_drawing_area.height());
+        let scale = f64::min(
+            widget_width / f64::from(picture_width),
+            widget_height / f64::from(picture_height),
+        );
+
+        if scale <= 0.0 {
+            return None;
+        }
+
+        let (upright_width, upright_height) = match orientation {
+            PictureOrientation::East
+            | PictureOrientation::EastMirrored
+            | PictureOrientation::West
+            | PictureOrientation::WestMirrored => (picture_height, picture_width),
+            _ => (picture_width, picture_height),
+        };
+
+        let offset_x = (widget_width - f64::from(upright_width) * scale) / 2.0;
+        let offset_y = (widget_height - f64::from(upright_height) * scale) / 2.0;
+
+        let bounds = face_extractor::Rect {
+            x: ((x - offset_x) / scale) as f32,
+            y: ((y - offset_y) / scale) as f32,
+            width: (width / scale) as f32,
+            height: (height / scale) as f32,
+        };
+
+        // Ignore accidental clicks
+        if bounds.width < 10.0 || bounds.height < 10.0 {
+            return None;
+        }
+
+        Some(bounds)
+    }
+}